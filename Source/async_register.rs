@@ -0,0 +1,107 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// `GlobalHotKeyManager::register_async` on the backends answering on another
+// thread. The backend is waited for on the blocking threads of tokio, which
+// keep going when the future is dropped, so the registration is undone by
+// whichever of the two is last.
+
+use std::{
+	mem,
+	sync::{Arc, Mutex},
+};
+
+use crate::{
+	hotkey::HotKey,
+	platform_impl,
+	process::{self, ManagerId},
+};
+
+/// A registration of `hotkey`, whose process claims are already taken.
+pub(crate) struct Registration {
+	pub(crate) manager:ManagerId,
+	pub(crate) platform_impl:Arc<platform_impl::GlobalHotKeyManager>,
+	pub(crate) hotkey:HotKey,
+	pub(crate) claimed:Vec<HotKey>,
+}
+
+impl Registration {
+	/// Registers the hotkey with the backend. Once it fails, or when the future
+	/// is dropped before it completes, the hotkey is unregistered and the claims
+	/// released again.
+	pub(crate) async fn run(self) -> crate::Result<()> {
+		let (registration, outcome) = (Arc::new(self), Arc::new(Mutex::new(Outcome::Running)));
+
+		let mut guard = Guard { outcome:outcome.clone(), registration:Some(registration.clone()) };
+
+		let task = {
+			let registration = registration.clone();
+
+			tokio::task::spawn_blocking(move || {
+				let result = registration.platform_impl.register(registration.hotkey);
+
+				let mut outcome = outcome.lock().unwrap();
+
+				match *outcome {
+					Outcome::Cancelled => registration.undo(result.is_ok()),
+					_ => *outcome = Outcome::Finished(result),
+				}
+			})
+		};
+
+		task.await.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+
+		// Completed, the guard has nothing left to undo.
+		guard.registration = None;
+
+		let outcome = mem::replace(&mut *guard.outcome.lock().unwrap(), Outcome::Cancelled);
+
+		let Outcome::Finished(result) = outcome else {
+			unreachable!("the task ends once the backend answered");
+		};
+
+		if result.is_err() {
+			registration.undo(false);
+		}
+
+		result
+	}
+
+	fn undo(&self, registered:bool) {
+		if registered {
+			if let Err(e) = self.platform_impl.unregister(self.hotkey) {
+				log::warn!("Failed to undo the cancelled registration of `{}`: {}", self.hotkey, e);
+			}
+		}
+
+		process::release(self.manager, &self.claimed);
+	}
+}
+
+enum Outcome {
+	Running,
+	// The future was dropped, the task undoes the registration once done.
+	Cancelled,
+	Finished(crate::Result<()>),
+}
+
+// Undoes the registration if the future is dropped before it completes.
+struct Guard {
+	outcome:Arc<Mutex<Outcome>>,
+	registration:Option<Arc<Registration>>,
+}
+
+impl Drop for Guard {
+	fn drop(&mut self) {
+		let Some(registration) = self.registration.take() else {
+			return;
+		};
+
+		let mut outcome = self.outcome.lock().unwrap();
+
+		if let Outcome::Finished(result) = mem::replace(&mut *outcome, Outcome::Cancelled) {
+			registration.undo(result.is_ok());
+		}
+	}
+}
@@ -0,0 +1,121 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::GlobalHotKeyEvent;
+
+// The event senders of the relay threads, forgotten once their thread stopped.
+static BATCHERS:Mutex<Vec<Sender<GlobalHotKeyEvent>>> = Mutex::new(Vec::new());
+
+/// Starts a relay thread delivering the events of every `window` at once.
+pub(crate) fn batched_receiver(window:Duration) -> Receiver<Vec<GlobalHotKeyEvent>> {
+	let (events_tx, events_rx) = unbounded();
+
+	let (batches_tx, batches_rx) = unbounded();
+
+	BATCHERS.lock().unwrap().push(events_tx);
+
+	std::thread::spawn(move || relay(window, &events_rx, &batches_tx));
+
+	batches_rx
+}
+
+// Runs until the batch receiver is dropped, which is noticed at the next
+// delivery.
+fn relay(
+	window:Duration,
+	events:&Receiver<GlobalHotKeyEvent>,
+	batches:&Sender<Vec<GlobalHotKeyEvent>>,
+) {
+	let mut flush_at = Instant::now() + window;
+
+	loop {
+		std::thread::sleep(flush_at.saturating_duration_since(Instant::now()));
+
+		flush_at += window;
+
+		let batch = events.try_iter().collect::<Vec<_>>();
+
+		if !batch.is_empty() && batches.send(batch).is_err() {
+			return;
+		}
+	}
+}
+
+/// Queues `event` for every relay thread, forgetting the stopped ones.
+pub(crate) fn dispatch(event:GlobalHotKeyEvent) {
+	BATCHERS.lock().unwrap().retain(|batcher| batcher.send(event).is_ok());
+}
+
+#[test]
+fn test_batched_receiver() {
+	use crate::{
+		HotKeyState,
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let batches = batched_receiver(Duration::from_millis(200));
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyJ);
+
+	manager.register(hotkey).unwrap();
+
+	// A double press lands in the same window.
+	for _ in 0..2 {
+		manager.simulate_press(&hotkey);
+
+		manager.simulate_release(&hotkey);
+	}
+
+	// Other tests share the dispatch, only look at our own events.
+	let ours = |batch:Vec<GlobalHotKeyEvent>| {
+		batch.into_iter().filter(|e| e.id() == hotkey.id()).map(|e| e.state()).collect::<Vec<_>>()
+	};
+
+	let mut delivered = Vec::new();
+
+	let mut deliveries = 0;
+
+	while delivered.len() < 4 {
+		let batch = ours(batches.recv_timeout(Duration::from_secs(5)).unwrap());
+
+		if !batch.is_empty() {
+			delivered.extend(batch);
+
+			deliveries += 1;
+		}
+	}
+
+	// A window may end in the middle of the presses, which then span two deliveries.
+	assert!(deliveries <= 2);
+
+	assert_eq!(delivered, [
+		HotKeyState::Pressed,
+		HotKeyState::Released,
+		HotKeyState::Pressed,
+		HotKeyState::Released
+	]);
+
+	// Dropping the receiver stops the relay thread at its next delivery.
+	drop(batches);
+
+	let before = BATCHERS.lock().unwrap().len();
+
+	manager.simulate_press(&hotkey);
+
+	std::thread::sleep(Duration::from_millis(500));
+
+	manager.simulate_press(&hotkey);
+
+	assert!(BATCHERS.lock().unwrap().len() < before);
+}
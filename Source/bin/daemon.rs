@@ -0,0 +1,156 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Registers the hotkeys read from stdin and writes their events to stdout,
+//! for shell scripts and other languages.
+//!
+//! Every input line is `id:hotkey`, e.g. `1:ctrl+s`, and every output line is
+//! `id:pressed` or `id:released`. Empty lines and lines starting with `#` are
+//! ignored, invalid ones are reported on stderr. The daemon keeps running once
+//! stdin is closed, until it's killed.
+//!
+//! ```sh
+//! echo "1:ctrl+alt+KeyT" | global-hotkey-daemon | while IFS=: read id state; do
+//!     [ "$state" = pressed ] && notify-send "hotkey $id"
+//! done
+//! ```
+//!
+//! Not supported on macOS, where hotkeys need the main event loop of an
+//! application.
+
+use std::{
+	io::{BufRead, Write},
+	process::ExitCode,
+	time::Duration,
+};
+
+use crossbeam_channel::{RecvTimeoutError, unbounded};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
+
+// How often events are written out while no line comes in.
+const POLL_INTERVAL:Duration = Duration::from_millis(10);
+
+fn main() -> ExitCode {
+	if cfg!(target_os = "macos") {
+		eprintln!("global-hotkey-daemon: not supported on macOS");
+
+		return ExitCode::FAILURE;
+	}
+
+	let manager = match GlobalHotKeyManager::new() {
+		Ok(manager) => manager,
+		Err(e) => {
+			eprintln!("global-hotkey-daemon: {}", e);
+
+			return ExitCode::FAILURE;
+		},
+	};
+
+	// Reading stdin blocks, while the hotkeys are registered on this thread.
+	let (lines_tx, lines_rx) = unbounded();
+
+	std::thread::spawn(move || {
+		for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+			if lines_tx.send(line).is_err() {
+				break;
+			}
+		}
+	});
+
+	let mut stdin_open = true;
+
+	let mut stdout = std::io::stdout().lock();
+
+	loop {
+		if stdin_open {
+			match lines_rx.recv_timeout(POLL_INTERVAL) {
+				Ok(line) => register_line(&manager, &line),
+				Err(RecvTimeoutError::Timeout) => {},
+				Err(RecvTimeoutError::Disconnected) => stdin_open = false,
+			}
+		} else {
+			std::thread::sleep(POLL_INTERVAL);
+		}
+
+		#[cfg(target_os = "windows")]
+		global_hotkey::platform::windows::pump_messages();
+
+		while let Some(event) = GlobalHotKeyEvent::event_receiver().try_recv() {
+			// The reader went away, e.g. the end of a pipeline exited.
+			if writeln!(stdout, "{}", format_event(event)).and_then(|()| stdout.flush()).is_err() {
+				return ExitCode::SUCCESS;
+			}
+		}
+	}
+}
+
+fn register_line(manager:&GlobalHotKeyManager, line:&str) {
+	let result = match parse_line(line) {
+		Ok(Some(hotkey)) => manager.register(hotkey).map_err(|e| e.to_string()),
+		Ok(None) => Ok(()),
+		Err(e) => Err(e),
+	};
+
+	if let Err(e) = result {
+		eprintln!("global-hotkey-daemon: `{}`: {}", line.trim(), e);
+	}
+}
+
+// `None` for the lines without a hotkey.
+fn parse_line(line:&str) -> Result<Option<HotKey>, String> {
+	let line = line.trim();
+
+	if line.is_empty() || line.starts_with('#') {
+		return Ok(None);
+	}
+
+	let (id, hotkey) = line.split_once(':').ok_or("expected `id:hotkey`, e.g. `1:ctrl+s`")?;
+
+	let id = id.trim().parse::<u32>().map_err(|e| format!("invalid id: {}", e))?;
+
+	let hotkey = hotkey.trim().parse::<HotKey>().map_err(|e| e.to_string())?;
+
+	Ok(Some(hotkey.with_id(id)))
+}
+
+fn format_event(event:GlobalHotKeyEvent) -> String {
+	match event.state() {
+		HotKeyState::Pressed => format!("{}:pressed", event.id()),
+		HotKeyState::Released => format!("{}:released", event.id()),
+		// States added later are printed by number until they get a name.
+		state => format!("{}:{}", event.id(), u8::from(state)),
+	}
+}
+
+#[test]
+fn test_parse_line() {
+	use global_hotkey::hotkey::{Code, Modifiers};
+
+	let save = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS).with_id(1);
+
+	assert_eq!(parse_line("1:ctrl+s"), Ok(Some(save)));
+
+	assert_eq!(parse_line("  1 : ctrl+s \n"), Ok(Some(save)));
+
+	// The hotkey may contain a colon itself.
+	assert_eq!(
+		parse_line("2:ctrl+:").unwrap().unwrap(),
+		HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::Semicolon).with_id(2)
+	);
+
+	assert_eq!(parse_line(""), Ok(None));
+
+	assert_eq!(parse_line("# comment"), Ok(None));
+
+	assert!(parse_line("ctrl+s").is_err());
+
+	assert!(parse_line("x:ctrl+s").is_err());
+
+	assert!(parse_line("1:ctrl+nope").is_err());
+
+	assert_eq!(
+		format_event(GlobalHotKeyEvent::new(7, HotKeyState::Released)),
+		"7:released"
+	);
+}
@@ -0,0 +1,171 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{GlobalHotKeyManager, GlobalHotKeyManagerOptions, hotkey::HotKey};
+
+type ErrorHandler = Box<dyn Fn(crate::Error)>;
+
+/// Creates a [`GlobalHotKeyManager`] along with its initial hotkeys, obtained
+/// with [`GlobalHotKeyManager::builder`].
+///
+/// Nothing is registered until [`GlobalHotKeyManagerBuilder::build`] is
+/// called, which then registers the hotkeys in the order they were given.
+///
+/// ```no_run
+/// use global_hotkey::{GlobalHotKeyManager, hotkey::{Code, HotKey, Modifiers}};
+///
+/// let manager = GlobalHotKeyManager::builder()
+///     .register(HotKey::new(Some(Modifiers::SHIFT), Code::KeyD))
+///     .register_all(&[HotKey::new(None, Code::F9), HotKey::new(None, Code::F10)])
+///     .on_error(|e| eprintln!("{}", e))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct GlobalHotKeyManagerBuilder {
+	options:GlobalHotKeyManagerOptions,
+	hotkeys:Vec<HotKey>,
+	on_error:Option<ErrorHandler>,
+	fail_fast:bool,
+}
+
+impl std::fmt::Debug for GlobalHotKeyManagerBuilder {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("GlobalHotKeyManagerBuilder")
+			.field("options", &self.options)
+			.field("hotkeys", &self.hotkeys)
+			.field("fail_fast", &self.fail_fast)
+			.finish_non_exhaustive()
+	}
+}
+
+impl GlobalHotKeyManagerBuilder {
+	/// Creates a builder without any hotkey.
+	pub fn new() -> Self { Self::default() }
+
+	/// Sets the options the manager is created with, see
+	/// [`GlobalHotKeyManager::with_options`].
+	pub fn options(mut self, options:GlobalHotKeyManagerOptions) -> Self {
+		self.options = options;
+
+		self
+	}
+
+	/// Sets [`GlobalHotKeyManagerOptions::poll_interval`] of the options.
+	pub fn poll_interval(mut self, interval:std::time::Duration) -> Self {
+		self.options.poll_interval = Some(interval);
+
+		self
+	}
+
+	/// Adds `hotkey` to the hotkeys registered by [`GlobalHotKeyManagerBuilder::build`].
+	pub fn register(mut self, hotkey:HotKey) -> Self {
+		self.hotkeys.push(hotkey);
+
+		self
+	}
+
+	/// Adds all `hotkeys` to the hotkeys registered by
+	/// [`GlobalHotKeyManagerBuilder::build`].
+	pub fn register_all(mut self, hotkeys:&[HotKey]) -> Self {
+		self.hotkeys.extend_from_slice(hotkeys);
+
+		self
+	}
+
+	/// Sets the handler called with the error of every hotkey that fails to
+	/// register, failures are logged if none is set.
+	pub fn on_error<F:Fn(crate::Error) + 'static>(mut self, handler:F) -> Self {
+		self.on_error = Some(Box::new(handler));
+
+		self
+	}
+
+	/// Whether [`GlobalHotKeyManagerBuilder::build`] stops at the first
+	/// hotkey that fails to register and returns its error, `false` by
+	/// default. The hotkeys registered before it are unregistered again.
+	pub fn fail_fast(mut self, fail_fast:bool) -> Self {
+		self.fail_fast = fail_fast;
+
+		self
+	}
+
+	/// Creates the manager and registers the hotkeys.
+	///
+	/// Hotkeys that fail to register are reported to the error handler and
+	/// skipped, unless [`GlobalHotKeyManagerBuilder::fail_fast`] is set.
+	pub fn build(self) -> crate::Result<GlobalHotKeyManager> {
+		let manager = GlobalHotKeyManager::with_options(self.options)?;
+
+		let mut registered = Vec::new();
+
+		for hotkey in self.hotkeys {
+			let Err(e) = manager.register(hotkey) else {
+				registered.push(hotkey);
+
+				continue;
+			};
+
+			match &self.on_error {
+				Some(handler) => handler(e.clone()),
+				None => log::warn!("Failed to register `{}`: {}", hotkey, e),
+			}
+
+			if self.fail_fast {
+				let _ = manager.unregister_all(&registered);
+
+				return Err(e);
+			}
+		}
+
+		Ok(manager)
+	}
+}
+
+#[test]
+fn test_manager_builder() {
+	use std::{cell::RefCell, rc::Rc};
+
+	use crate::hotkey::{Code, Modifiers};
+
+	let hotkeys = [
+		HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F13),
+		HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F14),
+	];
+
+	let errors = Rc::new(RefCell::new(Vec::new()));
+
+	let manager = {
+		let errors = errors.clone();
+
+		GlobalHotKeyManagerBuilder::new()
+			.register(hotkeys[0])
+			.register_all(&hotkeys[1..])
+			.on_error(move |e| errors.borrow_mut().push(e))
+			.build()
+			.unwrap()
+	};
+
+	// Without a display server every registration fails, and the build still succeeds.
+	assert_eq!(manager.metrics().registered_count + errors.borrow().len(), hotkeys.len());
+
+	drop(manager);
+
+	let result = GlobalHotKeyManagerBuilder::new().register_all(&hotkeys).fail_fast(true).build();
+
+	assert_eq!(result.is_err(), !errors.borrow().is_empty());
+}
+
+#[test]
+fn test_zero_poll_interval() {
+	use std::time::Duration;
+
+	let result = GlobalHotKeyManagerBuilder::new().poll_interval(Duration::ZERO).build();
+
+	assert!(matches!(result, Err(crate::Error::UnsupportedOption(_))));
+
+	let result = GlobalHotKeyManagerBuilder::new().poll_interval(Duration::from_millis(5)).build();
+
+	assert!(result.is_ok());
+}
@@ -0,0 +1,145 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, RwLock, Weak},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+	GlobalHotKeyEvent,
+	process::{self, ManagerId},
+};
+
+pub(crate) type Callback = Arc<dyn Fn(GlobalHotKeyEvent) + Send + Sync + 'static>;
+
+type Registry = RwLock<HashMap<u64, Callback>>;
+
+// The registries of the managers which added a callback, forgotten once the
+// manager is dropped.
+static REGISTRIES:Lazy<Mutex<HashMap<ManagerId, Weak<Registry>>>> = Lazy::new(Default::default);
+
+/// The callbacks of a manager, called with the events of its hotkeys instead
+/// of the channel or the event handler.
+#[derive(Default)]
+pub(crate) struct Callbacks {
+	registry:Arc<Registry>,
+}
+
+impl Callbacks {
+	/// Replaces every callback of `manager` with `callback`, under id `0`.
+	pub(crate) fn set(&self, manager:ManagerId, callback:Callback) {
+		self.publish(manager);
+
+		let mut registry = self.registry.write().unwrap();
+
+		registry.clear();
+
+		registry.insert(0, callback);
+	}
+
+	/// Adds `callback` under `id`, replacing the callback it had.
+	pub(crate) fn add(&self, manager:ManagerId, id:u64, callback:Callback) {
+		self.publish(manager);
+
+		self.registry.write().unwrap().insert(id, callback);
+	}
+
+	/// Removes the callback under `id`, returning whether there was one.
+	pub(crate) fn remove(&self, id:u64) -> bool {
+		self.registry.write().unwrap().remove(&id).is_some()
+	}
+
+	fn publish(&self, manager:ManagerId) {
+		let mut registries = REGISTRIES.lock().unwrap();
+
+		registries.retain(|_, registry| registry.strong_count() > 0);
+
+		registries.entry(manager).or_insert_with(|| Arc::downgrade(&self.registry));
+	}
+}
+
+/// Calls the callbacks of the manager holding the hotkey of `event`, returning
+/// `false` if it has none.
+pub(crate) fn dispatch(event:GlobalHotKeyEvent) -> bool {
+	let Some(manager) = process::holder(event.id()) else {
+		return false;
+	};
+
+	let registry = REGISTRIES.lock().unwrap().get(&manager).and_then(Weak::upgrade);
+
+	// Cloned so a callback may add or remove callbacks itself.
+	let callbacks = match registry {
+		Some(registry) => registry.read().unwrap().values().cloned().collect::<Vec<_>>(),
+		None => return false,
+	};
+
+	for callback in &callbacks {
+		callback(event);
+	}
+
+	!callbacks.is_empty()
+}
+
+#[test]
+fn test_callbacks() {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use crate::{
+		HotKeyState,
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyL);
+
+	manager.register(hotkey).unwrap();
+
+	let calls = Arc::new(AtomicUsize::new(0));
+
+	let counter = |step:usize| {
+		let calls = calls.clone();
+
+		Arc::new(move |event:GlobalHotKeyEvent| {
+			assert_eq!(event.id(), hotkey.id());
+
+			calls.fetch_add(step, Ordering::SeqCst);
+		})
+	};
+
+	manager.add_callback(1, counter(1));
+
+	manager.add_callback(2, counter(10));
+
+	manager.simulate_press(&hotkey);
+
+	assert_eq!(calls.load(Ordering::SeqCst), 11);
+
+	assert!(manager.remove_callback(2));
+
+	assert!(!manager.remove_callback(2));
+
+	manager.set_callback(counter(100));
+
+	manager.simulate_release(&hotkey);
+
+	assert_eq!(calls.load(Ordering::SeqCst), 111);
+
+	// The callbacks took the events, the channel didn't get them.
+	assert!(
+		!GlobalHotKeyEvent::event_receiver()
+			.try_iter()
+			.any(|e| e.id() == hotkey.id() && e.state() == HotKeyState::Pressed)
+	);
+
+	assert!(manager.remove_callback(0));
+
+	manager.simulate_press(&hotkey);
+
+	assert_eq!(calls.load(Ordering::SeqCst), 111);
+}
@@ -0,0 +1,84 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// The single place deciding how a hotkey is written and identified, so that
+// ids, `Display`, persistence and the platform mappings agree on which
+// modifiers a hotkey holds and in which order they come.
+
+use keyboard_types::{Code, Modifiers};
+
+use crate::hotkey::{ModifierSide, ModifierSides};
+
+/// The modifiers a hotkey can hold, in the order `Display` writes them.
+pub(crate) const MODIFIERS:[Modifiers; 4] =
+	[Modifiers::SHIFT, Modifiers::CONTROL, Modifiers::ALT, Modifiers::SUPER];
+
+/// The order of the modifiers in Electron and GTK accelerators.
+pub(crate) const ACCELERATOR_MODIFIERS:[Modifiers; 4] =
+	[Modifiers::CONTROL, Modifiers::ALT, Modifiers::SHIFT, Modifiers::SUPER];
+
+/// Folds [`Modifiers::META`] into [`Modifiers::SUPER`], every backend maps
+/// both to the same key. Other modifiers are kept as they are.
+pub(crate) const fn fold(mods:Modifiers) -> Modifiers {
+	if mods.contains(Modifiers::META) {
+		mods.difference(Modifiers::META).union(Modifiers::SUPER)
+	} else {
+		mods
+	}
+}
+
+/// Returns the modifiers of `mods` found in `order`, in that order, along
+/// with the side `sides` requires for them.
+pub(crate) fn ordered(
+	order:[Modifiers; 4],
+	mods:Modifiers,
+	sides:ModifierSides,
+) -> impl Iterator<Item = (Modifiers, ModifierSide)> {
+	let mods = fold(mods);
+
+	order.into_iter().filter(move |modifier| mods.contains(*modifier)).map(move |modifier| {
+		(modifier, sides.side(modifier))
+	})
+}
+
+/// Returns the modifiers of `mods` in the canonical order of [`MODIFIERS`].
+pub(crate) fn modifiers(
+	mods:Modifiers,
+	sides:ModifierSides,
+) -> impl Iterator<Item = (Modifiers, ModifierSide)> {
+	ordered(MODIFIERS, mods, sides)
+}
+
+/// The token `key` is written with.
+pub(crate) fn key_token(key:Code) -> String { key.to_string() }
+
+/// The id generated for a hotkey, packing its modifiers, sides and key. Only
+/// hotkeys every backend treats as the same share one, i.e. a
+/// [`Modifiers::META`] hotkey and its [`Modifiers::SUPER`] counterpart, since
+/// the modifiers are folded first.
+pub(crate) const fn id(mods:Modifiers, sides:ModifierSides, key:Code) -> u32 {
+	fold(mods).bits() << 16 | sides.bits() << 8 | key as u32
+}
+
+#[test]
+fn test_canonical_order() {
+	let sides = ModifierSides { alt:ModifierSide::Right, ..ModifierSides::EITHER };
+
+	let mods = Modifiers::META | Modifiers::ALT | Modifiers::SHIFT;
+
+	assert_eq!(fold(mods), Modifiers::SUPER | Modifiers::ALT | Modifiers::SHIFT);
+
+	assert_eq!(modifiers(mods, sides).collect::<Vec<_>>(), [
+		(Modifiers::SHIFT, ModifierSide::Either),
+		(Modifiers::ALT, ModifierSide::Right),
+		(Modifiers::SUPER, ModifierSide::Either),
+	]);
+
+	assert_eq!(
+		ordered(ACCELERATOR_MODIFIERS, mods, sides).map(|(m, _)| m).collect::<Vec<_>>(),
+		[Modifiers::ALT, Modifiers::SHIFT, Modifiers::SUPER]
+	);
+
+	assert_eq!(id(mods, sides, Code::KeyA), id(fold(mods), sides, Code::KeyA));
+}
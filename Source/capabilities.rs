@@ -0,0 +1,32 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Describes which optional features the current platform backend supports.
+///
+/// Obtained through [`GlobalHotKeyManager::capabilities`](crate::GlobalHotKeyManager::capabilities).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+	/// Whether hotkeys can require a modifier to be pressed on a specific side
+	/// of the keyboard, see [`HotKey::with_side`](crate::hotkey::HotKey::with_side).
+	pub sided_modifiers:bool,
+	/// Whether hotkeys can swallow the key press, see
+	/// [`HotKeyOptions::consume`](crate::HotKeyOptions::consume).
+	pub consume:bool,
+	/// Whether hotkeys can observe the key press without swallowing it, meaning
+	/// they can be registered with [`HotKeyOptions::consume`](crate::HotKeyOptions::consume)
+	/// set to `false`.
+	pub passthrough:bool,
+	/// Whether a key can tap for a hotkey and hold for combinations, see
+	/// [`GlobalHotKeyManager::register_tap_hold`](crate::GlobalHotKeyManager::register_tap_hold).
+	pub tap_hold:bool,
+	/// Whether hotkeys can be restricted to some keyboards, see
+	/// [`HotKeyOptions::device`](crate::HotKeyOptions::device).
+	pub device_filter:bool,
+	/// The number of hotkeys a manager can hold at once, `None` if the backend
+	/// has no known limit. Going past it fails with
+	/// [`Error::QuotaExceeded`](crate::Error::QuotaExceeded) before reaching the
+	/// OS.
+	pub max_hotkeys:Option<usize>,
+}
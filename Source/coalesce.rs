@@ -0,0 +1,217 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Condvar, Mutex, Weak},
+	time::{Duration, Instant},
+};
+
+use crate::{GlobalHotKeyEvent, HotKeyState};
+
+/// A subscription to hotkey events that keeps at most one pending event per
+/// hotkey id, obtained with [`GlobalHotKeyEvent::subscribe_coalesced`].
+///
+/// A new event replaces the pending event of the same hotkey in place, so a
+/// slow consumer only sees the latest state of each hotkey instead of every
+/// transition. The latest event is always kept, so a hotkey pressed and then
+/// released is observed as released, never as nothing.
+///
+/// Clones share the same queue, the subscription ends when the last one is
+/// dropped.
+#[derive(Clone)]
+pub struct CoalescingReceiver {
+	inner:Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+	pending:Mutex<Pending>,
+	ready:Condvar,
+}
+
+// The ids in delivery order, along with their latest state.
+#[derive(Default)]
+struct Pending {
+	order:VecDeque<u32>,
+	states:HashMap<u32, HotKeyState>,
+}
+
+impl Pending {
+	fn pop(&mut self) -> Option<GlobalHotKeyEvent> {
+		let id = self.order.pop_front()?;
+
+		let state = self.states.remove(&id)?;
+
+		Some(GlobalHotKeyEvent::new(id, state))
+	}
+}
+
+static SUBSCRIBERS:Mutex<Vec<Weak<Inner>>> = Mutex::new(Vec::new());
+
+impl CoalescingReceiver {
+	pub(crate) fn subscribe() -> Self {
+		let inner = Arc::new(Inner::default());
+
+		SUBSCRIBERS.lock().unwrap().push(Arc::downgrade(&inner));
+
+		Self { inner }
+	}
+
+	/// Returns the oldest pending event without blocking.
+	pub fn try_recv(&self) -> Option<GlobalHotKeyEvent> { self.inner.pending.lock().unwrap().pop() }
+
+	/// Blocks until an event is pending and returns it.
+	pub fn recv(&self) -> GlobalHotKeyEvent {
+		let mut pending = self.inner.pending.lock().unwrap();
+
+		loop {
+			if let Some(event) = pending.pop() {
+				return event;
+			}
+
+			pending = self.inner.ready.wait(pending).unwrap();
+		}
+	}
+
+	/// Blocks until an event is pending or `timeout` elapsed.
+	pub fn recv_timeout(&self, timeout:Duration) -> Option<GlobalHotKeyEvent> {
+		let deadline = Instant::now() + timeout;
+
+		let mut pending = self.inner.pending.lock().unwrap();
+
+		loop {
+			if let Some(event) = pending.pop() {
+				return Some(event);
+			}
+
+			let remaining = deadline.checked_duration_since(Instant::now())?;
+
+			pending = self.inner.ready.wait_timeout(pending, remaining).unwrap().0;
+		}
+	}
+
+	/// Returns the number of pending events, at most one per hotkey id.
+	pub fn len(&self) -> usize { self.inner.pending.lock().unwrap().order.len() }
+
+	/// Returns `true` if no event is pending.
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl std::fmt::Debug for CoalescingReceiver {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("CoalescingReceiver").field("pending", &self.len()).finish()
+	}
+}
+
+/// Queues `event` in every live subscription, forgetting the dropped ones.
+pub(crate) fn dispatch(event:GlobalHotKeyEvent) {
+	SUBSCRIBERS.lock().unwrap().retain(|subscriber| {
+		let Some(inner) = subscriber.upgrade() else {
+			return false;
+		};
+
+		let mut pending = inner.pending.lock().unwrap();
+
+		if pending.states.insert(event.id(), event.state()).is_none() {
+			pending.order.push_back(event.id());
+		}
+
+		inner.ready.notify_one();
+
+		true
+	});
+}
+
+#[test]
+fn test_coalescing_receiver() {
+	use std::{
+		collections::BTreeMap,
+		sync::atomic::{AtomicBool, Ordering},
+	};
+
+	use crate::{
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let receiver = GlobalHotKeyEvent::subscribe_coalesced();
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkeys = [Code::F21, Code::F22, Code::F23, Code::F24]
+		.map(|code| HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), code));
+
+	manager.register_all(&hotkeys).unwrap();
+
+	// Other tests share the subscriptions, only look at our own events.
+	let ours = |event:&GlobalHotKeyEvent| hotkeys.iter().any(|h| h.id() == event.id());
+
+	// A pending press replaced by the release is still observed.
+	manager.simulate_press(&hotkeys[0]);
+
+	manager.simulate_release(&hotkeys[0]);
+
+	let events = std::iter::from_fn(|| receiver.try_recv()).filter(ours).collect::<Vec<_>>();
+
+	assert_eq!(events, [GlobalHotKeyEvent::new(hotkeys[0].id(), HotKeyState::Released)]);
+
+	// Hammer the queue with one thread per hotkey while a consumer drains it.
+	let done = AtomicBool::new(false);
+
+	let latest = std::thread::scope(|s| {
+		let consumer = s.spawn(|| {
+			let mut latest = BTreeMap::new();
+
+			loop {
+				// Checked before draining so the last events are always seen.
+				let finished = done.load(Ordering::SeqCst);
+
+				while let Some(event) = receiver.recv_timeout(Duration::from_millis(10)) {
+					if ours(&event) {
+						latest.insert(event.id(), event.state());
+					}
+				}
+
+				if finished {
+					return latest;
+				}
+			}
+		});
+
+		let producers = hotkeys
+			.iter()
+			.map(|hotkey| {
+				let manager = &manager;
+
+				s.spawn(move || {
+					for _ in 0..1000 {
+						assert!(manager.simulate_press(hotkey));
+
+						assert!(manager.simulate_release(hotkey));
+					}
+				})
+			})
+			.collect::<Vec<_>>();
+
+		for producer in producers {
+			producer.join().unwrap();
+		}
+
+		done.store(true, Ordering::SeqCst);
+
+		consumer.join().unwrap()
+	});
+
+	for hotkey in &hotkeys {
+		assert_eq!(latest.get(&hotkey.id()), Some(&HotKeyState::Released));
+	}
+
+	// Dropped subscriptions are forgotten.
+	drop(receiver);
+
+	GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(hotkeys[0].id(), HotKeyState::Pressed));
+
+	assert!(SUBSCRIBERS.lock().unwrap().iter().all(|s| s.strong_count() > 0));
+}
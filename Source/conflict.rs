@@ -0,0 +1,91 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{SystemShortcut, hotkey::HotKey};
+
+/// What [`GlobalHotKeyManager::diagnose_conflict`](crate::GlobalHotKeyManager::diagnose_conflict)
+/// found out about the owner of a hotkey, e.g. to explain an
+/// [`Error::AlreadyRegistered`](crate::Error::AlreadyRegistered) to the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictInfo {
+	pub hotkey:HotKey,
+	pub owner:ConflictOwner,
+}
+
+/// Who holds a hotkey, as far as it could be determined.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictOwner {
+	/// Nothing holds the hotkey, it can be registered.
+	Free,
+	/// The manager asked registered the hotkey itself, possibly with other
+	/// modifier sides.
+	ThisManager,
+	/// Another manager of the process registered the hotkey.
+	OtherManager { manager:usize },
+	/// The system keeps the hotkey to itself, see [`SystemShortcut`].
+	System(SystemShortcut),
+	/// The system keeps the power key until it's allowed with
+	/// [`GlobalHotKeyManager::allow_power_key`](crate::GlobalHotKeyManager::allow_power_key).
+	PowerKey,
+	/// The backend can't register the hotkey whatever holds it, e.g. its key
+	/// has no equivalent on the platform or its sides can't be told apart.
+	Unsupported,
+	/// Another application registered the hotkey.
+	///
+	/// The platforms don't tell which one, `likely` is the window manager
+	/// when it could be found, since it holds the shortcuts of the desktop.
+	/// The hotkey may still belong to any other application.
+	External { likely:Option<ProcessInfo> },
+	/// The hotkey couldn't be probed, e.g. without a display.
+	Unknown,
+}
+
+/// A process found holding a hotkey, with what could be read of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+	pub pid:Option<u32>,
+	pub name:Option<String>,
+}
+
+impl ProcessInfo {
+	/// Completes a process known by its `name` and maybe `pid`, reading its
+	/// name from `/proc` when it's missing.
+	#[cfg_attr(
+		not(any(
+			target_os = "linux",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "openbsd",
+			target_os = "netbsd"
+		)),
+		allow(dead_code)
+	)]
+	pub(crate) fn new(pid:Option<u32>, name:Option<String>) -> Option<Self> {
+		let name = name.filter(|name| !name.is_empty()).or_else(|| {
+			let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid?)).ok()?;
+
+			Some(comm.trim_end().to_string())
+		});
+
+		(pid.is_some() || name.is_some()).then_some(Self { pid, name })
+	}
+}
+
+#[test]
+fn test_process_info() {
+	assert_eq!(ProcessInfo::new(None, None), None);
+
+	assert_eq!(ProcessInfo::new(None, Some(String::new())), None);
+
+	let named = ProcessInfo::new(Some(1), Some("xfwm4".to_string())).unwrap();
+
+	assert_eq!(named.name.as_deref(), Some("xfwm4"));
+
+	if cfg!(target_os = "linux") {
+		let own = ProcessInfo::new(Some(std::process::id()), None).unwrap();
+
+		assert!(own.name.is_some_and(|name| !name.is_empty()));
+	}
+}
@@ -0,0 +1,40 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	any::Any,
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use crate::hotkey::HotKey;
+
+/// An opaque payload attached to a hotkey, see
+/// [`GlobalHotKeyManager::register_with_data`](crate::GlobalHotKeyManager::register_with_data).
+pub type HotKeyData = Arc<dyn Any + Send + Sync>;
+
+/// The payloads of the registered hotkeys, by id.
+#[derive(Default)]
+pub(crate) struct DataStore {
+	data:Mutex<HashMap<u32, HotKeyData>>,
+}
+
+impl DataStore {
+	pub(crate) fn insert(&self, id:u32, data:HotKeyData) {
+		self.data.lock().unwrap().insert(id, data);
+	}
+
+	pub(crate) fn get(&self, id:u32) -> Option<HotKeyData> {
+		self.data.lock().unwrap().get(&id).cloned()
+	}
+
+	/// Drops the payloads of `hotkeys`.
+	pub(crate) fn remove(&self, hotkeys:&[HotKey]) {
+		let mut data = self.data.lock().unwrap();
+
+		for hotkey in hotkeys {
+			data.remove(&hotkey.id());
+		}
+	}
+}
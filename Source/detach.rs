@@ -0,0 +1,114 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::mem::ManuallyDrop;
+
+use crate::GlobalHotKeyManager;
+
+/// A manager given up with [`GlobalHotKeyManager::detach`], whose hotkeys stay
+/// registered and keep emitting events.
+///
+/// Control is regained with [`GlobalHotKeyManager::reattach`]. Dropping the
+/// handle doesn't unregister anything, the hotkeys then stay registered until
+/// the process exits, when the operating system releases them.
+///
+/// A handle only makes sense in the process that detached it, it can't be
+/// handed to another process.
+#[must_use = "dropping the handle leaves the hotkeys registered until the process exits"]
+pub struct DetachedHandle {
+	manager:ManuallyDrop<GlobalHotKeyManager>,
+}
+
+impl std::fmt::Debug for DetachedHandle {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DetachedHandle")
+			.field("registered_count", &self.manager.metrics().registered_count)
+			.finish()
+	}
+}
+
+impl DetachedHandle {
+	pub(crate) fn new(manager:GlobalHotKeyManager) -> Self {
+		Self { manager:ManuallyDrop::new(manager) }
+	}
+
+	pub(crate) fn into_manager(self) -> GlobalHotKeyManager {
+		let mut handle = ManuallyDrop::new(self);
+
+		// The handle is never dropped, so the manager is only taken once.
+		unsafe { ManuallyDrop::take(&mut handle.manager) }
+	}
+}
+
+#[test]
+fn test_detach_reattach() {
+	use crate::hotkey::{Code, HotKey, Modifiers};
+
+	let manager = GlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F15);
+
+	// Without a display server the registration fails, the manager state is still kept.
+	let registered = manager.register(hotkey).is_ok();
+
+	let group = manager.create_group(&[hotkey.with_id(1)]);
+
+	let manager = GlobalHotKeyManager::reattach(manager.detach());
+
+	assert_eq!(manager.metrics().registered_count, usize::from(registered));
+
+	assert!(!matches!(manager.activate_group(group), Err(crate::Error::UnknownGroup(_))));
+
+	if registered {
+		manager.unregister(hotkey).unwrap();
+	}
+}
+
+// Runs this test again in a child process that exits with a detached manager.
+#[test]
+fn test_detached_process_exit() {
+	use std::{
+		process::Command,
+		time::{Duration, Instant},
+	};
+
+	use crate::hotkey::{Code, HotKey, Modifiers};
+
+	const CHILD:&str = "GLOBAL_HOTKEY_DETACHED_CHILD";
+
+	if std::env::var_os(CHILD).is_some() {
+		let manager = GlobalHotKeyManager::new().unwrap();
+
+		let _ = manager.register(HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F16));
+
+		drop(manager.detach());
+
+		return;
+	}
+
+	let mut child = Command::new(std::env::current_exe().unwrap())
+		.args(["--exact", "detach::test_detached_process_exit", "--nocapture"])
+		.env(CHILD, "1")
+		.spawn()
+		.unwrap();
+
+	let deadline = Instant::now() + Duration::from_secs(30);
+
+	// The backend keeps running after the handle is dropped, it mustn't keep the process alive.
+	let status = loop {
+		if let Some(status) = child.try_wait().unwrap() {
+			break status;
+		}
+
+		if Instant::now() > deadline {
+			let _ = child.kill();
+
+			panic!("the process didn't exit with a detached manager");
+		}
+
+		std::thread::sleep(Duration::from_millis(50));
+	};
+
+	assert!(status.success());
+}
@@ -0,0 +1,178 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// A keyboard attached to the machine, as listed by
+/// [`GlobalHotKeyManager::keyboard_devices`](crate::GlobalHotKeyManager::keyboard_devices).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardDevice {
+	/// The platform id of the device, e.g. `/dev/input/event3` on Linux or
+	/// its device path on Windows.
+	pub id:String,
+	pub name:String,
+	/// The USB vendor id, `None` for devices without one, like the built-in
+	/// keyboard of a laptop.
+	pub vendor_id:Option<u16>,
+	pub product_id:Option<u16>,
+}
+
+/// The keyboards a hotkey is restricted to, see
+/// [`HotKeyOptions::device`](crate::HotKeyOptions::device).
+///
+/// ```
+/// use global_hotkey::{DeviceFilter, HotKeyOptions};
+///
+/// // Only the pedal triggers the hotkey, not the same key of the keyboard.
+/// let pedal = DeviceFilter::UsbId { vendor:0x0426, product:0x3011 };
+///
+/// let options = HotKeyOptions::new().device(pedal);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DeviceFilter {
+	/// The devices with this exact [`KeyboardDevice::name`].
+	Name(String),
+	/// The devices with these USB ids, every unit of a model.
+	UsbId { vendor:u16, product:u16 },
+	/// The device with this [`KeyboardDevice::id`], which may change when it's
+	/// plugged again.
+	Id(String),
+}
+
+impl DeviceFilter {
+	/// Returns `true` if presses of `device` may trigger the hotkey.
+	pub fn matches(&self, device:&KeyboardDevice) -> bool {
+		match self {
+			Self::Name(name) => device.name == *name,
+			Self::UsbId { vendor, product } => {
+				device.vendor_id == Some(*vendor) && device.product_id == Some(*product)
+			},
+			Self::Id(id) => device.id == *id,
+		}
+	}
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn keyboard_devices() -> crate::Result<Vec<KeyboardDevice>> {
+	let devices = std::fs::read_to_string("/proc/bus/input/devices")
+		.map_err(|e| crate::Error::os(e.raw_os_error().unwrap_or(0).into(), e.to_string()))?;
+
+	Ok(parse_input_devices(&devices))
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn keyboard_devices() -> crate::Result<Vec<KeyboardDevice>> {
+	crate::platform_impl::keyboard_devices()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn keyboard_devices() -> crate::Result<Vec<KeyboardDevice>> {
+	Err(crate::Error::PlatformUnsupported(
+		"Keyboard devices can only be listed on Linux and Windows".to_string(),
+	))
+}
+
+/// Reads the USB ids of a Windows device path, e.g.
+/// `\\?\HID#VID_046D&PID_C52B&MI_00#...`.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn usb_ids(path:&str) -> (Option<u16>, Option<u16>) {
+	let path = path.to_ascii_uppercase();
+
+	let id = |prefix:&str| {
+		let start = path.find(prefix)? + prefix.len();
+
+		u16::from_str_radix(path.get(start..start + 4)?, 16).ok()
+	};
+
+	(id("VID_"), id("PID_"))
+}
+
+/// Reads the keyboards of `/proc/bus/input/devices`, the devices with a `kbd`
+/// handler and an event node.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_input_devices(devices:&str) -> Vec<KeyboardDevice> {
+	let keyboard = |section:&str| {
+		let mut device = KeyboardDevice {
+			id:String::new(),
+			name:String::new(),
+			vendor_id:None,
+			product_id:None,
+		};
+
+		let mut kbd = false;
+
+		for line in section.lines() {
+			if let Some(ids) = line.strip_prefix("I: ") {
+				for (key, value) in ids.split_whitespace().filter_map(|id| id.split_once('=')) {
+					let value = u16::from_str_radix(value, 16).ok().filter(|id| *id != 0);
+
+					match key {
+						"Vendor" => device.vendor_id = value,
+						"Product" => device.product_id = value,
+						_ => {},
+					}
+				}
+			} else if let Some(name) = line.strip_prefix("N: Name=") {
+				device.name = name.trim_matches('"').to_string();
+			} else if let Some(handlers) = line.strip_prefix("H: Handlers=") {
+				for handler in handlers.split_whitespace() {
+					if handler == "kbd" {
+						kbd = true;
+					} else if handler.starts_with("event") {
+						device.id = format!("/dev/input/{}", handler);
+					}
+				}
+			}
+		}
+
+		(kbd && !device.id.is_empty()).then_some(device)
+	};
+
+	devices.split("\n\n").filter_map(keyboard).collect()
+}
+
+#[test]
+fn test_keyboard_devices() {
+	let devices = "\
+I: Bus=0011 Vendor=0001 Product=0001 Version=ab41
+N: Name=\"AT Translated Set 2 keyboard\"
+P: Phys=isa0060/serio0/input0
+H: Handlers=sysrq kbd leds event3
+B: EV=120013
+
+I: Bus=0003 Vendor=046d Product=c077 Version=0111
+N: Name=\"Logitech USB Optical Mouse\"
+H: Handlers=mouse0 event5
+B: EV=17
+
+I: Bus=0003 Vendor=0426 Product=3011 Version=0110
+N: Name=\"VEC USB Footpedal\"
+H: Handlers=kbd event7
+B: EV=100013
+";
+
+	let keyboards = parse_input_devices(devices);
+
+	assert_eq!(keyboards.len(), 2);
+
+	assert_eq!(keyboards[0].id, "/dev/input/event3");
+
+	assert_eq!(keyboards[0].vendor_id, Some(0x0001));
+
+	let pedal = &keyboards[1];
+
+	assert_eq!(pedal.name, "VEC USB Footpedal");
+
+	assert!(DeviceFilter::UsbId { vendor:0x0426, product:0x3011 }.matches(pedal));
+
+	assert!(DeviceFilter::Name("VEC USB Footpedal".to_string()).matches(pedal));
+
+	assert!(!DeviceFilter::Id("/dev/input/event7".to_string()).matches(&keyboards[0]));
+
+	let path = r"\\?\HID#VID_0426&PID_3011&MI_00#8&2a7c5b1&0&0000#{884b96c3}";
+
+	assert_eq!(usb_ids(path), (Some(0x0426), Some(0x3011)));
+
+	assert_eq!(usb_ids(r"\\?\ACPI#PNP0303#4&1d401fb5&0#{884b96c3}"), (None, None));
+}
@@ -0,0 +1,140 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashSet;
+
+use crate::{
+	HotKeyManager,
+	hotkey::{HotKey, hotkey_set_diff},
+};
+
+/// The registrations turning one set of hotkeys into another, e.g. when a
+/// plugin is updated, leaving alone the hotkeys both sets have.
+///
+/// ```no_run
+/// # use std::collections::HashSet;
+/// # use global_hotkey::{GlobalHotKeyManager, HotKeyDiff, hotkey::HotKey};
+/// # fn main() -> global_hotkey::Result<()> {
+/// let manager = GlobalHotKeyManager::new()?;
+///
+/// let old = ["Ctrl+KeyA", "Ctrl+KeyB"].map(|s| s.parse::<HotKey>().unwrap());
+///
+/// let new = ["Ctrl+KeyB", "Ctrl+KeyC"].map(|s| s.parse::<HotKey>().unwrap());
+///
+/// manager.register_all(&old)?;
+///
+/// let diff = HotKeyDiff::compute(&HashSet::from(old), &HashSet::from(new));
+///
+/// assert_eq!(diff.to_unregister, [old[0]]);
+///
+/// diff.apply(&manager)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HotKeyDiff {
+	/// The hotkeys of the new set only, ordered by id.
+	pub to_register:Vec<HotKey>,
+	/// The hotkeys of the old set only, ordered by id.
+	pub to_unregister:Vec<HotKey>,
+}
+
+impl HotKeyDiff {
+	/// Computes the changes from the `old` set to the `new` one, comparing
+	/// hotkeys like [`hotkey_set_diff`] does.
+	///
+	/// A hotkey keeping its id but not its shortcut is in both lists, its old
+	/// shortcut is unregistered and the new one registered under the same id.
+	pub fn compute(old:&HashSet<HotKey>, new:&HashSet<HotKey>) -> Self {
+		let sorted = |hotkeys:&HashSet<HotKey>| {
+			let mut hotkeys = hotkeys.iter().copied().collect::<Vec<_>>();
+
+			hotkeys.sort_by_key(HotKey::id);
+
+			hotkeys
+		};
+
+		let (to_register, to_unregister) = hotkey_set_diff(&sorted(old), &sorted(new));
+
+		Self { to_register, to_unregister }
+	}
+
+	/// Returns `true` if both sets hold the same hotkeys.
+	pub fn is_empty(&self) -> bool { self.to_register.is_empty() && self.to_unregister.is_empty() }
+
+	/// Unregisters the hotkeys of [`HotKeyDiff::to_unregister`] from `manager`,
+	/// then registers the ones of [`HotKeyDiff::to_register`], so a new hotkey
+	/// sharing the keys of an old one doesn't find them taken.
+	///
+	/// Stops at the first hotkey that fails, the changes made before it are
+	/// kept.
+	pub fn apply<M:HotKeyManager>(&self, manager:&M) -> crate::Result<()> {
+		for hotkey in &self.to_unregister {
+			manager.unregister(*hotkey)?;
+		}
+
+		for hotkey in &self.to_register {
+			manager.register(*hotkey)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[test]
+fn test_hotkey_diff() {
+	use crate::{
+		MockGlobalHotKeyManager,
+		hotkey::{Code, Modifiers},
+	};
+
+	let hotkey = |code| HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), code);
+
+	let (kept, removed, added) = (hotkey(Code::KeyE), hotkey(Code::KeyG), hotkey(Code::KeyF));
+
+	let old = HashSet::from([kept, removed]);
+
+	let new = HashSet::from([kept, added]);
+
+	let diff = HotKeyDiff::compute(&old, &new);
+
+	assert_eq!(diff, HotKeyDiff { to_register:vec![added], to_unregister:vec![removed] });
+
+	assert!(HotKeyDiff::compute(&new, &new).is_empty());
+
+	// The same custom id on other keys.
+	let (before, after) = (kept.with_id(7), added.with_id(7));
+
+	let moved = HotKeyDiff::compute(&HashSet::from([before]), &HashSet::from([after]));
+
+	assert_eq!(moved, HotKeyDiff { to_register:vec![after], to_unregister:vec![before] });
+
+	assert_eq!(HotKeyDiff::compute(&new, &old), HotKeyDiff {
+		to_register:vec![removed],
+		to_unregister:vec![added],
+	});
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	manager.register_all(&[kept, removed]).unwrap();
+
+	diff.apply(&manager).unwrap();
+
+	assert!(manager.is_registered(&kept) && manager.is_registered(&added));
+
+	assert!(!manager.is_registered(&removed));
+
+	// Old hotkeys that are gone already stop the changes.
+	assert!(diff.apply(&manager).is_err());
+
+	manager.unregister_all(&[kept, added]).unwrap();
+
+	manager.register(before).unwrap();
+
+	moved.apply(&manager).unwrap();
+
+	assert!(manager.is_registered(&after) && !manager.is_registered(&before));
+
+	manager.unregister(after).unwrap();
+}
@@ -2,16 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::sync::Arc;
+
+use keyboard_types::Code;
 use thiserror::Error;
 
-use crate::hotkey::HotKey;
+use crate::{
+	Backend,
+	GroupId,
+	SystemShortcut,
+	hotkey::{HotKey, HotKeyParseError},
+};
 
 /// Errors returned by tray-icon.
 #[non_exhaustive]
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum Error {
-	#[error(transparent)]
-	OsError(#[from] std::io::Error),
+	/// An error reported by the operating system, `code` is the raw platform
+	/// error code (`GetLastError` on Windows, `OSStatus` on macOS, `errno` or
+	/// the X11 status elsewhere), `0` if there is none. `io_error` is the
+	/// [`std::io::Error`] it was made from, see [`Error::io_error`].
+	#[error("{message}")]
+	OsError { code:i64, message:String, backend:Backend, io_error:Option<Arc<std::io::Error>> },
 	#[error("{0}")]
 	HotKeyParseError(String),
 	#[error("Couldn't recognize \"{0}\" as a valid HotKey Code, if you feel like it should be, please report this to https://github.com/tauri-apps/global-hotkey")]
@@ -23,15 +35,415 @@ pub enum Error {
 		 only contain one main key"
 	)]
 	UnexpectedHotKeyFormat(String),
-	#[error("{0}")]
-	FailedToRegister(String),
-	#[error("Failed to unregister hotkey: {0:?}")]
+	/// The backend couldn't register `hotkey`, `reason` tells why.
+	#[error("{}", register_failure_message(.hotkey, .reason))]
+	FailedToRegister {
+		hotkey:HotKey,
+		#[source]
+		reason:RegisterFailureReason,
+	},
+	#[error("Failed to unregister hotkey '{0}'")]
 	FailedToUnRegister(HotKey),
-	#[error("HotKey already registerd: {0:?}")]
+	#[error("Hotkey '{0}' is already registered")]
 	AlreadyRegistered(HotKey),
+	/// The hotkey can't be registered because `existing`, registered with the
+	/// same manager, is triggered by the same keys.
+	///
+	/// [`Error::AlreadyRegistered`] is returned instead when the keys are taken
+	/// by another application.
+	#[error("Can't register {attempted}, {existing} (id {}) is already registered", .existing.id())]
+	AlreadyRegisteredConflict { attempted:HotKey, existing:HotKey },
+	/// The hotkey can't be registered because another manager of the same
+	/// process holds the same keys, `by_manager` is its
+	/// [`GlobalHotKeyManager::id`](crate::GlobalHotKeyManager::id).
+	#[error("Can't register {attempted}, its keys are registered by manager {by_manager}")]
+	AlreadyRegisteredInProcess { attempted:HotKey, by_manager:usize },
+	/// Registering would exceed the number of hotkeys the backend can hold, see
+	/// [`Capabilities::max_hotkeys`](crate::Capabilities::max_hotkeys).
+	#[error("Can't register more than {limit} hotkeys")]
+	QuotaExceeded { limit:usize },
 	#[error("Failed to watch media key event")]
 	FailedToWatchMediaKeyEvent,
+	#[error("{0}")]
+	PlatformUnsupported(String),
+	/// The platform backend can't honor one of the requested
+	/// [`HotKeyOptions`](crate::HotKeyOptions), or the
+	/// [`GlobalHotKeyManagerOptions`](crate::GlobalHotKeyManagerOptions) are
+	/// invalid.
+	#[error("{0}")]
+	UnsupportedOption(String),
+	/// The application lacks an OS permission needed for the operation.
+	#[error("{0}")]
+	PermissionDenied(String),
+	/// [`GlobalHotKeyManager::start_recording`](crate::GlobalHotKeyManager::start_recording)
+	/// was called while a [`RecordingHandle`](crate::RecordingHandle) is still alive.
+	#[error("A hotkey recording is already in progress")]
+	RecordingInProgress,
+	/// The [`GroupId`] wasn't created by this manager.
+	#[error("Unknown hotkey group {}", .0.id())]
+	UnknownGroup(GroupId),
+	/// The hotkey is handled by the operating system and would never fire, see
+	/// [`HotKey::conflicts_with_system`].
+	#[error("{0} is reserved by the system")]
+	ReservedBySystem(SystemShortcut),
+	/// A hotkey of [`Code::Power`] was refused, registering it requires
+	/// [`GlobalHotKeyManager::allow_power_key`](crate::GlobalHotKeyManager::allow_power_key).
+	#[error("{0} takes over the power key, which has to be allowed first")]
+	PowerKeyNotAllowed(HotKey),
+	/// [`GlobalHotKeyEvent::from_json`](crate::GlobalHotKeyEvent::from_json)
+	/// was given something else than an event in its JSON form.
+	#[error("Invalid hotkey event JSON: {0}")]
+	InvalidEventJson(String),
+	/// The number isn't the one of any [`HotKeyState`](crate::HotKeyState),
+	/// e.g. it comes from a newer version with more states.
+	#[error("Unknown hotkey state {0}")]
+	UnknownHotKeyState(u8),
+	/// The events can't be posted to the main thread, e.g. because the call
+	/// wasn't made on it.
+	#[error("{0}")]
+	MainThreadUnavailable(String),
+	/// [`HotKey::from_env`](crate::hotkey::HotKey::from_env) found the
+	/// variable unset or not valid unicode.
+	#[error("Can't read a hotkey from the environment variable {name}")]
+	EnvVar {
+		name:String,
+		#[source]
+		source:std::env::VarError,
+	},
+	/// [`HotKeyMap::rebind`](crate::HotKeyMap::rebind) was given an action
+	/// that isn't bound to any hotkey.
+	#[error("The action isn't bound to any hotkey")]
+	UnboundAction,
+}
+
+/// The kind of an [`Error`], to tell errors apart without binding what they
+/// hold, e.g. to handle every [`ErrorKind::AlreadyRegistered`] the same way.
+///
+/// Each kind is named after the [`Error`] variant it stands for, new ones are
+/// added along with the variants.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+	OsError,
+	HotKeyParseError,
+	UnrecognizedHotKeyCode,
+	EmptyHotKeyToken,
+	UnexpectedHotKeyFormat,
+	FailedToRegister,
+	FailedToUnRegister,
+	AlreadyRegistered,
+	AlreadyRegisteredConflict,
+	AlreadyRegisteredInProcess,
+	QuotaExceeded,
+	FailedToWatchMediaKeyEvent,
+	PlatformUnsupported,
+	UnsupportedOption,
+	PermissionDenied,
+	RecordingInProgress,
+	UnknownGroup,
+	ReservedBySystem,
+	PowerKeyNotAllowed,
+	InvalidEventJson,
+	UnknownHotKeyState,
+	MainThreadUnavailable,
+	EnvVar,
+	UnboundAction,
+}
+
+/// Why a hotkey couldn't be registered, see [`Error::FailedToRegister`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum RegisterFailureReason {
+	/// The key has no scancode (virtual key on Windows, keycode on X11) in the
+	/// current keyboard layout.
+	UnknownKey,
+	/// The platform keeps the key to itself whatever the layout, e.g.
+	/// [`Code::Power`] or the brightness keys on Windows and macOS.
+	UnsupportedKey,
+	/// The backend can't tell the left and right modifiers apart, see
+	/// [`HotKey::sides`].
+	UnsupportedModifierSides,
+	/// The backend can't observe the hotkey without consuming it, see
+	/// [`HotKeyOptions::consume`](crate::HotKeyOptions::consume).
+	UnsupportedPassthrough,
+	/// Another client grabbed the keys while the manager was suspended.
+	TakenWhileSuspended,
+	/// The global key event monitor of non-consuming hotkeys couldn't be added.
+	KeyMonitorUnavailable,
+	/// The keyboard event tap of sided hotkeys couldn't be created, which
+	/// requires the Accessibility permission.
+	EventTapUnavailable,
+	/// The operating system considers the hotkey invalid, the
+	/// [`Error::OsError`] tells why.
+	Invalid(Box<Error>),
+}
+
+impl std::fmt::Display for RegisterFailureReason {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			RegisterFailureReason::UnknownKey => "the key is unknown to the platform",
+			RegisterFailureReason::UnsupportedKey => "the key can't be grabbed on this platform",
+			RegisterFailureReason::UnsupportedModifierSides => "sided modifiers are not supported",
+			RegisterFailureReason::UnsupportedPassthrough => {
+				"the hotkey can't be registered without consuming it"
+			},
+			RegisterFailureReason::TakenWhileSuspended => {
+				"the keys were taken by another client while suspended"
+			},
+			RegisterFailureReason::KeyMonitorUnavailable => {
+				"the key event monitor couldn't be added"
+			},
+			RegisterFailureReason::EventTapUnavailable => {
+				"the keyboard event tap couldn't be created"
+			},
+			RegisterFailureReason::Invalid(_) => "the hotkey is invalid",
+		})
+	}
+}
+
+impl RegisterFailureReason {
+	/// Why `key` without a mapping on Windows or macOS fails, the keys the
+	/// system keeps to itself are told apart from the ones of the layout.
+	#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+	pub(crate) fn missing_key(key:Code) -> Self {
+		match key {
+			Code::BrightnessUp
+			| Code::BrightnessDown
+			| Code::Eject
+			| Code::Power
+			| Code::WakeUp => RegisterFailureReason::UnsupportedKey,
+			_ => RegisterFailureReason::UnknownKey,
+		}
+	}
+}
+
+// Implemented by hand so the source of `Invalid` is the error itself rather
+// than its box, which `downcast_ref::<Error>` wouldn't see through.
+impl std::error::Error for RegisterFailureReason {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			RegisterFailureReason::Invalid(error) => Some(&**error),
+			_ => None,
+		}
+	}
+}
+
+// The messages `FailedToRegister` had when it only held a message.
+fn register_failure_message(hotkey:&HotKey, reason:&RegisterFailureReason) -> String {
+	match reason {
+		RegisterFailureReason::UnknownKey if cfg!(target_os = "windows") => {
+			format!("Unable to register hotkey (unknown VKCode for this key: {}).", hotkey.key)
+		},
+		RegisterFailureReason::UnknownKey => {
+			format!(
+				"Unable to register accelerator (unknown scancode for this key: {}).",
+				hotkey.key
+			)
+		},
+		RegisterFailureReason::UnsupportedKey => {
+			format!(
+				"Unable to register {}, the system doesn't let {} be grabbed",
+				hotkey, hotkey.key
+			)
+		},
+		RegisterFailureReason::UnsupportedModifierSides => {
+			format!(
+				"Unable to register hotkey (sided modifiers are not supported on Windows): {}",
+				hotkey
+			)
+		},
+		RegisterFailureReason::UnsupportedPassthrough => {
+			format!(
+				"Unable to register hotkey (sided modifiers or this key are not supported on \
+				 Windows): {}",
+				hotkey
+			)
+		},
+		RegisterFailureReason::TakenWhileSuspended => {
+			format!(
+				"Unable to grab {} on resume, it was taken by another client while suspended",
+				hotkey
+			)
+		},
+		RegisterFailureReason::KeyMonitorUnavailable => {
+			"Unable to add a global key event monitor.".to_string()
+		},
+		RegisterFailureReason::EventTapUnavailable => {
+			"Unable to create a keyboard event tap, make sure the application has the \
+			 Accessibility permission."
+				.to_string()
+		},
+		RegisterFailureReason::Invalid(error) => {
+			format!("Unable to register hotkey, {} is not a valid hotkey: {}", hotkey, error)
+		},
+	}
+}
+
+impl From<HotKeyParseError> for Error {
+	fn from(error:HotKeyParseError) -> Self {
+		match error {
+			HotKeyParseError::UnsupportedKey(key) => Error::UnrecognizedHotKeyCode(key),
+			HotKeyParseError::EmptyToken(hotkey) => Error::EmptyHotKeyToken(hotkey),
+			HotKeyParseError::InvalidFormat(hotkey) => Error::UnexpectedHotKeyFormat(hotkey),
+		}
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(error:std::io::Error) -> Self {
+		Error::OsError {
+			code:error.raw_os_error().map(i64::from).unwrap_or_default(),
+			message:error.to_string(),
+			backend:crate::platform_impl::BACKEND,
+			io_error:Some(Arc::new(error)),
+		}
+	}
+}
+
+impl Error {
+	/// Returns the kind of the error, see [`ErrorKind`].
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Error::OsError { .. } => ErrorKind::OsError,
+			Error::HotKeyParseError(_) => ErrorKind::HotKeyParseError,
+			Error::UnrecognizedHotKeyCode(_) => ErrorKind::UnrecognizedHotKeyCode,
+			Error::EmptyHotKeyToken(_) => ErrorKind::EmptyHotKeyToken,
+			Error::UnexpectedHotKeyFormat(_) => ErrorKind::UnexpectedHotKeyFormat,
+			Error::FailedToRegister { .. } => ErrorKind::FailedToRegister,
+			Error::FailedToUnRegister(_) => ErrorKind::FailedToUnRegister,
+			Error::AlreadyRegistered(_) => ErrorKind::AlreadyRegistered,
+			Error::AlreadyRegisteredConflict { .. } => ErrorKind::AlreadyRegisteredConflict,
+			Error::AlreadyRegisteredInProcess { .. } => ErrorKind::AlreadyRegisteredInProcess,
+			Error::QuotaExceeded { .. } => ErrorKind::QuotaExceeded,
+			Error::FailedToWatchMediaKeyEvent => ErrorKind::FailedToWatchMediaKeyEvent,
+			Error::PlatformUnsupported(_) => ErrorKind::PlatformUnsupported,
+			Error::UnsupportedOption(_) => ErrorKind::UnsupportedOption,
+			Error::PermissionDenied(_) => ErrorKind::PermissionDenied,
+			Error::RecordingInProgress => ErrorKind::RecordingInProgress,
+			Error::UnknownGroup(_) => ErrorKind::UnknownGroup,
+			Error::ReservedBySystem(_) => ErrorKind::ReservedBySystem,
+			Error::PowerKeyNotAllowed(_) => ErrorKind::PowerKeyNotAllowed,
+			Error::InvalidEventJson(_) => ErrorKind::InvalidEventJson,
+			Error::UnknownHotKeyState(_) => ErrorKind::UnknownHotKeyState,
+			Error::MainThreadUnavailable(_) => ErrorKind::MainThreadUnavailable,
+			Error::EnvVar { .. } => ErrorKind::EnvVar,
+			Error::UnboundAction => ErrorKind::UnboundAction,
+		}
+	}
+
+	/// The [`std::io::Error`] an [`Error::OsError`] was made from, e.g. by a
+	/// failing system call of the backend.
+	pub fn io_error(&self) -> Option<&std::io::Error> {
+		match self {
+			Error::OsError { io_error, .. } => io_error.as_deref(),
+			_ => None,
+		}
+	}
+
+	/// An [`Error::OsError`] of the backend of the platform.
+	pub(crate) fn os<S:Into<String>>(code:i64, message:S) -> Self {
+		Error::OsError {
+			code,
+			message:message.into(),
+			backend:crate::platform_impl::BACKEND,
+			io_error:None,
+		}
+	}
+
+	/// Turns [`Error::AlreadyRegistered`] into [`Error::AlreadyRegisteredConflict`]
+	/// if one of the `registered` hotkeys overlaps the attempted one.
+	pub(crate) fn with_conflict<'a, I:IntoIterator<Item = &'a HotKey>>(self, registered:I) -> Self {
+		match self {
+			Error::AlreadyRegistered(attempted) => {
+				match registered.into_iter().find(|h| h.overlaps(&attempted)) {
+					Some(existing) => {
+						Error::AlreadyRegisteredConflict { attempted, existing:*existing }
+					},
+					None => Error::AlreadyRegistered(attempted),
+				}
+			},
+			error => error,
+		}
+	}
 }
 
 /// Convenient type alias of Result type for tray-icon.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[test]
+fn test_failed_to_register_source() {
+	use std::error::Error as _;
+
+	let hotkey = "Shift+KeyD".parse::<HotKey>().unwrap();
+
+	let error = Error::FailedToRegister {
+		hotkey,
+		reason:RegisterFailureReason::Invalid(Box::new(Error::os(-9870, "'inv'"))),
+	};
+
+	assert_eq!(
+		error.to_string(),
+		format!("Unable to register hotkey, {} is not a valid hotkey: 'inv'", hotkey)
+	);
+
+	let reason = error.source().unwrap();
+
+	assert_eq!(reason.to_string(), "the hotkey is invalid");
+
+	let os_error = reason.source().and_then(|e| e.downcast_ref::<Error>());
+
+	assert!(matches!(
+		os_error,
+		Some(Error::OsError { code:-9870, backend, .. })
+			if *backend == crate::platform_impl::BACKEND
+	));
+
+	assert!(Error::os(0, "").source().is_none());
+
+	let error = Error::from(std::io::Error::from_raw_os_error(2));
+
+	assert!(matches!(error, Error::OsError { code:2, .. }));
+
+	assert_eq!(error.io_error().and_then(std::io::Error::raw_os_error), Some(2));
+
+	assert!(Error::os(2, "").io_error().is_none());
+}
+
+#[test]
+fn test_error_kind() {
+	let hotkey = "Shift+KeyD".parse::<HotKey>().unwrap();
+
+	assert_eq!(Error::AlreadyRegistered(hotkey).kind(), ErrorKind::AlreadyRegistered);
+
+	let conflict = Error::AlreadyRegistered(hotkey).with_conflict(&[hotkey]);
+
+	assert_eq!(conflict.kind(), ErrorKind::AlreadyRegisteredConflict);
+
+	let error = Error::FailedToRegister { hotkey, reason:RegisterFailureReason::UnknownKey };
+
+	assert_eq!(error.kind(), ErrorKind::FailedToRegister);
+
+	assert_eq!(Error::os(0, "").kind(), ErrorKind::OsError);
+
+	assert_eq!(Error::RecordingInProgress.kind(), ErrorKind::RecordingInProgress);
+}
+
+#[test]
+fn test_error_display() {
+	let hotkey = "Shift+KeyD".parse::<HotKey>().unwrap();
+
+	assert_eq!(
+		Error::AlreadyRegistered(hotkey).to_string(),
+		"Hotkey 'shift+KeyD' is already registered"
+	);
+
+	assert_eq!(
+		Error::FailedToUnRegister(hotkey.with_id(7)).to_string(),
+		"Failed to unregister hotkey 'shift+KeyD'"
+	);
+
+	// The hotkeys other errors hold are written the same way.
+	let conflict = Error::AlreadyRegistered(hotkey.with_id(7)).with_conflict(&[hotkey]);
+
+	assert!(conflict.to_string().starts_with("Can't register shift+KeyD, shift+KeyD (id"));
+}
@@ -0,0 +1,32 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::panic::{self, AssertUnwindSafe};
+
+/// A task run on the thread of the backend, see
+/// [`GlobalHotKeyManager::send_raw`](crate::GlobalHotKeyManager::send_raw).
+///
+/// The messages of the backends themselves aren't exposed, they change with
+/// the backend, so an extension is any closure run in their place.
+pub struct ExtensionMessage {
+	task:Box<dyn FnOnce() + Send>,
+}
+
+impl ExtensionMessage {
+	/// Creates a message running `task` once the backend thread gets to it.
+	pub fn new<F:FnOnce() + Send + 'static>(task:F) -> Self { Self { task:Box::new(task) } }
+
+	// A panicking task mustn't take the backend thread down.
+	pub(crate) fn run(self) {
+		if panic::catch_unwind(AssertUnwindSafe(self.task)).is_err() {
+			log::error!("An extension message panicked on the backend thread");
+		}
+	}
+}
+
+impl std::fmt::Debug for ExtensionMessage {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ExtensionMessage").finish_non_exhaustive()
+	}
+}
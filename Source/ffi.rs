@@ -0,0 +1,373 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A flat C API, enabled with the `ffi` feature, declared in
+//! `include/global_hotkey.h`.
+//!
+//! Build a library C can link against with
+//! `cargo rustc --release --features ffi --crate-type staticlib` (or `cdylib`).
+//!
+//! ## Ownership
+//!
+//! - Handles returned through out pointers (`GhkManager*`, `GhkHotKey*`) are
+//!   owned by the caller and released with the matching `*_free` function.
+//! - Strings passed in are borrowed for the duration of the call only.
+//! - The string returned by [`ghk_last_error_message`] is owned by the
+//!   library and stays valid until the next call on the same thread.
+//!
+//! Every function returns a [`GhkStatus`], panics are caught at the boundary
+//! and reported as [`GhkStatus::Panic`].
+
+use std::{
+	cell::RefCell,
+	ffi::{CStr, CString, c_char, c_void},
+	panic::{self, AssertUnwindSafe},
+	ptr,
+	sync::{Mutex, Once},
+};
+
+use crate::{
+	GlobalHotKeyEvent,
+	GlobalHotKeyManager,
+	HotKeyState,
+	RegisterFailureReason,
+	hotkey::HotKey,
+};
+
+/// The result of every function of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GhkStatus {
+	Ok = 0,
+	/// A pointer was null or a string wasn't valid UTF-8.
+	InvalidArgument = 1,
+	/// The hotkey string couldn't be parsed.
+	ParseError = 2,
+	/// The hotkey is already registered, by this application or another one.
+	AlreadyRegistered = 3,
+	/// The platform or the hotkey isn't supported.
+	Unsupported = 4,
+	/// The operating system reported an error.
+	OsError = 5,
+	/// The library panicked, the handles involved shouldn't be used anymore.
+	Panic = 6,
+	/// Any other error, see [`ghk_last_error_message`].
+	Other = 7,
+}
+
+impl From<&crate::Error> for GhkStatus {
+	fn from(error:&crate::Error) -> Self {
+		match error {
+			crate::Error::HotKeyParseError(_)
+			| crate::Error::UnrecognizedHotKeyCode(_)
+			| crate::Error::EmptyHotKeyToken(_)
+			| crate::Error::UnexpectedHotKeyFormat(_) => GhkStatus::ParseError,
+			crate::Error::AlreadyRegistered(_)
+			| crate::Error::AlreadyRegisteredConflict { .. }
+			| crate::Error::AlreadyRegisteredInProcess { .. } => GhkStatus::AlreadyRegistered,
+			crate::Error::PlatformUnsupported(_) | crate::Error::UnsupportedOption(_) => {
+				GhkStatus::Unsupported
+			},
+			crate::Error::OsError { .. }
+			| crate::Error::FailedToRegister { reason:RegisterFailureReason::Invalid(_), .. } => {
+				GhkStatus::OsError
+			},
+			crate::Error::FailedToRegister {
+				reason:
+					RegisterFailureReason::UnknownKey
+					| RegisterFailureReason::UnsupportedModifierSides
+					| RegisterFailureReason::UnsupportedPassthrough,
+				..
+			} => GhkStatus::Unsupported,
+			_ => GhkStatus::Other,
+		}
+	}
+}
+
+/// A manager, see [`GlobalHotKeyManager`].
+pub struct GhkManager(GlobalHotKeyManager);
+
+/// A parsed hotkey, see [`HotKey`].
+pub struct GhkHotKey(HotKey);
+
+/// Called for every hotkey event with the hotkey id, `0` for pressed and `1`
+/// for released, and the `user` pointer given to [`ghk_set_callback`].
+pub type GhkCallback = extern "C" fn(id:u32, state:i32, user:*mut c_void);
+
+thread_local! {
+	static LAST_ERROR:RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// The user pointer is only handed back to the C callback, which is
+// responsible for its thread safety.
+struct Callback(GhkCallback, *mut c_void);
+
+unsafe impl Send for Callback {}
+
+static CALLBACK:Mutex<Option<Callback>> = Mutex::new(None);
+
+static CALLBACK_HANDLER:Once = Once::new();
+
+fn set_last_error(message:String) {
+	// Interior nul bytes would truncate the message, drop them instead.
+	let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// Runs `f` with the last error cleared, turning errors and panics into a status.
+fn guard<F:FnOnce() -> Result<(), (GhkStatus, String)>>(f:F) -> GhkStatus {
+	LAST_ERROR.with(|last| *last.borrow_mut() = None);
+
+	match panic::catch_unwind(AssertUnwindSafe(f)) {
+		Ok(Ok(())) => GhkStatus::Ok,
+
+		Ok(Err((status, message))) => {
+			set_last_error(message);
+
+			status
+		},
+
+		Err(payload) => {
+			let message = payload
+				.downcast_ref::<&str>()
+				.map(|s| s.to_string())
+				.or_else(|| payload.downcast_ref::<String>().cloned())
+				.unwrap_or_else(|| "unknown panic".to_string());
+
+			set_last_error(format!("global-hotkey panicked: {}", message));
+
+			GhkStatus::Panic
+		},
+	}
+}
+
+fn error(e:crate::Error) -> (GhkStatus, String) { (GhkStatus::from(&e), e.to_string()) }
+
+fn null_argument(name:&str) -> (GhkStatus, String) {
+	(GhkStatus::InvalidArgument, format!("`{}` is null", name))
+}
+
+/// Creates a manager and stores it in `*out`.
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ghk_manager_new(out:*mut *mut GhkManager) -> GhkStatus {
+	guard(|| {
+		if out.is_null() {
+			return Err(null_argument("out"));
+		}
+
+		let manager = GlobalHotKeyManager::new().map_err(error)?;
+
+		*out = Box::into_raw(Box::new(GhkManager(manager)));
+
+		Ok(())
+	})
+}
+
+/// Drops a manager, which unregisters its hotkeys. `manager` may be null.
+///
+/// # Safety
+///
+/// `manager` must come from [`ghk_manager_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ghk_manager_free(manager:*mut GhkManager) -> GhkStatus {
+	guard(|| {
+		if !manager.is_null() {
+			drop(Box::from_raw(manager));
+		}
+
+		Ok(())
+	})
+}
+
+/// Parses a hotkey such as `"shift+alt+KeyQ"` and stores it in `*out`.
+///
+/// # Safety
+///
+/// `hotkey` must be a nul-terminated string and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ghk_hotkey_parse(
+	hotkey:*const c_char,
+	out:*mut *mut GhkHotKey,
+) -> GhkStatus {
+	guard(|| {
+		if hotkey.is_null() {
+			return Err(null_argument("hotkey"));
+		}
+
+		if out.is_null() {
+			return Err(null_argument("out"));
+		}
+
+		let hotkey = CStr::from_ptr(hotkey)
+			.to_str()
+			.map_err(|e| (GhkStatus::InvalidArgument, e.to_string()))?;
+
+		let hotkey = hotkey.parse::<HotKey>().map_err(|e| (GhkStatus::ParseError, e.to_string()))?;
+
+		*out = Box::into_raw(Box::new(GhkHotKey(hotkey)));
+
+		Ok(())
+	})
+}
+
+/// Returns the id events of `hotkey` are emitted with, `0` if it is null.
+///
+/// # Safety
+///
+/// `hotkey` must come from [`ghk_hotkey_parse`].
+#[no_mangle]
+pub unsafe extern "C" fn ghk_hotkey_id(hotkey:*const GhkHotKey) -> u32 {
+	hotkey.as_ref().map_or(0, |hotkey| hotkey.0.id())
+}
+
+/// Drops a hotkey, registrations made with it are kept. `hotkey` may be null.
+///
+/// # Safety
+///
+/// `hotkey` must come from [`ghk_hotkey_parse`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ghk_hotkey_free(hotkey:*mut GhkHotKey) -> GhkStatus {
+	guard(|| {
+		if !hotkey.is_null() {
+			drop(Box::from_raw(hotkey));
+		}
+
+		Ok(())
+	})
+}
+
+/// Registers `hotkey` with `manager`.
+///
+/// # Safety
+///
+/// `manager` and `hotkey` must come from [`ghk_manager_new`] and
+/// [`ghk_hotkey_parse`].
+#[no_mangle]
+pub unsafe extern "C" fn ghk_register(
+	manager:*const GhkManager,
+	hotkey:*const GhkHotKey,
+) -> GhkStatus {
+	guard(|| {
+		let manager = manager.as_ref().ok_or_else(|| null_argument("manager"))?;
+
+		let hotkey = hotkey.as_ref().ok_or_else(|| null_argument("hotkey"))?;
+
+		manager.0.register(hotkey.0).map_err(error)
+	})
+}
+
+/// Unregisters `hotkey` from `manager`.
+///
+/// # Safety
+///
+/// `manager` and `hotkey` must come from [`ghk_manager_new`] and
+/// [`ghk_hotkey_parse`].
+#[no_mangle]
+pub unsafe extern "C" fn ghk_unregister(
+	manager:*const GhkManager,
+	hotkey:*const GhkHotKey,
+) -> GhkStatus {
+	guard(|| {
+		let manager = manager.as_ref().ok_or_else(|| null_argument("manager"))?;
+
+		let hotkey = hotkey.as_ref().ok_or_else(|| null_argument("hotkey"))?;
+
+		manager.0.unregister(hotkey.0).map_err(error)
+	})
+}
+
+/// Sets the function called for every hotkey event, replacing the previous
+/// one. A null `callback` drops the events.
+///
+/// The callback runs on the thread delivering the events, which depends on
+/// the platform, `user` is passed back untouched.
+///
+/// Events go through [`GlobalHotKeyEvent::set_event_handler`], so this has no
+/// effect if the application installed its own handler from Rust.
+#[no_mangle]
+pub extern "C" fn ghk_set_callback(callback:Option<GhkCallback>, user:*mut c_void) -> GhkStatus {
+	guard(|| {
+		*CALLBACK.lock().unwrap() = callback.map(|callback| Callback(callback, user));
+
+		CALLBACK_HANDLER.call_once(|| {
+			GlobalHotKeyEvent::set_event_handler(Some(|event:GlobalHotKeyEvent| {
+				if let Some(Callback(callback, user)) = &*CALLBACK.lock().unwrap() {
+					let state = match event.state() {
+						HotKeyState::Pressed => 0,
+						HotKeyState::Released => 1,
+					};
+
+					callback(event.id(), state, *user);
+				}
+			}));
+		});
+
+		Ok(())
+	})
+}
+
+/// Returns the message of the last error on this thread, or null if the last
+/// call succeeded.
+///
+/// The string is owned by the library and valid until the next call on the
+/// same thread.
+#[no_mangle]
+pub extern "C" fn ghk_last_error_message() -> *const c_char {
+	LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+#[test]
+fn test_ffi() {
+	let valid = CString::new("shift+alt+KeyQ").unwrap();
+
+	let invalid_str = CString::new("shift+alt").unwrap();
+
+	unsafe {
+		let mut hotkey = ptr::null_mut();
+
+		assert_eq!(ghk_hotkey_parse(valid.as_ptr(), &mut hotkey), GhkStatus::Ok);
+		assert!(ghk_last_error_message().is_null());
+		assert_eq!(ghk_hotkey_id(hotkey), "shift+alt+KeyQ".parse::<HotKey>().unwrap().id());
+
+		let mut invalid = ptr::null_mut();
+
+		assert_eq!(ghk_hotkey_parse(invalid_str.as_ptr(), &mut invalid), GhkStatus::ParseError);
+		assert!(invalid.is_null());
+		assert!(!CStr::from_ptr(ghk_last_error_message()).to_bytes().is_empty());
+
+		assert_eq!(ghk_hotkey_parse(ptr::null(), &mut invalid), GhkStatus::InvalidArgument);
+		assert_eq!(ghk_register(ptr::null(), hotkey), GhkStatus::InvalidArgument);
+
+		assert_eq!(ghk_hotkey_free(hotkey), GhkStatus::Ok);
+		assert_eq!(ghk_hotkey_free(ptr::null_mut()), GhkStatus::Ok);
+		assert_eq!(ghk_manager_free(ptr::null_mut()), GhkStatus::Ok);
+	}
+
+	assert_eq!(guard(|| panic!("boom")), GhkStatus::Panic);
+}
+
+// Compiles the C smoke test against the header when a C compiler is around.
+#[test]
+fn test_ffi_header() {
+	let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+
+	let status = std::process::Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".into()))
+		.arg("-fsyntax-only")
+		.arg("-Wall")
+		.arg("-Werror")
+		.arg("-I")
+		.arg(root.join("include"))
+		.arg(root.join("include/smoke.c"))
+		.status();
+
+	match status {
+		Ok(status) => assert!(status.success(), "include/smoke.c doesn't compile"),
+		Err(e) => eprintln!("skipping the C smoke test, no C compiler: {}", e),
+	}
+}
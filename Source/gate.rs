@@ -0,0 +1,166 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::HashMap,
+	panic::{self, AssertUnwindSafe},
+	sync::{
+		Arc,
+		RwLock,
+		atomic::{AtomicBool, Ordering},
+	},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{GlobalHotKeyEvent, HotKeyState};
+
+/// The predicate of [`HotKeyOptions::gate`](crate::HotKeyOptions::gate),
+/// options holding the same one are equal.
+#[derive(Clone)]
+pub(crate) struct Gate(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl Gate {
+	pub(crate) fn new<F:Fn() -> bool + Send + Sync + 'static>(gate:F) -> Self {
+		Self(Arc::new(gate))
+	}
+
+	// A panicking gate is closed, the panic mustn't take the event thread down.
+	fn open(&self, id:u32) -> bool {
+		panic::catch_unwind(AssertUnwindSafe(|| (self.0)())).unwrap_or_else(|_| {
+			log::error!("The gate of hotkey {:#010x} panicked, its press is dropped", id);
+
+			false
+		})
+	}
+}
+
+impl std::fmt::Debug for Gate {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str("Gate(..)") }
+}
+
+impl PartialEq for Gate {
+	fn eq(&self, other:&Self) -> bool { Arc::ptr_eq(&self.0, &other.0) }
+}
+
+impl Eq for Gate {}
+
+// The gate of a registered hotkey, along with whether its last press went
+// through, which its release follows.
+struct Entry {
+	gate:Gate,
+	passed:AtomicBool,
+}
+
+static GATES:Lazy<RwLock<HashMap<u32, Arc<Entry>>>> = Lazy::new(Default::default);
+
+/// Sets the gate of the hotkey with this id, removing it for `None`.
+pub(crate) fn set(id:u32, gate:Option<&Gate>) {
+	let mut gates = GATES.write().unwrap();
+
+	match gate {
+		Some(gate) => {
+			gates.insert(id, Arc::new(Entry { gate:gate.clone(), passed:AtomicBool::new(false) }));
+		},
+		None => {
+			gates.remove(&id);
+		},
+	}
+}
+
+/// Whether `event` is emitted. Presses ask the gate of their hotkey, releases
+/// go through if their press did, so every emitted press gets its release.
+pub(crate) fn admit(event:GlobalHotKeyEvent) -> bool {
+	// Cloned so the gate may register or unregister hotkeys itself.
+	let Some(entry) = GATES.read().unwrap().get(&event.id()).cloned() else {
+		return true;
+	};
+
+	match event.state() {
+		HotKeyState::Pressed => {
+			let open = entry.gate.open(event.id());
+
+			entry.passed.store(open, Ordering::Relaxed);
+
+			open
+		},
+		HotKeyState::Released => entry.passed.swap(false, Ordering::Relaxed),
+	}
+}
+
+/// Whether the last press of the hotkey with this id went through its gate,
+/// `true` for hotkeys without one.
+#[cfg_attr(
+	not(any(
+		target_os = "linux",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "openbsd",
+		target_os = "netbsd"
+	)),
+	allow(dead_code)
+)]
+pub(crate) fn passed(id:u32) -> bool {
+	GATES.read().unwrap().get(&id).map_or(true, |entry| entry.passed.load(Ordering::Relaxed))
+}
+
+#[test]
+fn test_gate() {
+	use std::sync::atomic::AtomicUsize;
+
+	let open = Arc::new(AtomicBool::new(true));
+
+	let calls = Arc::new(AtomicUsize::new(0));
+
+	let gate = {
+		let (open, calls) = (open.clone(), calls.clone());
+
+		Gate::new(move || {
+			calls.fetch_add(1, Ordering::Relaxed);
+
+			open.load(Ordering::Relaxed)
+		})
+	};
+
+	// Not a hotkey id, the ids of other tests aren't touched.
+	let id = 0xffff_0001;
+
+	let pressed = GlobalHotKeyEvent::new(id, HotKeyState::Pressed);
+
+	let released = GlobalHotKeyEvent::new(id, HotKeyState::Released);
+
+	assert!(admit(pressed) && passed(id));
+
+	set(id, Some(&gate));
+
+	assert!(admit(pressed) && passed(id));
+
+	// The release follows its press, whatever the gate says by then.
+	open.store(false, Ordering::Relaxed);
+
+	assert!(admit(released));
+
+	assert!(!admit(pressed) && !passed(id));
+
+	open.store(true, Ordering::Relaxed);
+
+	assert!(!admit(released));
+
+	// Only presses ask the gate.
+	assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+	assert_eq!(gate, gate.clone());
+
+	assert_ne!(gate, Gate::new(|| true));
+
+	let panicking = Gate::new(|| panic!("closed"));
+
+	set(id, Some(&panicking));
+
+	assert!(!admit(pressed));
+
+	set(id, None);
+
+	assert!(admit(pressed));
+}
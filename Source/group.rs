@@ -0,0 +1,142 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::BTreeMap,
+	sync::{
+		Mutex,
+		atomic::{AtomicU32, Ordering},
+	},
+};
+
+use crate::hotkey::HotKey;
+
+/// Identifies a group of hotkeys created with
+/// [`GlobalHotKeyManager::create_group`](crate::GlobalHotKeyManager::create_group).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupId(u32);
+
+impl GroupId {
+	/// Returns the numeric value of this id, unique within the manager that created it.
+	pub fn id(&self) -> u32 { self.0 }
+}
+
+/// A set of hotkeys registered and unregistered together.
+#[derive(Debug)]
+pub(crate) struct HotKeyGroup {
+	hotkeys:Vec<HotKey>,
+	active:bool,
+}
+
+/// The groups of a manager, the actual registration is done by the caller.
+#[derive(Debug, Default)]
+pub(crate) struct HotKeyGroups {
+	next_id:AtomicU32,
+	groups:Mutex<BTreeMap<GroupId, HotKeyGroup>>,
+}
+
+impl HotKeyGroups {
+	pub(crate) fn create(&self, hotkeys:&[HotKey]) -> GroupId {
+		let id = GroupId(self.next_id.fetch_add(1, Ordering::SeqCst));
+
+		self.groups
+			.lock()
+			.unwrap()
+			.insert(id, HotKeyGroup { hotkeys:hotkeys.to_vec(), active:false });
+
+		id
+	}
+
+	/// Calls `register` with the hotkeys of an inactive group, which is only
+	/// marked active if it succeeds.
+	pub(crate) fn activate<F:FnOnce(&[HotKey]) -> crate::Result<()>>(
+		&self,
+		id:GroupId,
+		register:F,
+	) -> crate::Result<()> {
+		let mut groups = self.groups.lock().unwrap();
+
+		let group = groups.get_mut(&id).ok_or(crate::Error::UnknownGroup(id))?;
+
+		if !group.active {
+			register(&group.hotkeys)?;
+
+			group.active = true;
+		}
+
+		Ok(())
+	}
+
+	/// Calls `unregister` with the hotkeys of an active group, which is marked
+	/// inactive even if some of them were already unregistered individually.
+	pub(crate) fn deactivate<F:FnOnce(&[HotKey]) -> crate::Result<()>>(
+		&self,
+		id:GroupId,
+		unregister:F,
+	) -> crate::Result<()> {
+		let mut groups = self.groups.lock().unwrap();
+
+		let group = groups.get_mut(&id).ok_or(crate::Error::UnknownGroup(id))?;
+
+		if group.active {
+			group.active = false;
+
+			unregister(&group.hotkeys)?;
+		}
+
+		Ok(())
+	}
+
+	/// Marks every group inactive, their hotkeys were unregistered by the caller.
+	pub(crate) fn deactivate_all(&self) {
+		for group in self.groups.lock().unwrap().values_mut() {
+			group.active = false;
+		}
+	}
+
+	pub(crate) fn group_of(&self, hotkey_id:u32) -> Option<GroupId> {
+		self.groups
+			.lock()
+			.unwrap()
+			.iter()
+			.find(|(_, group)| group.active && group.hotkeys.iter().any(|h| h.id() == hotkey_id))
+			.map(|(id, _)| *id)
+	}
+}
+
+#[test]
+fn test_hotkey_groups() {
+	use crate::hotkey::{Code, Modifiers};
+
+	let groups = HotKeyGroups::default();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::KeyE);
+
+	let editing = groups.create(&[hotkey]);
+
+	assert!(groups.activate(editing, |_| Err(crate::Error::AlreadyRegistered(hotkey))).is_err());
+
+	assert_eq!(groups.group_of(hotkey.id()), None);
+
+	groups.activate(editing, |hotkeys| {
+		assert_eq!(hotkeys, [hotkey]);
+
+		Ok(())
+	})
+	.unwrap();
+
+	// Already active, nothing to register.
+	groups.activate(editing, |_| unreachable!()).unwrap();
+
+	assert_eq!(groups.group_of(hotkey.id()), Some(editing));
+
+	groups.deactivate(editing, |_| Ok(())).unwrap();
+
+	assert_eq!(groups.group_of(hotkey.id()), None);
+
+	assert!(matches!(
+		groups.activate(GroupId(42), |_| Ok(())),
+		Err(crate::Error::UnknownGroup(GroupId(42)))
+	));
+}
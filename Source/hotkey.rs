@@ -27,10 +27,18 @@
 //! # assert!("shift+KeyQ+alt".parse::<HotKey>().is_err());
 //! ```
 
-use std::{borrow::Borrow, fmt::Display, hash::Hash, str::FromStr};
+use std::{
+	borrow::Borrow,
+	collections::{HashMap, HashSet},
+	fmt::Display,
+	hash::Hash,
+	str::FromStr,
+};
 
 pub use keyboard_types::{Code, Modifiers};
 
+use crate::canonical;
+
 #[cfg(target_os = "macos")]
 pub const CMD_OR_CTRL:Modifiers = Modifiers::SUPER;
 #[cfg(not(target_os = "macos"))]
@@ -49,19 +57,172 @@ pub enum HotKeyParseError {
 	InvalidFormat(String),
 }
 
+/// Which physical side of the keyboard a modifier key has to be pressed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ModifierSide {
+	/// Either the left or the right key satisfies the modifier.
+	#[default]
+	Either,
+	/// Only the left key satisfies the modifier.
+	Left,
+	/// Only the right key satisfies the modifier.
+	Right,
+}
+
+impl ModifierSide {
+	const fn bits(self) -> u32 {
+		match self {
+			ModifierSide::Either => 0,
+			ModifierSide::Left => 1,
+			ModifierSide::Right => 2,
+		}
+	}
+
+	fn overlaps(self, other:ModifierSide) -> bool {
+		self == ModifierSide::Either || other == ModifierSide::Either || self == other
+	}
+}
+
+/// Per-modifier side requirements of a [`HotKey`].
+///
+/// Sides are only meaningful for modifiers that are part of [`HotKey::mods`],
+/// check [`Capabilities::sided_modifiers`](crate::Capabilities::sided_modifiers)
+/// to know if the current platform can distinguish them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModifierSides {
+	pub shift:ModifierSide,
+	pub control:ModifierSide,
+	pub alt:ModifierSide,
+	pub super_key:ModifierSide,
+}
+
+impl ModifierSides {
+	/// No side requirements, any side satisfies every modifier.
+	pub const EITHER:Self = Self {
+		shift:ModifierSide::Either,
+		control:ModifierSide::Either,
+		alt:ModifierSide::Either,
+		super_key:ModifierSide::Either,
+	};
+
+	/// Returns `true` if none of the modifiers require a specific side.
+	pub fn is_either(&self) -> bool { *self == Self::EITHER }
+
+	/// Returns the side required for `modifier`, which should be one of
+	/// [`Modifiers::SHIFT`], [`Modifiers::CONTROL`], [`Modifiers::ALT`] or
+	/// [`Modifiers::SUPER`].
+	pub fn side(&self, modifier:Modifiers) -> ModifierSide {
+		match self.slot(modifier) {
+			Some(i) => self.as_array()[i],
+			None => ModifierSide::Either,
+		}
+	}
+
+	/// Returns `true` if a key press could satisfy both `self` and `other`.
+	pub fn overlaps(&self, other:&ModifierSides) -> bool {
+		self.as_array().iter().zip(other.as_array()).all(|(a, b)| a.overlaps(b))
+	}
+
+	fn set(&mut self, modifier:Modifiers, side:ModifierSide) {
+		match self.slot(modifier) {
+			Some(0) => self.shift = side,
+			Some(1) => self.control = side,
+			Some(2) => self.alt = side,
+			Some(3) => self.super_key = side,
+			_ => {},
+		}
+	}
+
+	fn slot(&self, modifier:Modifiers) -> Option<usize> {
+		if modifier == Modifiers::SHIFT {
+			Some(0)
+		} else if modifier == Modifiers::CONTROL {
+			Some(1)
+		} else if modifier == Modifiers::ALT {
+			Some(2)
+		} else if modifier == Modifiers::SUPER || modifier == Modifiers::META {
+			Some(3)
+		} else {
+			None
+		}
+	}
+
+	fn as_array(&self) -> [ModifierSide; 4] { [self.shift, self.control, self.alt, self.super_key] }
+
+	// Written out rather than folded over `as_array` to stay usable in `const`.
+	pub(crate) const fn bits(&self) -> u32 {
+		self.shift.bits()
+			| self.control.bits() << 2
+			| self.alt.bits() << 4
+			| self.super_key.bits() << 6
+	}
+}
+
 /// A keyboard shortcut that consists of an optional combination
 /// of modifier keys (provided by [`Modifiers`](crate::hotkey::Modifiers)) and
 /// one key ([`Code`](crate::hotkey::Code)).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Hotkeys parse from strings like `shift+alt+KeyQ`. The shifted symbols of
+/// the US layout stand for their key along with [`Modifiers::SHIFT`], so
+/// `Ctrl+?` is the same hotkey (and id) as `Ctrl+Shift+Slash`. Writing `Shift`
+/// as well changes nothing, and a sided `LeftShift` or `RightShift` keeps its
+/// side.
+///
+/// ## Platform-specific:
+///
+/// Some keys are handled by the operating system before any application:
+///
+/// | Key | Windows | macOS | Linux (X11) |
+/// |---|---|---|---|
+/// | [`Code::PrintScreen`] | Keyboard hook | Yes | Yes |
+/// | [`Code::Pause`] | Yes | Unsupported | Yes |
+/// | [`Code::ContextMenu`] | Yes | Yes | Yes |
+/// | [`Code::WakeUp`] | Unsupported | Unsupported | Yes |
+///
+/// - **Windows**: `RegisterHotKey` only reports `PrintScreen` once released,
+///   so it's matched by the low-level keyboard hook like a non-consuming
+///   hotkey. It's still swallowed unless registered with
+///   [`HotKeyOptions::consume`](crate::HotKeyOptions::consume) off.
+/// - **Windows / Linux**: `Control+Pause` produces `Break`, which still
+///   triggers a hotkey registered as `Control+Pause`.
+/// - **Linux (X11)**: `WakeUp` is also handled by the power manager of some
+///   desktops (e.g. to turn the screen back on). A grab on it may take the key
+///   away from it, or fail with [`Error::AlreadyRegistered`](crate::Error::AlreadyRegistered)
+///   if it grabbed the key first.
+#[derive(Clone, Copy)]
 pub struct HotKey {
 	/// The hotkey modifiers.
 	pub mods:Modifiers,
+	/// The side each modifier has to be pressed on.
+	pub sides:ModifierSides,
 	/// The hotkey key.
 	pub key:Code,
 	/// The hotkey id.
 	pub id:u32,
 }
 
+// `META` is the same key as `SUPER` to every backend, hotkeys holding either
+// are equal whichever one they were created with.
+impl PartialEq for HotKey {
+	fn eq(&self, other:&Self) -> bool {
+		canonical::fold(self.mods) == canonical::fold(other.mods)
+			&& self.sides == other.sides
+			&& self.key == other.key
+			&& self.id == other.id
+	}
+}
+
+impl Eq for HotKey {}
+
+impl std::hash::Hash for HotKey {
+	fn hash<H:std::hash::Hasher>(&self, state:&mut H) {
+		canonical::fold(self.mods).hash(state);
+		self.sides.hash(state);
+		self.key.hash(state);
+		self.id.hash(state);
+	}
+}
+
 #[cfg(feature = "serde")]
 impl<'de> serde::Deserialize<'de> for HotKey {
 	fn deserialize<D>(deserializer:D) -> Result<Self, D::Error>
@@ -88,23 +249,116 @@ impl HotKey {
 	/// Creates a new hotkey to define keyboard shortcuts throughout your
 	/// application. Only [`Modifiers::ALT`], [`Modifiers::SHIFT`],
 	/// [`Modifiers::CONTROL`], and [`Modifiers::SUPER`]
+	///
+	/// [`Modifiers::META`] is rewritten to [`Modifiers::SUPER`], see
+	/// [`HotKey::new_exact`] to keep it.
 	pub fn new(mods:Option<Modifiers>, key:Code) -> Self {
-		let mut mods = mods.unwrap_or_else(Modifiers::empty);
+		Self::from_parts(mods.unwrap_or_else(Modifiers::empty), key)
+	}
+
+	/// Creates a hotkey like [`HotKey::new`], keeping [`Modifiers::META`]
+	/// instead of rewriting it to [`Modifiers::SUPER`], e.g. for modifiers
+	/// coming from a browser where they are distinct.
+	///
+	/// Both are still the same key to every backend, so the hotkey has the id
+	/// of its `SUPER` counterpart and is equal to it. It's written with `meta`
+	/// though, which parses back to the same `META` hotkey.
+	///
+	/// ```
+	/// use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+	///
+	/// let meta = HotKey::new_exact(Some(Modifiers::META), Code::KeyK);
+	///
+	/// assert_eq!(meta.mods, Modifiers::META);
+	///
+	/// assert_eq!(meta, HotKey::new(Some(Modifiers::SUPER), Code::KeyK));
+	///
+	/// assert_eq!(meta.to_string(), "meta+KeyK");
+	/// ```
+	pub fn new_exact(mods:Option<Modifiers>, key:Code) -> Self {
+		let mods = mods.unwrap_or_else(Modifiers::empty);
+
+		let sides = ModifierSides::EITHER;
+
+		Self { mods, sides, key, id:canonical::id(mods, sides, key) }
+	}
 
-		if mods.contains(Modifiers::META) {
-			mods.remove(Modifiers::META);
+	/// Creates the same hotkey as [`HotKey::new`] in a `const` context, so
+	/// hotkeys can be defined as constants.
+	///
+	/// ```
+	/// use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+	///
+	/// const SAVE:HotKey = HotKey::from_parts(Modifiers::CONTROL, Code::KeyS);
+	///
+	/// assert_eq!(SAVE, HotKey::new(Some(Modifiers::CONTROL), Code::KeyS));
+	/// ```
+	pub const fn from_parts(mods:Modifiers, key:Code) -> Self {
+		let mods = canonical::fold(mods);
+
+		let sides = ModifierSides::EITHER;
+
+		Self { mods, sides, key, id:canonical::id(mods, sides, key) }
+	}
 
-			mods.insert(Modifiers::SUPER);
+	/// Returns a copy of this hotkey that only triggers when `modifier` is
+	/// pressed on the given `side`, for example to make `RightCmd+Space`
+	/// distinct from `LeftCmd+Space`.
+	///
+	/// `modifier` is added to the hotkey modifiers if it wasn't already part
+	/// of them.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **macOS**: Sided hotkeys are matched in a keyboard event tap which
+	///   requires the Accessibility permission.
+	/// - **Windows**: Unsupported, registering a sided hotkey fails.
+	pub fn with_side(mut self, modifier:Modifiers, side:ModifierSide) -> Self {
+		let modifier = canonical::fold(modifier);
+
+		// Kept as `META` if the hotkey was created with it.
+		if !canonical::fold(self.mods).contains(modifier) {
+			self.mods.insert(modifier);
 		}
 
-		Self { mods, key, id:mods.bits() << 16 | key as u32 }
+		self.sides.set(modifier, side);
+
+		self.id = canonical::id(self.mods, self.sides, self.key);
+
+		self
 	}
 
-	/// Returns the id associated with this hotKey
-	/// which is a hash of the string represention of modifiers and key within
-	/// this hotKey.
+	/// Returns a copy of this hotkey using `id` instead of the generated one,
+	/// for example to map events straight to an index in your own action table.
+	///
+	/// Call it last, [`HotKey::with_side`] regenerates the id.
+	pub fn with_id(mut self, id:u32) -> Self {
+		self.id = id;
+
+		self
+	}
+
+	/// Returns the id associated with this hotkey, the one set with
+	/// [`HotKey::with_id`] or else the generated one.
+	///
+	/// The generated id packs the hotkey rather than hashing it: the
+	/// [`Modifiers`] bits, with `META` folded into `SUPER`, from bit 16, the
+	/// [`ModifierSides`] in bits 8 to 15 and the [`Code`] in the low 8 bits.
+	/// Two generated ids are only equal for the same key, modifiers and sides,
+	/// except that a [`HotKey::new_exact`] `META` hotkey has the id of its
+	/// `SUPER` counterpart. A custom id may collide with any other id.
 	pub fn id(&self) -> u32 { self.id }
 
+	/// Returns `true` if `other` has the same modifiers and key, whatever its
+	/// id and modifier sides.
+	///
+	/// `==` compares the ids as well, so a hotkey given its own id with
+	/// [`HotKey::with_id`] differs from the same keys built with
+	/// [`HotKey::new`].
+	pub fn eq_by_key(&self, other:&HotKey) -> bool {
+		canonical::fold(self.mods) == canonical::fold(other.mods) && self.key == other.key
+	}
+
 	/// Returns `true` if this [`Code`] and [`Modifiers`] matches this hotkey.
 	pub fn matches(&self, modifiers:impl Borrow<Modifiers>, key:impl Borrow<Code>) -> bool {
 		// Should be a const but const bit_or doesn't work here.
@@ -114,32 +368,333 @@ impl HotKey {
 
 		let key = key.borrow();
 
-		self.mods == *modifiers & base_mods && self.key == *key
+		canonical::fold(self.mods) == canonical::fold(*modifiers) & base_mods && self.key == *key
+	}
+
+	/// Creates a hotkey from an X11 keysym, for example one reported by another
+	/// input library.
+	///
+	/// Letters are accepted in either case, `XK_a` and `XK_A` both give
+	/// [`Code::KeyA`]. Keysyms of keys that can't be registered, e.g. the
+	/// modifier keys `XK_Shift_L` or `XK_Super_R`, are rejected.
+	#[cfg(any(
+		target_os = "linux",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "openbsd",
+		target_os = "netbsd"
+	))]
+	pub fn from_x11_keysym(keysym:u32, mods:Modifiers) -> crate::Result<Self> {
+		// Latin-1 keysyms are the ASCII codes, the table only knows lower case letters.
+		let lowercase = if (u32::from(b'A')..=u32::from(b'Z')).contains(&keysym) {
+			keysym + u32::from(b'a' - b'A')
+		} else {
+			keysym
+		};
+
+		let hotkey = crate::platform_impl::x11_keysym_to_keycode(lowercase)
+			.map(|key| Self::new(Some(mods), key));
+
+		match hotkey.filter(crate::platform_impl::is_supported) {
+			Some(hotkey) => Ok(hotkey),
+			None => Err(crate::Error::UnrecognizedHotKeyCode(format!("keysym {:#x}", keysym))),
+		}
+	}
+
+	/// Creates a hotkey from a Windows virtual-key code, for example one
+	/// reported by a keyboard hook.
+	///
+	/// Codes shared by several keys give the main one, `VK_RETURN` gives
+	/// [`Code::Enter`] rather than [`Code::NumpadEnter`].
+	#[cfg(target_os = "windows")]
+	pub fn from_vk(vk:u32, mods:Modifiers) -> crate::Result<Self> {
+		match u16::try_from(vk).ok().and_then(crate::platform_impl::vk_to_key) {
+			Some(key) => Ok(Self::new(Some(mods), key)),
+			None => Err(crate::Error::UnrecognizedHotKeyCode(format!("virtual key {:#x}", vk))),
+		}
 	}
 
+	/// Creates a hotkey from a Linux evdev key code, `KEY_A` (30) gives
+	/// [`Code::KeyA`], for example one read from `/dev/input` or by a Wayland
+	/// compositor.
+	///
+	/// Only the key is taken from the code, the modifiers come from `mods`.
+	pub fn from_evdev_code(code:u32, mods:Modifiers) -> crate::Result<Self> {
+		match crate::evdev::evdev_to_code(code) {
+			Some(key) => Ok(Self::new(Some(mods), key)),
+			None => Err(crate::Error::UnrecognizedHotKeyCode(format!("evdev code {}", code))),
+		}
+	}
+
+	/// Returns `true` if the current platform backend knows how to register
+	/// this hotkey, some keys only exist on specific keyboard layouts or
+	/// platforms.
+	pub fn is_supported(&self) -> bool { crate::platform_impl::is_supported(self) }
+
+	/// Returns the [`SystemShortcut`](crate::SystemShortcut) this hotkey
+	/// collides with on the current platform, if any.
+	///
+	/// Such hotkeys are handled by the system and never fire, so
+	/// [`GlobalHotKeyManager`](crate::GlobalHotKeyManager) refuses them unless
+	/// [`GlobalHotKeyManager::allow_system_conflicts`](crate::GlobalHotKeyManager::allow_system_conflicts)
+	/// is enabled.
+	pub fn conflicts_with_system(&self) -> Option<crate::SystemShortcut> {
+		crate::reserved::find(self)
+	}
+
+	/// Returns `true` if a key press could trigger both this hotkey and
+	/// `other`, meaning they can't be registered at the same time.
+	pub fn overlaps(&self, other:&HotKey) -> bool {
+		self.eq_by_key(other) && self.sides.overlaps(&other.sides)
+	}
+
+	/// Returns `true` if `other` uses the same key and at least the modifiers
+	/// of this hotkey, e.g. `shift+KeyS` is a submatch of `control+shift+KeyS`.
+	///
+	/// Useful to tell users which hotkey they were likely trying to press when
+	/// a combination is missing some modifiers.
+	pub fn is_submatch_of(&self, other:&HotKey) -> bool {
+		self.key == other.key && canonical::fold(other.mods).contains(canonical::fold(self.mods))
+	}
+
+	/// Returns the hotkeys of `registered` using `key`, whatever their modifiers.
+	pub fn candidates_for_key(key:Code, registered:&[HotKey]) -> Vec<HotKey> {
+		registered.iter().filter(|hotkey| hotkey.key == key).copied().collect()
+	}
+
+	/// Describes this hotkey for logs and bug reports, e.g.
+	/// `shift+control+KeyA (id 0x02080013)`.
+	pub fn describe(&self) -> String { format!("{} (id {:#010x})", self, self.id) }
+
 	/// Converts this hotkey into a string.
 	pub fn into_string(self) -> String {
 		let mut hotkey = String::new();
 
-		if self.mods.contains(Modifiers::SHIFT) {
-			hotkey.push_str("shift+")
+		for (modifier, side) in canonical::modifiers(self.mods, self.sides) {
+			match side {
+				ModifierSide::Either => {},
+				ModifierSide::Left => hotkey.push_str("left"),
+				ModifierSide::Right => hotkey.push_str("right"),
+			}
+
+			// Written as it was given, see `HotKey::new_exact`.
+			let meta = modifier == Modifiers::SUPER
+				&& self.mods.contains(Modifiers::META)
+				&& !self.mods.contains(Modifiers::SUPER);
+
+			hotkey.push_str(if meta { "meta" } else { modifier_token(modifier) });
+
+			hotkey.push('+');
+		}
+
+		hotkey.push_str(&canonical::key_token(self.key));
+
+		hotkey
+	}
+
+	/// Converts this hotkey into an [Electron accelerator], e.g.
+	/// `CommandOrControl+Shift+S`.
+	///
+	/// On macOS a hotkey using both [`Modifiers::SUPER`] and
+	/// [`Modifiers::CONTROL`] is written with `CommandOrControl`, the other
+	/// modifiers keep their own names. Electron has no sided modifiers so the
+	/// sides are dropped, and keys it has no name for are written as their
+	/// [`Code`].
+	///
+	/// [Electron accelerator]: https://www.electronjs.org/docs/latest/api/accelerator
+	pub fn to_electron_accelerator(&self) -> String {
+		let macos = cfg!(target_os = "macos");
+
+		let cmd_or_ctrl =
+			macos && canonical::fold(self.mods).contains(Modifiers::SUPER | Modifiers::CONTROL);
+
+		let mut accelerator = Vec::new();
+
+		if cmd_or_ctrl {
+			accelerator.push("CommandOrControl");
 		}
 
-		if self.mods.contains(Modifiers::CONTROL) {
-			hotkey.push_str("control+")
+		for (modifier, _) in
+			canonical::ordered(canonical::ACCELERATOR_MODIFIERS, self.mods, self.sides)
+		{
+			if cmd_or_ctrl && (Modifiers::SUPER | Modifiers::CONTROL).contains(modifier) {
+				continue;
+			}
+
+			accelerator.push(match modifier {
+				Modifiers::SUPER if macos => "Command",
+				Modifiers::SUPER => "Super",
+				Modifiers::CONTROL => "Control",
+				Modifiers::ALT => "Alt",
+				_ => "Shift",
+			});
 		}
 
-		if self.mods.contains(Modifiers::ALT) {
-			hotkey.push_str("alt+")
+		let key = electron_key(self.key);
+
+		accelerator.push(&key);
+
+		accelerator.join("+")
+	}
+
+	/// Converts this hotkey into a GTK accelerator, e.g. `<Primary><Shift>s`,
+	/// as parsed by `gtk_accelerator_parse`.
+	///
+	/// The primary modifier of the platform ([`Modifiers::CONTROL`], or
+	/// [`Modifiers::SUPER`] on macOS) is written as `<Primary>`. GTK has no
+	/// sided modifiers so the sides are dropped, and keys without a GDK key
+	/// name are written as their [`Code`].
+	pub fn to_gtk_accelerator(&self) -> String {
+		let mut accelerator = String::new();
+
+		if canonical::fold(self.mods).contains(CMD_OR_CTRL) {
+			accelerator.push_str("<Primary>");
 		}
 
-		if self.mods.contains(Modifiers::SUPER) {
-			hotkey.push_str("super+")
+		for (modifier, _) in
+			canonical::ordered(canonical::ACCELERATOR_MODIFIERS, self.mods, self.sides)
+		{
+			if modifier != CMD_OR_CTRL {
+				accelerator.push_str(match modifier {
+					Modifiers::CONTROL => "<Control>",
+					Modifiers::ALT => "<Alt>",
+					Modifiers::SHIFT => "<Shift>",
+					_ => "<Super>",
+				});
+			}
 		}
 
-		hotkey.push_str(&self.key.to_string());
+		accelerator.push_str(&gtk_key(self.key));
 
-		hotkey
+		accelerator
+	}
+
+	/// Creates a hotkey from a GTK accelerator, e.g. `<Primary><Shift>s`.
+	///
+	/// `<Primary>` is [`Modifiers::CONTROL`], or [`Modifiers::SUPER`] on macOS,
+	/// following GTK. Modifier names are case-insensitive and letters are
+	/// accepted in either case.
+	pub fn from_gtk_accelerator(accelerator:&str) -> crate::Result<Self> {
+		let mut mods = Modifiers::empty();
+
+		let mut rest = accelerator.trim();
+
+		while let Some(group) = rest.strip_prefix('<') {
+			let (name, after) = group
+				.split_once('>')
+				.ok_or_else(|| crate::Error::UnexpectedHotKeyFormat(accelerator.to_string()))?;
+
+			mods |= match name.to_uppercase().as_str() {
+				"PRIMARY" => CMD_OR_CTRL,
+				"CONTROL" | "CTRL" | "CTL" => Modifiers::CONTROL,
+				"SHIFT" | "SHFT" => Modifiers::SHIFT,
+				"ALT" | "MOD1" => Modifiers::ALT,
+				"SUPER" | "META" => Modifiers::SUPER,
+				_ => return Err(crate::Error::UnexpectedHotKeyFormat(accelerator.to_string())),
+			};
+
+			rest = after;
+		}
+
+		if rest.is_empty() {
+			return Err(crate::Error::EmptyHotKeyToken(accelerator.to_string()));
+		}
+
+		match parse_gtk_key(rest) {
+			Some(key) => Ok(Self::new(Some(mods), key)),
+			None => Err(crate::Error::UnrecognizedHotKeyCode(rest.to_string())),
+		}
+	}
+
+	/// Parses `hotkey` like [`FromStr`], correcting the modifier and key names
+	/// that are within two edits of a known name, e.g. `Contrl+S` or `Shfit+S`.
+	///
+	/// Returns the hotkey along with a hint such as `Did you mean 'ctrl+s'?`
+	/// when a name was corrected, to show the user before keeping it. Fails
+	/// with the error of the strict parse when no correction makes the string
+	/// parse.
+	///
+	/// ```
+	/// use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+	///
+	/// let (hotkey, hint) = HotKey::parse_lenient("Ctlr+S")?;
+	///
+	/// assert_eq!(hotkey, HotKey::new(Some(Modifiers::CONTROL), Code::KeyS));
+	///
+	/// assert_eq!(hint.as_deref(), Some("Did you mean 'ctrl+S'?"));
+	/// # Ok::<(), global_hotkey::Error>(())
+	/// ```
+	pub fn parse_lenient(hotkey:&str) -> crate::Result<(Self, Option<String>)> {
+		let error = match parse_hotkey(hotkey) {
+			Ok(parsed) => return Ok((parsed, None)),
+			Err(error) => error,
+		};
+
+		let tokens = hotkey.split('+').map(str::trim).collect::<Vec<_>>();
+
+		let corrected = tokens
+			.iter()
+			.map(|token| {
+				match parse_modifier(token).is_some() || parse_key_token(token).is_ok() {
+					true => token.to_string(),
+					false => closest_name(token).unwrap_or_else(|| token.to_string()),
+				}
+			})
+			.collect::<Vec<_>>();
+
+		if corrected == tokens {
+			return Err(error.into());
+		}
+
+		let corrected = corrected.join("+");
+
+		match parse_hotkey(&corrected) {
+			Ok(parsed) => Ok((parsed, Some(format!("Did you mean '{}'?", corrected)))),
+			Err(_) => Err(error.into()),
+		}
+	}
+
+	/// Parses the hotkey held by the environment variable `var_name`, e.g. to
+	/// let a deployment pick the hotkey of a service.
+	///
+	/// Fails with [`Error::EnvVar`](crate::Error::EnvVar) if the variable is
+	/// unset or not valid unicode, and with the error of [`FromStr`] if it
+	/// doesn't hold a hotkey.
+	///
+	/// ```no_run
+	/// use global_hotkey::hotkey::HotKey;
+	///
+	/// let hotkey = HotKey::from_env("APP_TOGGLE_HOTKEY")?;
+	/// # Ok::<(), global_hotkey::Error>(())
+	/// ```
+	pub fn from_env(var_name:&str) -> crate::Result<Self> {
+		let value = std::env::var(var_name)
+			.map_err(|source| crate::Error::EnvVar { name:var_name.to_string(), source })?;
+
+		Ok(parse_hotkey(&value)?)
+	}
+
+	/// Like [`HotKey::from_env`], falling back to `default` when the variable
+	/// is unset. A variable that is set but can't be read or parsed falls back
+	/// to `default` too, with a warning logged.
+	///
+	/// ```
+	/// use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+	///
+	/// let default = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyP);
+	///
+	/// let hotkey = HotKey::from_env_or("APP_PALETTE_HOTKEY", default);
+	/// ```
+	pub fn from_env_or(var_name:&str, default:HotKey) -> HotKey {
+		match Self::from_env(var_name) {
+			Ok(hotkey) => hotkey,
+			Err(crate::Error::EnvVar { source:std::env::VarError::NotPresent, .. }) => default,
+			Err(e) => {
+				log::warn!("Ignoring ${}, using `{}` instead: {}", var_name, default, e);
+
+				default
+			},
+		}
 	}
 }
 
@@ -149,6 +704,17 @@ impl Display for HotKey {
 	}
 }
 
+// The bitflags and the decimal id are hard to read in logs, the accelerator
+// string and the hex id are what bug reports need.
+impl std::fmt::Debug for HotKey {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("HotKey")
+			.field("accelerator", &self.into_string())
+			.field("id", &format_args!("{:#010x}", self.id))
+			.finish()
+	}
+}
+
 // HotKey::from_str is available to be backward
 // compatible with tauri and it also open the option
 // to generate hotkey from string
@@ -175,12 +741,18 @@ fn parse_hotkey(hotkey:&str) -> Result<HotKey, HotKeyParseError> {
 
 	let mut mods = Modifiers::empty();
 
+	let mut sides = ModifierSides::EITHER;
+
 	let mut key = None;
 
 	match tokens.len() {
 		// single key hotkey
 		1 => {
-			key = Some(parse_key(tokens[0])?);
+			let (code, implied) = parse_key_token(tokens[0])?;
+
+			mods |= implied;
+
+			key = Some(code);
 		},
 		// modifiers and key comobo hotkey
 		_ => {
@@ -201,166 +773,538 @@ fn parse_hotkey(hotkey:&str) -> Result<HotKey, HotKeyParseError> {
 					return Err(HotKeyParseError::InvalidFormat(hotkey.to_string()));
 				}
 
-				match token.to_uppercase().as_str() {
-					"OPTION" | "ALT" => {
-						mods |= Modifiers::ALT;
-					},
-					"CONTROL" | "CTRL" => {
-						mods |= Modifiers::CONTROL;
-					},
-					"COMMAND" | "CMD" | "SUPER" => {
-						mods |= Modifiers::SUPER;
-					},
-					"SHIFT" => {
-						mods |= Modifiers::SHIFT;
-					},
-					#[cfg(target_os = "macos")]
-					"COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => {
-						mods |= Modifiers::SUPER;
-					},
-					#[cfg(not(target_os = "macos"))]
-					"COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => {
-						mods |= Modifiers::CONTROL;
+				match parse_modifier(token) {
+					Some((modifier, side)) => {
+						mods |= modifier;
+
+						if side != ModifierSide::Either {
+							sides.set(modifier, side);
+						}
 					},
 
-					_ => {
-						key = Some(parse_key(token)?);
+					None => {
+						let (code, implied) = parse_key_token(token)?;
+
+						mods |= implied;
+
+						key = Some(code);
 					},
 				}
 			}
 		},
 	}
 
-	Ok(HotKey::new(
+	// A lone `meta` is kept, so the hotkeys of `HotKey::new_exact` parse back
+	// the same.
+	if mods.contains(Modifiers::SUPER) {
+		mods = canonical::fold(mods);
+	}
+
+	let mut hotkey = HotKey::new_exact(
 		Some(mods),
 		key.ok_or_else(|| HotKeyParseError::InvalidFormat(hotkey.to_string()))?,
-	))
+	);
+
+	for (modifier, side) in canonical::modifiers(mods, sides) {
+		if side != ModifierSide::Either {
+			hotkey = hotkey.with_side(modifier, side);
+		}
+	}
+
+	Ok(hotkey)
 }
 
-fn parse_key(key:&str) -> Result<Code, HotKeyParseError> {
+// Parses a modifier token, optionally prefixed or suffixed with the side it
+// has to be pressed on, e.g. `RightCmd`, `LShift` or `AltLeft`.
+fn parse_modifier(token:&str) -> Option<(Modifiers, ModifierSide)> {
+	let token = token.to_uppercase();
+
+	if let Some(modifier) = parse_modifier_name(&token) {
+		return Some((modifier, ModifierSide::Either));
+	}
+
+	for (side, prefixes, suffix) in [
+		(ModifierSide::Left, ["LEFT", "L"], "LEFT"),
+		(ModifierSide::Right, ["RIGHT", "R"], "RIGHT"),
+	] {
+		let name = prefixes
+			.iter()
+			.find_map(|prefix| token.strip_prefix(prefix))
+			.or_else(|| token.strip_suffix(suffix));
+
+		if let Some(modifier) = name.and_then(parse_sided_modifier_name) {
+			return Some((modifier, side));
+		}
+	}
+
+	None
+}
+
+/// Checks that `hotkey` parses as a [`HotKey`], e.g. to validate a shortcut
+/// field as the user types, returning the parse error otherwise.
+pub fn validate_hotkey_string(hotkey:&str) -> crate::Result<()> {
+	parse_hotkey(hotkey)?;
+
+	Ok(())
+}
+
+/// Returns the first pair of hotkeys sharing the same id while describing
+/// different shortcuts, these can't be told apart in [`GlobalHotKeyEvent`](crate::GlobalHotKeyEvent)s.
+///
+/// Generated ids pack the modifiers, sides and key into the `u32` so they
+/// never collide. Collisions come from custom ids, if those are derived from a
+/// 32-bit hash the birthday bound makes a collision likely (~50%) past roughly
+/// 77,000 hotkeys and already about 1% at 9,300. For large sets prefer
+/// assigning sequential ids with [`HotKey::with_id`].
+pub fn check_id_uniqueness(hotkeys:&[HotKey]) -> Option<(HotKey, HotKey)> {
+	let mut seen = HashMap::with_capacity(hotkeys.len());
+
+	for hotkey in hotkeys {
+		if let Some(other) = seen.insert(hotkey.id, *hotkey) {
+			if (other.mods, other.sides, other.key) != (hotkey.mods, hotkey.sides, hotkey.key) {
+				return Some((other, *hotkey));
+			}
+		}
+	}
+
+	None
+}
+
+/// Returns the hotkeys to register and to unregister when switching from the
+/// `old` set to the `new` one, as `(to_register, to_unregister)`, each in the
+/// order of its set.
+///
+/// Hotkeys are compared by shortcut and id. A hotkey with a custom id that
+/// changed its shortcut is part of both lists, so its old shortcut is
+/// unregistered before the new one is registered under the same id.
+///
+/// ```no_run
+/// # use global_hotkey::{GlobalHotKeyManager, hotkey::{hotkey_set_diff, HotKey}};
+/// # let manager = GlobalHotKeyManager::new()?;
+/// # let (old, new) = (Vec::<HotKey>::new(), Vec::<HotKey>::new());
+/// let (to_register, to_unregister) = hotkey_set_diff(&old, &new);
+///
+/// manager.unregister_all(&to_unregister)?;
+/// manager.register_all(&to_register)?;
+/// # Ok::<(), global_hotkey::Error>(())
+/// ```
+pub fn hotkey_set_diff(old:&[HotKey], new:&[HotKey]) -> (Vec<HotKey>, Vec<HotKey>) {
+	let old_set = old.iter().collect::<HashSet<_>>();
+
+	let new_set = new.iter().collect::<HashSet<_>>();
+
+	let to_register = new.iter().filter(|hotkey| !old_set.contains(hotkey)).copied();
+
+	let to_unregister = old.iter().filter(|hotkey| !new_set.contains(hotkey)).copied();
+
+	(to_register.collect(), to_unregister.collect())
+}
+
+fn parse_modifier_name(name:&str) -> Option<Modifiers> {
+	match name {
+		"COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => Some(CMD_OR_CTRL),
+		_ => parse_sided_modifier_name(name),
+	}
+}
+
+// The names of the modifiers a side can be attached to, in uppercase.
+// `CmdOrCtrl` resolves to a different modifier per platform so it isn't one of them.
+const MODIFIER_NAMES:[(Modifiers, &[&str]); 4] = [
+	(Modifiers::ALT, &["OPTION", "OPT", "ALT"]),
+	(Modifiers::CONTROL, &["CONTROL", "CTRL"]),
+	(Modifiers::SUPER, &["COMMAND", "CMD", "SUPER", "META", "WIN", "WINDOWS"]),
+	(Modifiers::SHIFT, &["SHIFT"]),
+];
+
+fn parse_sided_modifier_name(name:&str) -> Option<Modifiers> {
+	// The same key as `SUPER`, kept apart to be written back as it was.
+	if name == "META" {
+		return Some(Modifiers::META);
+	}
+
+	MODIFIER_NAMES.iter().find(|(_, names)| names.contains(&name)).map(|(modifier, _)| *modifier)
+}
+
+// The token of `modifier`, one of the canonical modifiers, in `Display`.
+fn modifier_token(modifier:Modifiers) -> &'static str {
+	match modifier {
+		Modifiers::SHIFT => "shift",
+		Modifiers::CONTROL => "control",
+		Modifiers::ALT => "alt",
+		_ => "super",
+	}
+}
+
+// The name of `key` in an Electron accelerator, the `Code` names of the keys
+// missing here are the same.
+fn electron_key(key:Code) -> String {
+	use Code::*;
+
+	let name = match key {
+		Backquote => "`",
+		Backslash => "\\",
+		BracketLeft => "[",
+		BracketRight => "]",
+		Comma => ",",
+		Equal => "=",
+		Minus => "-",
+		Period => ".",
+		Quote => "'",
+		Semicolon => ";",
+		Slash => "/",
+		Enter => "Return",
+		CapsLock => "Capslock",
+		NumLock => "Numlock",
+		ScrollLock => "Scrolllock",
+		ArrowDown => "Down",
+		ArrowLeft => "Left",
+		ArrowRight => "Right",
+		ArrowUp => "Up",
+		Numpad0 => "num0",
+		Numpad1 => "num1",
+		Numpad2 => "num2",
+		Numpad3 => "num3",
+		Numpad4 => "num4",
+		Numpad5 => "num5",
+		Numpad6 => "num6",
+		Numpad7 => "num7",
+		Numpad8 => "num8",
+		Numpad9 => "num9",
+		NumpadAdd => "numadd",
+		NumpadDecimal => "numdec",
+		NumpadDivide => "numdiv",
+		NumpadMultiply => "nummult",
+		NumpadSubtract => "numsub",
+		AudioVolumeDown => "VolumeDown",
+		AudioVolumeUp => "VolumeUp",
+		AudioVolumeMute => "VolumeMute",
+		MediaTrackNext => "MediaNextTrack",
+		MediaTrackPrevious => "MediaPreviousTrack",
+		_ => {
+			let name = key.to_string();
+
+			let short = name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit"));
+
+			return short.map_or_else(|| name.clone(), str::to_string);
+		},
+	};
+
+	name.to_string()
+}
+
+// Parses a key token, along with the modifiers a shifted symbol implies.
+fn parse_key_token(key:&str) -> Result<(Code, Modifiers), HotKeyParseError> {
+	match parse_key(key) {
+		Ok(code) => Ok((code, Modifiers::empty())),
+		Err(error) => parse_shifted_symbol(key).map(|code| (code, Modifiers::SHIFT)).ok_or(error),
+	}
+}
+
+// The keys typing the shifted symbols of the US layout. `+` is left out as it
+// separates the tokens.
+const SHIFTED_SYMBOLS:[(&str, Code); 20] = {
+	use Code::*;
+
+	[
+		("~", Backquote),
+		("!", Digit1),
+		("@", Digit2),
+		("#", Digit3),
+		("$", Digit4),
+		("%", Digit5),
+		("^", Digit6),
+		("&", Digit7),
+		("*", Digit8),
+		("(", Digit9),
+		(")", Digit0),
+		("_", Minus),
+		("{", BracketLeft),
+		("}", BracketRight),
+		("|", Backslash),
+		(":", Semicolon),
+		("\"", Quote),
+		("<", Comma),
+		(">", Period),
+		("?", Slash),
+	]
+};
+
+fn parse_shifted_symbol(symbol:&str) -> Option<Code> {
+	SHIFTED_SYMBOLS.iter().find(|(name, _)| *name == symbol).map(|(_, code)| *code)
+}
+
+// The GDK key names of the keys other than letters, digits and the ones
+// named like their `Code` (`F1`, `Tab`, `Home`, ...).
+const GTK_KEYS:[(Code, &str); 53] = [
+	(Code::Backquote, "grave"),
+	(Code::Backslash, "backslash"),
+	(Code::BracketLeft, "bracketleft"),
+	(Code::BracketRight, "bracketright"),
+	(Code::Comma, "comma"),
+	(Code::Equal, "equal"),
+	(Code::Minus, "minus"),
+	(Code::Period, "period"),
+	(Code::Quote, "apostrophe"),
+	(Code::Semicolon, "semicolon"),
+	(Code::Slash, "slash"),
+	(Code::Backspace, "BackSpace"),
+	(Code::CapsLock, "Caps_Lock"),
+	(Code::Enter, "Return"),
+	(Code::Space, "space"),
+	(Code::PageDown, "Page_Down"),
+	(Code::PageUp, "Page_Up"),
+	(Code::PrintScreen, "Print"),
+	(Code::ScrollLock, "Scroll_Lock"),
+	(Code::ArrowDown, "Down"),
+	(Code::ArrowLeft, "Left"),
+	(Code::ArrowRight, "Right"),
+	(Code::ArrowUp, "Up"),
+	(Code::NumLock, "Num_Lock"),
+	(Code::Numpad0, "KP_0"),
+	(Code::Numpad1, "KP_1"),
+	(Code::Numpad2, "KP_2"),
+	(Code::Numpad3, "KP_3"),
+	(Code::Numpad4, "KP_4"),
+	(Code::Numpad5, "KP_5"),
+	(Code::Numpad6, "KP_6"),
+	(Code::Numpad7, "KP_7"),
+	(Code::Numpad8, "KP_8"),
+	(Code::Numpad9, "KP_9"),
+	(Code::NumpadAdd, "KP_Add"),
+	(Code::NumpadComma, "KP_Separator"),
+	(Code::NumpadDecimal, "KP_Decimal"),
+	(Code::NumpadDivide, "KP_Divide"),
+	(Code::NumpadEnter, "KP_Enter"),
+	(Code::NumpadEqual, "KP_Equal"),
+	(Code::NumpadMultiply, "KP_Multiply"),
+	(Code::NumpadSubtract, "KP_Subtract"),
+	(Code::AudioVolumeDown, "XF86AudioLowerVolume"),
+	(Code::AudioVolumeUp, "XF86AudioRaiseVolume"),
+	(Code::AudioVolumeMute, "XF86AudioMute"),
+	(Code::MediaPlayPause, "XF86AudioPlay"),
+	(Code::MediaTrackNext, "XF86AudioNext"),
+	(Code::MediaTrackPrevious, "XF86AudioPrev"),
+	(Code::BrightnessUp, "XF86MonBrightnessUp"),
+	(Code::BrightnessDown, "XF86MonBrightnessDown"),
+	(Code::Eject, "XF86Eject"),
+	(Code::Power, "XF86PowerOff"),
+	(Code::WakeUp, "XF86WakeUp"),
+];
+
+// The GDK name of `key` in a GTK accelerator.
+fn gtk_key(key:Code) -> String {
+	if let Some((_, name)) = GTK_KEYS.iter().find(|(code, _)| *code == key) {
+		return name.to_string();
+	}
+
+	let name = key.to_string();
+
+	match name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")) {
+		Some(short) => short.to_lowercase(),
+		None => name,
+	}
+}
+
+fn parse_gtk_key(name:&str) -> Option<Code> {
+	if let Some((code, _)) = GTK_KEYS.iter().find(|(_, gtk)| gtk.eq_ignore_ascii_case(name)) {
+		return Some(*code);
+	}
+
+	// Letters, digits and the keys named like their `Code`.
+	let code = match name.len() {
+		1 => parse_key(name).ok()?,
+		_ => name.parse::<Code>().ok()?,
+	};
+
+	gtk_key(code).eq_ignore_ascii_case(name).then_some(code)
+}
+
+// The names of every key, in uppercase, as accepted by `parse_key`.
+const KEY_NAMES:[(Code, &[&str]); 129] = {
 	use Code::*;
 
-	match key.to_uppercase().as_str() {
-		"BACKQUOTE" | "`" => Ok(Backquote),
-		"BACKSLASH" | "\\" => Ok(Backslash),
-		"BRACKETLEFT" | "[" => Ok(BracketLeft),
-		"BRACKETRIGHT" | "]" => Ok(BracketRight),
-		"PAUSE" | "PAUSEBREAK" => Ok(Pause),
-		"COMMA" | "," => Ok(Comma),
-		"DIGIT0" | "0" => Ok(Digit0),
-		"DIGIT1" | "1" => Ok(Digit1),
-		"DIGIT2" | "2" => Ok(Digit2),
-		"DIGIT3" | "3" => Ok(Digit3),
-		"DIGIT4" | "4" => Ok(Digit4),
-		"DIGIT5" | "5" => Ok(Digit5),
-		"DIGIT6" | "6" => Ok(Digit6),
-		"DIGIT7" | "7" => Ok(Digit7),
-		"DIGIT8" | "8" => Ok(Digit8),
-		"DIGIT9" | "9" => Ok(Digit9),
-		"EQUAL" | "=" => Ok(Equal),
-		"KEYA" | "A" => Ok(KeyA),
-		"KEYB" | "B" => Ok(KeyB),
-		"KEYC" | "C" => Ok(KeyC),
-		"KEYD" | "D" => Ok(KeyD),
-		"KEYE" | "E" => Ok(KeyE),
-		"KEYF" | "F" => Ok(KeyF),
-		"KEYG" | "G" => Ok(KeyG),
-		"KEYH" | "H" => Ok(KeyH),
-		"KEYI" | "I" => Ok(KeyI),
-		"KEYJ" | "J" => Ok(KeyJ),
-		"KEYK" | "K" => Ok(KeyK),
-		"KEYL" | "L" => Ok(KeyL),
-		"KEYM" | "M" => Ok(KeyM),
-		"KEYN" | "N" => Ok(KeyN),
-		"KEYO" | "O" => Ok(KeyO),
-		"KEYP" | "P" => Ok(KeyP),
-		"KEYQ" | "Q" => Ok(KeyQ),
-		"KEYR" | "R" => Ok(KeyR),
-		"KEYS" | "S" => Ok(KeyS),
-		"KEYT" | "T" => Ok(KeyT),
-		"KEYU" | "U" => Ok(KeyU),
-		"KEYV" | "V" => Ok(KeyV),
-		"KEYW" | "W" => Ok(KeyW),
-		"KEYX" | "X" => Ok(KeyX),
-		"KEYY" | "Y" => Ok(KeyY),
-		"KEYZ" | "Z" => Ok(KeyZ),
-		"MINUS" | "-" => Ok(Minus),
-		"PERIOD" | "." => Ok(Period),
-		"QUOTE" | "'" => Ok(Quote),
-		"SEMICOLON" | ";" => Ok(Semicolon),
-		"SLASH" | "/" => Ok(Slash),
-		"BACKSPACE" => Ok(Backspace),
-		"CAPSLOCK" => Ok(CapsLock),
-		"ENTER" => Ok(Enter),
-		"SPACE" => Ok(Space),
-		"TAB" => Ok(Tab),
-		"DELETE" => Ok(Delete),
-		"END" => Ok(End),
-		"HOME" => Ok(Home),
-		"INSERT" => Ok(Insert),
-		"PAGEDOWN" => Ok(PageDown),
-		"PAGEUP" => Ok(PageUp),
-		"PRINTSCREEN" => Ok(PrintScreen),
-		"SCROLLLOCK" => Ok(ScrollLock),
-		"ARROWDOWN" | "DOWN" => Ok(ArrowDown),
-		"ARROWLEFT" | "LEFT" => Ok(ArrowLeft),
-		"ARROWRIGHT" | "RIGHT" => Ok(ArrowRight),
-		"ARROWUP" | "UP" => Ok(ArrowUp),
-		"NUMLOCK" => Ok(NumLock),
-		"NUMPAD0" | "NUM0" => Ok(Numpad0),
-		"NUMPAD1" | "NUM1" => Ok(Numpad1),
-		"NUMPAD2" | "NUM2" => Ok(Numpad2),
-		"NUMPAD3" | "NUM3" => Ok(Numpad3),
-		"NUMPAD4" | "NUM4" => Ok(Numpad4),
-		"NUMPAD5" | "NUM5" => Ok(Numpad5),
-		"NUMPAD6" | "NUM6" => Ok(Numpad6),
-		"NUMPAD7" | "NUM7" => Ok(Numpad7),
-		"NUMPAD8" | "NUM8" => Ok(Numpad8),
-		"NUMPAD9" | "NUM9" => Ok(Numpad9),
-		"NUMPADADD" | "NUMADD" | "NUMPADPLUS" | "NUMPLUS" => Ok(NumpadAdd),
-		"NUMPADDECIMAL" | "NUMDECIMAL" => Ok(NumpadDecimal),
-		"NUMPADDIVIDE" | "NUMDIVIDE" => Ok(NumpadDivide),
-		"NUMPADENTER" | "NUMENTER" => Ok(NumpadEnter),
-		"NUMPADEQUAL" | "NUMEQUAL" => Ok(NumpadEqual),
-		"NUMPADMULTIPLY" | "NUMMULTIPLY" => Ok(NumpadMultiply),
-		"NUMPADSUBTRACT" | "NUMSUBTRACT" => Ok(NumpadSubtract),
-		"ESCAPE" | "ESC" => Ok(Escape),
-		"F1" => Ok(F1),
-		"F2" => Ok(F2),
-		"F3" => Ok(F3),
-		"F4" => Ok(F4),
-		"F5" => Ok(F5),
-		"F6" => Ok(F6),
-		"F7" => Ok(F7),
-		"F8" => Ok(F8),
-		"F9" => Ok(F9),
-		"F10" => Ok(F10),
-		"F11" => Ok(F11),
-		"F12" => Ok(F12),
-		"AUDIOVOLUMEDOWN" | "VOLUMEDOWN" => Ok(AudioVolumeDown),
-		"AUDIOVOLUMEUP" | "VOLUMEUP" => Ok(AudioVolumeUp),
-		"AUDIOVOLUMEMUTE" | "VOLUMEMUTE" => Ok(AudioVolumeMute),
-		"MEDIAPLAY" => Ok(MediaPlay),
-		"MEDIAPAUSE" => Ok(MediaPause),
-		"MEDIAPLAYPAUSE" => Ok(MediaPlayPause),
-		"MEDIASTOP" => Ok(MediaStop),
-		"MEDIATRACKNEXT" => Ok(MediaTrackNext),
-		"MEDIATRACKPREV" | "MEDIATRACKPREVIOUS" => Ok(MediaTrackPrevious),
-		"F13" => Ok(F13),
-		"F14" => Ok(F14),
-		"F15" => Ok(F15),
-		"F16" => Ok(F16),
-		"F17" => Ok(F17),
-		"F18" => Ok(F18),
-		"F19" => Ok(F19),
-		"F20" => Ok(F20),
-		"F21" => Ok(F21),
-		"F22" => Ok(F22),
-		"F23" => Ok(F23),
-		"F24" => Ok(F24),
-
-		_ => Err(HotKeyParseError::UnsupportedKey(key.to_string())),
+	[
+		(Backquote, &["BACKQUOTE", "`"]),
+		(Backslash, &["BACKSLASH", "\\"]),
+		(BracketLeft, &["BRACKETLEFT", "["]),
+		(BracketRight, &["BRACKETRIGHT", "]"]),
+		(Pause, &["PAUSE", "PAUSEBREAK"]),
+		(ContextMenu, &["CONTEXTMENU", "MENU", "APPS"]),
+		(Comma, &["COMMA", ","]),
+		(Digit0, &["DIGIT0", "0"]),
+		(Digit1, &["DIGIT1", "1"]),
+		(Digit2, &["DIGIT2", "2"]),
+		(Digit3, &["DIGIT3", "3"]),
+		(Digit4, &["DIGIT4", "4"]),
+		(Digit5, &["DIGIT5", "5"]),
+		(Digit6, &["DIGIT6", "6"]),
+		(Digit7, &["DIGIT7", "7"]),
+		(Digit8, &["DIGIT8", "8"]),
+		(Digit9, &["DIGIT9", "9"]),
+		(Equal, &["EQUAL", "="]),
+		(KeyA, &["KEYA", "A"]),
+		(KeyB, &["KEYB", "B"]),
+		(KeyC, &["KEYC", "C"]),
+		(KeyD, &["KEYD", "D"]),
+		(KeyE, &["KEYE", "E"]),
+		(KeyF, &["KEYF", "F"]),
+		(KeyG, &["KEYG", "G"]),
+		(KeyH, &["KEYH", "H"]),
+		(KeyI, &["KEYI", "I"]),
+		(KeyJ, &["KEYJ", "J"]),
+		(KeyK, &["KEYK", "K"]),
+		(KeyL, &["KEYL", "L"]),
+		(KeyM, &["KEYM", "M"]),
+		(KeyN, &["KEYN", "N"]),
+		(KeyO, &["KEYO", "O"]),
+		(KeyP, &["KEYP", "P"]),
+		(KeyQ, &["KEYQ", "Q"]),
+		(KeyR, &["KEYR", "R"]),
+		(KeyS, &["KEYS", "S"]),
+		(KeyT, &["KEYT", "T"]),
+		(KeyU, &["KEYU", "U"]),
+		(KeyV, &["KEYV", "V"]),
+		(KeyW, &["KEYW", "W"]),
+		(KeyX, &["KEYX", "X"]),
+		(KeyY, &["KEYY", "Y"]),
+		(KeyZ, &["KEYZ", "Z"]),
+		(Minus, &["MINUS", "-"]),
+		(Period, &["PERIOD", "."]),
+		(Quote, &["QUOTE", "'"]),
+		(Semicolon, &["SEMICOLON", ";"]),
+		(Slash, &["SLASH", "/"]),
+		(Backspace, &["BACKSPACE"]),
+		(CapsLock, &["CAPSLOCK"]),
+		(Enter, &["ENTER"]),
+		(Space, &["SPACE"]),
+		(Tab, &["TAB"]),
+		(Delete, &["DELETE"]),
+		(End, &["END"]),
+		(Home, &["HOME"]),
+		(Insert, &["INSERT"]),
+		(PageDown, &["PAGEDOWN"]),
+		(PageUp, &["PAGEUP"]),
+		(PrintScreen, &["PRINTSCREEN"]),
+		(ScrollLock, &["SCROLLLOCK"]),
+		(ArrowDown, &["ARROWDOWN", "DOWN"]),
+		(ArrowLeft, &["ARROWLEFT", "LEFT"]),
+		(ArrowRight, &["ARROWRIGHT", "RIGHT"]),
+		(ArrowUp, &["ARROWUP", "UP"]),
+		(NumLock, &["NUMLOCK"]),
+		(Numpad0, &["NUMPAD0", "NUM0"]),
+		(Numpad1, &["NUMPAD1", "NUM1"]),
+		(Numpad2, &["NUMPAD2", "NUM2"]),
+		(Numpad3, &["NUMPAD3", "NUM3"]),
+		(Numpad4, &["NUMPAD4", "NUM4"]),
+		(Numpad5, &["NUMPAD5", "NUM5"]),
+		(Numpad6, &["NUMPAD6", "NUM6"]),
+		(Numpad7, &["NUMPAD7", "NUM7"]),
+		(Numpad8, &["NUMPAD8", "NUM8"]),
+		(Numpad9, &["NUMPAD9", "NUM9"]),
+		(NumpadAdd, &["NUMPADADD", "NUMADD", "NUMPADPLUS", "NUMPLUS"]),
+		(NumpadComma, &["NUMPADCOMMA", "NUMCOMMA", "NUMPADSEPARATOR"]),
+		(NumpadDecimal, &["NUMPADDECIMAL", "NUMDECIMAL"]),
+		(NumpadDivide, &["NUMPADDIVIDE", "NUMDIVIDE"]),
+		(NumpadEnter, &["NUMPADENTER", "NUMENTER"]),
+		(NumpadEqual, &["NUMPADEQUAL", "NUMEQUAL"]),
+		(NumpadMultiply, &["NUMPADMULTIPLY", "NUMMULTIPLY"]),
+		(NumpadSubtract, &["NUMPADSUBTRACT", "NUMSUBTRACT"]),
+		(Escape, &["ESCAPE", "ESC"]),
+		(F1, &["F1"]),
+		(F2, &["F2"]),
+		(F3, &["F3"]),
+		(F4, &["F4"]),
+		(F5, &["F5"]),
+		(F6, &["F6"]),
+		(F7, &["F7"]),
+		(F8, &["F8"]),
+		(F9, &["F9"]),
+		(F10, &["F10"]),
+		(F11, &["F11"]),
+		(F12, &["F12"]),
+		(AudioVolumeDown, &["AUDIOVOLUMEDOWN", "VOLUMEDOWN"]),
+		(AudioVolumeUp, &["AUDIOVOLUMEUP", "VOLUMEUP"]),
+		(AudioVolumeMute, &["AUDIOVOLUMEMUTE", "VOLUMEMUTE"]),
+		(MediaPlay, &["MEDIAPLAY"]),
+		(MediaPause, &["MEDIAPAUSE"]),
+		(MediaPlayPause, &["MEDIAPLAYPAUSE"]),
+		(MediaStop, &["MEDIASTOP"]),
+		(MediaTrackNext, &["MEDIATRACKNEXT"]),
+		(MediaTrackPrevious, &["MEDIATRACKPREV", "MEDIATRACKPREVIOUS"]),
+		(BrightnessUp, &["BRIGHTNESSUP"]),
+		(BrightnessDown, &["BRIGHTNESSDOWN"]),
+		(Eject, &["EJECT"]),
+		(Power, &["POWER"]),
+		(WakeUp, &["WAKEUP"]),
+		(F13, &["F13"]),
+		(F14, &["F14"]),
+		(F15, &["F15"]),
+		(F16, &["F16"]),
+		(F17, &["F17"]),
+		(F18, &["F18"]),
+		(F19, &["F19"]),
+		(F20, &["F20"]),
+		(F21, &["F21"]),
+		(F22, &["F22"]),
+		(F23, &["F23"]),
+		(F24, &["F24"]),
+		(IntlBackslash, &["INTLBACKSLASH", "SECTION", "§"]),
+		(IntlYen, &["INTLYEN", "YEN", "¥"]),
+		(IntlRo, &["INTLRO", "RO"]),
+		(Lang1, &["LANG1", "KANA"]),
+		(Lang2, &["LANG2", "EISU"]),
+	]
+};
+
+// The known modifier or key name closest to `token`, in lowercase, if it's
+// within two edits and those don't rewrite most of the token.
+fn closest_name(token:&str) -> Option<String> {
+	let upper = token.to_uppercase();
+
+	let modifiers = MODIFIER_NAMES.iter().flat_map(|(_, names)| names.iter());
+
+	let keys = KEY_NAMES.iter().flat_map(|(_, names)| names.iter());
+
+	let (distance, name) = modifiers
+		.chain(keys)
+		.map(|name| (levenshtein(&upper, name), name))
+		.min_by_key(|(distance, _)| *distance)?;
+
+	(distance <= 2 && distance < upper.chars().count()).then(|| name.to_lowercase())
+}
+
+// The number of single character insertions, deletions and substitutions
+// turning `a` into `b`.
+fn levenshtein(a:&str, b:&str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+
+	let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+	for (i, a) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+
+		row[0] = i + 1;
+
+		for (j, b) in b.iter().enumerate() {
+			let substitution = diagonal + usize::from(a != *b);
+
+			diagonal = row[j + 1];
+
+			row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+		}
 	}
+
+	row[b.len()]
+}
+
+fn parse_key(key:&str) -> Result<Code, HotKeyParseError> {
+	let upper = key.to_uppercase();
+
+	KEY_NAMES
+		.iter()
+		.find(|(_, names)| names.contains(&upper.as_str()))
+		.map(|(code, _)| *code)
+		.ok_or_else(|| HotKeyParseError::UnsupportedKey(key.to_string()))
 }
 
 #[test]
@@ -377,28 +1321,55 @@ fn test_parse_hotkey() {
 		};
 	}
 
-	assert_parse_hotkey!("KeyX", HotKey { mods:Modifiers::empty(), key:Code::KeyX, id:0 });
+	assert_parse_hotkey!(
+		"KeyX",
+		HotKey { mods:Modifiers::empty(), sides:ModifierSides::EITHER, key:Code::KeyX, id:0 }
+	);
 
-	assert_parse_hotkey!("CTRL+KeyX", HotKey { mods:Modifiers::CONTROL, key:Code::KeyX, id:0 });
+	assert_parse_hotkey!(
+		"CTRL+KeyX",
+		HotKey { mods:Modifiers::CONTROL, sides:ModifierSides::EITHER, key:Code::KeyX, id:0 }
+	);
 
-	assert_parse_hotkey!("SHIFT+KeyC", HotKey { mods:Modifiers::SHIFT, key:Code::KeyC, id:0 });
+	assert_parse_hotkey!(
+		"SHIFT+KeyC",
+		HotKey { mods:Modifiers::SHIFT, sides:ModifierSides::EITHER, key:Code::KeyC, id:0 }
+	);
 
-	assert_parse_hotkey!("SHIFT+KeyC", HotKey { mods:Modifiers::SHIFT, key:Code::KeyC, id:0 });
+	assert_parse_hotkey!(
+		"SHIFT+KeyC",
+		HotKey { mods:Modifiers::SHIFT, sides:ModifierSides::EITHER, key:Code::KeyC, id:0 }
+	);
 
 	assert_parse_hotkey!(
 		"super+ctrl+SHIFT+alt+ArrowUp",
 		HotKey {
 			mods:Modifiers::SUPER | Modifiers::CONTROL | Modifiers::SHIFT | Modifiers::ALT,
+			sides:ModifierSides::EITHER,
 			key:Code::ArrowUp,
 			id:0,
 		}
 	);
 
-	assert_parse_hotkey!("Digit5", HotKey { mods:Modifiers::empty(), key:Code::Digit5, id:0 });
+	assert_parse_hotkey!(
+		"Digit5",
+		HotKey { mods:Modifiers::empty(), sides:ModifierSides::EITHER, key:Code::Digit5, id:0 }
+	);
+
+	assert_parse_hotkey!(
+		"alt+NumpadComma",
+		HotKey { mods:Modifiers::ALT, sides:ModifierSides::EITHER, key:Code::NumpadComma, id:0 }
+	);
 
-	assert_parse_hotkey!("KeyG", HotKey { mods:Modifiers::empty(), key:Code::KeyG, id:0 });
+	assert_parse_hotkey!(
+		"KeyG",
+		HotKey { mods:Modifiers::empty(), sides:ModifierSides::EITHER, key:Code::KeyG, id:0 }
+	);
 
-	assert_parse_hotkey!("SHiFT+F12", HotKey { mods:Modifiers::SHIFT, key:Code::F12, id:0 });
+	assert_parse_hotkey!(
+		"SHiFT+F12",
+		HotKey { mods:Modifiers::SHIFT, sides:ModifierSides::EITHER, key:Code::F12, id:0 }
+	);
 
 	assert_parse_hotkey!(
 		"CmdOrCtrl+Space",
@@ -407,11 +1378,42 @@ fn test_parse_hotkey() {
 			mods:Modifiers::SUPER,
 			#[cfg(not(target_os = "macos"))]
 			mods:Modifiers::CONTROL,
+			sides:ModifierSides::EITHER,
 			key:Code::Space,
 			id:0,
 		}
 	);
 
+	assert_parse_hotkey!(
+		"Win+Opt+KeyE",
+		HotKey {
+			mods:Modifiers::SUPER | Modifiers::ALT,
+			sides:ModifierSides::EITHER,
+			key:Code::KeyE,
+			id:0,
+		}
+	);
+
+	assert_parse_hotkey!(
+		"windows+meta+KeyE",
+		HotKey { mods:Modifiers::SUPER, sides:ModifierSides::EITHER, key:Code::KeyE, id:0 }
+	);
+
+	assert_parse_hotkey!(
+		"Ctrl+Yen",
+		HotKey { mods:Modifiers::CONTROL, sides:ModifierSides::EITHER, key:Code::IntlYen, id:0 }
+	);
+
+	assert_parse_hotkey!(
+		"Kana",
+		HotKey { mods:Modifiers::empty(), sides:ModifierSides::EITHER, key:Code::Lang1, id:0 }
+	);
+
+	assert_parse_hotkey!(
+		"Alt+§",
+		HotKey { mods:Modifiers::ALT, sides:ModifierSides::EITHER, key:Code::IntlBackslash, id:0 }
+	);
+
 	// Ensure that if it is just multiple modifiers, we do not panic.
 	// This would be a regression if this happened.
 	if HotKey::from_str("Shift+Ctrl").is_ok() {
@@ -419,6 +1421,155 @@ fn test_parse_hotkey() {
 	}
 }
 
+#[test]
+fn test_parse_shifted_symbols() {
+	for (symbol, key) in [
+		("~", "Backquote"),
+		("!", "Digit1"),
+		("@", "Digit2"),
+		("#", "Digit3"),
+		("$", "Digit4"),
+		("%", "Digit5"),
+		("^", "Digit6"),
+		("&", "Digit7"),
+		("*", "Digit8"),
+		("(", "Digit9"),
+		(")", "Digit0"),
+		("_", "Minus"),
+		("{", "BracketLeft"),
+		("}", "BracketRight"),
+		("|", "Backslash"),
+		(":", "Semicolon"),
+		("\"", "Quote"),
+		("<", "Comma"),
+		(">", "Period"),
+		("?", "Slash"),
+	] {
+		let explicit = parse_hotkey(&format!("Ctrl+Shift+{}", key)).unwrap();
+
+		assert_eq!(parse_hotkey(&format!("Ctrl+{}", symbol)).unwrap(), explicit);
+
+		// Writing the implied shift as well is the same hotkey.
+		assert_eq!(parse_hotkey(&format!("Ctrl+Shift+{}", symbol)).unwrap(), explicit);
+
+		assert_eq!(parse_hotkey(symbol).unwrap(), parse_hotkey(&format!("Shift+{}", key)).unwrap());
+	}
+
+	let id = |hotkey| parse_hotkey(hotkey).unwrap().id();
+
+	assert_eq!(id("Ctrl+?"), id("Ctrl+Shift+Slash"));
+
+	// A sided shift keeps its side.
+	assert_eq!(parse_hotkey("RightShift+!").unwrap(), parse_hotkey("RightShift+Digit1").unwrap());
+
+	// The unshifted spellings don't imply anything.
+	assert_eq!(parse_hotkey("Ctrl+/").unwrap().mods, Modifiers::CONTROL);
+
+	assert!(parse_hotkey("Ctrl+Shift").is_err());
+
+	assert!(parse_hotkey("Ctrl++").is_err());
+}
+
+#[test]
+fn test_parse_lenient() {
+	let ctrl_s = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS);
+
+	assert_eq!(HotKey::parse_lenient("Ctrl+KeyS").unwrap(), (ctrl_s, None));
+
+	for (typo, hint) in [
+		("Contrl+S", "Did you mean 'control+S'?"),
+		("Ctr+S", "Did you mean 'ctrl+S'?"),
+		("ctrl+KeS", "Did you mean 'ctrl+keys'?"),
+	] {
+		assert_eq!(HotKey::parse_lenient(typo).unwrap(), (ctrl_s, Some(hint.to_string())));
+	}
+
+	let (hotkey, hint) = HotKey::parse_lenient("Shfit+Escpe").unwrap();
+
+	assert_eq!(hotkey, HotKey::new(Some(Modifiers::SHIFT), Code::Escape));
+
+	assert_eq!(hint.as_deref(), Some("Did you mean 'shift+escape'?"));
+
+	// Too far from any name, or in the wrong order.
+	assert!(matches!(
+		HotKey::parse_lenient("Ctrl+Banana"),
+		Err(crate::Error::UnrecognizedHotKeyCode(_))
+	));
+
+	assert!(HotKey::parse_lenient("S+Ctrl").is_err());
+
+	assert_eq!(levenshtein("SHFIT", "SHIFT"), 2);
+
+	assert_eq!(levenshtein("", "CTRL"), 4);
+}
+
+#[test]
+fn test_hotkey_from_env() {
+	let default = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyP);
+
+	// Names of their own, the tests run in parallel in one process.
+	let (set, invalid, unset) =
+		("GLOBAL_HOTKEY_TEST_SET", "GLOBAL_HOTKEY_TEST_INVALID", "GLOBAL_HOTKEY_TEST_UNSET");
+
+	std::env::set_var(set, "Alt+F4");
+
+	std::env::set_var(invalid, "Alt+Nope");
+
+	let alt_f4 = HotKey::new(Some(Modifiers::ALT), Code::F4);
+
+	assert_eq!(HotKey::from_env(set).unwrap(), alt_f4);
+
+	assert_eq!(HotKey::from_env_or(set, default), alt_f4);
+
+	assert!(matches!(
+		HotKey::from_env(unset),
+		Err(crate::Error::EnvVar { source:std::env::VarError::NotPresent, .. })
+	));
+
+	assert_eq!(HotKey::from_env_or(unset, default), default);
+
+	assert!(matches!(HotKey::from_env(invalid), Err(crate::Error::UnrecognizedHotKeyCode(_))));
+
+	assert_eq!(HotKey::from_env_or(invalid, default), default);
+}
+
+#[test]
+fn test_validate_hotkey_string() {
+	assert!(validate_hotkey_string("shift+alt+KeyQ").is_ok());
+
+	assert!(matches!(validate_hotkey_string("shift+alt+"), Err(crate::Error::EmptyHotKeyToken(_))));
+
+	assert!(matches!(
+		validate_hotkey_string("shift+KeyQ+KeyW"),
+		Err(crate::Error::UnexpectedHotKeyFormat(_))
+	));
+
+	assert!(matches!(
+		validate_hotkey_string("shift+Foo"),
+		Err(crate::Error::UnrecognizedHotKeyCode(key)) if key == "Foo"
+	));
+}
+
+#[test]
+fn test_hotkey_set_diff() {
+	let [a, b, c, d] =
+		["Shift+KeyA", "Shift+KeyB", "Alt+KeyC", "Control+KeyD"].map(|h| parse_hotkey(h).unwrap());
+
+	assert_eq!(hotkey_set_diff(&[a, b, c], &[d, c, a]), (vec![d], vec![b]));
+
+	assert_eq!(hotkey_set_diff(&[], &[a, b]), (vec![a, b], vec![]));
+
+	assert_eq!(hotkey_set_diff(&[a, b], &[a, b]), (vec![], vec![]));
+
+	// A custom id moved to another shortcut is registered again.
+	assert_eq!(hotkey_set_diff(&[a.with_id(1)], &[b.with_id(1)]), (
+		vec![b.with_id(1)],
+		vec![a.with_id(1)]
+	));
+
+	assert_eq!(hotkey_set_diff(&[a], &[a.with_id(1)]), (vec![a.with_id(1)], vec![a]));
+}
+
 #[test]
 fn test_equality() {
 	let h1 = parse_hotkey("Shift+KeyR").unwrap();
@@ -442,4 +1593,468 @@ fn test_equality() {
 			&& h4.id() == h5.id()
 			&& h5.id() != h6.id()
 	);
+
+	// The derived `PartialEq` compares the ids too.
+	assert_ne!(h1, h1.with_id(1));
+
+	assert!(h1.eq_by_key(&h1.with_id(1)));
+
+	assert!(h1.eq_by_key(&h1.with_side(Modifiers::SHIFT, ModifierSide::Left)));
+
+	assert!(!h1.eq_by_key(&h4));
+}
+
+#[test]
+fn test_check_id_uniqueness() {
+	let a = HotKey::new(Some(Modifiers::SHIFT), Code::KeyA);
+
+	let b = HotKey::new(Some(Modifiers::SHIFT), Code::KeyB);
+
+	assert_eq!(check_id_uniqueness(&[a, b, a]), None);
+
+	let c = HotKey::new(None, Code::KeyC).with_id(a.id());
+
+	assert_eq!(check_id_uniqueness(&[a, b, c]), Some((a, c)));
+}
+
+#[test]
+fn test_sided_modifiers() {
+	let right = parse_hotkey("RightCmd+Space").unwrap();
+
+	let left = parse_hotkey("LSuper+Space").unwrap();
+
+	let either = parse_hotkey("Super+Space").unwrap();
+
+	assert_eq!(right.mods, Modifiers::SUPER);
+
+	assert_eq!(right.sides.side(Modifiers::SUPER), ModifierSide::Right);
+
+	assert_eq!(left.sides.side(Modifiers::SUPER), ModifierSide::Left);
+
+	assert!(either.sides.is_either());
+
+	assert!(right.id() != left.id() && left.id() != either.id() && right.id() != either.id());
+
+	assert!(!right.overlaps(&left));
+
+	assert!(right.overlaps(&either) && left.overlaps(&either));
+
+	assert_eq!(
+		right,
+		HotKey::new(None, Code::Space).with_side(Modifiers::SUPER, ModifierSide::Right)
+	);
+
+	assert_eq!(parse_hotkey("ShiftLeft+alt+KeyA").unwrap().into_string(), "leftshift+alt+KeyA");
+
+	assert_eq!(parse_hotkey(&right.into_string()).unwrap(), right);
+
+	// Unsided hotkeys keep the same ids they always had.
+	assert_eq!(either.id(), Modifiers::SUPER.bits() << 16 | Code::Space as u32);
+
+	// A side can't be attached to a modifier that differs between platforms.
+	assert!(parse_hotkey("RightCmdOrCtrl+Space").is_err());
+}
+
+#[test]
+fn test_ids_of_every_accepted_string() {
+	// The ids generated before canonicalization had a module of its own, spelled
+	// out so that changing the packing fails here.
+	let reference = |mods:Modifiers, sides:[u32; 4], key:Code| {
+		let sides = sides.iter().enumerate().fold(0, |bits, (i, side)| bits | side << (i * 2));
+
+		mods.bits() << 16 | sides << 8 | key as u32
+	};
+
+	let keys = KEY_NAMES
+		.iter()
+		.flat_map(|(code, names)| names.iter().map(|name| (*name, *code, Modifiers::empty())))
+		.chain(SHIFTED_SYMBOLS.iter().map(|(name, code)| (*name, *code, Modifiers::SHIFT)));
+
+	for (key_name, key, implied) in keys {
+		for combination in 0..16usize {
+			let mut tokens = Vec::new();
+
+			let (mut mods, mut sides) = (implied, [0; 4]);
+
+			// Each combination picks other aliases and sides for its modifiers.
+			for (i, (modifier, names)) in MODIFIER_NAMES.iter().enumerate() {
+				if combination & 1 << i == 0 {
+					continue;
+				}
+
+				let side = (combination + i) % 3;
+
+				let name = names[combination % names.len()];
+
+				tokens.push(format!("{}{}", ["", "Left", "Right"][side], name.to_lowercase()));
+
+				mods |= *modifier;
+
+				sides[canonical::MODIFIERS.iter().position(|m| m == modifier).unwrap()] =
+					side as u32;
+			}
+
+			tokens.push(key_name.to_string());
+
+			let string = tokens.join("+");
+
+			let hotkey = parse_hotkey(&string).unwrap();
+
+			assert_eq!(hotkey.id(), reference(mods, sides, key), "{}", string);
+
+			assert_eq!(parse_hotkey(&hotkey.to_string()).unwrap().id(), hotkey.id(), "{}", string);
+		}
+	}
+
+	// `META` is identified as `SUPER`, and written as it was set.
+	let meta = HotKey { mods:Modifiers::META, sides:ModifierSides::EITHER, key:Code::KeyM, id:0 };
+
+	assert_eq!(meta.to_string(), "meta+KeyM");
+
+	assert_eq!(
+		parse_hotkey(&meta.to_string()).unwrap(),
+		HotKey::new(Some(Modifiers::META), Code::KeyM)
+	);
+}
+
+#[test]
+fn test_new_exact() {
+	let folded = HotKey::new(Some(Modifiers::META | Modifiers::SHIFT), Code::KeyM);
+
+	assert_eq!(folded.mods, Modifiers::SUPER | Modifiers::SHIFT);
+
+	// The ids of the folded hotkeys never change.
+	assert_eq!(folded.id(), 0x2200_0000 | Code::KeyM as u32);
+
+	let exact = HotKey::new_exact(Some(Modifiers::META | Modifiers::SHIFT), Code::KeyM);
+
+	assert_eq!(exact.mods, Modifiers::META | Modifiers::SHIFT);
+
+	assert_eq!(exact.id(), folded.id());
+
+	assert_eq!(exact, folded);
+
+	assert!(exact.overlaps(&folded));
+
+	assert_eq!(exact.to_string(), "shift+meta+KeyM");
+
+	assert_eq!(folded.to_string(), "shift+super+KeyM");
+
+	// Both round-trip as they were written.
+	for hotkey in [exact, folded] {
+		assert_eq!(parse_hotkey(&hotkey.to_string()).unwrap().mods, hotkey.mods);
+	}
+
+	let sided = exact.with_side(Modifiers::META, ModifierSide::Left);
+
+	assert_eq!(sided.mods, Modifiers::META | Modifiers::SHIFT);
+
+	assert_eq!(sided.to_string(), "shift+leftmeta+KeyM");
+
+	assert_eq!(parse_hotkey("shift+leftmeta+KeyM").unwrap().mods, sided.mods);
+
+	assert_eq!(sided.id(), folded.with_side(Modifiers::SUPER, ModifierSide::Left).id());
+}
+
+#[cfg(any(
+	target_os = "linux",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "openbsd",
+	target_os = "netbsd"
+))]
+#[test]
+fn test_from_x11_keysym() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::KeyK);
+
+	// `XK_k` and `XK_K`.
+	assert_eq!(HotKey::from_x11_keysym(0x6b, Modifiers::CONTROL).unwrap(), hotkey);
+	assert_eq!(HotKey::from_x11_keysym(0x4b, Modifiers::CONTROL).unwrap(), hotkey);
+
+	// `XK_F5`.
+	assert_eq!(HotKey::from_x11_keysym(0xffc2, Modifiers::empty()).unwrap().key, Code::F5);
+
+	// `XK_KP_Separator`.
+	assert_eq!(
+		HotKey::from_x11_keysym(0xffac, Modifiers::empty()).unwrap().key,
+		Code::NumpadComma
+	);
+
+	// `XK_Num_Lock` and `XK_KP_Enter`.
+	assert_eq!(HotKey::from_x11_keysym(0xff7f, Modifiers::empty()).unwrap().key, Code::NumLock);
+	assert_eq!(
+		HotKey::from_x11_keysym(0xff8d, Modifiers::empty()).unwrap().key,
+		Code::NumpadEnter
+	);
+
+	// `XK_Shift_L` is known but can't be grabbed.
+	for keysym in [0x12345678, 0xffe1] {
+		assert!(matches!(
+			HotKey::from_x11_keysym(keysym, Modifiers::empty()),
+			Err(crate::Error::UnrecognizedHotKeyCode(_))
+		));
+	}
+}
+
+#[test]
+fn test_from_evdev_code() {
+	// `KEY_S`, `KEY_F5` and `KEY_KPENTER`.
+	assert_eq!(
+		HotKey::from_evdev_code(31, Modifiers::CONTROL).unwrap(),
+		HotKey::new(Some(Modifiers::CONTROL), Code::KeyS)
+	);
+
+	assert_eq!(HotKey::from_evdev_code(63, Modifiers::empty()).unwrap().key, Code::F5);
+
+	assert_eq!(HotKey::from_evdev_code(96, Modifiers::empty()).unwrap().key, Code::NumpadEnter);
+
+	// `KEY_RESERVED` and a gap of the table.
+	for code in [0, 84] {
+		assert!(matches!(
+			HotKey::from_evdev_code(code, Modifiers::empty()),
+			Err(crate::Error::UnrecognizedHotKeyCode(_))
+		));
+	}
+}
+
+#[test]
+fn test_from_parts() {
+	const PALETTE:HotKey = HotKey::from_parts(Modifiers::META.union(Modifiers::SHIFT), Code::KeyP);
+
+	let parsed = "Super+Shift+KeyP".parse::<HotKey>().unwrap();
+
+	assert_eq!(PALETTE, parsed);
+
+	assert_eq!(PALETTE.id(), parsed.id());
+
+	let sided =
+		HotKey::new(Some(Modifiers::ALT), Code::KeyP).with_side(Modifiers::ALT, ModifierSide::Left);
+
+	assert_ne!(sided.id(), HotKey::from_parts(Modifiers::ALT, Code::KeyP).id());
+}
+
+#[test]
+fn test_parse_special_keys() {
+	for (token, key) in [
+		("PrintScreen", Code::PrintScreen),
+		("Pause", Code::Pause),
+		("PauseBreak", Code::Pause),
+		("ContextMenu", Code::ContextMenu),
+		("Menu", Code::ContextMenu),
+		("apps", Code::ContextMenu),
+		("BrightnessUp", Code::BrightnessUp),
+		("BrightnessDown", Code::BrightnessDown),
+		("Eject", Code::Eject),
+		("Power", Code::Power),
+		("WakeUp", Code::WakeUp),
+	] {
+		let hotkey = format!("Shift+{}", token).parse::<HotKey>().unwrap();
+
+		assert_eq!(hotkey, HotKey::new(Some(Modifiers::SHIFT), key), "{}", token);
+
+		assert_eq!(hotkey.to_string().parse::<HotKey>().unwrap(), hotkey);
+	}
+
+	// Supported by every backend but macOS, which has no `Pause` key.
+	assert_eq!(
+		HotKey::new(None, Code::Pause).is_supported(),
+		cfg!(not(target_os = "macos"))
+	);
+
+	assert!(HotKey::new(None, Code::PrintScreen).is_supported());
+
+	assert!(HotKey::new(None, Code::ContextMenu).is_supported());
+}
+
+#[test]
+fn test_is_submatch_of() {
+	let save_as = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyS);
+
+	let pressed = HotKey::new(Some(Modifiers::SHIFT), Code::KeyS);
+
+	assert!(pressed.is_submatch_of(&save_as));
+
+	assert!(save_as.is_submatch_of(&save_as));
+
+	assert!(!save_as.is_submatch_of(&pressed));
+
+	assert!(!HotKey::new(Some(Modifiers::SHIFT), Code::KeyD).is_submatch_of(&save_as));
+
+	let save = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS);
+
+	let quit = HotKey::new(Some(Modifiers::CONTROL), Code::KeyQ);
+
+	let registered = [save, quit, save_as];
+
+	assert_eq!(HotKey::candidates_for_key(Code::KeyS, &registered), [save, save_as]);
+
+	assert!(HotKey::candidates_for_key(Code::KeyD, &registered).is_empty());
+}
+
+#[test]
+fn test_to_electron_accelerator() {
+	let accelerator = |hotkey:&str| hotkey.parse::<HotKey>().unwrap().to_electron_accelerator();
+
+	assert_eq!(accelerator("shift+control+KeyS"), "Control+Shift+S");
+
+	assert_eq!(accelerator("RightAlt+Digit4"), "Alt+4");
+
+	assert_eq!(accelerator("Enter"), "Return");
+
+	assert_eq!(accelerator("ctrl+Numpad7"), "Control+num7");
+
+	assert_eq!(accelerator("alt+BracketLeft"), "Alt+[");
+
+	assert_eq!(accelerator("shift+F13"), "Shift+F13");
+
+	assert_eq!(accelerator("PageUp"), "PageUp");
+
+	if cfg!(target_os = "macos") {
+		assert_eq!(accelerator("super+control+shift+KeyZ"), "CommandOrControl+Shift+Z");
+
+		assert_eq!(accelerator("super+KeyZ"), "Command+Z");
+	} else {
+		assert_eq!(accelerator("super+control+shift+KeyZ"), "Control+Shift+Super+Z");
+
+		assert_eq!(accelerator("super+KeyZ"), "Super+Z");
+	}
+}
+
+// A key of every kind `parse_key` knows, for the property tests.
+#[cfg(test)]
+static PROPTEST_KEYS:[Code; 16] = [
+	Code::KeyA,
+	Code::Digit0,
+	Code::Backquote,
+	Code::Backslash,
+	Code::Equal,
+	Code::Quote,
+	Code::Space,
+	Code::ArrowUp,
+	Code::Numpad7,
+	Code::NumpadComma,
+	Code::Escape,
+	Code::F24,
+	Code::AudioVolumeMute,
+	Code::MediaTrackPrevious,
+	Code::IntlYen,
+	Code::Lang2,
+];
+
+// Sided modifiers, short keys and blanks, joined with `+` into hotkeys.
+#[cfg(test)]
+const PROPTEST_TOKEN:&str = "(?i)(left|right|l|r)?(shift|ctrl|cmd|alt)|[a-z0-9§]{0,6}| *";
+
+#[cfg(test)]
+proptest::proptest! {
+	#[test]
+	fn test_parse_display_roundtrip(
+		mods in 0..16u8,
+		sides in proptest::collection::vec(0..3u8, 4),
+		key in proptest::sample::select(&PROPTEST_KEYS[..]),
+	) {
+		let modifiers = [Modifiers::SHIFT, Modifiers::CONTROL, Modifiers::ALT, Modifiers::SUPER];
+
+		let used = (0..4).filter(|i| mods & 1 << i != 0);
+
+		let mut hotkey =
+			HotKey::new(Some(used.clone().map(|i| modifiers[i]).collect()), key);
+
+		for i in used {
+			match sides[i] {
+				1 => hotkey = hotkey.with_side(modifiers[i], ModifierSide::Left),
+				2 => hotkey = hotkey.with_side(modifiers[i], ModifierSide::Right),
+				_ => {},
+			}
+		}
+
+		let parsed = parse_hotkey(&hotkey.to_string()).unwrap();
+
+		proptest::prop_assert_eq!(parsed, hotkey);
+
+		proptest::prop_assert_eq!(parse_hotkey(&parsed.to_string()).unwrap(), parsed);
+	}
+
+	#[test]
+	fn test_parse_never_panics(hotkey in "\\PC*") {
+		if let Ok(parsed) = parse_hotkey(&hotkey) {
+			proptest::prop_assert_eq!(parse_hotkey(&parsed.to_string()).unwrap(), parsed);
+		}
+	}
+
+	#[test]
+	fn test_parse_tokens_never_panics(
+		tokens in proptest::collection::vec(PROPTEST_TOKEN, 0..6)
+	) {
+		if let Ok(parsed) = parse_hotkey(&tokens.join("+")) {
+			proptest::prop_assert_eq!(parse_hotkey(&parsed.to_string()).unwrap(), parsed);
+		}
+	}
+}
+
+#[test]
+fn test_gtk_accelerator() {
+	let primary = if cfg!(target_os = "macos") { Modifiers::SUPER } else { Modifiers::CONTROL };
+
+	let other = if cfg!(target_os = "macos") { "<Control>" } else { "<Super>" };
+
+	for (accelerator, mods, key) in [
+		("<Primary><Shift>s", primary | Modifiers::SHIFT, Code::KeyS),
+		("<Alt>F4", Modifiers::ALT, Code::F4),
+		("<Primary>1", primary, Code::Digit1),
+		("<Primary>Page_Up", primary, Code::PageUp),
+		("<Shift>KP_Add", Modifiers::SHIFT, Code::NumpadAdd),
+		("Escape", Modifiers::empty(), Code::Escape),
+		("XF86AudioMute", Modifiers::empty(), Code::AudioVolumeMute),
+	] {
+		let hotkey = HotKey::from_gtk_accelerator(accelerator).unwrap();
+
+		assert_eq!(hotkey, HotKey::new(Some(mods), key));
+
+		assert_eq!(hotkey.to_gtk_accelerator(), accelerator);
+	}
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::Slash);
+
+	assert_eq!(hotkey.to_gtk_accelerator(), format!("<Primary>{}slash", other));
+
+	// Names are case-insensitive, as in GTK.
+	assert_eq!(
+		HotKey::from_gtk_accelerator("<control><SHIFT>A").unwrap(),
+		HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyA)
+	);
+
+	assert!(matches!(
+		HotKey::from_gtk_accelerator("<Primary>"),
+		Err(crate::Error::EmptyHotKeyToken(_))
+	));
+
+	assert!(matches!(
+		HotKey::from_gtk_accelerator("<Hyper>a"),
+		Err(crate::Error::UnexpectedHotKeyFormat(_))
+	));
+
+	assert!(matches!(
+		HotKey::from_gtk_accelerator("<Primary"),
+		Err(crate::Error::UnexpectedHotKeyFormat(_))
+	));
+
+	assert!(matches!(
+		HotKey::from_gtk_accelerator("<Primary>KeyA"),
+		Err(crate::Error::UnrecognizedHotKeyCode(_))
+	));
+}
+
+#[test]
+fn test_hotkey_debug() {
+	let hotkey = HotKey::new(Some(Modifiers::SHIFT | Modifiers::CONTROL), Code::KeyA);
+
+	assert_eq!(hotkey.describe(), "shift+control+KeyA (id 0x02080013)");
+
+	assert_eq!(
+		format!("{:?}", hotkey),
+		r#"HotKey { accelerator: "shift+control+KeyA", id: 0x02080013 }"#
+	);
+
+	assert_eq!(hotkey.with_id(10).describe(), "shift+control+KeyA (id 0x0000000a)");
 }
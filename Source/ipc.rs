@@ -0,0 +1,258 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	io::{BufRead, BufReader},
+	net::Shutdown,
+	os::unix::net::{UnixListener, UnixStream},
+	path::{Path, PathBuf},
+	sync::{
+		Arc,
+		Mutex,
+		Weak,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+
+use crossbeam_channel::{Receiver, unbounded};
+
+use crate::GlobalHotKeyEvent;
+
+// How long a client may keep an event from being written before it's
+// dropped, so that a client that stopped reading doesn't hold up the others.
+const WRITE_TIMEOUT:Duration = Duration::from_millis(100);
+
+/// Forwards every hotkey event of the process to the clients connected to a
+/// Unix domain socket, e.g. for a daemon holding the grabs on behalf of
+/// several applications.
+///
+/// Each event is written as a line of its JSON form, see
+/// [`GlobalHotKeyEvent::to_json`], which [`GlobalHotKeyClient`] reads back.
+/// The clients receive the events in addition to
+/// [`GlobalHotKeyEvent::event_receiver`] or the handler set with
+/// [`GlobalHotKeyEvent::set_event_handler`], like
+/// [`GlobalHotKeyEvent::subscribe_coalesced`].
+///
+/// ```no_run
+/// # use global_hotkey::{GlobalHotKeyManager, GlobalHotKeyServer, hotkey::{Code, HotKey}};
+/// # fn main() -> global_hotkey::Result<()> {
+/// let manager = GlobalHotKeyManager::new()?;
+///
+/// manager.register(HotKey::new(None, Code::F9))?;
+///
+/// let _server = GlobalHotKeyServer::bind("/tmp/hotkeys.sock")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Dropping the server stops accepting clients, disconnects the connected
+/// ones and removes the socket file.
+pub struct GlobalHotKeyServer {
+	inner:Arc<Inner>,
+	path:PathBuf,
+}
+
+#[derive(Default)]
+struct Inner {
+	clients:Mutex<Vec<UnixStream>>,
+	closed:AtomicBool,
+}
+
+static SERVERS:Mutex<Vec<Weak<Inner>>> = Mutex::new(Vec::new());
+
+impl GlobalHotKeyServer {
+	/// Listens on the socket at `path` and forwards the events to the clients
+	/// connecting to it from then on.
+	///
+	/// Fails if the socket can't be created, e.g. because `path` already
+	/// exists.
+	pub fn bind<P:AsRef<Path>>(path:P) -> crate::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+
+		let listener = UnixListener::bind(&path)?;
+
+		let inner = Arc::new(Inner::default());
+
+		SERVERS.lock().unwrap().push(Arc::downgrade(&inner));
+
+		let accepting = inner.clone();
+
+		std::thread::spawn(move || accept(&listener, &accepting));
+
+		Ok(Self { inner, path })
+	}
+
+	/// Returns the path of the socket.
+	pub fn path(&self) -> &Path { &self.path }
+
+	/// Returns the number of clients connected, as of the last event written
+	/// to them.
+	pub fn client_count(&self) -> usize { self.inner.clients.lock().unwrap().len() }
+}
+
+impl Drop for GlobalHotKeyServer {
+	fn drop(&mut self) {
+		self.inner.closed.store(true, Ordering::SeqCst);
+
+		// Wakes up the accepting thread, which sees the server closed.
+		let _ = UnixStream::connect(&self.path);
+
+		for client in self.inner.clients.lock().unwrap().drain(..) {
+			let _ = client.shutdown(Shutdown::Both);
+		}
+
+		let _ = std::fs::remove_file(&self.path);
+	}
+}
+
+// Runs until the server is dropped, which is noticed at the next connection.
+fn accept(listener:&UnixListener, inner:&Inner) {
+	for stream in listener.incoming() {
+		if inner.closed.load(Ordering::SeqCst) {
+			return;
+		}
+
+		let client = stream.and_then(|client| {
+			client.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+			Ok(client)
+		});
+
+		match client {
+			Ok(client) => inner.clients.lock().unwrap().push(client),
+			Err(e) => log::warn!("Failed to accept a hotkey event client: {}", e),
+		}
+	}
+}
+
+/// Writes `event` to the clients of every server, dropping the clients that
+/// went away and forgetting the dropped servers.
+pub(crate) fn dispatch(event:GlobalHotKeyEvent) {
+	SERVERS.lock().unwrap().retain(|server| {
+		let Some(server) = server.upgrade() else {
+			return false;
+		};
+
+		server.clients.lock().unwrap().retain(|mut client| event.write_to(&mut client).is_ok());
+
+		true
+	});
+}
+
+/// Receives the events forwarded by a [`GlobalHotKeyServer`].
+///
+/// ```no_run
+/// # use global_hotkey::GlobalHotKeyClient;
+/// # fn main() -> global_hotkey::Result<()> {
+/// let client = GlobalHotKeyClient::connect("/tmp/hotkeys.sock")?;
+///
+/// for event in client.receiver() {
+///     println!("{:?}", event);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A thread reads the socket until the server goes away or the client is
+/// dropped, the channel is disconnected then.
+pub struct GlobalHotKeyClient {
+	stream:UnixStream,
+	receiver:Receiver<GlobalHotKeyEvent>,
+}
+
+impl GlobalHotKeyClient {
+	/// Connects to the server listening on the socket at `path`.
+	pub fn connect<P:AsRef<Path>>(path:P) -> crate::Result<Self> {
+		let stream = UnixStream::connect(path)?;
+
+		let reader = BufReader::new(stream.try_clone()?);
+
+		let (sender, receiver) = unbounded();
+
+		std::thread::spawn(move || {
+			for line in reader.lines().map_while(Result::ok) {
+				match GlobalHotKeyEvent::from_json(&line) {
+					Ok(event) => {
+						if sender.send(event).is_err() {
+							return;
+						}
+					},
+					Err(e) => log::warn!("Skipping a hotkey event line: {}", e),
+				}
+			}
+		});
+
+		Ok(Self { stream, receiver })
+	}
+
+	/// Returns the channel receiving the events of the server.
+	pub fn receiver(&self) -> Receiver<GlobalHotKeyEvent> { self.receiver.clone() }
+}
+
+impl Drop for GlobalHotKeyClient {
+	fn drop(&mut self) { let _ = self.stream.shutdown(Shutdown::Both); }
+}
+
+#[test]
+fn test_unix_socket_forwarding() {
+	use crate::{
+		HotKeyState,
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let path = std::env::temp_dir().join(format!("global-hotkey-{}.sock", std::process::id()));
+
+	let server = GlobalHotKeyServer::bind(&path).unwrap();
+
+	assert!(GlobalHotKeyServer::bind(&path).is_err());
+
+	let clients = [(); 2].map(|()| GlobalHotKeyClient::connect(&path).unwrap());
+
+	// The connections are accepted on another thread.
+	for _ in 0..500 {
+		if server.client_count() == 2 {
+			break;
+		}
+
+		std::thread::sleep(Duration::from_millis(10));
+	}
+
+	assert_eq!(server.client_count(), 2);
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyU);
+
+	manager.register(hotkey).unwrap();
+
+	manager.simulate_press(&hotkey);
+
+	manager.simulate_release(&hotkey);
+
+	// Other tests share the dispatch, only look at our own events.
+	for client in &clients {
+		let ours = client
+			.receiver()
+			.iter()
+			.filter(|e| e.id() == hotkey.id())
+			.map(|e| e.state())
+			.take(2)
+			.collect::<Vec<_>>();
+
+		assert_eq!(ours, [HotKeyState::Pressed, HotKeyState::Released]);
+	}
+
+	let receiver = clients[0].receiver();
+
+	drop(server);
+
+	assert!(!path.exists());
+
+	// Disconnected once the pending events are read.
+	assert!(receiver.iter().all(|e| e.id() != hotkey.id()));
+
+	assert!(GlobalHotKeyClient::connect(&path).is_err());
+}
@@ -0,0 +1,104 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A stable JSON form of [`GlobalHotKeyEvent`], for passing events to other
+//! processes, e.g. `{"id":1234567890,"state":"pressed"}`.
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GlobalHotKeyEvent, HotKeyState};
+
+// Mirrors the public types so the JSON form doesn't follow changes to their
+// derived serde representation, which spells the states `"Pressed"`.
+#[derive(Serialize, Deserialize)]
+struct JsonEvent {
+	id:u32,
+	state:JsonState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonState {
+	Pressed,
+	Released,
+}
+
+impl From<GlobalHotKeyEvent> for JsonEvent {
+	fn from(event:GlobalHotKeyEvent) -> Self {
+		let state = match event.state() {
+			HotKeyState::Pressed => JsonState::Pressed,
+			HotKeyState::Released => JsonState::Released,
+		};
+
+		Self { id:event.id(), state }
+	}
+}
+
+impl From<JsonEvent> for GlobalHotKeyEvent {
+	fn from(event:JsonEvent) -> Self {
+		let state = match event.state {
+			JsonState::Pressed => HotKeyState::Pressed,
+			JsonState::Released => HotKeyState::Released,
+		};
+
+		Self::new(event.id, state)
+	}
+}
+
+impl GlobalHotKeyEvent {
+	/// Returns the event as JSON, e.g. `{"id":1234567890,"state":"pressed"}`.
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(&JsonEvent::from(*self)).expect("event serialization can't fail")
+	}
+
+	/// Parses an event written by [`GlobalHotKeyEvent::to_json`].
+	pub fn from_json(s:&str) -> crate::Result<Self> {
+		serde_json::from_str::<JsonEvent>(s)
+			.map(Self::from)
+			.map_err(|e| crate::Error::InvalidEventJson(e.to_string()))
+	}
+
+	/// Writes the event as JSON followed by a newline, so a stream of events
+	/// can be read back one line at a time.
+	pub fn write_to<W:Write>(&self, mut writer:W) -> std::io::Result<()> {
+		serde_json::to_writer(&mut writer, &JsonEvent::from(*self))?;
+
+		writer.write_all(b"\n")
+	}
+}
+
+#[test]
+fn test_event_json() {
+	use std::io::BufRead;
+
+	let event = GlobalHotKeyEvent::new(1234567890, HotKeyState::Pressed);
+
+	assert_eq!(event.to_json(), r#"{"id":1234567890,"state":"pressed"}"#);
+
+	let released = GlobalHotKeyEvent::new(7, HotKeyState::Released);
+
+	let parsed = GlobalHotKeyEvent::from_json(r#"{ "state": "released", "id": 7 }"#);
+
+	assert_eq!(parsed.unwrap(), released);
+
+	assert!(matches!(
+		GlobalHotKeyEvent::from_json(r#"{"id":7,"state":"Pressed"}"#),
+		Err(crate::Error::InvalidEventJson(_))
+	));
+
+	let mut stream = Vec::new();
+
+	event.write_to(&mut stream).unwrap();
+
+	released.write_to(&mut stream).unwrap();
+
+	let events = stream
+		.lines()
+		.map(|line| GlobalHotKeyEvent::from_json(&line.unwrap()).unwrap())
+		.collect::<Vec<_>>();
+
+	assert_eq!(events, [event, released]);
+}
@@ -14,8 +14,14 @@
 //!
 //! ## Platform-specific notes:
 //!
-//! - On Windows a win32 event loop must be running on the thread. It doesn't need to be the main thread but you have to create the global hotkey manager on the same thread as the event loop.
+//! - On Windows a win32 event loop must be running on the thread. It doesn't need to be the main thread but you have to create the global hotkey manager on the same thread as the event loop. A thread without an event loop can call `platform::windows::pump_messages` periodically instead.
+//! - On Windows, [`WindowsBackend::RawInput`] observes the keys through Raw Input instead of `RegisterHotKey` and a keyboard hook, telling which keyboard they came from.
 //! - On macOS, an event loop must be running on the main thread so you also need to create the global hotkey manager on the main thread.
+//! - On Linux, libX11 is loaded at runtime. Enable the `x11rb` feature to talk to the X server with the pure-Rust [x11rb](https://docs.rs/x11rb) crate instead, for systems where libX11 isn't installed.
+//! - With the `winit` feature, [`winit::HotKeyPlugin`] creates the manager on the thread of a winit event loop and delivers the events inside it.
+//! - With the `lockfree-ringbuf` feature, the event channel is a ring buffer allocated once, see [`GlobalHotKeyEvent::set_buffer_size`].
+//! - On Unix, the `unix-socket` feature adds [`GlobalHotKeyServer`], forwarding the events to other processes over a Unix domain socket, and [`GlobalHotKeyClient`] receiving them.
+//! - On any other platform, the crate still compiles but [`GlobalHotKeyManager::new`] returns [`Error::PlatformUnsupported`].
 //!
 //! # Example
 //!
@@ -35,11 +41,11 @@
 //!
 //! # Processing global hotkey events
 //!
-//! You can also listen for the menu events using [`GlobalHotKeyEvent::receiver`] to get events for the hotkey pressed events.
+//! You can also listen for the menu events using [`GlobalHotKeyEvent::event_receiver`] to get events for the hotkey pressed events.
 //! ```no_run
 //! use global_hotkey::GlobalHotKeyEvent;
 //!
-//! if let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+//! if let Some(event) = GlobalHotKeyEvent::event_receiver().try_recv() {
 //!     println!("{:?}", event);
 //! }
 //! ```
@@ -50,45 +56,187 @@
 //! - macOS
 //! - Linux (X11 Only)
 
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 
+#[cfg(all(feature = "async", not(any(target_os = "windows", target_os = "macos"))))]
+mod async_register;
+mod batch;
+mod builder;
+mod canonical;
+mod callback;
+mod capabilities;
+mod coalesce;
+mod conflict;
+mod data;
+mod detach;
+mod device;
+mod diff;
 mod error;
+mod evdev;
+mod extension;
+mod gate;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod group;
 pub mod hotkey;
+#[cfg(all(unix, feature = "unix-socket"))]
+mod ipc;
+#[cfg(feature = "serde")]
+mod json;
+mod main_thread;
+mod map;
+mod metrics;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod platform;
+mod options;
+#[cfg(feature = "persistence")]
+pub mod persistence;
 mod platform_impl;
+mod process;
+pub mod raw;
+mod receiver;
+mod recorder;
+mod registry;
+mod reserved;
+mod status;
+mod tap_hold;
+#[cfg(feature = "winit")]
+pub mod winit;
 
+pub use self::builder::GlobalHotKeyManagerBuilder;
+pub use self::capabilities::Capabilities;
+pub use self::coalesce::CoalescingReceiver;
+pub use self::conflict::{ConflictInfo, ConflictOwner, ProcessInfo};
+pub use self::data::HotKeyData;
+pub use self::detach::DetachedHandle;
+pub use self::device::{DeviceFilter, KeyboardDevice};
+pub use self::diff::HotKeyDiff;
 pub use self::error::*;
+pub use self::extension::ExtensionMessage;
+pub use self::group::GroupId;
+#[cfg(all(unix, feature = "unix-socket"))]
+pub use self::ipc::{GlobalHotKeyClient, GlobalHotKeyServer};
+pub use self::map::{HotKeyManager, HotKeyMap};
+pub use self::metrics::{HotKeyMetrics, ManagerMetrics};
+pub use self::options::{
+    GlobalHotKeyManagerOptions, HotKeyOptions, LockState, LockToggle, WindowsBackend,
+};
+pub use self::raw::RawPlatformEvent;
+pub use self::receiver::GlobalHotKeyEventReceiver;
+pub use self::recorder::RecordingHandle;
+pub use self::registry::HotKeyRegistry;
+pub use self::reserved::SystemShortcut;
+pub use self::status::{Backend, Health, HotKeyStatus, ManagerStatus};
+#[cfg(any(test, feature = "mock"))]
+pub use self::mock::MockGlobalHotKeyManager;
+use callback::Callbacks;
+use data::DataStore;
+use group::HotKeyGroups;
 use hotkey::HotKey;
+use metrics::Metrics;
+use process::ManagerId;
 
 /// Describes the state of the [`HotKey`].
+///
+/// More states may be added in the future. Each state has a stable number, converted with
+/// `u8::from(state)` and `HotKeyState::try_from(number)`, e.g. to pass events to another
+/// language.
+#[non_exhaustive]
+#[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum HotKeyState {
     /// The [`HotKey`] is pressed (the key is down).
-    Pressed,
+    Pressed = 0,
     /// The [`HotKey`] is released (the key is up).
-    Released,
+    Released = 1,
+}
+
+impl HotKeyState {
+    /// Returns `true` for [`HotKeyState::Pressed`], `false` for [`HotKeyState::Released`].
+    pub fn as_bool(&self) -> bool {
+        *self == Self::Pressed
+    }
+
+    /// Returns [`HotKeyState::Pressed`] for `true`, [`HotKeyState::Released`] for `false`.
+    pub fn from_bool(pressed: bool) -> Self {
+        if pressed {
+            Self::Pressed
+        } else {
+            Self::Released
+        }
+    }
+}
+
+impl From<HotKeyState> for u8 {
+    fn from(state: HotKeyState) -> Self {
+        state as u8
+    }
+}
+
+impl TryFrom<u8> for HotKeyState {
+    type Error = Error;
+
+    /// Fails with [`Error::UnknownHotKeyState`] for numbers of no state, e.g. a state added
+    /// by a newer version of the crate.
+    fn try_from(value: u8) -> crate::Result<Self> {
+        match value {
+            0 => Ok(Self::Pressed),
+            1 => Ok(Self::Released),
+            _ => Err(Error::UnknownHotKeyState(value)),
+        }
+    }
 }
 
 /// Describes a global hotkey event emitted when a [`HotKey`] is pressed or released.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct GlobalHotKeyEvent {
     /// Id of the associated [`HotKey`].
+    #[deprecated(note = "use `GlobalHotKeyEvent::id`, the field will become private")]
     pub id: u32,
     /// State of the associated [`HotKey`].
+    #[deprecated(note = "use `GlobalHotKeyEvent::state`, the field will become private")]
     pub state: HotKeyState,
 }
 
-/// A reciever that could be used to listen to global hotkey events.
-pub type GlobalHotKeyEventReceiver = Receiver<GlobalHotKeyEvent>;
 type GlobalHotKeyEventHandler = Box<dyn Fn(GlobalHotKeyEvent) + Send + Sync + 'static>;
 
 static GLOBAL_HOTKEY_CHANNEL: Lazy<(Sender<GlobalHotKeyEvent>, GlobalHotKeyEventReceiver)> =
-    Lazy::new(unbounded);
+    Lazy::new(|| {
+        #[cfg(feature = "lockfree-ringbuf")]
+        let (sender, receiver) =
+            crossbeam_channel::bounded(GLOBAL_HOTKEY_BUFFER_SIZE.load(Ordering::Relaxed));
+        #[cfg(not(feature = "lockfree-ringbuf"))]
+        let (sender, receiver) = unbounded();
+
+        (sender, GlobalHotKeyEventReceiver::new(receiver))
+    });
 static GLOBAL_HOTKEY_EVENT_HANDLER: OnceCell<Option<GlobalHotKeyEventHandler>> = OnceCell::new();
+// The capacity of the event channel with the `lockfree-ringbuf` feature, read once when it's
+// created.
+static GLOBAL_HOTKEY_BUFFER_SIZE: AtomicUsize = AtomicUsize::new(64);
 
+// The fields are only deprecated for the users of the crate.
+#[allow(deprecated)]
 impl GlobalHotKeyEvent {
+    /// Creates an event, e.g. to test the code handling them.
+    pub fn new(id: u32, state: HotKeyState) -> Self {
+        Self { id, state }
+    }
+
     /// Returns the id of the associated [`HotKey`].
     pub fn id(&self) -> u32 {
         self.id
@@ -99,22 +247,70 @@ impl GlobalHotKeyEvent {
         self.state
     }
 
+    /// Returns `true` if the associated [`HotKey`] was pressed.
+    pub fn is_pressed(&self) -> bool {
+        self.state == HotKeyState::Pressed
+    }
+
+    /// Returns `true` if the associated [`HotKey`] was released.
+    pub fn is_released(&self) -> bool {
+        self.state == HotKeyState::Released
+    }
+
     /// Gets a reference to the event channel's [`GlobalHotKeyEventReceiver`]
     /// which can be used to listen for global hotkey events.
     ///
     /// ## Note
     ///
     /// This will not receive any events if [`GlobalHotKeyEvent::set_event_handler`] has been called with a `Some` value.
-    pub fn receiver<'a>() -> &'a GlobalHotKeyEventReceiver {
+    pub fn event_receiver<'a>() -> &'a GlobalHotKeyEventReceiver {
         &GLOBAL_HOTKEY_CHANNEL.1
     }
 
+    /// Returns every pending event of [`GlobalHotKeyEvent::event_receiver`] without blocking,
+    /// e.g. once per frame of a game loop or an immediate-mode GUI.
+    ///
+    /// Nothing is allocated when no event is pending.
+    pub fn drain() -> Vec<GlobalHotKeyEvent> {
+        Self::event_receiver().try_drain()
+    }
+
+    /// Gets a reference to the `crossbeam-channel` receiver of the event channel, which
+    /// [`GlobalHotKeyEvent::event_receiver`] wraps.
+    #[deprecated(
+        note = "use `GlobalHotKeyEvent::event_receiver`, the channel implementation may change"
+    )]
+    pub fn receiver<'a>() -> &'a Receiver<GlobalHotKeyEvent> {
+        GLOBAL_HOTKEY_CHANNEL.1.channel()
+    }
+
+    /// Sets how many events the channel of [`GlobalHotKeyEvent::event_receiver`] holds with the
+    /// `lockfree-ringbuf` feature, 64 by default.
+    ///
+    /// The channel is then a ring buffer allocated once, so dispatching an event never
+    /// allocates. Events arriving while it's full are dropped and counted in
+    /// [`ManagerMetrics::events_dropped`].
+    ///
+    /// Returns `false` and changes nothing if the channel was created already, which happens
+    /// when the first manager is created or the receiver is first used. Without the feature
+    /// the channel is unbounded and the size is ignored.
+    pub fn set_buffer_size(capacity: usize) -> bool {
+        if Lazy::get(&GLOBAL_HOTKEY_CHANNEL).is_some() {
+            return false;
+        }
+
+        // A zero capacity channel only hands events to a receiver already waiting.
+        GLOBAL_HOTKEY_BUFFER_SIZE.store(capacity.max(1), Ordering::Relaxed);
+
+        true
+    }
+
     /// Set a handler to be called for new events. Useful for implementing custom event sender.
     ///
     /// ## Note
     ///
     /// Calling this function with a `Some` value,
-    /// will not send new events to the channel associated with [`GlobalHotKeyEvent::receiver`]
+    /// will not send new events to the channel associated with [`GlobalHotKeyEvent::event_receiver`]
     pub fn set_event_handler<F: Fn(GlobalHotKeyEvent) + Send + Sync + 'static>(f: Option<F>) {
         if let Some(f) = f {
             let _ = GLOBAL_HOTKEY_EVENT_HANDLER.set(Some(Box::new(f)));
@@ -123,41 +319,1510 @@ impl GlobalHotKeyEvent {
         }
     }
 
+    /// Subscribes to hotkey events, keeping at most one pending event per hotkey id, see
+    /// [`CoalescingReceiver`].
+    ///
+    /// Useful when only the latest state of a hotkey matters and a slow consumer would rather
+    /// skip intermediate events. Subscriptions receive every event in addition to the
+    /// channel of [`GlobalHotKeyEvent::event_receiver`] or the handler set with
+    /// [`GlobalHotKeyEvent::set_event_handler`].
+    pub fn subscribe_coalesced() -> CoalescingReceiver {
+        CoalescingReceiver::subscribe()
+    }
+
+    /// Returns a channel receiving the events of every `window_ms` milliseconds at once, e.g.
+    /// to treat an accidental double press as a single action.
+    ///
+    /// A relay thread wakes up every `window_ms` (at least 1) and delivers the events collected
+    /// since, windows without events aren't delivered. Like
+    /// [`GlobalHotKeyEvent::subscribe_coalesced`], the channel receives every event in addition
+    /// to [`GlobalHotKeyEvent::event_receiver`] or the handler set with
+    /// [`GlobalHotKeyEvent::set_event_handler`]. The thread stops at the first delivery after
+    /// the receiver was dropped.
+    pub fn batched_receiver(window_ms: u64) -> Receiver<Vec<GlobalHotKeyEvent>> {
+        batch::batched_receiver(Duration::from_millis(window_ms.max(1)))
+    }
+
     pub(crate) fn send(event: GlobalHotKeyEvent) {
-        if let Some(handler) = GLOBAL_HOTKEY_EVENT_HANDLER.get_or_init(|| None) {
+        if !gate::admit(event) {
+            log::trace!("Dropping {:?} at the gate of its hotkey", event);
+
+            return;
+        }
+
+        log::trace!("Dispatching {:?}", event);
+
+        let metrics = metrics::Dispatch::of(&event);
+
+        metrics.record_trigger(&event);
+
+        coalesce::dispatch(event);
+
+        batch::dispatch(event);
+
+        #[cfg(all(unix, feature = "unix-socket"))]
+        ipc::dispatch(event);
+
+        let start = Instant::now();
+
+        if callback::dispatch(event) {
+            // Handed to the callbacks of the manager holding the hotkey.
+        } else if let Some(handler) = GLOBAL_HOTKEY_EVENT_HANDLER.get_or_init(|| None) {
+            handler(event);
+        } else if GLOBAL_HOTKEY_CHANNEL.0.try_send(event).is_err() {
+            metrics.record_drop();
+
+            return;
+        }
+
+        metrics.record_dispatch(start.elapsed());
+    }
+}
+
+// Includes the accelerator of the hotkey while a manager of the process holds it, so logged
+// events can be read without looking the ids up.
+#[allow(deprecated)]
+impl std::fmt::Debug for GlobalHotKeyEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut event = f.debug_struct("GlobalHotKeyEvent");
+
+        event.field("id", &format_args!("{:#010x}", self.id));
+
+        event.field("state", &self.state);
+
+        if let Some(hotkey) = process::registered(self.id) {
+            event.field("accelerator", &hotkey.into_string());
+        }
+
+        event.finish()
+    }
+}
+
+/// Emitted when the keyboard layout changed while hotkeys were registered.
+///
+/// Hotkeys are bound to the key producing their [`Code`](hotkey::Code) in the current layout,
+/// so after a layout switch (e.g. QWERTY to AZERTY) they may have moved to another physical key.
+/// They are resolved again automatically, use this event to review the hotkey descriptions
+/// shown to the user.
+///
+/// ## Platform-specific:
+///
+/// - **Windows / macOS**: Unsupported, never emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutChangedEvent {
+    /// Ids of the hotkeys that moved to another physical key, or that could not be registered
+    /// again in the new layout and were dropped.
+    pub ids: Vec<u32>,
+}
+
+/// A reciever that could be used to listen to keyboard layout change events.
+pub type LayoutChangedEventReceiver = Receiver<LayoutChangedEvent>;
+type LayoutChangedEventHandler = Box<dyn Fn(LayoutChangedEvent) + Send + Sync + 'static>;
+
+static LAYOUT_CHANGED_CHANNEL: Lazy<(Sender<LayoutChangedEvent>, LayoutChangedEventReceiver)> =
+    Lazy::new(unbounded);
+static LAYOUT_CHANGED_EVENT_HANDLER: OnceCell<Option<LayoutChangedEventHandler>> =
+    OnceCell::new();
+
+impl LayoutChangedEvent {
+    /// Gets a reference to the event channel's [`LayoutChangedEventReceiver`]
+    /// which can be used to listen for keyboard layout change events.
+    ///
+    /// ## Note
+    ///
+    /// This will not receive any events if [`LayoutChangedEvent::set_event_handler`] has been called with a `Some` value.
+    pub fn receiver<'a>() -> &'a LayoutChangedEventReceiver {
+        &LAYOUT_CHANGED_CHANNEL.1
+    }
+
+    /// Set a handler to be called for new events. Useful for implementing custom event sender.
+    ///
+    /// ## Note
+    ///
+    /// Calling this function with a `Some` value,
+    /// will not send new events to the channel associated with [`LayoutChangedEvent::receiver`]
+    pub fn set_event_handler<F: Fn(LayoutChangedEvent) + Send + Sync + 'static>(f: Option<F>) {
+        if let Some(f) = f {
+            let _ = LAYOUT_CHANGED_EVENT_HANDLER.set(Some(Box::new(f)));
+        } else {
+            let _ = LAYOUT_CHANGED_EVENT_HANDLER.set(None);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn send(event: LayoutChangedEvent) {
+        log::debug!("Keyboard layout changed, moved hotkeys: {:?}", event.ids);
+
+        if let Some(handler) = LAYOUT_CHANGED_EVENT_HANDLER.get_or_init(|| None) {
             handler(event);
         } else {
-            let _ = GLOBAL_HOTKEY_CHANNEL.0.send(event);
+            let _ = LAYOUT_CHANGED_CHANNEL.0.send(event);
         }
     }
 }
 
+/// Registers global hotkeys with the OS and delivers their events through
+/// [`GlobalHotKeyEvent::event_receiver`].
+///
+/// ## Thread safety
+///
+/// The manager is [`Send`] and [`Sync`] on every platform, so it can be shared between threads
+/// (e.g. in an `Arc` or a `static`) and its methods called from any of them. Each platform runs
+/// the calls on the thread its OS APIs require and waits for the answer, so concurrent calls
+/// are handled one at a time:
+///
+/// - **Linux (X11)**: Every call sends a message through a [`crossbeam_channel`] channel,
+///   which supports any number of concurrent senders, to the thread owning the X11 connection.
+///   The thread is started by the first call registering a hotkey, see
+///   [`GlobalHotKeyManagerOptions::stop_when_idle`].
+/// - **Windows**: Calls from other threads are sent to the hidden window of the thread that
+///   created the manager, they block until that thread's event loop dispatches them (see
+///   `platform::windows::pump_messages` for threads without one).
+/// - **macOS**: The Carbon and AppKit calls are run on the main thread, they block until the
+///   main event loop gets to them. A call made while the main thread waits for it deadlocks.
+///
+/// ## Extending
+///
+/// The messages of the backends are an implementation detail and aren't exposed, they change
+/// with the backend. Code that has to run on the thread of the backend (e.g. a plugin
+/// system's) is sent there with [`GlobalHotKeyManager::send_raw`] instead, and hotkey events
+/// are fed into other machinery with [`GlobalHotKeyEvent::set_event_handler`].
 pub struct GlobalHotKeyManager {
-    platform_impl: platform_impl::GlobalHotKeyManager,
+    // Dropping the sender stops the status watcher thread.
+    status_watcher: Mutex<Option<Sender<()>>>,
+    // Shared with the status watcher thread, which only keeps a weak reference.
+    platform_impl: Arc<platform_impl::GlobalHotKeyManager>,
+    groups: HotKeyGroups,
+    allow_system_conflicts: AtomicBool,
+    allow_power_key: AtomicBool,
+    // Kept here so `metrics` doesn't wait for the backend, and for `rebind`.
+    registered: Mutex<HashMap<HotKey, HotKeyOptions>>,
+    data: DataStore,
+    callbacks: Callbacks,
+    metrics: Metrics,
+    id: ManagerId,
+    #[cfg(feature = "persistence")]
+    registry: persistence::Registry,
 }
 
+// Documented above, and relied upon by applications keeping the manager in a `static`.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<GlobalHotKeyManager>();
+};
+
 impl GlobalHotKeyManager {
+    /// Creates a manager with the default [`GlobalHotKeyManagerOptions`].
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The X server is only connected to once a hotkey is registered, so the
+    ///   manager is created even without a display and registering fails instead.
+    /// - **Windows**: The events are posted to a hidden window of the calling thread, which has
+    ///   to run a message loop or call `platform::windows::pump_messages` periodically.
+    /// - **macOS**: Has to be called on the main thread, which has to run an event loop.
+    #[cfg_attr(
+        target_os = "linux",
+        doc = "",
+        doc = "```no_run",
+        doc = "# use global_hotkey::{GlobalHotKeyManager, hotkey::{Code, HotKey}};",
+        doc = "let manager = GlobalHotKeyManager::new().unwrap();",
+        doc = "",
+        doc = "// Connects to the X server, which fails without a display.",
+        doc = "if let Err(e) = manager.register(HotKey::new(None, Code::F9)) {",
+        doc = "    eprintln!(\"Global hotkeys are unavailable: {}\", e);",
+        doc = "}",
+        doc = "```"
+    )]
+    #[cfg_attr(
+        target_os = "windows",
+        doc = "",
+        doc = "```no_run",
+        doc = "# use global_hotkey::{GlobalHotKeyManager, hotkey::{Code, HotKey}};",
+        doc = "use global_hotkey::platform::windows::pump_messages;",
+        doc = "",
+        doc = "// A thread without an event loop of its own.",
+        doc = "std::thread::spawn(|| {",
+        doc = "    let manager = GlobalHotKeyManager::new().unwrap();",
+        doc = "",
+        doc = "    manager.register(HotKey::new(None, Code::F9)).unwrap();",
+        doc = "",
+        doc = "    while pump_messages() {",
+        doc = "        std::thread::sleep(std::time::Duration::from_millis(10));",
+        doc = "    }",
+        doc = "});",
+        doc = "```"
+    )]
     pub fn new() -> crate::Result<Self> {
+        Self::with_options(GlobalHotKeyManagerOptions::default())
+    }
+
+    /// Creates a manager with non-default [`GlobalHotKeyManagerOptions`].
+    ///
+    /// Fails with [`Error::UnsupportedOption`] if the options can't apply, e.g. a zero
+    /// [`GlobalHotKeyManagerOptions::poll_interval`].
+    pub fn with_options(options: GlobalHotKeyManagerOptions) -> crate::Result<Self> {
+        options.check()?;
+
         Ok(Self {
-            platform_impl: platform_impl::GlobalHotKeyManager::new()?,
+            status_watcher: Mutex::new(None),
+            platform_impl: Arc::new(platform_impl::GlobalHotKeyManager::new(&options)?),
+            groups: HotKeyGroups::default(),
+            allow_system_conflicts: AtomicBool::new(false),
+            allow_power_key: AtomicBool::new(false),
+            registered: Mutex::new(HashMap::new()),
+            data: DataStore::default(),
+            callbacks: Callbacks::default(),
+            metrics: Metrics::default(),
+            id: ManagerId::default(),
+            #[cfg(feature = "persistence")]
+            registry: persistence::Registry::default(),
         })
     }
 
+    /// Returns the id of the manager, unique within the process. It's the `by_manager` of
+    /// [`Error::AlreadyRegisteredInProcess`] when another manager holds a hotkey.
+    pub fn id(&self) -> usize {
+        self.id.get()
+    }
+
+    /// Gives up the manager while leaving its hotkeys registered, they keep emitting events
+    /// even if the returned handle is dropped, see [`DetachedHandle`].
+    ///
+    /// Useful when the object owning the manager is torn down (e.g. during a reload) but the
+    /// hotkeys should persist for the life of the process.
+    pub fn detach(self) -> DetachedHandle {
+        DetachedHandle::new(self)
+    }
+
+    /// Regains control over the manager given up with [`GlobalHotKeyManager::detach`], with
+    /// its hotkeys, groups and payloads. Dropping it unregisters them as usual.
+    pub fn reattach(handle: DetachedHandle) -> Self {
+        handle.into_manager()
+    }
+
+    /// Returns a builder creating a manager along with its initial hotkeys, see
+    /// [`GlobalHotKeyManagerBuilder`].
+    pub fn builder() -> GlobalHotKeyManagerBuilder {
+        GlobalHotKeyManagerBuilder::new()
+    }
+
+    /// Registers `hotkey` with the default [`HotKeyOptions`].
+    ///
+    /// Fails with [`Error::ReservedBySystem`] if the hotkey is handled by the operating system,
+    /// see [`GlobalHotKeyManager::allow_system_conflicts`], with [`Error::PowerKeyNotAllowed`]
+    /// for [`Code::Power`](hotkey::Code::Power) unless
+    /// [`GlobalHotKeyManager::allow_power_key`] was called, with
+    /// [`Error::AlreadyRegisteredInProcess`] if another manager of the process holds its keys,
+    /// and with [`Error::QuotaExceeded`] if the backend can't hold another hotkey.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The key is grabbed along with every combination of NumLock and
+    ///   CapsLock, so the hotkey fires whichever locks are on, unless forbidden with
+    ///   [`HotKeyOptions::forbid_mods`] or required with [`HotKeyOptions::require_locks`].
+    ///   Keys grabbed by another client fail with
+    ///   [`Error::AlreadyRegistered`].
+    /// - **Windows**: The hotkey id is the one given to `RegisterHotKey`, it has to be unique
+    ///   within the process, so the same hotkey can't be registered by two managers. Keys
+    ///   registered by another application fail with [`Error::AlreadyRegistered`].
+    /// - **macOS**: The shortcuts the system keeps to itself, such as `Cmd+Tab`, fail with
+    ///   [`Error::ReservedBySystem`], see [`SystemShortcut::all`].
+    #[cfg_attr(
+        target_os = "linux",
+        doc = "",
+        doc = "```no_run",
+        doc = "# use global_hotkey::{GlobalHotKeyManager, hotkey::{Code, HotKey, Modifiers}};",
+        doc = "# let manager = GlobalHotKeyManager::new().unwrap();",
+        doc = "// Fires whether NumLock or CapsLock is on.",
+        doc = "manager.register(HotKey::new(Some(Modifiers::CONTROL), Code::KeyK))?;",
+        doc = "# Ok::<(), global_hotkey::Error>(())",
+        doc = "```"
+    )]
+    #[cfg_attr(
+        target_os = "windows",
+        doc = "",
+        doc = "```no_run",
+        doc = "# use global_hotkey::{Error, GlobalHotKeyManager};",
+        doc = "# use global_hotkey::hotkey::{Code, HotKey, Modifiers};",
+        doc = "let (first, second) = (GlobalHotKeyManager::new()?, GlobalHotKeyManager::new()?);",
+        doc = "",
+        doc = "let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyK);",
+        doc = "",
+        doc = "first.register(hotkey)?;",
+        doc = "",
+        doc = "let result = second.register(hotkey);",
+        doc = "",
+        doc = "assert!(matches!(result, Err(Error::AlreadyRegisteredInProcess { .. })));",
+        doc = "# Ok::<(), Error>(())",
+        doc = "```"
+    )]
+    #[cfg_attr(
+        target_os = "macos",
+        doc = "",
+        doc = "```no_run",
+        doc = "# use global_hotkey::{Error, GlobalHotKeyManager};",
+        doc = "# use global_hotkey::hotkey::{Code, HotKey, Modifiers};",
+        doc = "# let manager = GlobalHotKeyManager::new().unwrap();",
+        doc = "let switcher = HotKey::new(Some(Modifiers::SUPER), Code::Tab);",
+        doc = "",
+        doc = "assert!(matches!(manager.register(switcher), Err(Error::ReservedBySystem(_))));",
+        doc = "```"
+    )]
     pub fn register(&self, hotkey: HotKey) -> crate::Result<()> {
-        self.platform_impl.register(hotkey)
+        self.check_system_conflicts(&[hotkey])?;
+
+        self.check_quota(&[hotkey])?;
+
+        self.claimed(&[hotkey], || self.platform_impl.register(hotkey))?;
+
+        self.remember(&[hotkey], &HotKeyOptions::default());
+
+        Ok(())
     }
 
+    /// Registers `hotkey` like [`GlobalHotKeyManager::register`], without blocking the task
+    /// while the backend answers, for async code.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The wait for the X11 event thread runs on the blocking threads of
+    ///   `tokio`, it has to be awaited within a tokio runtime.
+    /// - **Windows**: Registering doesn't wait for another thread, the hotkey is registered
+    ///   right away. It has to be awaited on the thread of the manager.
+    /// - **macOS**: Same as Windows, on the main thread.
+    ///
+    /// Dropping the future before it completes leaves the hotkey unregistered, the backend may
+    /// still be registering it for a moment.
+    ///
+    /// ```no_run
+    /// # use global_hotkey::{GlobalHotKeyManager, hotkey::{Code, HotKey, Modifiers}};
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    ///
+    /// let manager = GlobalHotKeyManager::new()?;
+    ///
+    /// runtime.block_on(async {
+    ///     manager.register_async(HotKey::new(Some(Modifiers::CONTROL), Code::KeyK)).await
+    /// })?;
+    /// # Ok::<(), global_hotkey::Error>(())
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn register_async(&self, hotkey: HotKey) -> crate::Result<()> {
+        self.check_system_conflicts(&[hotkey])?;
+
+        self.check_quota(&[hotkey])?;
+
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        async_register::Registration {
+            manager: self.id,
+            platform_impl: self.platform_impl.clone(),
+            hotkey,
+            claimed: process::claim(self.id, &[hotkey])?,
+        }
+        .run()
+        .await?;
+
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        self.claimed(&[hotkey], || self.platform_impl.register(hotkey))?;
+
+        // Nothing is awaited past the registration, the hotkey is always kept.
+        self.remember(&[hotkey], &HotKeyOptions::default());
+
+        Ok(())
+    }
+
+    /// Checks that `hotkey` can be registered without keeping it, e.g. to tell whether a
+    /// combination is available while the user types it in a settings UI.
+    ///
+    /// Fails with the errors [`GlobalHotKeyManager::register`] would. The backend registers the
+    /// hotkey with the OS and unregisters it right away, on the thread handling its events so
+    /// that none is emitted in between. Another application may still take the keys before
+    /// they are registered for good.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows**: `PrintScreen` is matched by a keyboard hook, which no other application
+    ///   can take, so it's only checked against the hotkeys of the process.
+    /// - **macOS**: Same for the sided and media hotkeys, matched by event taps.
+    pub fn validate(&self, hotkey: HotKey) -> crate::Result<()> {
+        self.check_system_conflicts(&[hotkey])?;
+
+        self.check_quota(&[hotkey])?;
+
+        {
+            let registered = self.registered.lock().unwrap();
+
+            if registered.keys().any(|h| h.overlaps(&hotkey)) {
+                let error = crate::Error::AlreadyRegistered(hotkey);
+
+                return Err(error.with_conflict(registered.keys()));
+            }
+        }
+
+        // Claimed for the probe, so no other manager of the process registers it meanwhile.
+        let claimed = process::claim(self.id, &[hotkey])?;
+
+        let result = self.platform_impl.validate(hotkey);
+
+        process::release(self.id, &claimed);
+
+        result
+    }
+
+    /// Finds out what holds `hotkey`, best effort, e.g. to tell the user what to change when
+    /// registering it failed with [`Error::AlreadyRegistered`].
+    ///
+    /// The hotkey is probed like [`GlobalHotKeyManager::validate`] does, the hotkeys of the
+    /// process are told apart from the ones of other applications.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The X server doesn't tell which client holds a grab, the window
+    ///   manager is reported as the [`ConflictOwner::External`] owner it likely is when it
+    ///   advertises itself through EWMH.
+    /// - **Windows / macOS**: Other applications are reported without a likely owner.
+    pub fn diagnose_conflict(&self, hotkey: HotKey) -> ConflictInfo {
+        let owner = match self.validate(hotkey) {
+            Ok(()) => ConflictOwner::Free,
+            Err(Error::ReservedBySystem(shortcut)) => ConflictOwner::System(shortcut),
+            Err(Error::PowerKeyNotAllowed(_)) => ConflictOwner::PowerKey,
+            Err(Error::AlreadyRegisteredInProcess { by_manager, .. }) => {
+                ConflictOwner::OtherManager { manager: by_manager }
+            }
+            Err(Error::AlreadyRegistered(_) | Error::AlreadyRegisteredConflict { .. }) => {
+                if self.registered.lock().unwrap().keys().any(|h| h.overlaps(&hotkey)) {
+                    ConflictOwner::ThisManager
+                } else {
+                    ConflictOwner::External { likely: self.platform_impl.window_manager() }
+                }
+            }
+            Err(Error::FailedToRegister {
+                reason:
+                    RegisterFailureReason::UnknownKey
+                    | RegisterFailureReason::UnsupportedKey
+                    | RegisterFailureReason::UnsupportedModifierSides,
+                ..
+            }) => ConflictOwner::Unsupported,
+            // The backend couldn't be asked, e.g. without a display.
+            Err(_) if !hotkey.is_supported() => ConflictOwner::Unsupported,
+            Err(_) => ConflictOwner::Unknown,
+        };
+
+        ConflictInfo { hotkey, owner }
+    }
+
+    /// Registers `hotkey` with non-default [`HotKeyOptions`].
+    ///
+    /// Fails with [`Error::UnsupportedOption`] if the platform backend can't honor `options`,
+    /// see [`GlobalHotKeyManager::capabilities`].
+    pub fn register_with_options(
+        &self,
+        hotkey: HotKey,
+        options: HotKeyOptions,
+    ) -> crate::Result<()> {
+        options.check(&hotkey)?;
+
+        self.check_system_conflicts(&[hotkey])?;
+
+        self.check_quota(&[hotkey])?;
+
+        self.claimed(&options.claims(&hotkey), || {
+            self.platform_impl
+                .register_with_options(hotkey, options.clone())
+        })?;
+
+        self.remember(&[hotkey], &options);
+
+        Ok(())
+    }
+
+    /// Registers `tap` as a "tap for a hotkey, hold for a modifier" key, e.g. `CapsLock`
+    /// triggering one action when tapped and making `CapsLock+J` trigger another.
+    ///
+    /// Pressing and releasing the key of `tap` within `threshold` emits the press and release
+    /// of `tap` once the key is released. While it's held, pressing one of `hold_with` emits
+    /// its events instead and no tap fires. The modifiers of `hold_with` are matched against
+    /// the modifiers held besides the ones of `tap`. Holding the key past `threshold` without
+    /// pressing a combination emits nothing.
+    ///
+    /// Any other key pressed while `tap` is held ends the gesture and goes to the focused
+    /// window as usual. The combinations are claimed by the manager like registered hotkeys,
+    /// and the registration is undone with [`GlobalHotKeyManager::unregister`] of `tap`.
+    ///
+    /// Fails with [`Error::UnsupportedOption`] if one of `hold_with` uses the key of `tap`,
+    /// or if the backend doesn't support it, see [`Capabilities::tap_hold`].
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The key of `tap` is grabbed like a non-consuming hotkey, see
+    ///   [`HotKeyOptions::consume`]. Its press itself never reaches the focused window, and the
+    ///   keys pressed while it's held are delayed until the next poll of the event loop.
+    /// - **Windows / macOS**: Unsupported.
+    pub fn register_tap_hold(
+        &self,
+        tap: HotKey,
+        hold_with: &[HotKey],
+        threshold: Duration,
+    ) -> crate::Result<()> {
+        let options = HotKeyOptions::tap_hold(tap_hold::TapHold::new(hold_with, threshold));
+
+        self.register_with_options(tap, options)
+    }
+
+    /// Unregisters `hotkey`, dropping its payload and trigger counters.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: The grab is kept while a hotkey only differing in its modifier sides
+    ///   is still registered, see [`HotKey::sides`].
+    /// - **Windows**: Called from another thread, blocks until the thread that created the
+    ///   manager runs its message loop.
+    /// - **macOS**: Called from another thread, blocks until the main thread runs its event
+    ///   loop.
     pub fn unregister(&self, hotkey: HotKey) -> crate::Result<()> {
-        self.platform_impl.unregister(hotkey)
+        self.platform_impl.unregister(hotkey)?;
+
+        self.forget(&[hotkey]);
+
+        Ok(())
     }
 
-    pub fn register_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
-        self.platform_impl.register_all(hotkeys)?;
+    /// Registers `hotkey` with the default [`HotKeyOptions`] and attaches `data` to it, which
+    /// is returned by [`GlobalHotKeyManager::data_for`] for the id of its events.
+    ///
+    /// The payload is dropped when the hotkey is unregistered, and carried over by
+    /// [`GlobalHotKeyManager::rebind`].
+    pub fn register_with_data(&self, hotkey: HotKey, data: HotKeyData) -> crate::Result<()> {
+        self.register(hotkey)?;
+
+        self.data.insert(hotkey.id(), data);
+
         Ok(())
     }
 
+    /// Returns the payload attached to the registered hotkey with this id, see
+    /// [`GlobalHotKeyManager::register_with_data`].
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, hotkey::{Code, HotKey}};
+    /// # let manager = GlobalHotKeyManager::new().unwrap();
+    /// manager.register_with_data(HotKey::new(None, Code::F9), Arc::new("toggle overlay"))?;
+    ///
+    /// let event = GlobalHotKeyEvent::event_receiver().recv();
+    ///
+    /// if let Some(action) = manager.data_for(event.id()).and_then(|d| d.downcast::<&str>().ok()) {
+    ///     println!("{}", action);
+    /// }
+    /// # Ok::<(), global_hotkey::Error>(())
+    /// ```
+    pub fn data_for(&self, id: u32) -> Option<HotKeyData> {
+        self.data.get(id)
+    }
+
+    /// Replaces the registration of `old` with `new`, keeping its [`HotKeyOptions`] and its
+    /// payload.
+    ///
+    /// If `new` fails to register, `old` is registered again and the error is returned.
+    pub fn rebind(&self, old: HotKey, new: HotKey) -> crate::Result<()> {
+        let options = self.registered.lock().unwrap().get(&old).cloned().unwrap_or_default();
+
+        let data = self.data.get(old.id());
+
+        self.unregister(old)?;
+
+        if let Err(e) = self.register_with_options(new, options.clone()) {
+            self.register_with_options(old, options)?;
+
+            if let Some(data) = data {
+                self.data.insert(old.id(), data);
+            }
+
+            return Err(e);
+        }
+
+        if let Some(data) = data {
+            self.data.insert(new.id(), data);
+        }
+
+        Ok(())
+    }
+
+    /// Registers all `hotkeys`, stopping at the first one that fails. The ones registered
+    /// before it stay registered.
+    ///
+    /// Fails with [`Error::QuotaExceeded`] without registering any of them if they don't all
+    /// fit, see [`Capabilities::max_hotkeys`].
+    ///
+    /// In debug builds, a warning is logged if two of them share the same id,
+    /// see [`hotkey::check_id_uniqueness`].
+    pub fn register_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
+        self.register_all_with_options(hotkeys, HotKeyOptions::default())
+    }
+
+    /// Registers all `hotkeys` with the same non-default [`HotKeyOptions`],
+    /// see [`GlobalHotKeyManager::register_all`].
+    pub fn register_all_with_options(
+        &self,
+        hotkeys: &[HotKey],
+        options: HotKeyOptions,
+    ) -> crate::Result<()> {
+        #[cfg(debug_assertions)]
+        if let Some((a, b)) = hotkey::check_id_uniqueness(hotkeys) {
+            log::warn!(
+                "`{}` and `{}` share the id {}, their events can't be told apart",
+                a,
+                b,
+                a.id()
+            );
+        }
+
+        for hotkey in hotkeys {
+            options.check(hotkey)?;
+        }
+
+        self.check_system_conflicts(hotkeys)?;
+
+        self.check_quota(hotkeys)?;
+
+        let claims = hotkeys
+            .iter()
+            .flat_map(|hotkey| options.claims(hotkey))
+            .collect::<Vec<_>>();
+
+        let claimed = process::claim(self.id, &claims)?;
+
+        let results = self
+            .platform_impl
+            .register_all_with_options(hotkeys, options.clone());
+
+        let registered = hotkeys
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(hotkey, _)| *hotkey)
+            .collect::<Vec<_>>();
+
+        // The hotkeys registered before the failure stay registered, and keep their claims.
+        let kept = registered
+            .iter()
+            .flat_map(|hotkey| options.claims(hotkey))
+            .collect::<Vec<_>>();
+
+        let unused = claimed
+            .into_iter()
+            .filter(|hotkey| !kept.contains(hotkey))
+            .collect::<Vec<_>>();
+
+        process::release(self.id, &unused);
+
+        self.remember(&registered, &options);
+
+        results.into_iter().collect()
+    }
+
     pub fn unregister_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
         self.platform_impl.unregister_all(hotkeys)?;
+
+        self.forget(hotkeys);
+
+        Ok(())
+    }
+
+    /// Unregisters every hotkey of the manager, without having to know them, and deactivates
+    /// its groups. Useful to start over when switching profiles or reloading a configuration.
+    ///
+    /// Hotkeys kept while suspended are dropped too. The manager forgets every hotkey even if
+    /// an error is returned, the backend keeps going after a hotkey fails to unregister.
+    pub fn clear_all(&self) -> crate::Result<()> {
+        let result = self.platform_impl.clear_all();
+
+        let hotkeys = self.registered.lock().unwrap().keys().copied().collect::<Vec<_>>();
+
+        self.forget(&hotkeys);
+
+        self.groups.deactivate_all();
+
+        result
+    }
+
+    /// Creates an inactive group of hotkeys that are registered and unregistered together with
+    /// [`GlobalHotKeyManager::activate_group`] and [`GlobalHotKeyManager::deactivate_group`],
+    /// e.g. the shortcuts of an application mode.
+    pub fn create_group(&self, hotkeys: &[HotKey]) -> GroupId {
+        self.groups.create(hotkeys)
+    }
+
+    /// Registers all the hotkeys of a group at once, if one of them fails the ones already
+    /// registered are unregistered again and the group stays inactive.
+    ///
+    /// Activating an active group does nothing. While the manager is suspended the hotkeys
+    /// only become active after resuming, like any other registration.
+    pub fn activate_group(&self, group: GroupId) -> crate::Result<()> {
+        self.groups.activate(group, |hotkeys| {
+            self.check_system_conflicts(hotkeys)?;
+
+            self.check_quota(hotkeys)?;
+
+            self.claimed(hotkeys, || self.platform_impl.register_all_atomic(hotkeys))?;
+
+            self.remember(hotkeys, &HotKeyOptions::default());
+
+            Ok(())
+        })
+    }
+
+    /// Unregisters all the hotkeys of a group.
+    ///
+    /// Deactivating an inactive group does nothing. The group is deactivated even if an error
+    /// is returned because some of its hotkeys were already unregistered individually.
+    pub fn deactivate_group(&self, group: GroupId) -> crate::Result<()> {
+        self.groups.deactivate(group, |hotkeys| {
+            self.forget(hotkeys);
+
+            self.platform_impl.unregister_all(hotkeys)
+        })
+    }
+
+    /// Returns the active group the hotkey with this id was registered through, useful to
+    /// route a [`GlobalHotKeyEvent`] to the mode it belongs to.
+    pub fn group_of(&self, id: u32) -> Option<GroupId> {
+        self.groups.group_of(id)
+    }
+
+    /// Stops emitting events for all registered hotkeys without forgetting them,
+    /// the keys are released to other applications until [`GlobalHotKeyManager::resume`] is called.
+    ///
+    /// Hotkeys registered while suspended only become active after resuming.
+    pub fn suspend(&self) -> crate::Result<()> {
+        self.platform_impl.suspend()
+    }
+
+    /// Re-activates the hotkeys kept by [`GlobalHotKeyManager::suspend`].
+    pub fn resume(&self) -> crate::Result<()> {
+        self.platform_impl.resume()
+    }
+
+    /// Registers every hotkey with the operating system again, along with the ones that lost
+    /// their grab, and returns the hotkeys that failed to come back. Those are reported as
+    /// [`HotKeyStatus::LostGrab`] by [`GlobalHotKeyManager::status`] until the next successful
+    /// revalidation, or until they're registered again or unregistered.
+    ///
+    /// Grabs can silently stop working across system lifecycle events. The backends already
+    /// revalidate on their own when they notice one: on Windows after resuming from sleep,
+    /// unlocking the session or a display change, on macOS after waking up or switching back
+    /// to the user session, and on Linux when the connection to a restarted X server is
+    /// restored. Call it from your own power or session event handling to cover other cases.
+    ///
+    /// While suspended the hotkeys are only registered again once resumed.
+    pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> {
+        self.platform_impl.revalidate()
+    }
+
+    /// Returns the current operational state of the manager: its backend, its health and
+    /// the status of every hotkey it knows.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS**: Hotkeys are revalidated when the system wakes from sleep or the
+    ///   user session becomes active again (see [`GlobalHotKeyManager::revalidate`]), the ones
+    ///   that fail to come back are reported as [`HotKeyStatus::LostGrab`].
+    /// - **macOS**: Hotkeys going through an event tap are reported as
+    ///   [`HotKeyStatus::Failed`] while the system keeps the tap disabled or secure keyboard
+    ///   entry is on, and passthrough hotkeys while the Input Monitoring permission is
+    ///   missing.
+    /// - **Linux (X11)**: Hotkeys that can't be grabbed again after a keyboard layout change
+    ///   or on resume are reported as [`HotKeyStatus::LostGrab`]. With the `x11rb` feature,
+    ///   losing the connection to the X server is reported as [`Health::Failed`] until it's
+    ///   back and the hotkeys are grabbed again, Xlib exits the process instead.
+    pub fn status(&self) -> ManagerStatus {
+        self.platform_impl.status()
+    }
+
+    /// Returns the number of hotkeys registered through the manager, e.g. to show how many of
+    /// [`Capabilities::max_hotkeys`] are used.
+    pub fn registered_count(&self) -> usize {
+        self.registered.lock().unwrap().len()
+    }
+
+    /// Returns the event counters, along with the hotkeys registered through the manager and
+    /// how often each of them was pressed.
+    ///
+    /// Unlike [`GlobalHotKeyManager::status`], this only loads counters and never waits for
+    /// the backend.
+    pub fn metrics(&self) -> ManagerMetrics {
+        self.metrics.snapshot(self.registered.lock().unwrap().keys().map(HotKey::id))
+    }
+
+    /// Resets the trigger counters of the hotkeys registered through the manager, see
+    /// [`ManagerMetrics::hotkeys`].
+    pub fn reset_metrics(&self) {
+        for hotkey in self.registered.lock().unwrap().keys() {
+            self.metrics.reset(hotkey.id());
+        }
+    }
+
+    /// Sets a handler called with the new [`ManagerStatus`] whenever it changes, pass `None`
+    /// to stop watching.
+    ///
+    /// The status is polled every 500ms on a background thread, which the handler runs on.
+    pub fn set_status_handler<F: Fn(ManagerStatus) + Send + 'static>(&self, f: Option<F>) {
+        let mut watcher = self.status_watcher.lock().unwrap();
+        // Replacing the sender stops the previous watcher.
+        *watcher = None;
+
+        let Some(handler) = f else {
+            return;
+        };
+
+        let (tx, rx) = crossbeam_channel::bounded::<()>(0);
+        *watcher = Some(tx);
+
+        let platform_impl = Arc::downgrade(&self.platform_impl);
+        let mut status = self.platform_impl.status();
+
+        std::thread::spawn(move || {
+            // Not every transition has a notification, so poll it.
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(Duration::from_millis(500))
+            {
+                let Some(now) = platform_impl.upgrade().map(|p| p.status()) else {
+                    break;
+                };
+
+                if now != status {
+                    status = now;
+                    handler(status.clone());
+                }
+            }
+        });
+    }
+
+    /// Sets a handler called whenever secure keyboard entry is turned on or off,
+    /// pass `None` to stop watching.
+    ///
+    /// Useful to tell users that shortcuts are paused instead of appearing broken.
+    #[cfg(target_os = "macos")]
+    pub fn set_secure_input_handler<F: Fn(platform::macos::SecureInputEvent) + Send + 'static>(
+        &self,
+        f: Option<F>,
+    ) {
+        self.platform_impl
+            .set_secure_input_handler(f.map(|f| Box::new(f) as Box<_>))
+    }
+
+    /// Sets a hook called with every key event the backend receives, as the platform
+    /// reported it, before it's matched against the hotkeys. Useful to correlate hotkeys
+    /// with other input, e.g. by the timestamps of the events.
+    ///
+    /// The hook runs on the thread of the backend and every hotkey waits for it, it must
+    /// return quickly, e.g. by sending the event to a channel. It replaces the previous
+    /// one and can't set another hook itself.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: Called for the keys grabbed by the manager, or every key while
+    ///   recording.
+    /// - **Windows**: Called for the `WM_HOTKEY` messages of the manager and every key
+    ///   seen by the low-level keyboard hook, which is only installed for non-consuming
+    ///   hotkeys. Windows removes a low-level hook that takes too long.
+    /// - **macOS**: Unsupported, fails with [`Error::PlatformUnsupported`].
+    pub fn set_raw_event_hook<F: Fn(RawPlatformEvent) + Send + 'static>(
+        &self,
+        hook: F,
+    ) -> crate::Result<()> {
+        self.platform_impl.set_raw_event_hook(Box::new(hook))
+    }
+
+    /// Runs `message` on the thread of the backend, after the calls sent there before, and
+    /// waits for it. A panic of the task is logged and doesn't stop the backend.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux (X11)**: Runs on the event thread, which is started if it isn't running.
+    ///   Also runs while the backend is failed, e.g. without a display.
+    /// - **Windows**: Runs on the thread that created the manager.
+    /// - **macOS**: Runs on the main thread, sending a message while the main thread waits for
+    ///   it deadlocks.
+    pub fn send_raw(&self, message: ExtensionMessage) -> crate::Result<()> {
+        self.platform_impl.send_raw(message)
+    }
+
+    /// Grabs the whole keyboard so the user can press the combo they want to use as a hotkey,
+    /// key presses are delivered to [`RecordingHandle::receiver`] instead of the focused
+    /// application and registered hotkeys don't fire.
+    ///
+    /// Dropping the returned handle ends the recording, [`RecordingHandle::into_hotkey`]
+    /// converts the last combo pressed.
+    ///
+    /// Fails with [`Error::RecordingInProgress`] if another handle is still alive.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS**: Unsupported, returns [`Error::PlatformUnsupported`].
+    pub fn start_recording(&self) -> crate::Result<RecordingHandle> {
+        self.platform_impl.start_recording()
+    }
+
+    /// Lets hotkeys colliding with a [`SystemShortcut`] be registered anyway instead of failing
+    /// with [`Error::ReservedBySystem`], for systems known to be configured differently.
+    pub fn allow_system_conflicts(&self, allow: bool) {
+        self.allow_system_conflicts.store(allow, Ordering::Relaxed);
+    }
+
+    /// Lets hotkeys of [`Code::Power`](hotkey::Code::Power) be registered instead of failing
+    /// with [`Error::PowerKeyNotAllowed`]. Taking over the power key may leave the user with no
+    /// way to turn the machine off from the keyboard, e.g. on a kiosk, so it's refused until
+    /// allowed explicitly, whatever [`GlobalHotKeyManager::allow_system_conflicts`] says.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Windows / macOS**: The power key can't be grabbed, registering it fails with
+    ///   [`RegisterFailureReason::UnsupportedKey`] once allowed.
+    pub fn allow_power_key(&self, allow: bool) {
+        self.allow_power_key.store(allow, Ordering::Relaxed);
+    }
+
+    fn check_system_conflicts(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
+        let power = hotkeys.iter().find(|hotkey| hotkey.key == hotkey::Code::Power);
+
+        if let Some(hotkey) = power.filter(|_| !self.allow_power_key.load(Ordering::Relaxed)) {
+            return Err(crate::Error::PowerKeyNotAllowed(*hotkey));
+        }
+
+        if self.allow_system_conflicts.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        match hotkeys.iter().find_map(HotKey::conflicts_with_system) {
+            Some(shortcut) => {
+                log::debug!("Refusing to register {}", shortcut);
+
+                Err(crate::Error::ReservedBySystem(shortcut))
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Counts the hotkeys that aren't registered yet against the limit of the backend, so a set
+    // that doesn't fit is refused as a whole.
+    fn check_quota(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
+        let Some(limit) = self.platform_impl.capabilities().max_hotkeys else {
+            return Ok(());
+        };
+
+        let registered = self.registered.lock().unwrap();
+
+        let added = hotkeys
+            .iter()
+            .filter(|hotkey| !registered.contains_key(hotkey))
+            .collect::<HashSet<_>>()
+            .len();
+
+        if registered.len() + added > limit {
+            return Err(crate::Error::QuotaExceeded { limit });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the registered hotkeys with their options, along with the labels and disabled
+    /// hotkeys of the last set given to [`GlobalHotKeyManager::apply_set`].
+    #[cfg(feature = "persistence")]
+    pub fn export_set(&self) -> persistence::RegistrationSet {
+        self.registry.export()
+    }
+
+    /// Unregisters the hotkeys missing from `set` or whose options changed, then registers the
+    /// enabled hotkeys of `set` that aren't registered yet, returning the result of each
+    /// operation.
+    ///
+    /// Hotkeys already registered as described are left alone, so applying the same set again
+    /// does nothing.
+    #[cfg(feature = "persistence")]
+    pub fn apply_set(
+        &self,
+        set: &persistence::RegistrationSet,
+    ) -> Vec<(HotKey, crate::Result<()>)> {
+        let (removed, added) = self.registry.diff(set);
+
+        let mut results = Vec::with_capacity(removed.len() + added.len());
+
+        for hotkey in removed {
+            results.push((hotkey, self.unregister(hotkey)));
+        }
+
+        for (hotkey, options) in added {
+            results.push((hotkey, self.register_with_options(hotkey, options)));
+        }
+
+        self.registry.set_applied(set);
+
+        results
+    }
+
+    // Claims `hotkeys` within the process for `register`, releasing the new claims if it fails.
+    fn claimed<F: FnOnce() -> crate::Result<()>>(
+        &self,
+        hotkeys: &[HotKey],
+        register: F,
+    ) -> crate::Result<()> {
+        let claimed = process::claim(self.id, hotkeys)?;
+
+        register().map_err(|e| {
+            process::release(self.id, &claimed);
+
+            e
+        })
+    }
+
+    fn remember(&self, hotkeys: &[HotKey], options: &HotKeyOptions) {
+        let mut registered = self.registered.lock().unwrap();
+
+        for hotkey in hotkeys {
+            registered.insert(*hotkey, options.clone());
+
+            gate::set(hotkey.id(), options.gate.as_ref());
+
+            self.metrics.track(self.id, hotkey.id());
+        }
+
+        #[cfg(feature = "persistence")]
+        self.registry.remember(hotkeys, options);
+    }
+
+    fn forget(&self, hotkeys: &[HotKey]) {
+        let mut registered = self.registered.lock().unwrap();
+
+        for hotkey in hotkeys {
+            let options = registered.remove(hotkey);
+
+            // The combinations of a tap-hold hotkey are only claimed along with it.
+            if let Some(tap_hold) = options.and_then(|options| options.tap_hold) {
+                process::release(self.id, &tap_hold.hold_with);
+            }
+
+            gate::set(hotkey.id(), None);
+
+            self.metrics.untrack(hotkey.id());
+        }
+
+        self.data.remove(hotkeys);
+
+        process::release(self.id, hotkeys);
+
+        #[cfg(feature = "persistence")]
+        self.registry.forget(hotkeys);
+    }
+
+    /// Replaces every callback of the manager with `callback`, under id `0`, see
+    /// [`GlobalHotKeyManager::add_callback`].
+    pub fn set_callback(&self, callback: Arc<dyn Fn(GlobalHotKeyEvent) + Send + Sync>) {
+        self.callbacks.set(self.id, callback);
+    }
+
+    /// Adds `callback` under `id`, replacing the callback already added under it.
+    ///
+    /// The events of the hotkeys registered through this manager are handed to its callbacks,
+    /// in no particular order, instead of the channel of [`GlobalHotKeyEvent::event_receiver`]
+    /// or the handler set with [`GlobalHotKeyEvent::set_event_handler`]. This suits consumers
+    /// that can only be called back (e.g. C or COM objects). The callbacks run on the thread
+    /// dispatching the events, so a slow callback delays the events after it.
+    pub fn add_callback(&self, id: u64, callback: Arc<dyn Fn(GlobalHotKeyEvent) + Send + Sync>) {
+        self.callbacks.add(self.id, id, callback);
+    }
+
+    /// Removes the callback added under `id`, returning whether there was one. Once the last
+    /// one is removed, events go to the channel or the event handler again.
+    pub fn remove_callback(&self, id: u64) -> bool {
+        self.callbacks.remove(id)
+    }
+
+    /// Hands the events of the hotkeys registered through this manager to `dispatcher` on the
+    /// main thread, e.g. to update the UI right away, instead of the channel of
+    /// [`GlobalHotKeyEvent::event_receiver`] or the handler set with
+    /// [`GlobalHotKeyEvent::set_event_handler`].
+    ///
+    /// Has to be called on the main thread, where `dispatcher` stays. The events are posted to
+    /// it in the order they happen, so the `Released` event of a hotkey never overtakes its
+    /// `Pressed` event. Like [`GlobalHotKeyManager::set_callback`], it replaces the callbacks
+    /// of the manager.
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **macOS**: The events are posted to the main dispatch queue.
+    /// - **Windows**: The events are posted to the window of the manager, the main thread is
+    ///   the one that created the manager.
+    /// - **Linux**: Unsupported, X11 has no main thread the events could be posted to, use
+    ///   [`GlobalHotKeyManager::set_main_thread_dispatcher_with_proxy`].
+    pub fn set_main_thread_dispatcher<F: Fn(GlobalHotKeyEvent) + 'static>(
+        &self,
+        dispatcher: F,
+    ) -> crate::Result<()> {
+        let post = self.platform_impl.main_thread_poster()?;
+
+        self.callbacks.set(self.id, main_thread::install(self.id, Rc::new(dispatcher), post));
+
         Ok(())
     }
+
+    /// Like [`GlobalHotKeyManager::set_main_thread_dispatcher`], with `proxy` running the tasks
+    /// it's given on the thread this is called on, in the order it's given them. Works on every
+    /// platform, e.g. with a task queue drained by the event loop of the GUI framework.
+    pub fn set_main_thread_dispatcher_with_proxy<F, P>(&self, dispatcher: F, proxy: P)
+    where
+        F: Fn(GlobalHotKeyEvent) + 'static,
+        P: Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+    {
+        let dispatcher = main_thread::install(self.id, Rc::new(dispatcher), Arc::new(proxy));
+
+        self.callbacks.set(self.id, dispatcher);
+    }
+
+    /// Returns the optional features supported by the current platform backend.
+    pub fn capabilities(&self) -> Capabilities {
+        self.platform_impl.capabilities()
+    }
+
+    /// Lists the keyboards currently attached, e.g. to pick the one a hotkey is restricted to
+    /// with [`HotKeyOptions::device`].
+    ///
+    /// ## Platform-specific:
+    ///
+    /// - **Linux**: Reads `/proc/bus/input/devices`, which lists the devices with a keyboard
+    ///   handler, including some that only have a few keys like power buttons.
+    /// - **Windows**: Lists the keyboards of Raw Input, which has no display name for them,
+    ///   [`KeyboardDevice::name`] is their device path like [`KeyboardDevice::id`].
+    /// - **macOS**: Unsupported, fails with [`Error::PlatformUnsupported`].
+    pub fn keyboard_devices() -> crate::Result<Vec<KeyboardDevice>> {
+        device::keyboard_devices()
+    }
+}
+
+impl Drop for GlobalHotKeyManager {
+    fn drop(&mut self) {
+        // The backend unregisters the hotkeys when dropped, other managers may take them then.
+        process::release_all(self.id);
+
+        for hotkey in self.registered.get_mut().unwrap().keys() {
+            gate::set(hotkey.id(), None);
+        }
+
+        main_thread::forget(self.id);
+    }
+}
+
+#[test]
+fn test_hotkey_state_numbers() {
+    for state in [HotKeyState::Pressed, HotKeyState::Released] {
+        assert_eq!(HotKeyState::try_from(u8::from(state)).unwrap(), state);
+    }
+
+    assert_eq!(u8::from(HotKeyState::Released), 1);
+
+    assert!(matches!(HotKeyState::try_from(2), Err(Error::UnknownHotKeyState(2))));
+}
+
+#[test]
+fn test_hotkey_state_bools() {
+    for state in [HotKeyState::Pressed, HotKeyState::Released] {
+        assert_eq!(HotKeyState::from_bool(state.as_bool()), state);
+
+        let event = GlobalHotKeyEvent::new(1, state);
+
+        assert_eq!(event.is_pressed(), state.as_bool());
+
+        assert_ne!(event.is_pressed(), event.is_released());
+    }
+
+    assert!(HotKeyState::Pressed.as_bool());
+}
+
+#[test]
+fn test_event_debug() {
+    use hotkey::{Code, Modifiers};
+
+    let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::Numpad7);
+
+    let event = GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Pressed);
+
+    let id = format!("{:#010x}", hotkey.id());
+
+    assert_eq!(
+        format!("{:?}", event),
+        format!("GlobalHotKeyEvent {{ id: {}, state: Pressed }}", id)
+    );
+
+    let manager = process::ManagerId::default();
+
+    process::claim(manager, &[hotkey]).unwrap();
+
+    assert_eq!(
+        format!("{:?}", event),
+        format!(
+            "GlobalHotKeyEvent {{ id: {}, state: Pressed, accelerator: \"shift+control+Numpad7\" }}",
+            id
+        )
+    );
+
+    process::release_all(manager);
+}
+
+#[test]
+fn test_buffer_size() {
+    let receiver = GlobalHotKeyEvent::event_receiver().channel();
+
+    // The channel exists now, its size is settled.
+    assert!(!GlobalHotKeyEvent::set_buffer_size(8));
+
+    if cfg!(feature = "lockfree-ringbuf") {
+        assert_eq!(receiver.capacity(), Some(64));
+    } else {
+        assert_eq!(receiver.capacity(), None);
+    }
+}
+
+#[test]
+fn test_power_key_opt_in() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let power = HotKey::new(None, hotkey::Code::Power);
+
+    assert!(matches!(manager.register(power), Err(Error::PowerKeyNotAllowed(h)) if h == power));
+
+    manager.allow_power_key(true);
+
+    // Up to the backend then, which fails without a display.
+    assert!(!matches!(manager.validate(power), Err(Error::PowerKeyNotAllowed(_))));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "needs an X server"]
+fn test_power_key_registered() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let power = HotKey::new(None, hotkey::Code::Power);
+
+    manager.allow_power_key(true);
+
+    manager.register(power).unwrap();
+
+    manager.unregister(power).unwrap();
+}
+
+#[test]
+#[ignore = "needs a display"]
+fn test_validate() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let hotkey = HotKey::new(
+        Some(hotkey::Modifiers::CONTROL | hotkey::Modifiers::SHIFT),
+        hotkey::Code::F1,
+    );
+
+    assert!(matches!(
+        manager.validate(HotKey::new(None, hotkey::Code::Power)),
+        Err(Error::PowerKeyNotAllowed(_))
+    ));
+
+    manager.validate(hotkey).unwrap();
+
+    assert_eq!(manager.registered_count(), 0);
+
+    // The probe released its grab.
+    manager.register(hotkey).unwrap();
+
+    assert!(matches!(
+        manager.validate(hotkey),
+        Err(Error::AlreadyRegisteredConflict { existing, .. }) if existing == hotkey
+    ));
+
+    manager.unregister(hotkey).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[test]
+#[ignore = "needs a display"]
+fn test_register_async() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let hotkey = HotKey::new(
+        Some(hotkey::Modifiers::CONTROL | hotkey::Modifiers::SHIFT),
+        hotkey::Code::F2,
+    );
+
+    runtime.block_on(async {
+        let power = HotKey::new(None, hotkey::Code::Power);
+
+        assert!(matches!(manager.register_async(power).await, Err(Error::PowerKeyNotAllowed(_))));
+
+        manager.register_async(hotkey).await.unwrap();
+
+        assert_eq!(manager.registered_count(), 1);
+
+        assert!(manager.register_async(hotkey).await.is_err());
+
+        manager.unregister(hotkey).unwrap();
+    });
+}
+
+#[cfg(all(feature = "async", target_os = "linux"))]
+#[test]
+#[ignore = "needs an X server"]
+fn test_register_async_cancelled() {
+    use std::{
+        future::Future,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    let _runtime = runtime.enter();
+
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let hotkey = HotKey::new(
+        Some(hotkey::Modifiers::CONTROL | hotkey::Modifiers::SHIFT),
+        hotkey::Code::F3,
+    );
+
+    let mut future = Box::pin(manager.register_async(hotkey));
+
+    // Polled once, the backend is asked on a blocking thread meanwhile.
+    let polled = future
+        .as_mut()
+        .poll(&mut Context::from_waker(&Waker::from(Arc::new(Noop))));
+
+    drop(future);
+
+    if let Poll::Ready(Ok(())) = polled {
+        manager.unregister(hotkey).unwrap();
+    }
+
+    assert_eq!(manager.registered_count(), 0);
+
+    // Undone once the backend answered, which leaves the keys to other managers.
+    let start = Instant::now();
+
+    while process::holder(hotkey.id()).is_some() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let other = GlobalHotKeyManager::new().unwrap();
+
+    other.register(hotkey).unwrap();
+
+    other.unregister(hotkey).unwrap();
+}
+
+#[test]
+fn test_diagnose_unavailable() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let power = HotKey::new(None, hotkey::Code::Power);
+
+    assert_eq!(manager.diagnose_conflict(power).owner, ConflictOwner::PowerKey);
+
+    if let Some(shortcut) = SystemShortcut::all().first() {
+        let owner = manager.diagnose_conflict(shortcut.hotkey()).owner;
+
+        assert_eq!(owner, ConflictOwner::System(*shortcut));
+    }
+
+    // No platform has a key for it, with or without a display.
+    let unsupported = HotKey::new(Some(hotkey::Modifiers::CONTROL), hotkey::Code::Lang3);
+
+    assert_eq!(manager.diagnose_conflict(unsupported).owner, ConflictOwner::Unsupported);
+}
+
+#[test]
+#[ignore = "needs a display"]
+fn test_diagnose_conflict() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let other = GlobalHotKeyManager::new().unwrap();
+
+    let mods = hotkey::Modifiers::CONTROL | hotkey::Modifiers::SHIFT;
+
+    let (own, taken) =
+        (HotKey::new(Some(mods), hotkey::Code::F5), HotKey::new(Some(mods), hotkey::Code::F6));
+
+    manager.register(own).unwrap();
+
+    assert_eq!(manager.diagnose_conflict(own).owner, ConflictOwner::ThisManager);
+
+    other.register(taken).unwrap();
+
+    assert_eq!(manager.diagnose_conflict(taken), ConflictInfo {
+        hotkey: taken,
+        owner: ConflictOwner::OtherManager { manager: other.id() },
+    });
+
+    assert_eq!(other.diagnose_conflict(own).owner, ConflictOwner::OtherManager {
+        manager: manager.id(),
+    });
+
+    other.unregister(taken).unwrap();
+
+    assert_eq!(manager.diagnose_conflict(taken).owner, ConflictOwner::Free);
+
+    manager.unregister(own).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_send_raw() {
+    let manager = GlobalHotKeyManager::new().unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    // Runs on the event thread, with or without a display.
+    manager
+        .send_raw(ExtensionMessage::new(move || tx.send(std::thread::current().id()).unwrap()))
+        .unwrap();
+
+    assert_ne!(rx.try_recv().unwrap(), std::thread::current().id());
+
+    manager.send_raw(ExtensionMessage::new(|| panic!("extension"))).unwrap();
+
+    manager.send_raw(ExtensionMessage::new(|| {})).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "needs an X server"]
+fn test_register_all_partial() {
+    let (manager, other) =
+        (GlobalHotKeyManager::new().unwrap(), GlobalHotKeyManager::new().unwrap());
+
+    let mods = hotkey::Modifiers::CONTROL | hotkey::Modifiers::SHIFT;
+
+    let [a, b, c] = [hotkey::Code::KeyB, hotkey::Code::KeyC, hotkey::Code::KeyD]
+        .map(|code| HotKey::new(Some(mods), code));
+
+    manager.register(b).unwrap();
+
+    assert!(manager.register_all(&[a, b, c]).is_err());
+
+    // The hotkeys before the failure stay registered and claimed, the rest is left alone.
+    assert_eq!(manager.registered_count(), 2);
+
+    assert!(matches!(other.register(a), Err(Error::AlreadyRegisteredInProcess { .. })));
+
+    other.register(c).unwrap();
+
+    manager.unregister_all(&[a, b]).unwrap();
+
+    other.unregister(c).unwrap();
 }
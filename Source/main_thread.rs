@@ -0,0 +1,92 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+
+use crate::{GlobalHotKeyEvent, callback::Callback, process::ManagerId};
+
+/// A task to run on the main thread.
+pub(crate) type Task = Box<dyn FnOnce() + Send>;
+
+/// Runs the tasks it's given on the main thread, in the order it's given them.
+pub(crate) type Poster = Arc<dyn Fn(Task) + Send + Sync>;
+
+type Dispatcher = Rc<dyn Fn(GlobalHotKeyEvent)>;
+
+thread_local! {
+	// The dispatchers set on this thread, by manager. They aren't `Send`, so
+	// the events are brought to the thread they were set on instead.
+	static DISPATCHERS:RefCell<HashMap<ManagerId, Dispatcher>> = RefCell::default();
+}
+
+/// Keeps `dispatcher` on the current thread and returns the callback handing
+/// the events of `manager` to it through `post`.
+pub(crate) fn install(manager:ManagerId, dispatcher:Dispatcher, post:Poster) -> Callback {
+	DISPATCHERS.with(|dispatchers| dispatchers.borrow_mut().insert(manager, dispatcher));
+
+	Arc::new(move |event| post(Box::new(move || dispatch(manager, event))))
+}
+
+/// Forgets the dispatcher of `manager`, if it was set on the current thread.
+pub(crate) fn forget(manager:ManagerId) {
+	// Fails while the thread is exiting, its dispatchers are dropped anyway.
+	let _ = DISPATCHERS.try_with(|dispatchers| dispatchers.borrow_mut().remove(&manager));
+}
+
+fn dispatch(manager:ManagerId, event:GlobalHotKeyEvent) {
+	// Cloned so the dispatcher may replace itself.
+	let dispatcher = DISPATCHERS.with(|dispatchers| dispatchers.borrow().get(&manager).cloned());
+
+	match dispatcher {
+		Some(dispatcher) => dispatcher(event),
+		None => log::warn!("{:?} reached a thread without the dispatcher of its manager", event),
+	}
+}
+
+#[test]
+fn test_main_thread_dispatcher() {
+	use crossbeam_channel::unbounded;
+
+	use crate::{
+		HotKeyState,
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyO);
+
+	let (tasks_tx, tasks_rx) = unbounded::<Box<dyn FnOnce() + Send>>();
+
+	let states = Rc::new(RefCell::new(Vec::new()));
+
+	let dispatched = states.clone();
+
+	manager.set_main_thread_dispatcher_with_proxy(
+		move |event| dispatched.borrow_mut().push(event.state()),
+		move |task| {
+			let _ = tasks_tx.send(task);
+		},
+	);
+
+	manager.register(hotkey).unwrap();
+
+	// Dispatched from other threads, as a backend would.
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			manager.simulate_press(&hotkey);
+
+			manager.simulate_release(&hotkey);
+		});
+	});
+
+	assert!(states.borrow().is_empty());
+
+	for task in tasks_rx.try_iter() {
+		task();
+	}
+
+	assert_eq!(*states.borrow(), [HotKeyState::Pressed, HotKeyState::Released]);
+}
@@ -0,0 +1,428 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
+
+/// The actions bound to accelerators such as `Ctrl+Shift+P`, e.g. as read
+/// from a configuration file, registered together and looked up from their
+/// events.
+///
+/// Unlike [`HotKeyRegistry`](crate::HotKeyRegistry), the bindings are kept
+/// before they're registered, so that a configuration can be checked for two
+/// actions on the same accelerator first and every failing entry reported at
+/// once.
+///
+/// ```no_run
+/// # use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyMap};
+/// #[derive(PartialEq)]
+/// enum Action {
+///     CommandPalette,
+///     Quit,
+/// }
+///
+/// # fn main() -> global_hotkey::Result<()> {
+/// let manager = GlobalHotKeyManager::new()?;
+///
+/// let mut map = HotKeyMap::new();
+///
+/// map.insert("Ctrl+Shift+P", Action::CommandPalette)?;
+///
+/// map.insert("Ctrl+Q", Action::Quit)?;
+///
+/// for (hotkey, result) in map.attach(&manager) {
+///     if let Err(e) = result {
+///         eprintln!("{} isn't available: {}", hotkey, e);
+///     }
+/// }
+///
+/// match map.resolve(&GlobalHotKeyEvent::event_receiver().recv()) {
+///     Some(Action::CommandPalette) => {},
+///     Some(Action::Quit) => {},
+///     None => {},
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The map doesn't own the registrations, they stay registered with the
+/// manager when it's dropped.
+#[derive(Debug)]
+pub struct HotKeyMap<A> {
+	entries:Vec<Entry<A>>,
+}
+
+#[derive(Debug)]
+struct Entry<A> {
+	hotkey:HotKey,
+	action:A,
+	// Whether the hotkey was registered by `attach` and not unregistered since.
+	attached:bool,
+}
+
+impl<A> Default for HotKeyMap<A> {
+	fn default() -> Self { Self { entries:Vec::new() } }
+}
+
+impl<A> HotKeyMap<A> {
+	pub fn new() -> Self { Self::default() }
+
+	/// Binds `action` to the hotkey `accelerator` is parsed into, see
+	/// [`HotKey::from_str`](std::str::FromStr::from_str).
+	///
+	/// Fails with [`Error::AlreadyRegistered`](crate::Error::AlreadyRegistered)
+	/// if another action is bound to the same hotkey, however it's written.
+	pub fn insert(&mut self, accelerator:&str, action:A) -> crate::Result<()> {
+		self.insert_hotkey(accelerator.parse()?, action)
+	}
+
+	/// Binds `action` to `hotkey`, like [`HotKeyMap::insert`].
+	///
+	/// The hotkey is registered by the next [`HotKeyMap::attach`].
+	pub fn insert_hotkey(&mut self, hotkey:HotKey, action:A) -> crate::Result<()> {
+		if self.position(hotkey.id()).is_some() {
+			return Err(crate::Error::AlreadyRegistered(hotkey));
+		}
+
+		self.entries.push(Entry { hotkey, action, attached:false });
+
+		Ok(())
+	}
+
+	/// Registers the hotkeys of the map with `manager`, returning the result of
+	/// each of them in the order they were inserted.
+	///
+	/// The hotkeys already attached are skipped, a failing one is left out
+	/// and tried again by the next call.
+	pub fn attach<M:HotKeyManager>(&mut self, manager:&M) -> Vec<(HotKey, crate::Result<()>)> {
+		self.entries
+			.iter_mut()
+			.filter(|entry| !entry.attached)
+			.map(|entry| {
+				let result = manager.register(entry.hotkey);
+
+				entry.attached = result.is_ok();
+
+				(entry.hotkey, result)
+			})
+			.collect()
+	}
+
+	/// Unregisters the attached hotkeys of the map from `manager`, returning the
+	/// result of each of them. The bindings are kept.
+	pub fn detach<M:HotKeyManager>(&mut self, manager:&M) -> Vec<(HotKey, crate::Result<()>)> {
+		self.entries
+			.iter_mut()
+			.filter(|entry| entry.attached)
+			.map(|entry| {
+				let result = manager.unregister(entry.hotkey);
+
+				entry.attached = result.is_err();
+
+				(entry.hotkey, result)
+			})
+			.collect()
+	}
+
+	/// Returns the action of the hotkey `event` is for, whether it was pressed
+	/// or released, `None` if the map has no such hotkey.
+	pub fn resolve(&self, event:&GlobalHotKeyEvent) -> Option<&A> {
+		self.position(event.id()).map(|i| &self.entries[i].action)
+	}
+
+	/// Runs the action of the hotkey `event` is for when it was pressed,
+	/// returning whether an action ran. Releases run nothing.
+	pub fn run(&self, event:&GlobalHotKeyEvent) -> bool
+	where
+		A: Fn(), {
+		match self.resolve(event) {
+			Some(action) if event.state() == HotKeyState::Pressed => {
+				action();
+
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Binds `action` to `accelerator` instead of its current hotkey.
+	///
+	/// An attached hotkey is replaced with
+	/// [`GlobalHotKeyManager::rebind`], so that either the new hotkey is
+	/// registered or the old one is kept along with the binding and the
+	/// error is returned.
+	///
+	/// Fails with [`Error::UnboundAction`](crate::Error::UnboundAction) if
+	/// `action` isn't in the map, and with
+	/// [`Error::AlreadyRegistered`](crate::Error::AlreadyRegistered) if
+	/// another action is bound to `accelerator`.
+	pub fn rebind<M:HotKeyManager>(
+		&mut self,
+		manager:&M,
+		action:&A,
+		accelerator:&str,
+	) -> crate::Result<()>
+	where
+		A: PartialEq, {
+		let hotkey = accelerator.parse::<HotKey>()?;
+
+		let i = self
+			.entries
+			.iter()
+			.position(|entry| entry.action == *action)
+			.ok_or(crate::Error::UnboundAction)?;
+
+		let old = self.entries[i].hotkey;
+
+		if old.id() == hotkey.id() {
+			return Ok(());
+		}
+
+		if self.position(hotkey.id()).is_some() {
+			return Err(crate::Error::AlreadyRegistered(hotkey));
+		}
+
+		if self.entries[i].attached {
+			manager.rebind(old, hotkey)?;
+		}
+
+		self.entries[i].hotkey = hotkey;
+
+		Ok(())
+	}
+
+	/// Returns the hotkeys of the map along with their actions, in the order
+	/// they were inserted.
+	pub fn iter(&self) -> impl Iterator<Item = (&HotKey, &A)> {
+		self.entries.iter().map(|entry| (&entry.hotkey, &entry.action))
+	}
+
+	/// Returns the number of bindings of the map.
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	/// Returns `true` if the map has no binding.
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	fn position(&self, id:u32) -> Option<usize> {
+		self.entries.iter().position(|entry| entry.hotkey.id() == id)
+	}
+}
+
+/// The managers [`HotKeyMap`] and [`HotKeyDiff`](crate::HotKeyDiff) register
+/// hotkeys with, [`GlobalHotKeyManager`] and, with the `mock` feature,
+/// [`MockGlobalHotKeyManager`](crate::MockGlobalHotKeyManager).
+///
+/// Implemented by this crate only, the methods are the ones of the managers.
+pub trait HotKeyManager: sealed::Sealed {
+	fn register(&self, hotkey:HotKey) -> crate::Result<()>;
+
+	fn unregister(&self, hotkey:HotKey) -> crate::Result<()>;
+
+	fn rebind(&self, old:HotKey, new:HotKey) -> crate::Result<()>;
+}
+
+mod sealed {
+	pub trait Sealed {}
+}
+
+impl sealed::Sealed for GlobalHotKeyManager {}
+
+impl HotKeyManager for GlobalHotKeyManager {
+	fn register(&self, hotkey:HotKey) -> crate::Result<()> { self.register(hotkey) }
+
+	fn unregister(&self, hotkey:HotKey) -> crate::Result<()> { self.unregister(hotkey) }
+
+	fn rebind(&self, old:HotKey, new:HotKey) -> crate::Result<()> { self.rebind(old, new) }
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl sealed::Sealed for crate::MockGlobalHotKeyManager {}
+
+#[cfg(any(test, feature = "mock"))]
+impl HotKeyManager for crate::MockGlobalHotKeyManager {
+	fn register(&self, hotkey:HotKey) -> crate::Result<()> { self.register(hotkey) }
+
+	fn unregister(&self, hotkey:HotKey) -> crate::Result<()> { self.unregister(hotkey) }
+
+	fn rebind(&self, old:HotKey, new:HotKey) -> crate::Result<()> { self.rebind(old, new) }
+}
+
+#[test]
+fn test_hotkey_map_insert() {
+	let mut map = HotKeyMap::new();
+
+	map.insert("Ctrl+Shift+P", "palette").unwrap();
+
+	// The same hotkey written another way.
+	assert!(matches!(
+		map.insert("shift+control+KeyP", "other"),
+		Err(crate::Error::AlreadyRegistered(_))
+	));
+
+	assert!(map.insert("Ctrl+Nope", "other").is_err());
+
+	map.insert("Ctrl+Q", "quit").unwrap();
+
+	assert_eq!(map.iter().map(|(h, a)| (h.to_string(), *a)).collect::<Vec<_>>(), [
+		("shift+control+KeyP".to_string(), "palette"),
+		("control+KeyQ".to_string(), "quit"),
+	]);
+
+	let palette = "Ctrl+Shift+P".parse::<HotKey>().unwrap();
+
+	let released = GlobalHotKeyEvent::new(palette.id(), HotKeyState::Released);
+
+	assert_eq!(map.resolve(&released), Some(&"palette"));
+
+	assert_eq!(map.resolve(&GlobalHotKeyEvent::new(0, HotKeyState::Pressed)), None);
+}
+
+#[test]
+fn test_hotkey_map_attach() {
+	use crate::MockGlobalHotKeyManager;
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let other = MockGlobalHotKeyManager::new().unwrap();
+
+	let taken = "Ctrl+Super+KeyR".parse::<HotKey>().unwrap();
+
+	other.register(taken).unwrap();
+
+	let mut map = HotKeyMap::new();
+
+	map.insert("Ctrl+Super+KeyQ", 1).unwrap();
+
+	map.insert("Ctrl+Super+KeyR", 2).unwrap();
+
+	// Every entry is tried, the failing one is reported on its own.
+	let results = map.attach(&manager);
+
+	assert_eq!(results.len(), 2);
+
+	assert!(results[0].1.is_ok());
+
+	assert!(matches!(results[1], (hotkey, Err(crate::Error::AlreadyRegisteredInProcess { .. }))
+		if hotkey == taken));
+
+	let q = "Ctrl+Super+KeyQ".parse::<HotKey>().unwrap();
+
+	assert!(manager.is_registered(&q));
+
+	// Only the failing entry is tried again.
+	other.unregister(taken).unwrap();
+
+	let results = map.attach(&manager);
+
+	assert_eq!(results.iter().map(|(h, r)| (*h, r.is_ok())).collect::<Vec<_>>(), [(taken, true)]);
+
+	assert!(map.attach(&manager).is_empty());
+
+	assert_eq!(map.detach(&manager).len(), 2);
+
+	assert!(!manager.is_registered(&q) && !manager.is_registered(&taken));
+
+	assert!(map.detach(&manager).is_empty());
+
+	assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_hotkey_map_run() {
+	use std::{cell::Cell, rc::Rc};
+
+	use crate::MockGlobalHotKeyManager;
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let runs = Rc::new(Cell::new(0));
+
+	let mut map = HotKeyMap::<Box<dyn Fn()>>::new();
+
+	let counter = runs.clone();
+
+	map.insert("Ctrl+Super+KeyT", Box::new(move || counter.set(counter.get() + 1))).unwrap();
+
+	assert!(map.attach(&manager).iter().all(|(_, r)| r.is_ok()));
+
+	let hotkey = "Ctrl+Super+KeyT".parse::<HotKey>().unwrap();
+
+	assert!(map.run(&GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Pressed)));
+
+	assert!(!map.run(&GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Released)));
+
+	assert!(!map.run(&GlobalHotKeyEvent::new(hotkey.id() + 1, HotKeyState::Pressed)));
+
+	assert_eq!(runs.get(), 1);
+
+	map.detach(&manager);
+}
+
+#[test]
+fn test_hotkey_map_rebind() {
+	use crate::MockGlobalHotKeyManager;
+
+	#[derive(Debug, PartialEq)]
+	enum Action {
+		Palette,
+		Quit,
+		Missing,
+	}
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let other = MockGlobalHotKeyManager::new().unwrap();
+
+	let mut map = HotKeyMap::new();
+
+	map.insert("Ctrl+Super+KeyW", Action::Palette).unwrap();
+
+	map.insert("Ctrl+Super+KeyY", Action::Quit).unwrap();
+
+	map.attach(&manager);
+
+	let parse = |s:&str| s.parse::<HotKey>().unwrap();
+
+	map.rebind(&manager, &Action::Palette, "Ctrl+Super+KeyI").unwrap();
+
+	assert!(manager.is_registered(&parse("Ctrl+Super+KeyI")));
+
+	assert!(!manager.is_registered(&parse("Ctrl+Super+KeyW")));
+
+	let event = GlobalHotKeyEvent::new(parse("Ctrl+Super+KeyI").id(), HotKeyState::Pressed);
+
+	assert_eq!(map.resolve(&event), Some(&Action::Palette));
+
+	// Two actions can't share an accelerator.
+	assert!(matches!(
+		map.rebind(&manager, &Action::Palette, "Ctrl+Super+KeyY"),
+		Err(crate::Error::AlreadyRegistered(_))
+	));
+
+	assert!(matches!(
+		map.rebind(&manager, &Action::Missing, "Ctrl+Super+KeyW"),
+		Err(crate::Error::UnboundAction)
+	));
+
+	// A failed rebind keeps the old binding and registration.
+	let taken = parse("Ctrl+Super+KeyH");
+
+	other.register(taken).unwrap();
+
+	assert!(map.rebind(&manager, &Action::Quit, "Ctrl+Super+KeyH").is_err());
+
+	assert!(manager.is_registered(&parse("Ctrl+Super+KeyY")));
+
+	let event = GlobalHotKeyEvent::new(parse("Ctrl+Super+KeyY").id(), HotKeyState::Pressed);
+
+	assert_eq!(map.resolve(&event), Some(&Action::Quit));
+
+	other.unregister(taken).unwrap();
+
+	map.detach(&manager);
+
+	// Detached entries are rebound in the map only.
+	map.rebind(&manager, &Action::Quit, "Ctrl+Super+KeyH").unwrap();
+
+	assert!(!manager.is_registered(&taken));
+}
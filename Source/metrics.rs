@@ -0,0 +1,438 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc,
+		Mutex,
+		Weak,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+	GlobalHotKeyEvent,
+	HotKeyState,
+	process::{self, ManagerId},
+};
+
+/// Counters of the events dispatched by the crate, along with the hotkeys
+/// registered with a manager and how often they were triggered.
+///
+/// Obtained through [`GlobalHotKeyManager::metrics`](crate::GlobalHotKeyManager::metrics).
+/// Only the events of the hotkeys registered through the manager are counted.
+/// Events only carry the hotkey id, so while two managers hold hotkeys with
+/// the same custom id its events are counted by either of them.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ManagerMetrics {
+	/// The number of events handed to the event handler or the channel of
+	/// [`GlobalHotKeyEvent::event_receiver`](crate::GlobalHotKeyEvent::event_receiver).
+	pub events_dispatched:u64,
+	/// The number of events that couldn't be delivered, because the receiver
+	/// was dropped or, with the `lockfree-ringbuf` feature, the channel was full.
+	pub events_dropped:u64,
+	/// The average time spent handing an event over, in microseconds. A slow
+	/// event handler shows up here, as it delays the events after it.
+	pub avg_dispatch_latency_us:f64,
+	/// The number of hotkeys registered through the manager.
+	pub registered_count:usize,
+	/// The trigger counters of every hotkey registered through the manager,
+	/// by id.
+	pub hotkeys:HashMap<u32, HotKeyMetrics>,
+}
+
+/// How often a hotkey was triggered, see [`ManagerMetrics::hotkeys`].
+///
+/// The counters are kept across suspend and resume, and reset when the
+/// hotkey is unregistered or with
+/// [`GlobalHotKeyManager::reset_metrics`](crate::GlobalHotKeyManager::reset_metrics).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HotKeyMetrics {
+	/// The number of times the hotkey was pressed.
+	pub presses:u64,
+	/// When the hotkey was last pressed, `None` if it wasn't yet.
+	pub last_triggered:Option<SystemTime>,
+}
+
+// Updated by every dispatch from the backend threads.
+#[derive(Default)]
+struct Counters {
+	dispatched:AtomicU64,
+	dropped:AtomicU64,
+	latency_total_us:AtomicU64,
+	triggers:TriggerSlots,
+}
+
+// The counters of the managers which registered a hotkey, forgotten once the
+// manager is dropped.
+static MANAGERS:Lazy<Mutex<HashMap<ManagerId, Weak<Counters>>>> = Lazy::new(Default::default);
+
+/// The counters of a manager, updated with the events of its hotkeys.
+#[derive(Default)]
+pub(crate) struct Metrics {
+	counters:Arc<Counters>,
+}
+
+impl Metrics {
+	/// Starts counting the presses of the hotkey with this id.
+	pub(crate) fn track(&self, manager:ManagerId, id:u32) {
+		self.publish(manager);
+
+		if self.counters.triggers.find(id, true).is_none() {
+			log::warn!(
+				"More than {} hotkeys were registered, the presses of {} aren't counted",
+				TRIGGER_SLOTS,
+				id
+			);
+		}
+	}
+
+	/// Stops counting the presses of the hotkey with this id, freeing its slot.
+	pub(crate) fn untrack(&self, id:u32) {
+		if let Some(slot) = self.counters.triggers.find(id, false) {
+			slot.id.store(FREED_SLOT, Ordering::Release);
+		}
+	}
+
+	/// Resets the counters of the hotkey with this id.
+	pub(crate) fn reset(&self, id:u32) {
+		if let Some(slot) = self.counters.triggers.find(id, false) {
+			slot.presses.store(0, Ordering::Relaxed);
+
+			slot.last_triggered_ms.store(0, Ordering::Relaxed);
+		}
+	}
+
+	/// Returns the counters along with the ones of the `registered` hotkey ids.
+	pub(crate) fn snapshot<I:IntoIterator<Item = u32>>(&self, registered:I) -> ManagerMetrics {
+		let counters = &self.counters;
+
+		let hotkeys = registered
+			.into_iter()
+			.map(|id| (id, counters.triggers.metrics(id)))
+			.collect::<HashMap<_, _>>();
+
+		let registered_count = hotkeys.len();
+
+		let events_dispatched = counters.dispatched.load(Ordering::Relaxed);
+
+		let latency_total_us = counters.latency_total_us.load(Ordering::Relaxed);
+
+		ManagerMetrics {
+			events_dispatched,
+			events_dropped:counters.dropped.load(Ordering::Relaxed),
+			avg_dispatch_latency_us:if events_dispatched == 0 {
+				0.0
+			} else {
+				latency_total_us as f64 / events_dispatched as f64
+			},
+			registered_count,
+			hotkeys,
+		}
+	}
+
+	fn publish(&self, manager:ManagerId) {
+		let mut managers = MANAGERS.lock().unwrap();
+
+		managers.retain(|_, counters| counters.strong_count() > 0);
+
+		managers.entry(manager).or_insert_with(|| Arc::downgrade(&self.counters));
+	}
+}
+
+/// The counters of the manager holding the hotkey of an event, which every
+/// step of its dispatch updates.
+pub(crate) struct Dispatch(Option<Arc<Counters>>);
+
+impl Dispatch {
+	pub(crate) fn of(event:&GlobalHotKeyEvent) -> Self {
+		let counters = process::holder(event.id())
+			.and_then(|manager| MANAGERS.lock().unwrap().get(&manager).and_then(Weak::upgrade));
+
+		Self(counters)
+	}
+
+	pub(crate) fn record_trigger(&self, event:&GlobalHotKeyEvent) {
+		let Some(counters) = &self.0 else {
+			return;
+		};
+
+		if event.state() != HotKeyState::Pressed {
+			return;
+		}
+
+		let Some(slot) = counters.triggers.find(event.id(), false) else {
+			return;
+		};
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+		slot.presses.fetch_add(1, Ordering::Relaxed);
+
+		// At least 1ms so a press at the epoch still reads as triggered.
+		let now_ms = u64::try_from(now.as_millis()).unwrap_or(u64::MAX).max(1);
+
+		slot.last_triggered_ms.store(now_ms, Ordering::Relaxed);
+	}
+
+	pub(crate) fn record_dispatch(&self, latency:Duration) {
+		let Some(counters) = &self.0 else {
+			return;
+		};
+
+		counters.dispatched.fetch_add(1, Ordering::Relaxed);
+
+		let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+
+		counters.latency_total_us.fetch_add(latency_us, Ordering::Relaxed);
+	}
+
+	pub(crate) fn record_drop(&self) {
+		if let Some(counters) = &self.0 {
+			counters.dropped.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+}
+
+// The trigger counters of a hotkey id. Slots are claimed when a hotkey is
+// registered and freed when it's unregistered, dispatching only reads and
+// updates atomics.
+struct TriggerSlot {
+	// `EMPTY_SLOT` until claimed, the hotkey id afterwards and `FREED_SLOT`
+	// once freed.
+	id:AtomicU64,
+	presses:AtomicU64,
+	// Milliseconds since the Unix epoch, `0` if never triggered.
+	last_triggered_ms:AtomicU64,
+}
+
+impl Default for TriggerSlot {
+	fn default() -> Self {
+		Self {
+			id:AtomicU64::new(EMPTY_SLOT),
+			presses:AtomicU64::new(0),
+			last_triggered_ms:AtomicU64::new(0),
+		}
+	}
+}
+
+const EMPTY_SLOT:u64 = u64::MAX;
+
+// A tombstone, the probes for the ids claimed after it go on past it.
+const FREED_SLOT:u64 = u64::MAX - 1;
+
+const TRIGGER_SLOTS:usize = 1024;
+
+struct TriggerSlots(Box<[TriggerSlot]>);
+
+impl Default for TriggerSlots {
+	fn default() -> Self { Self((0..TRIGGER_SLOTS).map(|_| TriggerSlot::default()).collect()) }
+}
+
+impl TriggerSlots {
+	// Open addressing, probing from the slot of the id until it or an unclaimed
+	// slot is found. A claim takes the first freed slot on the way, if any.
+	fn find(&self, id:u32, claim:bool) -> Option<&TriggerSlot> {
+		let start = id as usize % TRIGGER_SLOTS;
+
+		let mut freed = None;
+
+		let mut unclaimed = None;
+
+		for i in 0..TRIGGER_SLOTS {
+			let slot = &self.0[(start + i) % TRIGGER_SLOTS];
+
+			match slot.id.load(Ordering::Acquire) {
+				current if current == u64::from(id) => return Some(slot),
+				EMPTY_SLOT => {
+					unclaimed = Some((slot, EMPTY_SLOT));
+
+					break;
+				},
+				FREED_SLOT if freed.is_none() => freed = Some((slot, FREED_SLOT)),
+				_ => {},
+			}
+		}
+
+		let (slot, current) = freed.or(unclaimed).filter(|_| claim)?;
+
+		match slot.id.compare_exchange(current, id.into(), Ordering::AcqRel, Ordering::Acquire) {
+			Ok(_) => {
+				// Counted from zero, whatever the id it was freed by left.
+				slot.presses.store(0, Ordering::Relaxed);
+
+				slot.last_triggered_ms.store(0, Ordering::Relaxed);
+
+				Some(slot)
+			},
+			// Claimed by another thread for the same id in the meantime.
+			Err(current) if current == u64::from(id) => Some(slot),
+			// Or for another id, the probing starts over.
+			Err(_) => self.find(id, claim),
+		}
+	}
+
+	fn metrics(&self, id:u32) -> HotKeyMetrics {
+		let Some(slot) = self.find(id, false) else {
+			return HotKeyMetrics::default();
+		};
+
+		let last_triggered = match slot.last_triggered_ms.load(Ordering::Relaxed) {
+			0 => None,
+			ms => Some(UNIX_EPOCH + Duration::from_millis(ms)),
+		};
+
+		HotKeyMetrics { presses:slot.presses.load(Ordering::Relaxed), last_triggered }
+	}
+}
+
+#[test]
+fn test_metrics() {
+	let metrics = Metrics::default();
+
+	let dispatch = Dispatch(Some(metrics.counters.clone()));
+
+	dispatch.record_dispatch(Duration::from_micros(40));
+
+	dispatch.record_dispatch(Duration::from_micros(20));
+
+	dispatch.record_drop();
+
+	// Events of no manager aren't counted.
+	Dispatch(None).record_drop();
+
+	let snapshot = metrics.snapshot([]);
+
+	assert_eq!((snapshot.events_dispatched, snapshot.events_dropped), (2, 1));
+
+	assert_eq!(snapshot.avg_dispatch_latency_us, 30.0);
+}
+
+#[test]
+fn test_trigger_slots() {
+	let metrics = Metrics::default();
+
+	let manager = ManagerId::default();
+
+	let dispatch = Dispatch(Some(metrics.counters.clone()));
+
+	let press = |id| dispatch.record_trigger(&GlobalHotKeyEvent::new(id, HotKeyState::Pressed));
+
+	// Ids sharing a first slot.
+	let (a, b) = (3, 3 + TRIGGER_SLOTS as u32);
+
+	metrics.track(manager, a);
+
+	metrics.track(manager, b);
+
+	press(a);
+
+	press(b);
+
+	press(b);
+
+	dispatch.record_trigger(&GlobalHotKeyEvent::new(b, HotKeyState::Released));
+
+	// Untracked ids aren't counted.
+	press(a + 1);
+
+	let snapshot = metrics.snapshot([a, b, a + 1]);
+
+	assert_eq!(snapshot.registered_count, 3);
+
+	assert_eq!(snapshot.hotkeys[&a].presses, 1);
+
+	assert_eq!(snapshot.hotkeys[&b].presses, 2);
+
+	assert!(snapshot.hotkeys[&b].last_triggered.is_some());
+
+	assert_eq!(snapshot.hotkeys[&(a + 1)], HotKeyMetrics::default());
+
+	metrics.reset(b);
+
+	assert_eq!(metrics.snapshot([b]).hotkeys[&b], HotKeyMetrics::default());
+
+	// `b` is still found past the freed slot of `a`, which a new id reuses.
+	metrics.untrack(a);
+
+	press(b);
+
+	assert_eq!(metrics.snapshot([a, b]).hotkeys[&b].presses, 1);
+
+	let c = a + 2 * TRIGGER_SLOTS as u32;
+
+	metrics.track(manager, c);
+
+	assert_eq!(metrics.snapshot([c]).hotkeys[&c], HotKeyMetrics::default());
+}
+
+#[test]
+fn test_freed_trigger_slots() {
+	let metrics = Metrics::default();
+
+	let manager = ManagerId::default();
+
+	let slots = &metrics.counters.triggers;
+
+	// More ids than slots, the slots of the unregistered ones are reused.
+	for id in 0..TRIGGER_SLOTS as u32 * 3 {
+		metrics.track(manager, id);
+
+		assert!(slots.find(id, false).is_some(), "{:#x}", id);
+
+		metrics.untrack(id);
+
+		assert!(slots.find(id, false).is_none());
+	}
+}
+
+#[test]
+fn test_metrics_per_manager() {
+	use crate::{
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let (a, b) = (MockGlobalHotKeyManager::new().unwrap(), MockGlobalHotKeyManager::new().unwrap());
+
+	let mods = Some(Modifiers::CONTROL | Modifiers::SUPER);
+
+	// The same custom id on two managers.
+	let id = 0x7d00_0001;
+
+	let (on_a, on_b) =
+		(HotKey::new(mods, Code::KeyM).with_id(id), HotKey::new(mods, Code::KeyZ).with_id(id));
+
+	a.register(on_a).unwrap();
+
+	b.register(on_b).unwrap();
+
+	// Unregistering it from `b` leaves the counters of `a` alone.
+	b.unregister(on_b).unwrap();
+
+	a.simulate_press(&on_a);
+
+	a.simulate_release(&on_a);
+
+	let metrics = a.metrics();
+
+	assert_eq!(metrics.hotkeys[&id].presses, 1);
+
+	// Only the events of `a`, whatever the other tests dispatch meanwhile.
+	assert_eq!(metrics.events_dispatched + metrics.events_dropped, 2);
+
+	b.register(on_b).unwrap();
+
+	let metrics = b.metrics();
+
+	assert_eq!(metrics.hotkeys[&id].presses, 0);
+
+	assert_eq!(metrics.events_dispatched + metrics.events_dropped, 0);
+}
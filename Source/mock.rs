@@ -0,0 +1,1141 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! An in-process [`GlobalHotKeyManager`](crate::GlobalHotKeyManager) stand-in
+//! for testing application logic without a display server.
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::{
+		Arc,
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::{Duration, Instant},
+};
+
+use keyboard_types::KeyState;
+
+use crate::{
+	Backend,
+	Capabilities,
+	GlobalHotKeyEvent,
+	GroupId,
+	HotKeyData,
+	HotKeyOptions,
+	HotKeyState,
+	HotKeyStatus,
+	LockState,
+	ManagerMetrics,
+	ManagerStatus,
+	RecordingHandle,
+	callback::Callbacks,
+	data::DataStore,
+	gate,
+	group::HotKeyGroups,
+	hotkey::{Code, HotKey, Modifiers},
+	metrics::Metrics,
+	process::{self, ManagerId},
+	recorder::{self, Recorder},
+	tap_hold::{Gesture, Next, TapHold},
+};
+
+/// A manager with the same interface as [`GlobalHotKeyManager`](crate::GlobalHotKeyManager)
+/// that never touches the OS.
+///
+/// Registered hotkeys are only stored in memory and events are produced by
+/// [`MockGlobalHotKeyManager::simulate_press`] and
+/// [`MockGlobalHotKeyManager::simulate_release`], which deliver them the same
+/// way real events are delivered, either to [`GlobalHotKeyEvent::event_receiver`]
+/// or to the handler set with [`GlobalHotKeyEvent::set_event_handler`].
+#[derive(Default)]
+pub struct MockGlobalHotKeyManager {
+	hotkeys:Mutex<HashSet<HotKey>>,
+	// Hotkeys dropped by `simulate_lost_grab`.
+	lost:Mutex<HashSet<HotKey>>,
+	suspended:AtomicBool,
+	recording:Arc<Mutex<Option<Recorder>>>,
+	// Tap-hold hotkeys by id, along with the gesture of the one held.
+	tap_holds:Mutex<HashMap<u32, (HotKey, TapHold)>>,
+	// The lock states required by the hotkeys registered with some, by id.
+	locks:Mutex<HashMap<u32, LockState>>,
+	gesture:Mutex<Option<Gesture>>,
+	groups:HotKeyGroups,
+	data:DataStore,
+	callbacks:Callbacks,
+	metrics:Metrics,
+	id:ManagerId,
+	// Set with `set_max_hotkeys`.
+	max_hotkeys:Mutex<Option<usize>>,
+}
+
+impl Drop for MockGlobalHotKeyManager {
+	fn drop(&mut self) {
+		process::release_all(self.id);
+
+		for hotkey in self.hotkeys.get_mut().unwrap().iter() {
+			gate::set(hotkey.id(), None);
+		}
+
+		crate::main_thread::forget(self.id);
+	}
+}
+
+impl std::fmt::Debug for MockGlobalHotKeyManager {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MockGlobalHotKeyManager")
+			.field("hotkeys", &self.hotkeys)
+			.field("suspended", &self.suspended)
+			.finish_non_exhaustive()
+	}
+}
+
+impl MockGlobalHotKeyManager {
+	pub fn new() -> crate::Result<Self> { Ok(Self::default()) }
+
+	/// Returns the id of the manager, mocks share the ids and the hotkeys of
+	/// the real managers of the process.
+	pub fn id(&self) -> usize { self.id.get() }
+
+	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> {
+		self.register_with_locks(hotkey, LockState::EITHER)
+	}
+
+	// Hotkeys only differing in lock requirements that can't hold at once
+	// don't overlap, the way the backends grab them.
+	fn register_with_locks(&self, hotkey:HotKey, locks:LockState) -> crate::Result<()> {
+		let mut hotkeys = self.hotkeys.lock().unwrap();
+
+		if let Some(existing) = self.overlapping(&hotkeys, &hotkey, &locks) {
+			return Err(crate::Error::AlreadyRegisteredConflict { attempted:hotkey, existing });
+		}
+
+		self.check_quota(&hotkeys, &[hotkey])?;
+
+		process::claim(self.id, &[hotkey])?;
+
+		hotkeys.insert(hotkey);
+
+		if !locks.is_either() {
+			self.locks.lock().unwrap().insert(hotkey.id(), locks);
+		}
+
+		self.lost.lock().unwrap().remove(&hotkey);
+
+		self.metrics.track(self.id, hotkey.id());
+
+		Ok(())
+	}
+
+	/// Fails like [`MockGlobalHotKeyManager::register`] would, without
+	/// registering `hotkey`.
+	pub fn validate(&self, hotkey:HotKey) -> crate::Result<()> {
+		let hotkeys = self.hotkeys.lock().unwrap();
+
+		if let Some(existing) = self.overlapping(&hotkeys, &hotkey, &LockState::EITHER) {
+			return Err(crate::Error::AlreadyRegisteredConflict { attempted:hotkey, existing });
+		}
+
+		self.check_quota(&hotkeys, &[hotkey])?;
+
+		let claimed = process::claim(self.id, &[hotkey])?;
+
+		process::release(self.id, &claimed);
+
+		Ok(())
+	}
+
+	pub fn unregister(&self, hotkey:HotKey) -> crate::Result<()> {
+		self.hotkeys.lock().unwrap().remove(&hotkey);
+
+		self.locks.lock().unwrap().remove(&hotkey.id());
+
+		if let Some((_, tap_hold)) = self.tap_holds.lock().unwrap().remove(&hotkey.id()) {
+			process::release(self.id, &tap_hold.hold_with);
+		}
+
+		self.lost.lock().unwrap().remove(&hotkey);
+
+		self.data.remove(&[hotkey]);
+
+		gate::set(hotkey.id(), None);
+
+		self.metrics.untrack(hotkey.id());
+
+		process::release(self.id, &[hotkey]);
+
+		Ok(())
+	}
+
+	pub fn register_with_data(&self, hotkey:HotKey, data:HotKeyData) -> crate::Result<()> {
+		self.register(hotkey)?;
+
+		self.data.insert(hotkey.id(), data);
+
+		Ok(())
+	}
+
+	pub fn data_for(&self, id:u32) -> Option<HotKeyData> { self.data.get(id) }
+
+	pub fn rebind(&self, old:HotKey, new:HotKey) -> crate::Result<()> {
+		let data = self.data.get(old.id());
+
+		self.unregister(old)?;
+
+		if let Err(e) = self.register(new) {
+			self.register(old)?;
+
+			if let Some(data) = data {
+				self.data.insert(old.id(), data);
+			}
+
+			return Err(e);
+		}
+
+		if let Some(data) = data {
+			self.data.insert(new.id(), data);
+		}
+
+		Ok(())
+	}
+
+	pub fn register_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
+		self.check_quota(&self.hotkeys.lock().unwrap(), hotkeys)?;
+
+		for hotkey in hotkeys {
+			self.register(*hotkey)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn register_all_with_options(
+		&self,
+		hotkeys:&[HotKey],
+		options:HotKeyOptions,
+	) -> crate::Result<()> {
+		self.check_quota(&self.hotkeys.lock().unwrap(), hotkeys)?;
+
+		for hotkey in hotkeys {
+			self.register_with_options(*hotkey, options.clone())?;
+		}
+
+		Ok(())
+	}
+
+	pub fn unregister_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
+		for hotkey in hotkeys {
+			self.unregister(*hotkey)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn clear_all(&self) -> crate::Result<()> {
+		let mut hotkeys = self.hotkeys.lock().unwrap().iter().copied().collect::<Vec<_>>();
+
+		hotkeys.extend(self.lost.lock().unwrap().iter().copied());
+
+		self.unregister_all(&hotkeys)?;
+
+		self.groups.deactivate_all();
+
+		Ok(())
+	}
+
+	pub fn create_group(&self, hotkeys:&[HotKey]) -> GroupId { self.groups.create(hotkeys) }
+
+	pub fn activate_group(&self, group:GroupId) -> crate::Result<()> {
+		self.groups.activate(group, |hotkeys| {
+			for (i, hotkey) in hotkeys.iter().enumerate() {
+				if let Err(e) = self.register(*hotkey) {
+					self.unregister_all(&hotkeys[..i])?;
+
+					return Err(e);
+				}
+			}
+
+			Ok(())
+		})
+	}
+
+	pub fn deactivate_group(&self, group:GroupId) -> crate::Result<()> {
+		self.groups.deactivate(group, |hotkeys| self.unregister_all(hotkeys))
+	}
+
+	pub fn group_of(&self, id:u32) -> Option<GroupId> { self.groups.group_of(id) }
+
+	pub fn suspend(&self) -> crate::Result<()> {
+		self.suspended.store(true, Ordering::SeqCst);
+
+		Ok(())
+	}
+
+	pub fn resume(&self) -> crate::Result<()> {
+		self.suspended.store(false, Ordering::SeqCst);
+
+		Ok(())
+	}
+
+	/// Gives back the grabs dropped by [`MockGlobalHotKeyManager::simulate_lost_grab`],
+	/// the mock never fails to grab so nothing is returned.
+	pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> {
+		let mut hotkeys = self.hotkeys.lock().unwrap();
+
+		hotkeys.extend(self.lost.lock().unwrap().drain());
+
+		Ok(Vec::new())
+	}
+
+	pub fn status(&self) -> ManagerStatus {
+		let suspended = self.suspended.load(Ordering::SeqCst);
+
+		let status = if suspended { HotKeyStatus::Suspended } else { HotKeyStatus::Active };
+
+		let hotkeys = self
+			.hotkeys
+			.lock()
+			.unwrap()
+			.iter()
+			.map(|hotkey| (*hotkey, status.clone()))
+			.chain(self.lost.lock().unwrap().iter().map(|h| (*h, HotKeyStatus::LostGrab)))
+			.collect();
+
+		ManagerStatus::new(Backend::Mock, suspended, hotkeys)
+	}
+
+	pub fn metrics(&self) -> ManagerMetrics {
+		self.metrics.snapshot(self.hotkeys.lock().unwrap().iter().map(HotKey::id))
+	}
+
+	pub fn reset_metrics(&self) {
+		for hotkey in self.hotkeys.lock().unwrap().iter() {
+			self.metrics.reset(hotkey.id());
+		}
+	}
+
+	/// The mock never swallows anything, so `options` only need to be valid.
+	pub fn register_with_options(
+		&self,
+		hotkey:HotKey,
+		options:HotKeyOptions,
+	) -> crate::Result<()> {
+		options.check(&hotkey)?;
+
+		let locks = options.lock_state();
+
+		let Some(tap_hold) = options.tap_hold else {
+			self.register_with_locks(hotkey, locks)?;
+
+			gate::set(hotkey.id(), options.gate.as_ref());
+
+			return Ok(());
+		};
+
+		process::claim(self.id, &tap_hold.hold_with)?;
+
+		if let Err(e) = self.register_with_locks(hotkey, locks) {
+			process::release(self.id, &tap_hold.hold_with);
+
+			return Err(e);
+		}
+
+		self.tap_holds.lock().unwrap().insert(hotkey.id(), (hotkey, tap_hold));
+
+		Ok(())
+	}
+
+	/// Registers a tap-hold hotkey driven by [`MockGlobalHotKeyManager::simulate_key`].
+	pub fn register_tap_hold(
+		&self,
+		tap:HotKey,
+		hold_with:&[HotKey],
+		threshold:Duration,
+	) -> crate::Result<()> {
+		self.register_with_options(tap, HotKeyOptions::tap_hold(TapHold::new(hold_with, threshold)))
+	}
+
+	/// Starts a recording fed by [`MockGlobalHotKeyManager::simulate_key`].
+	pub fn start_recording(&self) -> crate::Result<RecordingHandle> {
+		let mut recording = self.recording.lock().unwrap();
+
+		if recording.is_some() {
+			return Err(crate::Error::RecordingInProgress);
+		}
+
+		let (recorder, handle) = recorder::recording();
+
+		*recording = Some(recorder);
+
+		let shared = self.recording.clone();
+
+		Ok(handle.on_drop(move || {
+			shared.lock().unwrap().take();
+		}))
+	}
+
+	pub fn set_callback(&self, callback:Arc<dyn Fn(GlobalHotKeyEvent) + Send + Sync>) {
+		self.callbacks.set(self.id, callback);
+	}
+
+	pub fn add_callback(&self, id:u64, callback:Arc<dyn Fn(GlobalHotKeyEvent) + Send + Sync>) {
+		self.callbacks.add(self.id, id, callback);
+	}
+
+	pub fn remove_callback(&self, id:u64) -> bool { self.callbacks.remove(id) }
+
+	pub fn set_main_thread_dispatcher_with_proxy<F, P>(&self, dispatcher:F, proxy:P)
+	where
+		F: Fn(GlobalHotKeyEvent) + 'static,
+		P: Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static, {
+		let dispatcher =
+			crate::main_thread::install(self.id, std::rc::Rc::new(dispatcher), Arc::new(proxy));
+
+		self.callbacks.set(self.id, dispatcher);
+	}
+
+	pub fn capabilities(&self) -> Capabilities {
+		Capabilities {
+			sided_modifiers:true,
+			consume:true,
+			passthrough:true,
+			tap_hold:true,
+			device_filter:true,
+			max_hotkeys:*self.max_hotkeys.lock().unwrap(),
+		}
+	}
+
+	pub fn registered_count(&self) -> usize {
+		self.hotkeys.lock().unwrap().len() + self.lost.lock().unwrap().len()
+	}
+
+	/// Sets the limit reported as [`Capabilities::max_hotkeys`] and enforced by
+	/// the mock, to test how an application copes with a backend quota.
+	pub fn set_max_hotkeys(&self, limit:Option<usize>) {
+		*self.max_hotkeys.lock().unwrap() = limit;
+	}
+
+	/// Returns `true` if `hotkey` is currently registered.
+	pub fn is_registered(&self, hotkey:&HotKey) -> bool {
+		self.hotkeys.lock().unwrap().contains(hotkey)
+	}
+
+	/// Emits a [`HotKeyState::Pressed`] event for `hotkey`.
+	///
+	/// Returns `false` without emitting anything if `hotkey` isn't registered
+	/// or the manager is suspended.
+	pub fn simulate_press(&self, hotkey:&HotKey) -> bool {
+		self.simulate(hotkey, HotKeyState::Pressed)
+	}
+
+	/// Emits a [`HotKeyState::Released`] event for `hotkey`.
+	///
+	/// Returns `false` without emitting anything if `hotkey` isn't registered
+	/// or the manager is suspended.
+	pub fn simulate_release(&self, hotkey:&HotKey) -> bool {
+		self.simulate(hotkey, HotKeyState::Released)
+	}
+
+	/// Drops `hotkey` the way a backend loses a grab, e.g. when the system
+	/// wakes from sleep, so it's reported as [`HotKeyStatus::LostGrab`] until it
+	/// is registered again or unregistered.
+	///
+	/// Returns `false` if `hotkey` isn't registered.
+	pub fn simulate_lost_grab(&self, hotkey:&HotKey) -> bool {
+		if !self.hotkeys.lock().unwrap().remove(hotkey) {
+			return false;
+		}
+
+		self.lost.lock().unwrap().insert(*hotkey);
+
+		true
+	}
+
+	/// Delivers a raw key event to the recording started with
+	/// [`MockGlobalHotKeyManager::start_recording`], or otherwise to the
+	/// hotkeys registered with [`MockGlobalHotKeyManager::register_tap_hold`].
+	///
+	/// Returns `false` if neither took the key, the way a backend hands it to
+	/// the focused window.
+	pub fn simulate_key(&self, state:KeyState, code:Code, modifiers:Modifiers) -> bool {
+		if let Some(recorder) = self.recording.lock().unwrap().as_mut() {
+			recorder.send(state, code, modifiers);
+
+			return true;
+		}
+
+		let mut gesture = self.gesture.lock().unwrap();
+
+		if let Some(held) = gesture.as_mut() {
+			let (events, next) = held.key(state, code, modifiers, Instant::now());
+
+			if next != Next::Continue {
+				*gesture = None;
+			}
+
+			for event in events {
+				GlobalHotKeyEvent::send(event);
+			}
+
+			return next != Next::Abort;
+		}
+
+		if state != KeyState::Down || self.suspended.load(Ordering::SeqCst) {
+			return false;
+		}
+
+		let tap_holds = self.tap_holds.lock().unwrap();
+
+		let Some((tap, tap_hold)) = tap_holds.values().find(|(tap, _)| tap.matches(modifiers, code))
+		else {
+			return false;
+		};
+
+		*gesture = Some(Gesture::start(*tap, tap_hold.clone(), Instant::now()));
+
+		true
+	}
+
+	// The registered hotkey a press of `hotkey` would trigger too, in a state of
+	// the locks both accept.
+	fn overlapping(
+		&self,
+		hotkeys:&HashSet<HotKey>,
+		hotkey:&HotKey,
+		locks:&LockState,
+	) -> Option<HotKey> {
+		let required = self.locks.lock().unwrap();
+
+		let accepts =
+			|h:&HotKey| required.get(&h.id()).unwrap_or(&LockState::EITHER).overlaps(locks);
+
+		hotkeys.iter().find(|h| h.overlaps(hotkey) && accepts(h)).copied()
+	}
+
+	// Refuses `added` as a whole if the ones that aren't `registered` yet don't fit.
+	fn check_quota(&self, registered:&HashSet<HotKey>, added:&[HotKey]) -> crate::Result<()> {
+		let Some(limit) = *self.max_hotkeys.lock().unwrap() else {
+			return Ok(());
+		};
+
+		let lost = self.lost.lock().unwrap();
+
+		let added = added
+			.iter()
+			.filter(|hotkey| !registered.contains(hotkey) && !lost.contains(hotkey))
+			.collect::<HashSet<_>>()
+			.len();
+
+		if registered.len() + lost.len() + added > limit {
+			return Err(crate::Error::QuotaExceeded { limit });
+		}
+
+		Ok(())
+	}
+
+	fn simulate(&self, hotkey:&HotKey, state:HotKeyState) -> bool {
+		if !self.is_registered(hotkey) || self.suspended.load(Ordering::SeqCst) {
+			return false;
+		}
+
+		GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(hotkey.id(), state));
+
+		true
+	}
+}
+
+#[test]
+fn test_mock_manager() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F7);
+
+	assert!(!manager.simulate_press(&hotkey));
+
+	manager.register(hotkey).unwrap();
+
+	assert!(matches!(
+		manager.register(hotkey.with_id(1)),
+		Err(crate::Error::AlreadyRegisteredConflict { attempted, existing })
+			if attempted.id() == 1 && existing.id() == hotkey.id()
+	));
+
+	// With the `lockfree-ringbuf` feature the events of the other tests may
+	// have filled the channel, make room for ours.
+	while GlobalHotKeyEvent::event_receiver().try_recv().is_some() {}
+
+	assert!(manager.simulate_press(&hotkey));
+
+	assert!(manager.simulate_release(&hotkey));
+
+	// The channel is shared by every test in this process, only look at our own events.
+	let events = GlobalHotKeyEvent::event_receiver()
+		.try_iter()
+		.filter(|e| e.id() == hotkey.id())
+		.map(|e| e.state())
+		.collect::<Vec<_>>();
+
+	assert_eq!(events, [HotKeyState::Pressed, HotKeyState::Released]);
+
+	manager.unregister(hotkey).unwrap();
+
+	assert!(!manager.is_registered(&hotkey));
+
+	assert!(!manager.simulate_press(&hotkey));
+}
+
+#[test]
+fn test_gated_hotkey() {
+	use std::sync::{Arc, atomic::AtomicBool};
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyV);
+
+	let open = Arc::new(AtomicBool::new(true));
+
+	let options = {
+		let open = open.clone();
+
+		HotKeyOptions::new().gate(move || open.load(Ordering::SeqCst))
+	};
+
+	assert_ne!(options, HotKeyOptions::new());
+
+	manager.register_with_options(hotkey, options).unwrap();
+
+	while GlobalHotKeyEvent::event_receiver().try_recv().is_some() {}
+
+	manager.simulate_press(&hotkey);
+
+	manager.simulate_release(&hotkey);
+
+	// Dropped along with its release, even once the gate opened again.
+	open.store(false, Ordering::SeqCst);
+
+	manager.simulate_press(&hotkey);
+
+	open.store(true, Ordering::SeqCst);
+
+	manager.simulate_release(&hotkey);
+
+	// A press that went through gets its release.
+	manager.simulate_press(&hotkey);
+
+	open.store(false, Ordering::SeqCst);
+
+	manager.simulate_release(&hotkey);
+
+	let events = GlobalHotKeyEvent::event_receiver()
+		.try_iter()
+		.filter(|e| e.id() == hotkey.id())
+		.map(|e| e.state())
+		.collect::<Vec<_>>();
+
+	use HotKeyState::{Pressed, Released};
+
+	assert_eq!(events, [Pressed, Released, Pressed, Released]);
+
+	// The gate goes away with the hotkey.
+	manager.unregister(hotkey).unwrap();
+
+	manager.register(hotkey).unwrap();
+
+	manager.simulate_press(&hotkey);
+
+	assert!(GlobalHotKeyEvent::event_receiver().try_iter().any(|e| e.id() == hotkey.id()));
+
+	manager.unregister(hotkey).unwrap();
+}
+
+#[test]
+fn test_mock_manager_status() {
+	use crate::Health;
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::SHIFT | Modifiers::ALT), Code::F8);
+
+	manager.register(hotkey).unwrap();
+
+	let status = manager.status();
+
+	assert_eq!(status.backend, Backend::Mock);
+
+	assert_eq!(status.health, Health::Healthy);
+
+	assert_eq!(status.registered_count(), 1);
+
+	manager.suspend().unwrap();
+
+	let status = manager.status();
+
+	assert_eq!(status.health, Health::Suspended);
+
+	assert_eq!((status.registered_count(), status.suspended_count()), (1, 1));
+
+	assert!(!manager.simulate_press(&hotkey));
+
+	manager.resume().unwrap();
+
+	assert!(manager.simulate_press(&hotkey));
+
+	assert_eq!(manager.status().registered_count(), 1);
+
+	assert!(manager.simulate_lost_grab(&hotkey));
+
+	let status = manager.status();
+
+	assert_eq!(status.health, Health::Degraded);
+
+	assert_eq!(status.hotkey_status(hotkey.id()), Some(&HotKeyStatus::LostGrab));
+
+	assert_eq!(status.registered_count(), 0);
+
+	assert!(!manager.simulate_press(&hotkey));
+
+	manager.register(hotkey).unwrap();
+
+	assert_eq!(manager.status().health, Health::Healthy);
+}
+
+#[test]
+fn test_mock_manager_recording() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	assert!(!manager.simulate_key(KeyState::Down, Code::KeyR, Modifiers::empty()));
+
+	let handle = manager.start_recording().unwrap();
+
+	assert!(matches!(manager.start_recording(), Err(crate::Error::RecordingInProgress)));
+
+	assert!(manager.simulate_key(KeyState::Down, Code::KeyR, Modifiers::SHIFT));
+
+	assert_eq!(handle.into_hotkey(), Some(HotKey::new(Some(Modifiers::SHIFT), Code::KeyR)));
+
+	assert!(!manager.simulate_key(KeyState::Down, Code::KeyR, Modifiers::empty()));
+}
+
+#[test]
+fn test_mock_manager_groups() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let (save, print) = (
+		HotKey::new(Some(Modifiers::CONTROL), Code::KeyS),
+		HotKey::new(Some(Modifiers::CONTROL), Code::KeyP),
+	);
+
+	let editing = manager.create_group(&[save, print]);
+
+	manager.register(print).unwrap();
+
+	// `print` is taken, so `save` is rolled back.
+	assert!(manager.activate_group(editing).is_err());
+
+	assert!(!manager.is_registered(&save));
+
+	manager.unregister(print).unwrap();
+
+	manager.activate_group(editing).unwrap();
+
+	assert!(manager.is_registered(&save) && manager.is_registered(&print));
+
+	assert_eq!(manager.group_of(save.id()), Some(editing));
+
+	manager.deactivate_group(editing).unwrap();
+
+	assert!(!manager.is_registered(&save) && !manager.is_registered(&print));
+}
+
+#[test]
+fn test_mock_manager_data() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::F9);
+
+	let data:HotKeyData = Arc::new("toggle overlay");
+
+	manager.register_with_data(hotkey, data.clone()).unwrap();
+
+	let action = |id| manager.data_for(id).and_then(|d| d.downcast::<&str>().ok());
+
+	assert_eq!(action(hotkey.id()).as_deref(), Some(&"toggle overlay"));
+
+	// The payload follows the hotkey to its new keys.
+	let rebound = HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::F10);
+
+	manager.rebind(hotkey, rebound).unwrap();
+
+	assert!(manager.data_for(hotkey.id()).is_none());
+
+	assert_eq!(action(rebound.id()).as_deref(), Some(&"toggle overlay"));
+
+	// A failed rebind keeps the old registration and its payload.
+	let taken = HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::F11);
+
+	manager.register(taken).unwrap();
+
+	assert!(manager.rebind(rebound, taken).is_err());
+
+	assert!(manager.is_registered(&rebound));
+
+	assert_eq!(action(rebound.id()).as_deref(), Some(&"toggle overlay"));
+
+	// Unregistering drops the payload.
+	manager.unregister(rebound).unwrap();
+
+	assert!(manager.data_for(rebound.id()).is_none());
+
+	assert_eq!(Arc::strong_count(&data), 1);
+}
+
+#[test]
+fn test_mock_manager_metrics() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::F12);
+
+	manager.register(hotkey).unwrap();
+
+	let presses = || manager.metrics().hotkeys[&hotkey.id()].presses;
+
+	for _ in 0..3 {
+		assert!(manager.simulate_press(&hotkey));
+
+		assert!(manager.simulate_release(&hotkey));
+	}
+
+	assert_eq!(presses(), 3);
+
+	assert!(manager.metrics().hotkeys[&hotkey.id()].last_triggered.is_some());
+
+	// Presses while suspended aren't delivered, and the counters are kept.
+	manager.suspend().unwrap();
+
+	assert!(!manager.simulate_press(&hotkey));
+
+	manager.resume().unwrap();
+
+	assert!(manager.simulate_press(&hotkey));
+
+	assert_eq!(presses(), 4);
+
+	manager.reset_metrics();
+
+	assert_eq!(presses(), 0);
+
+	assert!(manager.simulate_press(&hotkey));
+
+	// Unregistering resets the counters.
+	manager.unregister(hotkey).unwrap();
+
+	assert!(!manager.metrics().hotkeys.contains_key(&hotkey.id()));
+
+	manager.register(hotkey).unwrap();
+
+	assert_eq!(presses(), 0);
+}
+
+#[test]
+fn test_mock_manager_clear_all() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkeys = [Code::KeyA, Code::KeyB, Code::KeyC]
+		.map(|code| HotKey::new(Some(Modifiers::SUPER | Modifiers::CONTROL), code));
+
+	manager.register_with_data(hotkeys[0], Arc::new(0)).unwrap();
+
+	let group = manager.create_group(&hotkeys[1..]);
+
+	manager.activate_group(group).unwrap();
+
+	assert!(manager.simulate_lost_grab(&hotkeys[1]));
+
+	manager.suspend().unwrap();
+
+	manager.clear_all().unwrap();
+
+	manager.resume().unwrap();
+
+	let status = manager.status();
+
+	assert!(status.hotkeys.is_empty());
+
+	assert!(hotkeys.iter().all(|hotkey| !manager.is_registered(hotkey)));
+
+	assert!(manager.data_for(hotkeys[0].id()).is_none());
+
+	assert_eq!(manager.group_of(hotkeys[2].id()), None);
+
+	// The group can be activated again from scratch.
+	manager.activate_group(group).unwrap();
+
+	assert_eq!(manager.group_of(hotkeys[2].id()), Some(group));
+}
+
+#[test]
+fn test_mock_managers_in_process() {
+	let (a, b) = (MockGlobalHotKeyManager::new().unwrap(), MockGlobalHotKeyManager::new().unwrap());
+
+	assert_ne!(a.id(), b.id());
+
+	let mods = Modifiers::SUPER | Modifiers::ALT | Modifiers::SHIFT;
+
+	let hotkey = HotKey::new(Some(mods), Code::KeyK);
+
+	a.register(hotkey).unwrap();
+
+	// Another id doesn't help, the keys are taken.
+	assert!(matches!(
+		b.register(hotkey.with_id(7)),
+		Err(crate::Error::AlreadyRegisteredInProcess { attempted, by_manager })
+			if attempted.id() == 7 && by_manager == a.id()
+	));
+
+	assert!(!b.is_registered(&hotkey.with_id(7)));
+
+	a.unregister(hotkey).unwrap();
+
+	b.register(hotkey).unwrap();
+
+	assert!(a.register(hotkey).is_err());
+
+	// Dropping a manager hands its keys over.
+	drop(b);
+
+	a.register(hotkey).unwrap();
+
+	// A hotkey getting its lost grab back keeps a single claim.
+	assert!(a.simulate_lost_grab(&hotkey));
+
+	a.register(hotkey).unwrap();
+
+	a.unregister(hotkey).unwrap();
+
+	MockGlobalHotKeyManager::new().unwrap().register(hotkey).unwrap();
+}
+
+#[test]
+fn test_mock_manager_register_all_partial() {
+	let (manager, other) =
+		(MockGlobalHotKeyManager::new().unwrap(), MockGlobalHotKeyManager::new().unwrap());
+
+	let [a, b, c] = [Code::KeyB, Code::KeyC, Code::KeyD]
+		.map(|code| HotKey::new(Some(Modifiers::SUPER | Modifiers::ALT | Modifiers::SHIFT), code));
+
+	manager.register(b).unwrap();
+
+	assert!(matches!(
+		manager.register_all_with_options(&[a, b, c], HotKeyOptions::new()),
+		Err(crate::Error::AlreadyRegisteredConflict { existing, .. }) if existing == b
+	));
+
+	// The hotkeys before the failure stay registered and claimed, the rest is left alone.
+	assert!(manager.is_registered(&a) && !manager.is_registered(&c));
+
+	assert_eq!(manager.registered_count(), 2);
+
+	assert!(matches!(other.register(a), Err(crate::Error::AlreadyRegisteredInProcess { .. })));
+
+	other.register(c).unwrap();
+
+	manager.unregister_all(&[a, b]).unwrap();
+
+	other.unregister(c).unwrap();
+}
+
+#[test]
+fn test_mock_manager_revalidate() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::F19);
+
+	manager.register(hotkey).unwrap();
+
+	assert!(manager.simulate_lost_grab(&hotkey));
+
+	assert!(!manager.simulate_press(&hotkey));
+
+	assert_eq!(manager.revalidate().unwrap(), []);
+
+	assert_eq!(manager.status().hotkey_status(hotkey.id()), Some(&HotKeyStatus::Active));
+
+	assert!(manager.simulate_press(&hotkey));
+}
+
+#[test]
+fn test_mock_manager_quota() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	assert_eq!(manager.capabilities().max_hotkeys, None);
+
+	manager.set_max_hotkeys(Some(2));
+
+	assert_eq!(manager.capabilities().max_hotkeys, Some(2));
+
+	let mods = Modifiers::SUPER | Modifiers::CONTROL | Modifiers::ALT;
+
+	let hotkeys = [Code::KeyE, Code::KeyF, Code::KeyG].map(|code| HotKey::new(Some(mods), code));
+
+	// The set is refused as a whole.
+	assert!(matches!(
+		manager.register_all(&hotkeys),
+		Err(crate::Error::QuotaExceeded { limit:2 })
+	));
+
+	assert_eq!(manager.registered_count(), 0);
+
+	manager.register_all(&hotkeys[..2]).unwrap();
+
+	assert!(matches!(manager.register(hotkeys[2]), Err(crate::Error::QuotaExceeded { limit:2 })));
+
+	// Registered hotkeys don't count twice, and lost ones still take a slot.
+	assert!(matches!(
+		manager.register(hotkeys[0]),
+		Err(crate::Error::AlreadyRegisteredConflict { .. })
+	));
+
+	assert!(manager.simulate_lost_grab(&hotkeys[1]));
+
+	assert_eq!(manager.registered_count(), 2);
+
+	assert!(manager.register(hotkeys[2]).is_err());
+
+	manager.unregister(hotkeys[1]).unwrap();
+
+	manager.register(hotkeys[2]).unwrap();
+
+	assert_eq!(manager.registered_count(), 2);
+}
+
+#[test]
+fn test_mock_manager_tap_hold() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let mods = Modifiers::CONTROL | Modifiers::SUPER;
+
+	let tap = HotKey::new(Some(mods), Code::CapsLock);
+
+	let combination = HotKey::new(None, Code::KeyU);
+
+	let events = Arc::new(Mutex::new(Vec::new()));
+
+	let seen = events.clone();
+
+	manager.add_callback(
+		0,
+		Arc::new(move |event:GlobalHotKeyEvent| {
+			seen.lock().unwrap().push((event.id(), event.state()))
+		}),
+	);
+
+	manager.register_tap_hold(tap, &[combination], Duration::from_secs(60)).unwrap();
+
+	// The combination belongs to the manager, although it's not grabbed.
+	assert!(MockGlobalHotKeyManager::new().unwrap().register(combination).is_err());
+
+	let key = |state, code| manager.simulate_key(state, code, mods);
+
+	assert!(key(KeyState::Down, Code::CapsLock));
+
+	assert!(key(KeyState::Up, Code::CapsLock));
+
+	for code in [Code::CapsLock, Code::KeyU] {
+		assert!(key(KeyState::Down, code));
+	}
+
+	for code in [Code::KeyU, Code::CapsLock] {
+		assert!(key(KeyState::Up, code));
+	}
+
+	// Other keys go to the focused window, and so does the rest of the gesture.
+	assert!(key(KeyState::Down, Code::CapsLock));
+
+	assert!(!key(KeyState::Down, Code::KeyI));
+
+	assert!(!key(KeyState::Up, Code::CapsLock));
+
+	assert_eq!(*events.lock().unwrap(), [
+		(tap.id(), HotKeyState::Pressed),
+		(tap.id(), HotKeyState::Released),
+		(combination.id(), HotKeyState::Pressed),
+		(combination.id(), HotKeyState::Released),
+	]);
+
+	manager.unregister(tap).unwrap();
+
+	assert!(!key(KeyState::Down, Code::CapsLock));
+
+	assert!(MockGlobalHotKeyManager::new().unwrap().register(combination).is_ok());
+}
+
+#[test]
+fn test_mock_manager_validate() {
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyP);
+
+	manager.validate(hotkey).unwrap();
+
+	// Nothing is kept, another manager can still take it.
+	assert_eq!(manager.registered_count(), 0);
+
+	let other = MockGlobalHotKeyManager::new().unwrap();
+
+	other.register(hotkey).unwrap();
+
+	assert!(matches!(
+		manager.validate(hotkey),
+		Err(crate::Error::AlreadyRegisteredInProcess { by_manager, .. }) if by_manager == other.id()
+	));
+
+	assert!(matches!(other.validate(hotkey), Err(crate::Error::AlreadyRegisteredConflict { .. })));
+
+	other.unregister(hotkey).unwrap();
+
+	manager.set_max_hotkeys(Some(0));
+
+	assert!(matches!(manager.validate(hotkey), Err(crate::Error::QuotaExceeded { limit:0 })));
+}
+
+#[test]
+fn test_mock_manager_require_locks() {
+	use crate::LockToggle;
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let numpad = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::Numpad5);
+
+	let (navigation, digit) = (numpad.with_id(1), numpad.with_id(2));
+
+	let require = |num_lock| {
+		HotKeyOptions::new().require_locks(LockState::new(num_lock, LockToggle::Either))
+	};
+
+	manager.register_with_options(navigation, require(LockToggle::Off)).unwrap();
+
+	// NumLock can't be both off and on, the two coexist.
+	manager.register_with_options(digit, require(LockToggle::On)).unwrap();
+
+	assert!(matches!(
+		manager.register(numpad),
+		Err(crate::Error::AlreadyRegisteredConflict { attempted, .. }) if attempted == numpad
+	));
+
+	// Forbidding NumLock requires it off.
+	let forbidding = HotKeyOptions::new().forbid_mods(Modifiers::NUM_LOCK);
+
+	assert!(manager.register_with_options(numpad.with_id(3), forbidding).is_err());
+
+	manager.unregister(digit).unwrap();
+
+	// Either state of NumLock overlaps the hotkey requiring it off.
+	assert!(manager.register(digit).is_err());
+
+	manager.unregister(navigation).unwrap();
+
+	manager.register(digit).unwrap();
+
+	manager.unregister(digit).unwrap();
+}
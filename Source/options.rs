@@ -0,0 +1,550 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::c_ulong, time::Duration};
+
+use keyboard_types::Modifiers;
+
+use crate::{DeviceFilter, canonical, gate::Gate, hotkey::HotKey, tap_hold::TapHold};
+
+/// Options controlling how a hotkey is registered, see
+/// [`GlobalHotKeyManager::register_with_options`](crate::GlobalHotKeyManager::register_with_options).
+///
+/// Backends that can't honor an option fail the registration with
+/// [`Error::UnsupportedOption`](crate::Error::UnsupportedOption) instead of
+/// silently ignoring it, [`Capabilities`](crate::Capabilities) tells which ones
+/// are available.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HotKeyOptions {
+	/// Whether the key press is swallowed so the focused application doesn't
+	/// receive it, `true` by default.
+	///
+	/// With `false` the hotkey only observes the key press, see
+	/// [`Capabilities::passthrough`](crate::Capabilities::passthrough).
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11)**: The key is grabbed synchronously and every press is
+	///   replayed to the focused window, which delays it until the next poll
+	///   of the event loop (at most 50ms).
+	/// - **Windows**: Non-consuming hotkeys are matched with a low-level
+	///   keyboard hook instead of `RegisterHotKey`, so the message loop of the
+	///   thread that created the manager must keep running.
+	/// - **macOS**: Non-consuming hotkeys are matched with a global `NSEvent`
+	///   monitor which requires the Input Monitoring permission, see
+	///   [`request_input_monitoring_permission`](crate::platform::macos::request_input_monitoring_permission),
+	///   and a local one for the key presses sent to the application itself,
+	///   which only sees them while its main event loop runs.
+	pub consume:bool,
+	/// Modifiers that must not be held for the hotkey to trigger, none by
+	/// default. E.g. `Ctrl+K` forbidding [`Modifiers::SHIFT`] doesn't trigger
+	/// on `Ctrl+Shift+K`, leaving it to the focused application.
+	///
+	/// Only [`Modifiers::SHIFT`], [`Modifiers::CONTROL`], [`Modifiers::ALT`],
+	/// [`Modifiers::SUPER`] (or [`Modifiers::META`]), [`Modifiers::CAPS_LOCK`]
+	/// and [`Modifiers::NUM_LOCK`] can be forbidden, and not the ones the
+	/// hotkey is made of. The locks are forbidden while they're toggled on,
+	/// although hotkeys otherwise ignore them.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11)**: The key isn't grabbed with the forbidden modifiers,
+	///   so those presses reach the focused window.
+	/// - **Windows**: Presses with a forbidden modifier are still swallowed
+	///   by consuming hotkeys, `RegisterHotKey` can't leave them out.
+	/// - **macOS**: [`Modifiers::NUM_LOCK`] can't be forbidden. Presses with a
+	///   forbidden modifier are still swallowed by consuming hotkeys.
+	pub forbid_mods:Modifiers,
+	/// Whether NumLock and CapsLock must be toggled on or off for the hotkey
+	/// to trigger, either by default.
+	///
+	/// Hotkeys with the same keys but lock requirements that can't hold at
+	/// once, e.g. `Numpad5` with NumLock off and with NumLock on, can both be
+	/// registered. Give them distinct ids with [`HotKey::with_id`] to tell
+	/// their events apart. A lock forbidden by [`HotKeyOptions::forbid_mods`]
+	/// is required off.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11)**: The key is only grabbed in the lock states the
+	///   hotkey requires, so the other presses reach the focused window.
+	/// - **Windows**: Hotkeys requiring a lock state are matched by a
+	///   low-level keyboard hook instead of `RegisterHotKey`, like
+	///   non-consuming hotkeys, so that the key is only swallowed in that state.
+	/// - **macOS**: NumLock can't be required, and hotkeys with the same keys
+	///   can't be registered together whatever their lock requirements.
+	pub require_locks:LockState,
+	/// The keyboards whose presses trigger the hotkey, any by default. E.g. a
+	/// foot pedal enumerating as a keyboard can trigger an action without the
+	/// same key of the main keyboard doing it, see
+	/// [`GlobalHotKeyManager::keyboard_devices`](crate::GlobalHotKeyManager::keyboard_devices).
+	///
+	/// Only backends reading the input devices themselves can tell them
+	/// apart, see [`Capabilities::device_filter`](crate::Capabilities::device_filter).
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11) / macOS**: Unsupported, key grabs and system hotkeys
+	///   don't tell which device a press came from.
+	/// - **Windows**: Only with [`WindowsBackend::RawInput`].
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub device:Option<DeviceFilter>,
+	// Set by `GlobalHotKeyManager::register_tap_hold`.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub(crate) tap_hold:Option<TapHold>,
+	// Set by `HotKeyOptions::gate`, never serialized.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub(crate) gate:Option<Gate>,
+}
+
+impl Default for HotKeyOptions {
+	fn default() -> Self {
+		Self {
+			consume:true,
+			forbid_mods:Modifiers::empty(),
+			require_locks:LockState::EITHER,
+			device:None,
+			tap_hold:None,
+			gate:None,
+		}
+	}
+}
+
+impl HotKeyOptions {
+	/// Creates the default options, which are the ones used by
+	/// [`GlobalHotKeyManager::register`](crate::GlobalHotKeyManager::register).
+	pub fn new() -> Self { Self::default() }
+
+	/// Sets [`HotKeyOptions::consume`].
+	pub fn consume(mut self, consume:bool) -> Self {
+		self.consume = consume;
+
+		self
+	}
+
+	/// Sets [`HotKeyOptions::forbid_mods`].
+	pub fn forbid_mods(mut self, mods:Modifiers) -> Self {
+		self.forbid_mods = mods;
+
+		self
+	}
+
+	/// Sets [`HotKeyOptions::require_locks`].
+	pub fn require_locks(mut self, locks:LockState) -> Self {
+		self.require_locks = locks;
+
+		self
+	}
+
+	/// Sets [`HotKeyOptions::device`].
+	pub fn device(mut self, filter:DeviceFilter) -> Self {
+		self.device = Some(filter);
+
+		self
+	}
+
+	/// Makes the hotkey only fire while `gate` returns `true`, e.g. while a
+	/// window of the application is focused, without registering and
+	/// unregistering it.
+	///
+	/// The gate is called for every press of the hotkey right before its event
+	/// is emitted, and the event is dropped when it returns `false`. The
+	/// release follows its press, so every emitted press gets its release even
+	/// if the gate closed meanwhile. Options with a gate are only equal to the
+	/// ones holding the same gate, and it isn't serialized.
+	///
+	/// The gate runs on the thread delivering the events, so it must return
+	/// quickly: the keyboard of the whole desktop may wait for it. It must not
+	/// call the manager, which waits for that thread on some platforms. A
+	/// panicking gate is logged and counts as `false`.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11)**: The key of a consuming hotkey is grabbed
+	///   synchronously like with [`HotKeyOptions::consume`] off, so the presses
+	///   the gate drops are replayed to the focused window, with the same delay.
+	///   Hotkeys sharing a grab must all have a gate or none.
+	/// - **Windows / macOS**: The presses the gate drops are still swallowed by
+	///   consuming hotkeys.
+	pub fn gate<F:Fn() -> bool + Send + Sync + 'static>(mut self, gate:F) -> Self {
+		self.gate = Some(Gate::new(gate));
+
+		self
+	}
+
+	/// Whether the key can be grabbed asynchronously, without deciding on each
+	/// press whether it's handed back to the focused window.
+	#[cfg_attr(
+		not(any(
+			target_os = "linux",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "openbsd",
+			target_os = "netbsd"
+		)),
+		allow(dead_code)
+	)]
+	pub(crate) fn grab_async(&self) -> bool { self.consume && self.gate.is_none() }
+
+	/// The lock state the hotkey requires, counting the locks it forbids as
+	/// required off.
+	pub(crate) fn lock_state(&self) -> LockState {
+		let off = |lock, toggle| {
+			match self.forbid_mods.contains(lock) {
+				true => LockToggle::Off,
+				false => toggle,
+			}
+		};
+
+		LockState {
+			num_lock:off(Modifiers::NUM_LOCK, self.require_locks.num_lock),
+			caps_lock:off(Modifiers::CAPS_LOCK, self.require_locks.caps_lock),
+		}
+	}
+
+	/// The modifiers the hotkey must not be pressed with, including the locks
+	/// it requires off.
+	pub(crate) fn forbidden(&self) -> Modifiers {
+		canonical::fold(self.forbid_mods) | self.lock_state().toggled(LockToggle::Off)
+	}
+
+	/// The locks that must be toggled on for the hotkey to trigger.
+	pub(crate) fn required_locks(&self) -> Modifiers { self.lock_state().toggled(LockToggle::On) }
+
+	/// Options registering `tap` as a tap-hold hotkey, its key is grabbed
+	/// without swallowing it so other keys can be handed back.
+	pub(crate) fn tap_hold(tap_hold:TapHold) -> Self {
+		Self { consume:false, tap_hold:Some(tap_hold), ..Self::default() }
+	}
+
+	/// The hotkeys registering `hotkey` with these options claims, the
+	/// combinations of a tap-hold hotkey belong to its manager too.
+	pub(crate) fn claims(&self, hotkey:&HotKey) -> Vec<HotKey> {
+		let hold_with = self.tap_hold.iter().flat_map(|tap_hold| tap_hold.hold_with.iter());
+
+		std::iter::once(*hotkey).chain(hold_with.copied()).collect()
+	}
+
+	/// Fails if the options can't apply to `hotkey` on any platform.
+	pub(crate) fn check(&self, hotkey:&HotKey) -> crate::Result<()> {
+		let unsupported = self.forbid_mods.difference(FORBIDDABLE);
+
+		if !unsupported.is_empty() {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"{:?} can't be forbidden",
+				unsupported
+			)));
+		}
+
+		if canonical::fold(self.forbid_mods).intersects(canonical::fold(hotkey.mods)) {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` can't forbid the modifiers it's made of",
+				hotkey
+			)));
+		}
+
+		let contradicting = self.forbid_mods & self.require_locks.toggled(LockToggle::On);
+
+		if !contradicting.is_empty() {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` can't both forbid and require {:?}",
+				hotkey, contradicting
+			)));
+		}
+
+		if let Some(tap_hold) = &self.tap_hold {
+			tap_hold.check(hotkey)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// The modifiers [`HotKeyOptions::forbid_mods`] accepts.
+const FORBIDDABLE:Modifiers = Modifiers::SHIFT
+	.union(Modifiers::CONTROL)
+	.union(Modifiers::ALT)
+	.union(Modifiers::SUPER)
+	.union(Modifiers::META)
+	.union(Modifiers::CAPS_LOCK)
+	.union(Modifiers::NUM_LOCK);
+
+/// Whether a lock has to be toggled on or off for a hotkey to trigger, see
+/// [`LockState`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LockToggle {
+	/// The hotkey triggers whether the lock is on or off.
+	#[default]
+	Either,
+	On,
+	Off,
+}
+
+impl LockToggle {
+	fn overlaps(self, other:LockToggle) -> bool {
+		!matches!((self, other), (Self::On, Self::Off) | (Self::Off, Self::On))
+	}
+}
+
+/// The state of the locks a hotkey requires to trigger, see
+/// [`HotKeyOptions::require_locks`].
+///
+/// ```
+/// use global_hotkey::{HotKeyOptions, LockState, LockToggle};
+///
+/// // Only with NumLock off, whatever the state of CapsLock.
+/// let navigation = LockState::new(LockToggle::Off, LockToggle::Either);
+///
+/// let options = HotKeyOptions::new().require_locks(navigation);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LockState {
+	pub num_lock:LockToggle,
+	pub caps_lock:LockToggle,
+}
+
+impl LockState {
+	/// Triggers whatever the state of the locks, the default.
+	pub const EITHER:Self = Self { num_lock:LockToggle::Either, caps_lock:LockToggle::Either };
+
+	pub fn new(num_lock:LockToggle, caps_lock:LockToggle) -> Self { Self { num_lock, caps_lock } }
+
+	/// Returns `true` if no lock state is required.
+	pub fn is_either(&self) -> bool { *self == Self::EITHER }
+
+	/// Returns `true` if a state of the locks satisfies both requirements, a
+	/// press of the same keys would trigger both hotkeys then.
+	pub fn overlaps(&self, other:&LockState) -> bool {
+		self.num_lock.overlaps(other.num_lock) && self.caps_lock.overlaps(other.caps_lock)
+	}
+
+	/// The locks required to be in the `toggle` state, as
+	/// [`Modifiers::NUM_LOCK`] and [`Modifiers::CAPS_LOCK`].
+	pub(crate) fn toggled(&self, toggle:LockToggle) -> Modifiers {
+		let mut locks = Modifiers::empty();
+
+		if self.num_lock == toggle {
+			locks |= Modifiers::NUM_LOCK;
+		}
+
+		if self.caps_lock == toggle {
+			locks |= Modifiers::CAPS_LOCK;
+		}
+
+		locks
+	}
+}
+
+/// Options controlling how a manager is created, see
+/// [`GlobalHotKeyManager::with_options`](crate::GlobalHotKeyManager::with_options).
+///
+/// Options that don't apply to the current platform are ignored.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobalHotKeyManagerOptions {
+	/// The X11 window the hotkeys are grabbed on instead of the root window of
+	/// the default screen, `None` by default.
+	///
+	/// This is an advanced option requiring knowledge of the X11 window
+	/// hierarchy, e.g. to grab on the root window of another screen or on a
+	/// nested root set up by a compositor. Keys are only grabbed while the
+	/// focus is within the window, which must exist for as long as the
+	/// manager is alive, including when it reconnects to the X server.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Windows / macOS**: Ignored.
+	pub root_window_override:Option<c_ulong>,
+	/// The X11 screen whose root window the hotkeys are grabbed on instead of
+	/// the default screen, `None` by default. Ignored if
+	/// [`GlobalHotKeyManagerOptions::root_window_override`] is set.
+	///
+	/// Screens are the separate X screens of a multi-screen setup, addressed
+	/// as `:0.0`, `:0.1` and so on, each with its own root window that keys
+	/// are only grabbed on while the pointer is on that screen. Monitors
+	/// joined into one logical screen by Xinerama or RandR share a single
+	/// root window and don't need this option. Register the hotkeys with one
+	/// manager per screen to have them on every screen.
+	///
+	/// Connecting fails with [`Error::OsError`](crate::Error::OsError) if the
+	/// X server has no such screen.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Windows / macOS**: Ignored.
+	pub screen_number:Option<i32>,
+	/// Whether the backend stops once no hotkey is left, `false` by default.
+	///
+	/// The backend is only started by the first call registering a hotkey, so
+	/// a manager that's never used doesn't hold a connection or a thread. With
+	/// this option it's also stopped when the last hotkey is unregistered, and
+	/// started again by the next registration, which reports the connection
+	/// failures.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Windows / macOS**: Ignored, the hotkeys don't need a thread of
+	///   their own.
+	pub stop_when_idle:bool,
+	/// How the Windows backend receives keys, [`WindowsBackend::HotKey`] by
+	/// default.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Linux (X11) / macOS**: Ignored.
+	pub windows_backend:WindowsBackend,
+	/// How often the X11 backend reads the key events, `None` for every
+	/// 50 ms.
+	///
+	/// Registering and unregistering hotkeys is answered right away whatever
+	/// the interval, only the events of the keys are delivered up to that late.
+	/// A shorter interval wakes the event thread more often. Creating the
+	/// manager fails with [`Error::UnsupportedOption`](crate::Error::UnsupportedOption)
+	/// if it's zero.
+	///
+	/// ## Platform-specific:
+	///
+	/// - **Windows / macOS**: Ignored, the events are delivered by the event
+	///   loop of the application.
+	pub poll_interval:Option<Duration>,
+}
+
+impl GlobalHotKeyManagerOptions {
+	/// Creates the default options, which are the ones used by
+	/// [`GlobalHotKeyManager::new`](crate::GlobalHotKeyManager::new).
+	pub fn new() -> Self { Self::default() }
+
+	/// Sets [`GlobalHotKeyManagerOptions::root_window_override`].
+	pub fn root_window_override(mut self, window:c_ulong) -> Self {
+		self.root_window_override = Some(window);
+
+		self
+	}
+
+	/// Sets [`GlobalHotKeyManagerOptions::screen_number`].
+	pub fn screen_number(mut self, screen:i32) -> Self {
+		self.screen_number = Some(screen);
+
+		self
+	}
+
+	/// Sets [`GlobalHotKeyManagerOptions::stop_when_idle`].
+	pub fn stop_when_idle(mut self, stop:bool) -> Self {
+		self.stop_when_idle = stop;
+
+		self
+	}
+
+	/// Sets [`GlobalHotKeyManagerOptions::windows_backend`].
+	pub fn windows_backend(mut self, backend:WindowsBackend) -> Self {
+		self.windows_backend = backend;
+
+		self
+	}
+
+	/// Sets [`GlobalHotKeyManagerOptions::poll_interval`].
+	pub fn poll_interval(mut self, interval:Duration) -> Self {
+		self.poll_interval = Some(interval);
+
+		self
+	}
+
+	/// Fails if the options can't apply on any platform.
+	pub(crate) fn check(&self) -> crate::Result<()> {
+		if self.poll_interval == Some(Duration::ZERO) {
+			return Err(crate::Error::UnsupportedOption(
+				"The poll interval can't be zero".to_string(),
+			));
+		}
+
+		Ok(())
+	}
+}
+
+/// How the Windows backend receives keys, see
+/// [`GlobalHotKeyManagerOptions::windows_backend`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WindowsBackend {
+	/// `RegisterHotKey`, with a low-level keyboard hook for the hotkeys it
+	/// can't register, like non-consuming ones.
+	#[default]
+	HotKey,
+	/// Raw Input, which observes the keys of every keyboard without a global
+	/// hook, as some anti-cheat and security software flag hooks.
+	///
+	/// Hotkeys never swallow their key: [`GlobalHotKeyManager::register`](crate::GlobalHotKeyManager::register)
+	/// observes it, and registering with [`HotKeyOptions::consume`] left to
+	/// `true` fails with [`Error::UnsupportedOption`](crate::Error::UnsupportedOption).
+	/// The events tell which keyboard a key came from, so hotkeys can be
+	/// restricted to some with [`HotKeyOptions::device`].
+	///
+	/// Windows delivers Raw Input to a single window of the process, the one
+	/// of the first manager created in this mode, or of another one once it's
+	/// dropped. The thread of that window must keep its message loop running
+	/// for the hotkeys of every manager in this mode.
+	RawInput,
+}
+
+#[test]
+fn test_forbid_mods_check() {
+	use keyboard_types::Code;
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyK);
+
+	assert!(HotKeyOptions::new().check(&hotkey).is_ok());
+
+	let locks = HotKeyOptions::new().forbid_mods(Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK);
+
+	assert!(locks.check(&hotkey).is_ok());
+
+	assert!(HotKeyOptions::new().forbid_mods(Modifiers::SHIFT).check(&hotkey).is_ok());
+
+	// `META` is the same modifier as `SUPER` for every backend.
+	for forbidden in [Modifiers::CONTROL, Modifiers::META, Modifiers::SCROLL_LOCK] {
+		assert!(matches!(
+			HotKeyOptions::new().forbid_mods(forbidden).check(&hotkey),
+			Err(crate::Error::UnsupportedOption(_))
+		));
+	}
+}
+
+#[test]
+fn test_require_locks() {
+	use keyboard_types::Code;
+
+	let hotkey = HotKey::new(None, Code::Numpad5);
+
+	let off = LockState::new(LockToggle::Off, LockToggle::Either);
+
+	let on = LockState::new(LockToggle::On, LockToggle::Either);
+
+	assert!(!off.overlaps(&on));
+
+	assert!(off.overlaps(&LockState::EITHER));
+
+	assert!(on.overlaps(&LockState::new(LockToggle::On, LockToggle::Off)));
+
+	let options = HotKeyOptions::new().require_locks(on).forbid_mods(Modifiers::CAPS_LOCK);
+
+	assert!(options.check(&hotkey).is_ok());
+
+	// A forbidden lock is required off.
+	assert_eq!(options.lock_state(), LockState::new(LockToggle::On, LockToggle::Off));
+
+	assert_eq!(options.required_locks(), Modifiers::NUM_LOCK);
+
+	assert_eq!(options.forbidden(), Modifiers::CAPS_LOCK);
+
+	assert!(matches!(
+		HotKeyOptions::new().require_locks(on).forbid_mods(Modifiers::NUM_LOCK).check(&hotkey),
+		Err(crate::Error::UnsupportedOption(_))
+	));
+}
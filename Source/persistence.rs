@@ -0,0 +1,218 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Saving and restoring the hotkeys of a [`GlobalHotKeyManager`](crate::GlobalHotKeyManager),
+//! see [`GlobalHotKeyManager::export_set`](crate::GlobalHotKeyManager::export_set).
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HotKeyOptions, hotkey::HotKey};
+
+/// A hotkey as it is persisted, along with the data applications usually keep
+/// next to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Registration {
+	/// The id events are emitted with, which may have been customized with
+	/// [`HotKey::with_id`].
+	pub id:u32,
+	/// The hotkey, stored as an accelerator string such as `"shift+alt+KeyQ"`.
+	pub hotkey:HotKey,
+	/// A user facing name for the action behind the hotkey.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label:Option<String>,
+	/// Disabled hotkeys are kept in the set but not registered.
+	#[serde(default = "enabled_by_default")]
+	pub enabled:bool,
+	/// The options the hotkey is registered with.
+	#[serde(default)]
+	pub options:HotKeyOptions,
+}
+
+fn enabled_by_default() -> bool { true }
+
+impl Registration {
+	/// Creates an enabled registration of `hotkey` with the default options.
+	pub fn new(hotkey:HotKey) -> Self {
+		Self { id:hotkey.id(), hotkey, label:None, enabled:true, options:HotKeyOptions::default() }
+	}
+
+	/// Sets [`Registration::label`].
+	pub fn label<S:Into<String>>(mut self, label:S) -> Self {
+		self.label = Some(label.into());
+
+		self
+	}
+
+	/// Sets [`Registration::enabled`].
+	pub fn enabled(mut self, enabled:bool) -> Self {
+		self.enabled = enabled;
+
+		self
+	}
+
+	/// Sets [`Registration::options`].
+	pub fn options(mut self, options:HotKeyOptions) -> Self {
+		self.options = options;
+
+		self
+	}
+
+	/// Returns the hotkey carrying [`Registration::id`].
+	pub fn to_hotkey(&self) -> HotKey { self.hotkey.with_id(self.id) }
+}
+
+// The accelerator string doesn't carry the id, which is compared through `id` instead.
+impl PartialEq for Registration {
+	fn eq(&self, other:&Self) -> bool {
+		self.to_hotkey() == other.to_hotkey()
+			&& self.label == other.label
+			&& self.enabled == other.enabled
+			&& self.options == other.options
+	}
+}
+
+impl Eq for Registration {}
+
+/// A serializable snapshot of the hotkeys registered with a manager, obtained
+/// with [`GlobalHotKeyManager::export_set`](crate::GlobalHotKeyManager::export_set)
+/// and restored with [`GlobalHotKeyManager::apply_set`](crate::GlobalHotKeyManager::apply_set).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrationSet {
+	/// The registrations, ordered by id.
+	#[serde(default)]
+	pub registrations:Vec<Registration>,
+}
+
+/// What the manager registered, used to diff against a [`RegistrationSet`].
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+	registered:Mutex<BTreeMap<u32, (HotKey, HotKeyOptions)>>,
+	// The last applied set, for the labels and the disabled hotkeys.
+	applied:Mutex<BTreeMap<u32, Registration>>,
+}
+
+impl Registry {
+	pub(crate) fn remember(&self, hotkeys:&[HotKey], options:&HotKeyOptions) {
+		let mut registered = self.registered.lock().unwrap();
+
+		for hotkey in hotkeys {
+			registered.insert(hotkey.id(), (*hotkey, options.clone()));
+		}
+	}
+
+	pub(crate) fn forget(&self, hotkeys:&[HotKey]) {
+		let mut registered = self.registered.lock().unwrap();
+
+		for hotkey in hotkeys {
+			registered.remove(&hotkey.id());
+		}
+	}
+
+	pub(crate) fn export(&self) -> RegistrationSet {
+		let registered = self.registered.lock().unwrap();
+
+		let applied = self.applied.lock().unwrap();
+
+		let mut registrations = registered
+			.iter()
+			.map(|(id, (hotkey, options))| {
+				Registration {
+					id:*id,
+					hotkey:*hotkey,
+					label:applied.get(id).and_then(|r| r.label.clone()),
+					enabled:true,
+					options:options.clone(),
+				}
+			})
+			.chain(
+				applied.values().filter(|r| !r.enabled && !registered.contains_key(&r.id)).cloned(),
+			)
+			.collect::<Vec<_>>();
+
+		registrations.sort_by_key(|r| r.id);
+
+		RegistrationSet { registrations }
+	}
+
+	/// Returns the hotkeys to unregister and the ones to register for the
+	/// manager to match `set`, unchanged ones are left alone.
+	pub(crate) fn diff(
+		&self,
+		set:&RegistrationSet,
+	) -> (Vec<HotKey>, Vec<(HotKey, HotKeyOptions)>) {
+		let registered = self.registered.lock().unwrap();
+
+		let wanted = set
+			.registrations
+			.iter()
+			.filter(|r| r.enabled)
+			.map(|r| (r.id, (r.to_hotkey(), r.options.clone())))
+			.collect::<BTreeMap<_, _>>();
+
+		let removed = registered
+			.iter()
+			.filter(|(id, current)| wanted.get(id) != Some(current))
+			.map(|(_, (hotkey, _))| *hotkey)
+			.collect();
+
+		let added = wanted
+			.into_iter()
+			.filter(|(id, w)| registered.get(id) != Some(w))
+			.map(|(_, w)| w)
+			.collect();
+
+		(removed, added)
+	}
+
+	pub(crate) fn set_applied(&self, set:&RegistrationSet) {
+		*self.applied.lock().unwrap() =
+			set.registrations.iter().map(|r| (r.id, r.clone())).collect();
+	}
+}
+
+#[test]
+fn test_registration_set() {
+	use crate::hotkey::{Code, ModifierSide, Modifiers};
+
+	let mut set = RegistrationSet {
+		registrations:vec![
+			Registration::new(HotKey::new(Some(Modifiers::SHIFT), Code::KeyS).with_id(7))
+				.label("Save all"),
+			Registration::new(
+				HotKey::new(None, Code::Space).with_side(Modifiers::SUPER, ModifierSide::Right),
+			)
+			.options(HotKeyOptions::new().consume(false)),
+			Registration::new(HotKey::new(Some(Modifiers::ALT), Code::F4)).enabled(false),
+		],
+	};
+
+	// Exports are ordered by id.
+	set.registrations.sort_by_key(|r| r.id);
+
+	let toml = toml::to_string(&set).unwrap();
+
+	assert_eq!(toml::from_str::<RegistrationSet>(&toml).unwrap(), set);
+
+	let registry = Registry::default();
+
+	let (removed, added) = registry.diff(&set);
+
+	assert!(removed.is_empty());
+
+	// The disabled hotkey isn't registered.
+	assert_eq!(added.len(), 2);
+
+	for (hotkey, options) in &added {
+		registry.remember(&[*hotkey], options);
+	}
+
+	registry.set_applied(&set);
+
+	// Applying the same set again has nothing to do.
+	assert_eq!(registry.diff(&set), (Vec::new(), Vec::new()));
+
+	assert_eq!(registry.export(), set);
+}
@@ -0,0 +1,42 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! macOS specific APIs.
+
+/// Emitted by the handler set with
+/// [`GlobalHotKeyManager::set_secure_input_handler`](crate::GlobalHotKeyManager::set_secure_input_handler)
+/// when secure keyboard entry is turned on or off.
+///
+/// While secure input is enabled, hotkeys relying on an event tap (sided
+/// modifiers and media keys) stop receiving events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecureInputEvent {
+	/// Whether secure input is now enabled.
+	pub enabled:bool,
+	/// The process that enabled secure input, if it could be determined.
+	pub pid:Option<i32>,
+}
+
+/// Returns `true` if the application was granted the Input Monitoring
+/// permission, required by hotkeys registered with
+/// [`HotKeyOptions::consume`](crate::HotKeyOptions::consume) set to `false`.
+pub fn has_input_monitoring_permission() -> bool {
+	crate::platform_impl::has_input_monitoring_permission()
+}
+
+/// Prompts the user to grant the Input Monitoring permission if it wasn't
+/// decided yet, returns `true` if it is granted.
+///
+/// The prompt is only shown once, after that the user has to change it in
+/// System Settings and the application usually has to be restarted.
+pub fn request_input_monitoring_permission() -> bool {
+	crate::platform_impl::request_input_monitoring_permission()
+}
+
+/// Returns `true` if a process currently has secure keyboard entry enabled,
+/// for example because a password field is focused.
+pub fn is_secure_input_enabled() -> bool { crate::platform_impl::is_secure_input_enabled() }
+
+/// Returns the PID of the process that enabled secure keyboard entry, if any.
+pub fn secure_input_pid() -> Option<i32> { crate::platform_impl::secure_input_pid() }
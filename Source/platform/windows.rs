@@ -0,0 +1,27 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Windows specific APIs.
+
+/// Dispatches the messages queued for the current thread without blocking,
+/// returns `false` once `WM_QUIT` was received.
+///
+/// Hotkey events are delivered through the message queue of the thread that
+/// created the [`GlobalHotKeyManager`](crate::GlobalHotKeyManager). Event loops
+/// such as winit or tao dispatch them already, threads without one call this
+/// periodically instead.
+pub fn pump_messages() -> bool { crate::platform_impl::pump_messages() }
+
+/// Synthesizes a press and release of `hotkey` with `SendInput`, along with
+/// its modifiers, e.g. to check in CI that a registered hotkey fires.
+///
+/// Sided modifiers are pressed on their side, the others on the left. The
+/// events go through the input queue of the interactive desktop like real key
+/// presses, the focused application receives the ones no hotkey consumes.
+///
+/// Only available with the `windows-integration-test` feature.
+#[cfg(feature = "windows-integration-test")]
+pub fn synthesize_key_press(hotkey:&crate::hotkey::HotKey) -> crate::Result<()> {
+	crate::platform_impl::synthesize_key_press(hotkey)
+}
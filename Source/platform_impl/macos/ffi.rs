@@ -5,10 +5,11 @@
 
 // taken from https://github.com/wusyong/carbon-bindgen/blob/467fca5d71047050b632fbdfb41b1f14575a8499/bindings.rs
 
-use std::ffi::{c_long, c_void};
+use std::ffi::{c_char, c_long, c_void};
 
 use objc2::encode::{Encode, Encoding, RefEncode};
 
+pub type Boolean = ::std::os::raw::c_uchar;
 pub type UInt32 = ::std::os::raw::c_uint;
 pub type SInt32 = ::std::os::raw::c_int;
 pub type OSStatus = SInt32;
@@ -73,6 +74,10 @@ pub const kEventHotKeyReleased:_bindgen_ty_1981 = 6;
 pub type _bindgen_ty_1 = ::std::os::raw::c_uint;
 pub const noErr:_bindgen_ty_1 = 0;
 
+// [Ref](https://github.com/phracker/MacOSX-SDKs/blob/ef9fe35d5691b6dd383c8c46d867a499817a01b6/MacOSX10.15.sdk/System/Library/Frameworks/Carbon.framework/Versions/A/Frameworks/HIToolbox.framework/Versions/A/Headers/CarbonEvents.h)
+pub const eventHotKeyExistsErr:OSStatus = -9878;
+pub const eventHotKeyInvalidErr:OSStatus = -9879;
+
 #[repr(C, packed(2))]
 #[derive(Debug, Copy, Clone)]
 pub struct EventHotKeyID {
@@ -124,6 +129,8 @@ extern {
 	) -> OSStatus;
 
 	pub fn UnregisterEventHotKey(inHotKey:EventHotKeyRef) -> OSStatus;
+
+	pub fn IsSecureEventInputEnabled() -> Boolean;
 }
 
 // Core Graphics
@@ -214,7 +221,7 @@ unsafe impl RefEncode for CGEvent {
 }
 
 pub type CGEventTapProxy = *const c_void;
-type CGEventTapCallBack = unsafe extern fn(
+pub type CGEventTapCallBack = unsafe extern fn(
 	proxy:CGEventTapProxy,
 	etype:CGEventType,
 	event:CGEventRef,
@@ -233,8 +240,48 @@ extern {
 	) -> CFMachPortRef;
 
 	pub fn CGEventTapEnable(tap:CFMachPortRef, enable:bool);
+
+	pub fn CGEventTapIsEnabled(tap:CFMachPortRef) -> bool;
+
+	pub fn CGEventGetIntegerValueField(event:CGEventRef, field:CGEventField) -> i64;
+
+	pub fn CGEventGetFlags(event:CGEventRef) -> CGEventFlags;
+
+	pub fn CGEventSourceFlagsState(state_id:CGEventSourceStateID) -> CGEventFlags;
+
+	pub fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+
+	pub fn CGPreflightListenEventAccess() -> bool;
+
+	pub fn CGRequestListenEventAccess() -> bool;
 }
 
+pub type CGEventField = u32;
+pub const kCGKeyboardEventAutorepeat:CGEventField = 8;
+pub const kCGKeyboardEventKeycode:CGEventField = 9;
+
+pub type CGEventSourceStateID = i32;
+pub const kCGEventSourceStateCombinedSessionState:CGEventSourceStateID = 0;
+
+// [Ref](https://github.com/phracker/MacOSX-SDKs/blob/ef9fe35d5691b6dd383c8c46d867a499817a01b6/MacOSX10.15.sdk/System/Library/Frameworks/CoreGraphics.framework/Versions/A/Headers/CGEventTypes.h)
+pub type CGEventFlags = u64;
+pub const kCGEventFlagMaskAlphaShift:CGEventFlags = 0x00010000;
+pub const kCGEventFlagMaskShift:CGEventFlags = 0x00020000;
+pub const kCGEventFlagMaskControl:CGEventFlags = 0x00040000;
+pub const kCGEventFlagMaskAlternate:CGEventFlags = 0x00080000;
+pub const kCGEventFlagMaskCommand:CGEventFlags = 0x00100000;
+
+// Device dependent flags, which tell which of the two modifier keys is down.
+// [Ref](https://github.com/phracker/MacOSX-SDKs/blob/ef9fe35d5691b6dd383c8c46d867a499817a01b6/MacOSX10.15.sdk/System/Library/Frameworks/IOKit.framework/Versions/A/Headers/hidsystem/IOLLEvent.h)
+pub const NX_DEVICELCTLKEYMASK:CGEventFlags = 0x00000001;
+pub const NX_DEVICELSHIFTKEYMASK:CGEventFlags = 0x00000002;
+pub const NX_DEVICERSHIFTKEYMASK:CGEventFlags = 0x00000004;
+pub const NX_DEVICELCMDKEYMASK:CGEventFlags = 0x00000008;
+pub const NX_DEVICERCMDKEYMASK:CGEventFlags = 0x00000010;
+pub const NX_DEVICELALTKEYMASK:CGEventFlags = 0x00000020;
+pub const NX_DEVICERALTKEYMASK:CGEventFlags = 0x00000040;
+pub const NX_DEVICERCTLKEYMASK:CGEventFlags = 0x00002000;
+
 // Core Foundation
 
 pub enum CFAllocator {}
@@ -254,7 +301,17 @@ pub type CFStringRef = *const CFString;
 pub enum CFMachPort {}
 pub type CFMachPortRef = *mut CFMachPort;
 
+pub enum CFDictionary {}
+pub type CFDictionaryRef = *const CFDictionary;
+pub enum CFNumber {}
+pub type CFNumberRef = *const CFNumber;
+
 pub type CFIndex = c_long;
+pub type CFNumberType = CFIndex;
+pub type CFStringEncoding = u32;
+
+pub const kCFNumberSInt32Type:CFNumberType = 3;
+pub const kCFStringEncodingUTF8:CFStringEncoding = 0x08000100;
 
 #[link(name = "CoreFoundation", kind = "framework")]
 extern {
@@ -277,4 +334,41 @@ extern {
 	pub fn CFRunLoopRemoveSource(rl:CFRunLoopRef, source:CFRunLoopSourceRef, mode:CFRunLoopMode);
 
 	pub fn CFRelease(cftype:*const c_void);
+
+	pub fn CFStringCreateWithCString(
+		alloc:CFAllocatorRef,
+		c_str:*const c_char,
+		encoding:CFStringEncoding,
+	) -> CFStringRef;
+
+	pub fn CFDictionaryGetValue(dict:CFDictionaryRef, key:*const c_void) -> *const c_void;
+
+	pub fn CFNumberGetValue(number:CFNumberRef, the_type:CFNumberType, value_ptr:*mut c_void)
+	-> bool;
+}
+
+#[repr(C)]
+pub struct dispatch_queue_s {
+	_unused:[u8; 0],
+}
+pub type dispatch_function_t = unsafe extern fn(context:*mut c_void);
+
+// libdispatch and pthread are part of libSystem, linked by default.
+extern {
+	// `dispatch_get_main_queue()` is a macro returning this queue.
+	pub static _dispatch_main_q: dispatch_queue_s;
+
+	pub fn dispatch_sync_f(
+		queue:*const dispatch_queue_s,
+		context:*mut c_void,
+		work:dispatch_function_t,
+	);
+
+	pub fn dispatch_async_f(
+		queue:*const dispatch_queue_s,
+		context:*mut c_void,
+		work:dispatch_function_t,
+	);
+
+	pub fn pthread_main_np() -> ::std::os::raw::c_int;
 }
@@ -1,47 +1,84 @@
 use keyboard_types::{Code, Modifiers};
-use objc2::{msg_send, rc::Retained, ClassType};
+use block2::RcBlock;
+use objc2::{class, msg_send, rc::Retained, runtime::AnyObject, ClassType};
 use objc2_app_kit::{NSEvent, NSEventModifierFlags, NSEventSubtype, NSEventType};
 use std::{
-    collections::{BTreeMap, HashSet},
-    ffi::c_void,
-    ptr,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    ffi::{c_char, c_void},
+    ptr::{self, NonNull},
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use crate::{
-    hotkey::HotKey,
+    hotkey::{HotKey, ModifierSide},
+    main_thread::{Poster, Task},
+    platform::macos::SecureInputEvent,
+    platform_impl::{register_until_failure, reregister},
     platform_impl::platform::ffi::{
         kCFAllocatorDefault, kCFRunLoopCommonModes, CFMachPortCreateRunLoopSource,
-        CFRunLoopAddSource, CFRunLoopGetMain, CGEventMask, CGEventRef, CGEventTapCreate,
-        CGEventTapEnable, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
-        CGEventTapProxy, CGEventType,
+        CFRunLoopAddSource, CFRunLoopGetMain, CGEventMask, CGEventRef, CGEventTapCallBack,
+        CGEventTapCreate, CGEventTapEnable, CGEventTapLocation, CGEventTapOptions,
+        CGEventTapPlacement, CGEventTapProxy, CGEventType,
     },
-    CGEventMaskBit, GlobalHotKeyEvent,
+    Backend, CGEventMaskBit, Capabilities, ExtensionMessage, GlobalHotKeyEvent,
+    GlobalHotKeyManagerOptions, HotKeyOptions, HotKeyStatus, LockToggle, ManagerStatus,
+    RecordingHandle, RegisterFailureReason,
 };
 
 use self::ffi::{
-    kEventClassKeyboard, kEventHotKeyPressed, kEventHotKeyReleased, kEventParamDirectObject, noErr,
-    typeEventHotKeyID, CFMachPortInvalidate, CFMachPortRef, CFRelease, CFRunLoopRemoveSource,
-    CFRunLoopSourceRef, EventHandlerCallRef, EventHandlerRef, EventHotKeyID, EventHotKeyRef,
-    EventRef, EventTypeSpec, GetApplicationEventTarget, GetEventKind, GetEventParameter,
-    InstallEventHandler, OSStatus, RegisterEventHotKey, RemoveEventHandler, UnregisterEventHotKey,
+    eventHotKeyExistsErr, eventHotKeyInvalidErr, kCFNumberSInt32Type, kCFStringEncodingUTF8,
+    kCGEventFlagMaskAlphaShift, kCGEventFlagMaskAlternate, kCGEventFlagMaskCommand,
+    kCGEventFlagMaskControl, kCGEventFlagMaskShift, kCGEventSourceStateCombinedSessionState,
+    kCGKeyboardEventAutorepeat, kCGKeyboardEventKeycode, kEventClassKeyboard, kEventHotKeyPressed,
+    kEventHotKeyReleased, kEventParamDirectObject, noErr, typeEventHotKeyID, CFDictionaryGetValue,
+    CFMachPortInvalidate, CFMachPortRef, CFNumberGetValue, CFNumberRef, CFRelease,
+    CFRunLoopRemoveSource, CFRunLoopSourceRef, CFStringCreateWithCString, CGEventFlags,
+    CGEventGetFlags, CGEventGetIntegerValueField, CGEventSourceFlagsState, CGEventTapIsEnabled,
+    CGPreflightListenEventAccess, CGRequestListenEventAccess, CGSessionCopyCurrentDictionary,
+    EventHandlerCallRef, EventHandlerRef, EventHotKeyID, EventHotKeyRef, EventRef, EventTypeSpec,
+    GetApplicationEventTarget, GetEventKind, GetEventParameter, InstallEventHandler,
+    IsSecureEventInputEnabled, NX_DEVICELALTKEYMASK, NX_DEVICELCMDKEYMASK, NX_DEVICELCTLKEYMASK,
+    NX_DEVICELSHIFTKEYMASK, NX_DEVICERALTKEYMASK, NX_DEVICERCMDKEYMASK, NX_DEVICERCTLKEYMASK,
+    NX_DEVICERSHIFTKEYMASK, OSStatus, RegisterEventHotKey, RemoveEventHandler,
+    UnregisterEventHotKey, _dispatch_main_q, dispatch_async_f, dispatch_sync_f, pthread_main_np,
 };
 
 mod ffi;
 
+pub(crate) const BACKEND: Backend = Backend::MacOS;
+
 pub struct GlobalHotKeyManager {
     event_handler_ptr: EventHandlerRef,
-    hotkeys: Mutex<BTreeMap<u32, HotKeyWrapper>>,
+    hotkeys: Arc<Mutex<BTreeMap<u32, HotKeyWrapper>>>,
+    // Carbon hotkeys that failed to register again when revalidating.
+    lost_hotkeys: Arc<Mutex<Vec<HotKey>>>,
+    wake_observers: Vec<Retained<AnyObject>>,
     event_tap: Mutex<Option<CFMachPortRef>>,
     event_tap_source: Mutex<Option<CFRunLoopSourceRef>>,
     media_hotkeys: Arc<Mutex<HashSet<HotKey>>>,
+    // Carbon hotkeys can't tell left and right modifiers apart, so hotkeys
+    // requiring a specific side are matched in a keyboard event tap instead.
+    //                           id    hotkey, pressed
+    sided_hotkeys: Arc<Mutex<BTreeMap<u32, (HotKey, bool)>>>,
+    key_event_tap: Mutex<Option<(CFMachPortRef, CFRunLoopSourceRef)>>,
+    // Non-consuming hotkeys, matched by a global and a local `NSEvent` monitor.
+    //                                id    hotkey, pressed
+    passthrough_hotkeys: Arc<Mutex<BTreeMap<u32, (HotKey, bool)>>>,
+    key_monitors: Mutex<Option<[Retained<AnyObject>; 2]>>,
+    // Hotkeys unregistered from the OS by `suspend`, `None` while active.
+    suspended: Arc<Mutex<Option<Vec<(HotKey, HotKeyOptions)>>>>,
+    // Dropping the sender stops the secure input watcher thread.
+    secure_input_watcher: Mutex<Option<crossbeam_channel::Sender<()>>>,
 }
 
+// The Carbon and AppKit calls are made on the main thread through
+// `run_on_main_thread`, the rest of the state is behind mutexes.
 unsafe impl Send for GlobalHotKeyManager {}
 unsafe impl Sync for GlobalHotKeyManager {}
 
 impl GlobalHotKeyManager {
-    pub fn new() -> crate::Result<Self> {
+    pub fn new(_options: &GlobalHotKeyManagerOptions) -> crate::Result<Self> {
         let pressed_event_type = EventTypeSpec {
             eventClass: kEventClassKeyboard,
             eventKind: kEventHotKeyPressed,
@@ -55,106 +92,444 @@ impl GlobalHotKeyManager {
         let ptr = unsafe {
             let mut handler_ref: EventHandlerRef = std::mem::zeroed();
 
-            let result = InstallEventHandler(
-                GetApplicationEventTarget(),
-                Some(hotkey_handler),
-                2,
-                event_types.as_ptr(),
-                std::ptr::null_mut(),
-                &mut handler_ref,
-            );
+            let result = run_on_main_thread(|| {
+                InstallEventHandler(
+                    GetApplicationEventTarget(),
+                    Some(hotkey_handler),
+                    2,
+                    event_types.as_ptr(),
+                    std::ptr::null_mut(),
+                    &mut handler_ref,
+                )
+            });
 
             if result != noErr as _ {
-                return Err(crate::Error::OsError(std::io::Error::last_os_error()));
+                return Err(crate::Error::os(
+                    result as i64,
+                    format!(
+                        "Failed to install the hotkey event handler: {}",
+                        describe_os_status(result)
+                    ),
+                ));
             }
 
             handler_ref
         };
 
+        let hotkeys = Arc::new(Mutex::new(BTreeMap::new()));
+        let lost_hotkeys = Arc::new(Mutex::new(Vec::new()));
+        let suspended = Arc::new(Mutex::new(None));
+        let wake_observers = run_on_main_thread(|| unsafe {
+            add_wake_observers(&hotkeys, &lost_hotkeys, &suspended)
+        });
+
         Ok(Self {
             event_handler_ptr: ptr,
-            hotkeys: Mutex::new(BTreeMap::new()),
+            hotkeys,
+            lost_hotkeys,
+            wake_observers,
             event_tap: Mutex::new(None),
             event_tap_source: Mutex::new(None),
             media_hotkeys: Arc::new(Mutex::new(HashSet::new())),
+            sided_hotkeys: Arc::new(Mutex::new(BTreeMap::new())),
+            key_event_tap: Mutex::new(None),
+            passthrough_hotkeys: Arc::new(Mutex::new(BTreeMap::new())),
+            key_monitors: Mutex::new(None),
+            suspended,
+            secure_input_watcher: Mutex::new(None),
         })
     }
 
     pub fn register(&self, hotkey: HotKey) -> crate::Result<()> {
-        let mut mods: u32 = 0;
-        if hotkey.mods.contains(Modifiers::SHIFT) {
-            mods |= 512;
-        }
-        if hotkey.mods.intersects(Modifiers::SUPER | Modifiers::META) {
-            mods |= 256;
-        }
-        if hotkey.mods.contains(Modifiers::ALT) {
-            mods |= 2048;
-        }
-        if hotkey.mods.contains(Modifiers::CONTROL) {
-            mods |= 4096;
-        }
-
-        if let Some(scan_code) = key_to_scancode(hotkey.key) {
-            let hotkey_id = EventHotKeyID {
-                id: hotkey.id(),
-                signature: {
-                    let mut res: u32 = 0;
-                    // can't find a resource for "htrs" so we construct it manually
-                    // the construction method below is taken from https://github.com/soffes/HotKey/blob/c13662730cb5bc28de4a799854bbb018a90649bf/Sources/HotKey/HotKeysController.swift#L27
-                    // and confirmed by applying the same method to `kEventParamDragRef` which is equal to `drag` in C
-                    // and converted to `1685217639` by rust-bindgen.
-                    for c in "htrs".chars() {
-                        res = (res << 8) + c as u32;
-                    }
-                    res
-                },
-            };
+        self.register_with_options(hotkey, HotKeyOptions::default())
+    }
 
-            let ptr = unsafe {
-                let mut hotkey_ref: EventHotKeyRef = std::mem::zeroed();
-                let result = RegisterEventHotKey(
-                    scan_code,
-                    mods,
-                    hotkey_id,
-                    GetApplicationEventTarget(),
-                    0,
-                    &mut hotkey_ref,
-                );
-
-                if result != noErr as _ {
-                    return Err(crate::Error::FailedToRegister(format!(
-                        "Unable to register hotkey: {}",
-                        hotkey.key
-                    )));
+    pub fn register_with_options(
+        &self,
+        hotkey: HotKey,
+        options: HotKeyOptions,
+    ) -> crate::Result<()> {
+        if options.tap_hold.is_some() {
+            return Err(crate::Error::UnsupportedOption(
+                "Tap-hold hotkeys aren't supported on macOS".to_string(),
+            ));
+        }
+
+        if options.device.is_some() {
+            return Err(crate::Error::UnsupportedOption(
+                "Hotkeys can't be restricted to a keyboard device on macOS".to_string(),
+            ));
+        }
+
+        if options.forbid_mods.contains(Modifiers::NUM_LOCK)
+            || options.require_locks.num_lock != LockToggle::Either
+        {
+            return Err(crate::Error::UnsupportedOption(
+                "NumLock can't be forbidden or required on macOS, which has no such lock"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(suspended) = self.suspended.lock().unwrap().as_mut() {
+            if suspended.iter().any(|(h, _)| h.overlaps(&hotkey)) {
+                return Err(crate::Error::AlreadyRegistered(hotkey)
+                    .with_conflict(suspended.iter().map(|(h, _)| h)));
+            }
+            restrict(hotkey.id(), &options);
+            suspended.push((hotkey, options));
+            return Ok(());
+        }
+
+        self.register_hotkey(hotkey, &options)?;
+        restrict(hotkey.id(), &options);
+        Ok(())
+    }
+
+    pub fn unregister(&self, hotkey: HotKey) -> crate::Result<()> {
+        self.lost_hotkeys.lock().unwrap().retain(|h| *h != hotkey);
+
+        if let Some(suspended) = self.suspended.lock().unwrap().as_mut() {
+            suspended.retain(|(h, _)| *h != hotkey);
+            restrict(hotkey.id(), &HotKeyOptions::default());
+            return Ok(());
+        }
+
+        self.unregister_hotkey(hotkey)?;
+        restrict(hotkey.id(), &HotKeyOptions::default());
+        Ok(())
+    }
+
+    pub fn validate(&self, hotkey: HotKey) -> crate::Result<()> {
+        // No other application can take the keys of the event taps.
+        let tapped = !hotkey.sides.is_either()
+            || (key_to_scancode(hotkey.key).is_none() && is_media_key(hotkey.key));
+        if tapped {
+            return Ok(());
+        }
+
+        // Both on the main thread, which handles the Carbon events, so none is
+        // sent in between.
+        run_on_main_thread(|| {
+            let ptr = register_event_hotkey(hotkey).map_err(|e| self.with_conflict(e))?;
+            unsafe { UnregisterEventHotKey(ptr) };
+            Ok(())
+        })
+    }
+
+    pub fn register_all_with_options(
+        &self,
+        hotkeys: &[HotKey],
+        options: HotKeyOptions,
+    ) -> Vec<crate::Result<()>> {
+        register_until_failure(hotkeys, |hotkey| {
+            self.register_with_options(hotkey, options.clone())
+        })
+    }
+
+    pub fn register_all_atomic(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
+        for (i, hotkey) in hotkeys.iter().enumerate() {
+            if let Err(e) = self.register(*hotkey) {
+                for hotkey in &hotkeys[..i] {
+                    let _ = self.unregister(*hotkey);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unregister_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
+        for hotkey in hotkeys {
+            self.unregister(*hotkey)?;
+        }
+        Ok(())
+    }
+
+    pub fn clear_all(&self) -> crate::Result<()> {
+        self.lost_hotkeys.lock().unwrap().clear();
+
+        if let Some(suspended) = self.suspended.lock().unwrap().as_mut() {
+            suspended.clear();
+            return Ok(());
+        }
+
+        // Keep going so one failure doesn't leave the other hotkeys registered.
+        let mut result = Ok(());
+        for (hotkey, _) in self.registered_hotkeys() {
+            if let Err(e) = self.unregister_hotkey(hotkey) {
+                if result.is_ok() {
+                    result = Err(e);
                 }
+            }
+        }
+        result
+    }
 
-                hotkey_ref
-            };
+    pub fn suspend(&self) -> crate::Result<()> {
+        let mut suspended = self.suspended.lock().unwrap();
+        if suspended.is_some() {
+            return Ok(());
+        }
 
+        let hotkeys = self.registered_hotkeys();
+        for (hotkey, _) in &hotkeys {
+            self.unregister_hotkey(*hotkey)?;
+        }
+        *suspended = Some(hotkeys);
+
+        Ok(())
+    }
+
+    pub fn resume(&self) -> crate::Result<()> {
+        let Some(hotkeys) = self.suspended.lock().unwrap().take() else {
+            return Ok(());
+        };
+
+        // Keep going so one hotkey taken by another app doesn't disable the rest.
+        let mut result = Ok(());
+        for (hotkey, options) in hotkeys {
+            if let Err(e) = self.register_hotkey(hotkey, &options) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> {
+        let failed = run_on_main_thread(|| {
+            revalidate_event_hotkeys(&self.hotkeys, &self.lost_hotkeys, &self.suspended)
+        });
+
+        // The system disables taps that time out, they only exist while active.
+        let media_tap = *self.event_tap.lock().unwrap();
+        let sided_tap = self.key_event_tap.lock().unwrap().map(|(tap, _)| tap);
+        for tap in [media_tap, sided_tap].into_iter().flatten() {
+            unsafe { CGEventTapEnable(tap, true) };
+        }
+
+        Ok(failed)
+    }
+
+    pub fn status(&self) -> ManagerStatus {
+        let lost = self
+            .lost_hotkeys
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|h| (*h, HotKeyStatus::LostGrab))
+            .collect::<Vec<_>>();
+
+        if let Some(hotkeys) = &*self.suspended.lock().unwrap() {
+            let hotkeys = hotkeys
+                .iter()
+                .map(|(h, _)| (*h, HotKeyStatus::Suspended))
+                .chain(lost)
+                .collect();
+            return ManagerStatus::new(BACKEND, true, hotkeys);
+        }
+
+        let mut hotkeys = self
+            .hotkeys
+            .lock()
+            .unwrap()
+            .values()
+            .map(|w| (w.hotkey, HotKeyStatus::Active))
+            .collect::<Vec<_>>();
+
+        let media_tap = *self.event_tap.lock().unwrap();
+        let media_status = tap_status(media_tap);
+        hotkeys.extend(
+            self.media_hotkeys
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|h| (*h, media_status.clone())),
+        );
+
+        let sided_tap = self.key_event_tap.lock().unwrap().map(|(tap, _)| tap);
+        let sided_status = tap_status(sided_tap);
+        hotkeys.extend(
+            self.sided_hotkeys
+                .lock()
+                .unwrap()
+                .values()
+                .map(|(h, _)| (*h, sided_status.clone())),
+        );
+
+        // The permission can be revoked in System Settings while the monitor runs.
+        let passthrough_status = if has_input_monitoring_permission() {
+            HotKeyStatus::Active
+        } else {
+            HotKeyStatus::Failed(crate::Error::PermissionDenied(
+                "The Input Monitoring permission was revoked.".to_string(),
+            ))
+        };
+        hotkeys.extend(
+            self.passthrough_hotkeys
+                .lock()
+                .unwrap()
+                .values()
+                .map(|(h, _)| (*h, passthrough_status.clone())),
+        );
+
+        hotkeys.extend(lost);
+
+        ManagerStatus::new(BACKEND, false, hotkeys)
+    }
+
+    pub fn start_recording(&self) -> crate::Result<RecordingHandle> {
+        Err(crate::Error::PlatformUnsupported(
+            "Recording hotkeys is not supported on macOS".to_string(),
+        ))
+    }
+
+    pub fn send_raw(&self, message: ExtensionMessage) -> crate::Result<()> {
+        run_on_main_thread(|| message.run());
+
+        Ok(())
+    }
+
+    // The owner of a Carbon hotkey can't be queried.
+    pub fn window_manager(&self) -> Option<crate::ProcessInfo> {
+        None
+    }
+
+    pub fn set_raw_event_hook(&self, _hook: crate::raw::RawEventHook) -> crate::Result<()> {
+        Err(crate::Error::PlatformUnsupported(
+            "Raw event hooks are not supported on macOS".to_string(),
+        ))
+    }
+
+    pub fn main_thread_poster(&self) -> crate::Result<Poster> {
+        if unsafe { pthread_main_np() } == 0 {
+            return Err(crate::Error::MainThreadUnavailable(
+                "The main thread dispatcher has to be set on the main thread".to_string(),
+            ));
+        }
+
+        Ok(Arc::new(post_to_main_queue))
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            sided_modifiers: true,
+            consume: true,
+            passthrough: true,
+            tap_hold: false,
+            device_filter: false,
+            max_hotkeys: None,
+        }
+    }
+
+    pub fn set_secure_input_handler(
+        &self,
+        handler: Option<Box<dyn Fn(SecureInputEvent) + Send + 'static>>,
+    ) {
+        let mut watcher = self.secure_input_watcher.lock().unwrap();
+        // Replacing the sender stops the previous watcher.
+        *watcher = None;
+
+        let Some(handler) = handler else {
+            return;
+        };
+
+        let (tx, rx) = crossbeam_channel::bounded::<()>(0);
+        *watcher = Some(tx);
+
+        std::thread::spawn(move || {
+            let mut enabled = is_secure_input_enabled();
+            // There is no notification for secure input changes, so poll it.
+            while let Err(crossbeam_channel::RecvTimeoutError::Timeout) =
+                rx.recv_timeout(Duration::from_millis(500))
+            {
+                let now_enabled = is_secure_input_enabled();
+                if now_enabled != enabled {
+                    enabled = now_enabled;
+                    handler(SecureInputEvent {
+                        enabled,
+                        pid: if enabled { secure_input_pid() } else { None },
+                    });
+                }
+            }
+        });
+    }
+
+    fn registered_hotkeys(&self) -> Vec<(HotKey, HotKeyOptions)> {
+        let mut hotkeys = self
+            .hotkeys
+            .lock()
+            .unwrap()
+            .values()
+            .map(|w| w.hotkey)
+            .collect::<Vec<_>>();
+        hotkeys.extend(self.media_hotkeys.lock().unwrap().iter().copied());
+        hotkeys.extend(self.sided_hotkeys.lock().unwrap().values().map(|(h, _)| *h));
+
+        let mut hotkeys = hotkeys
+            .into_iter()
+            .map(|h| (h, HotKeyOptions::default()))
+            .collect::<Vec<_>>();
+        hotkeys.extend(
+            self.passthrough_hotkeys
+                .lock()
+                .unwrap()
+                .values()
+                .map(|(h, _)| (*h, HotKeyOptions::default().consume(false))),
+        );
+        hotkeys
+    }
+
+    fn register_hotkey(&self, hotkey: HotKey, options: &HotKeyOptions) -> crate::Result<()> {
+        if !options.consume {
+            return self.register_passthrough(hotkey);
+        }
+
+        if !hotkey.sides.is_either() {
+            return self.register_sided(hotkey);
+        }
+
+        if key_to_scancode(hotkey.key).is_some() {
+            let ptr = register_event_hotkey(hotkey).map_err(|e| self.with_conflict(e))?;
+            self.lost_hotkeys.lock().unwrap().retain(|h| *h != hotkey);
             self.hotkeys
                 .lock()
                 .unwrap()
                 .insert(hotkey.id(), HotKeyWrapper { ptr, hotkey });
             Ok(())
         } else if is_media_key(hotkey.key) {
-            {
-                let mut media_hotkeys = self.media_hotkeys.lock().unwrap();
-                if !media_hotkeys.insert(hotkey) {
-                    return Err(crate::Error::AlreadyRegistered(hotkey));
-                }
+            let inserted = self.media_hotkeys.lock().unwrap().insert(hotkey);
+            if !inserted {
+                return Err(self.with_conflict(crate::Error::AlreadyRegistered(hotkey)));
             }
             self.start_watching_media_keys()
         } else {
-            Err(crate::Error::FailedToRegister(format!(
-                "Unable to register accelerator (unknown scancode for this key: {}).",
-                hotkey.key
-            )))
+            Err(crate::Error::FailedToRegister {
+                hotkey,
+                reason: RegisterFailureReason::missing_key(hotkey.key),
+            })
         }
     }
 
-    pub fn unregister(&self, hotkey: HotKey) -> crate::Result<()> {
-        if is_media_key(hotkey.key) {
+    fn unregister_hotkey(&self, hotkey: HotKey) -> crate::Result<()> {
+        {
+            let mut passthrough_hotkeys = self.passthrough_hotkeys.lock().unwrap();
+            if passthrough_hotkeys.remove(&hotkey.id()).is_some() {
+                if passthrough_hotkeys.is_empty() {
+                    self.stop_key_monitor();
+                }
+                return Ok(());
+            }
+        }
+
+        if !hotkey.sides.is_either() {
+            let mut sided_hotkeys = self.sided_hotkeys.lock().unwrap();
+            sided_hotkeys.remove(&hotkey.id());
+            if sided_hotkeys.is_empty() {
+                self.stop_watching_key_events();
+            }
+        } else if is_media_key(hotkey.key) {
             let mut media_hotkey = self.media_hotkeys.lock().unwrap();
             media_hotkey.remove(&hotkey);
             if media_hotkey.is_empty() {
@@ -167,26 +542,154 @@ impl GlobalHotKeyManager {
         Ok(())
     }
 
-    pub fn register_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
-        for hotkey in hotkeys {
-            self.register(*hotkey)?;
+    fn register_sided(&self, hotkey: HotKey) -> crate::Result<()> {
+        if key_to_scancode(hotkey.key).is_none() {
+            return Err(crate::Error::FailedToRegister {
+                hotkey,
+                reason: RegisterFailureReason::missing_key(hotkey.key),
+            });
         }
-        Ok(())
+
+        if self.overlaps_registered(&hotkey) {
+            return Err(self.with_conflict(crate::Error::AlreadyRegistered(hotkey)));
+        }
+        self.sided_hotkeys
+            .lock()
+            .unwrap()
+            .insert(hotkey.id(), (hotkey, false));
+
+        let result = self.start_watching_key_events(hotkey);
+        if result.is_err() {
+            self.sided_hotkeys.lock().unwrap().remove(&hotkey.id());
+        }
+        result
     }
 
-    pub fn unregister_all(&self, hotkeys: &[HotKey]) -> crate::Result<()> {
-        for hotkey in hotkeys {
-            self.unregister(*hotkey)?;
+    fn register_passthrough(&self, hotkey: HotKey) -> crate::Result<()> {
+        if key_to_scancode(hotkey.key).is_none() {
+            return Err(crate::Error::FailedToRegister {
+                hotkey,
+                reason: RegisterFailureReason::missing_key(hotkey.key),
+            });
+        }
+
+        if !has_input_monitoring_permission() {
+            return Err(crate::Error::PermissionDenied(
+                "Non-consuming hotkeys require the Input Monitoring permission.".to_string(),
+            ));
+        }
+
+        if self.overlaps_registered(&hotkey) {
+            return Err(self.with_conflict(crate::Error::AlreadyRegistered(hotkey)));
+        }
+        self.passthrough_hotkeys
+            .lock()
+            .unwrap()
+            .insert(hotkey.id(), (hotkey, false));
+
+        let result = self.start_key_monitor(hotkey);
+        if result.is_err() {
+            self.passthrough_hotkeys
+                .lock()
+                .unwrap()
+                .remove(&hotkey.id());
+        }
+        result
+    }
+
+    // Carbon, sided and non-consuming hotkeys all see the same key presses, so
+    // they can't overlap each other.
+    fn overlaps_registered(&self, hotkey: &HotKey) -> bool {
+        let hotkeys = self.hotkeys.lock().unwrap();
+        let sided_hotkeys = self.sided_hotkeys.lock().unwrap();
+        let passthrough_hotkeys = self.passthrough_hotkeys.lock().unwrap();
+
+        let overlapping = hotkeys
+            .values()
+            .map(|w| &w.hotkey)
+            .chain(sided_hotkeys.values().map(|(h, _)| h))
+            .chain(passthrough_hotkeys.values().map(|(h, _)| h))
+            .any(|h| h.overlaps(hotkey));
+        overlapping
+    }
+
+    fn with_conflict(&self, error: crate::Error) -> crate::Error {
+        error.with_conflict(self.registered_hotkeys().iter().map(|(h, _)| h))
+    }
+
+    fn start_key_monitor(&self, hotkey: HotKey) -> crate::Result<()> {
+        let mut key_monitors = self.key_monitors.lock().unwrap();
+
+        if key_monitors.is_some() {
+            return Ok(());
         }
+
+        let passthrough_hotkeys = self.passthrough_hotkeys.clone();
+        let dispatch = move |event: &NSEvent| unsafe {
+            dispatch_key_event(
+                &mut passthrough_hotkeys.lock().unwrap(),
+                event.r#type() == NSEventType::KeyDown,
+                event.keyCode() as u32,
+                event.isARepeat(),
+                event.modifierFlags().0 as CGEventFlags,
+            );
+        };
+        let global = RcBlock::new({
+            let dispatch = dispatch.clone();
+            move |event: NonNull<NSEvent>| dispatch(unsafe { event.as_ref() })
+        });
+        // The events sent to the application itself only reach a local
+        // monitor, which hands them on unchanged.
+        let local = RcBlock::new(move |event: NonNull<NSEvent>| -> *mut NSEvent {
+            dispatch(unsafe { event.as_ref() });
+            event.as_ptr()
+        });
+
+        // Global monitors only observe events sent to other applications and
+        // can't modify or swallow them.
+        let mask: u64 = (1 << NSEventType::KeyDown.0) | (1 << NSEventType::KeyUp.0);
+        let monitors = run_on_main_thread(|| unsafe {
+            let global: Option<Retained<AnyObject>> = msg_send![
+                NSEvent::class(),
+                addGlobalMonitorForEventsMatchingMask: mask,
+                handler: &*global
+            ];
+            let local: Option<Retained<AnyObject>> = msg_send![
+                NSEvent::class(),
+                addLocalMonitorForEventsMatchingMask: mask,
+                handler: &*local
+            ];
+            [global, local]
+        });
+
+        *key_monitors = Some(match monitors {
+            [Some(global), Some(local)] => [global, local],
+            monitors => {
+                remove_monitors(monitors.into_iter().flatten());
+                return Err(crate::Error::FailedToRegister {
+                    hotkey,
+                    reason: RegisterFailureReason::KeyMonitorUnavailable,
+                });
+            }
+        });
+        log::debug!("Added the key event monitors for non-consuming hotkeys");
+
         Ok(())
     }
 
+    fn stop_key_monitor(&self) {
+        if let Some(monitors) = self.key_monitors.lock().unwrap().take() {
+            remove_monitors(monitors);
+            log::debug!("Removed the key event monitors");
+        }
+    }
+
     unsafe fn unregister_hotkey_ptr(
         &self,
         ptr: EventHotKeyRef,
         hotkey: HotKey,
     ) -> crate::Result<()> {
-        if UnregisterEventHotKey(ptr) != noErr as _ {
+        if run_on_main_thread(|| UnregisterEventHotKey(ptr)) != noErr as _ {
             return Err(crate::Error::FailedToUnRegister(hotkey));
         }
 
@@ -201,37 +704,54 @@ impl GlobalHotKeyManager {
             return Ok(());
         }
 
-        unsafe {
-            let event_mask: CGEventMask = CGEventMaskBit!(CGEventType::SystemDefined);
-            let tap = CGEventTapCreate(
-                CGEventTapLocation::Session,
-                CGEventTapPlacement::HeadInsertEventTap,
-                CGEventTapOptions::Default,
+        let event_mask: CGEventMask = CGEventMaskBit!(CGEventType::SystemDefined);
+        let (tap, loop_source) = unsafe {
+            create_event_tap(
                 event_mask,
                 media_key_event_callback,
                 Arc::into_raw(self.media_hotkeys.clone()) as *const c_void,
-            );
-            if tap.is_null() {
-                return Err(crate::Error::FailedToWatchMediaKeyEvent);
-            }
-            *event_tap = Some(tap);
+            )
+        }
+        .ok_or(crate::Error::FailedToWatchMediaKeyEvent)?;
+        *event_tap = Some(tap);
+        *event_tap_source = Some(loop_source);
+        log::debug!("Started watching media key events");
 
-            let loop_source = CFMachPortCreateRunLoopSource(kCFAllocatorDefault, tap, 0);
-            if loop_source.is_null() {
-                // cleanup event_tap
-                CFMachPortInvalidate(tap);
-                CFRelease(tap as *const c_void);
-                *event_tap = None;
+        Ok(())
+    }
 
-                return Err(crate::Error::FailedToWatchMediaKeyEvent);
-            }
-            *event_tap_source = Some(loop_source);
+    fn start_watching_key_events(&self, hotkey: HotKey) -> crate::Result<()> {
+        let mut key_event_tap = self.key_event_tap.lock().unwrap();
 
-            let run_loop = CFRunLoopGetMain();
-            CFRunLoopAddSource(run_loop, loop_source, kCFRunLoopCommonModes);
-            CGEventTapEnable(tap, true);
+        if key_event_tap.is_some() {
+            return Ok(());
+        }
 
-            Ok(())
+        let event_mask: CGEventMask =
+            CGEventMaskBit!(CGEventType::KeyDown) | CGEventMaskBit!(CGEventType::KeyUp);
+
+        // The tap is removed in `Drop` before `sided_hotkeys` is released.
+        let tap = unsafe {
+            create_event_tap(
+                event_mask,
+                key_event_callback,
+                Arc::as_ptr(&self.sided_hotkeys) as *const c_void,
+            )
+        }
+        .ok_or(crate::Error::FailedToRegister {
+            hotkey,
+            reason: RegisterFailureReason::EventTapUnavailable,
+        })?;
+        *key_event_tap = Some(tap);
+        log::debug!("Started watching key events for sided hotkeys");
+
+        Ok(())
+    }
+
+    fn stop_watching_key_events(&self) {
+        if let Some((tap, loop_source)) = self.key_event_tap.lock().unwrap().take() {
+            unsafe { remove_event_tap(tap, loop_source) };
+            log::debug!("Stopped watching key events");
         }
     }
 
@@ -293,11 +813,116 @@ impl Drop for GlobalHotKeyManager {
         for (_, hotkeywrapper) in hotkeys {
             let _ = self.unregister(hotkeywrapper.hotkey);
         }
-        unsafe {
+        run_on_main_thread(|| unsafe {
+            remove_wake_observers(&self.wake_observers);
             RemoveEventHandler(self.event_handler_ptr);
+        });
+        self.stop_watching_media_keys();
+        self.stop_watching_key_events();
+        self.stop_key_monitor();
+    }
+}
+
+// Status of the hotkeys matched by an event tap, which the system disables when
+// the callback is too slow and which stops receiving keys under secure input.
+fn tap_status(tap: Option<CFMachPortRef>) -> HotKeyStatus {
+    if tap.is_some_and(|tap| !unsafe { CGEventTapIsEnabled(tap) }) {
+        HotKeyStatus::Failed(crate::Error::os(0, "The event tap was disabled by the system"))
+    } else if is_secure_input_enabled() {
+        HotKeyStatus::Failed(crate::Error::PermissionDenied(
+            "Secure keyboard entry is enabled, event taps receive no keys".to_string(),
+        ))
+    } else {
+        HotKeyStatus::Active
+    }
+}
+
+// Removes `NSEvent` monitors, which has to happen on the main thread.
+fn remove_monitors(monitors: impl IntoIterator<Item = Retained<AnyObject>>) {
+    run_on_main_thread(|| unsafe {
+        for monitor in monitors {
+            let _: () = msg_send![NSEvent::class(), removeMonitor: &*monitor];
         }
-        self.stop_watching_media_keys()
+    });
+}
+
+// Runs `task` on the main thread once the main queue gets to it, without waiting.
+fn post_to_main_queue(task: Task) {
+    unsafe extern "C" fn trampoline(context: *mut c_void) {
+        let task = Box::from_raw(context as *mut Task);
+        task();
     }
+
+    let task = Box::into_raw(Box::new(task));
+
+    unsafe { dispatch_async_f(&_dispatch_main_q, task as *mut c_void, trampoline) };
+}
+
+// Carbon and AppKit expect to be called from the main thread, which the
+// manager may not be used from since it is `Send` and `Sync`. `dispatch_sync_f`
+// blocks the calling thread until `f` returned, so `f` and its result are never
+// used by two threads at once.
+fn run_on_main_thread<R, F: FnOnce() -> R>(f: F) -> R {
+    if unsafe { pthread_main_np() } != 0 {
+        return f();
+    }
+
+    unsafe extern "C" fn trampoline(context: *mut c_void) {
+        let run = &mut *(context as *mut &mut dyn FnMut());
+        run();
+    }
+
+    let mut f = Some(f);
+    let mut result = None;
+    let mut run = || result = f.take().map(|f| f());
+    let mut run: &mut dyn FnMut() = &mut run;
+
+    unsafe {
+        dispatch_sync_f(
+            &_dispatch_main_q,
+            &mut run as *mut &mut dyn FnMut() as *mut c_void,
+            trampoline,
+        )
+    };
+
+    result.expect("the main queue didn't run the closure")
+}
+
+unsafe fn create_event_tap(
+    event_mask: CGEventMask,
+    callback: CGEventTapCallBack,
+    user_info: *const c_void,
+) -> Option<(CFMachPortRef, CFRunLoopSourceRef)> {
+    let tap = CGEventTapCreate(
+        CGEventTapLocation::Session,
+        CGEventTapPlacement::HeadInsertEventTap,
+        CGEventTapOptions::Default,
+        event_mask,
+        callback,
+        user_info,
+    );
+    if tap.is_null() {
+        return None;
+    }
+
+    let loop_source = CFMachPortCreateRunLoopSource(kCFAllocatorDefault, tap, 0);
+    if loop_source.is_null() {
+        CFMachPortInvalidate(tap);
+        CFRelease(tap as *const c_void);
+        return None;
+    }
+
+    CFRunLoopAddSource(CFRunLoopGetMain(), loop_source, kCFRunLoopCommonModes);
+    CGEventTapEnable(tap, true);
+
+    Some((tap, loop_source))
+}
+
+unsafe fn remove_event_tap(tap: CFMachPortRef, loop_source: CFRunLoopSourceRef) {
+    CFRunLoopRemoveSource(CFRunLoopGetMain(), loop_source, kCFRunLoopCommonModes);
+    CFRelease(loop_source as *const c_void);
+    CFMachPortInvalidate(tap);
+    CFRelease(tap as *const c_void);
 }
 
 unsafe extern "C" fn hotkey_handler(
@@ -319,19 +944,20 @@ unsafe extern "C" fn hotkey_handler(
 
     if result == noErr as _ {
         let event_kind = GetEventKind(event);
-        match event_kind {
+        let state = match event_kind {
             #[allow(non_upper_case_globals)]
-            kEventHotKeyPressed => GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-                id: event_hotkey.id,
-                state: crate::HotKeyState::Pressed,
-            }),
+            kEventHotKeyPressed => crate::HotKeyState::Pressed,
             #[allow(non_upper_case_globals)]
-            kEventHotKeyReleased => GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-                id: event_hotkey.id,
-                state: crate::HotKeyState::Released,
-            }),
-            _ => {}
+            kEventHotKeyReleased => crate::HotKeyState::Released,
+            _ => return noErr as _,
         };
+
+        // Carbon events carry no modifier flags.
+        let flags = CGEventSourceFlagsState(kCGEventSourceStateCombinedSessionState);
+
+        if !left_out(event_hotkey.id, state, flags) {
+            GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(event_hotkey.id, state));
+        }
     }
 
     noErr as _
@@ -373,7 +999,7 @@ unsafe extern "C" fn media_key_event_callback(
             mods |= Modifiers::ALT;
         }
         if flags.contains(NSEventModifierFlags::Command) {
-            mods |= Modifiers::META;
+            mods |= Modifiers::SUPER;
         }
 
         // Generate hotkey for matching
@@ -385,13 +1011,13 @@ unsafe extern "C" fn media_key_event_callback(
         if let Some(media_hotkey) = media_hotkeys.lock().unwrap().get(&hotkey) {
             let key_flags = data_1 & 0x0000FFFF;
             let is_pressed: bool = ((key_flags & 0xFF00) >> 8) == 0xA;
-            GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-                id: media_hotkey.id(),
-                state: match is_pressed {
-                    true => crate::HotKeyState::Pressed,
-                    false => crate::HotKeyState::Released,
-                },
-            });
+            let state = match is_pressed {
+                true => crate::HotKeyState::Pressed,
+                false => crate::HotKeyState::Released,
+            };
+            if !left_out(media_hotkey.id(), state, flags.0 as CGEventFlags) {
+                GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(media_hotkey.id(), state));
+            }
 
             // Hotkey was found, return null to stop propagate event
             return ptr::null();
@@ -401,6 +1027,316 @@ unsafe extern "C" fn media_key_event_callback(
     event
 }
 
+unsafe extern "C" fn key_event_callback(
+    _proxy: CGEventTapProxy,
+    ev_type: CGEventType,
+    event: CGEventRef,
+    user_info: *const c_void,
+) -> CGEventRef {
+    if ev_type != CGEventType::KeyDown && ev_type != CGEventType::KeyUp {
+        return event;
+    }
+
+    let keycode = CGEventGetIntegerValueField(event, kCGKeyboardEventKeycode) as u32;
+    let is_repeat = CGEventGetIntegerValueField(event, kCGKeyboardEventAutorepeat) != 0;
+    let flags = CGEventGetFlags(event);
+
+    // Owned by the manager which removes this tap before dropping it.
+    let sided_hotkeys = &*(user_info as *const Mutex<BTreeMap<u32, (HotKey, bool)>>);
+
+    let consumed = dispatch_key_event(
+        &mut sided_hotkeys.lock().unwrap(),
+        ev_type == CGEventType::KeyDown,
+        keycode,
+        is_repeat,
+        flags,
+    );
+
+    if consumed {
+        ptr::null()
+    } else {
+        event
+    }
+}
+
+// Emits events for the hotkeys matching a key press, returns `true` if any of
+// them matched.
+fn dispatch_key_event(
+    hotkeys: &mut BTreeMap<u32, (HotKey, bool)>,
+    is_key_down: bool,
+    keycode: u32,
+    is_repeat: bool,
+    flags: CGEventFlags,
+) -> bool {
+    let mut matched = false;
+    for (hotkey, pressed) in hotkeys.values_mut() {
+        if key_to_scancode(hotkey.key) != Some(keycode) {
+            continue;
+        }
+
+        match is_key_down {
+            true if *pressed => matched = true,
+            true if !is_repeat
+                && flags_match(flags, hotkey)
+                && !caps_lock_mismatch(hotkey.id(), flags) =>
+            {
+                GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+                    hotkey.id(),
+                    crate::HotKeyState::Pressed,
+                ));
+                *pressed = true;
+                matched = true;
+            }
+            false if *pressed => {
+                GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+                    hotkey.id(),
+                    crate::HotKeyState::Released,
+                ));
+                *pressed = false;
+                matched = true;
+            }
+            _ => {}
+        }
+    }
+    matched
+}
+
+// The modifiers forbidden by the hotkeys registered with some, along with the
+// locks they require on, by id. Kept out of the managers as the Carbon handler
+// isn't given one.
+static RESTRICTED: Mutex<BTreeMap<u32, (Modifiers, Modifiers)>> = Mutex::new(BTreeMap::new());
+
+// Hotkeys whose press was left out by `left_out`, so is their release.
+static LEFT_OUT: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+fn restrict(id: u32, options: &HotKeyOptions) {
+    let (forbidden, required) = (options.forbidden(), options.required_locks());
+    let mut restricted = RESTRICTED.lock().unwrap();
+    if forbidden.is_empty() && required.is_empty() {
+        restricted.remove(&id);
+    } else {
+        restricted.insert(id, (forbidden, required));
+    }
+}
+
+// Whether the hotkey with this id forbids CapsLock while it's on, or requires
+// it while it's off. Its other forbidden modifiers never match, hotkeys
+// require their modifiers exactly.
+fn caps_lock_mismatch(id: u32, flags: CGEventFlags) -> bool {
+    let on = flags & kCGEventFlagMaskAlphaShift != 0;
+    RESTRICTED
+        .lock()
+        .unwrap()
+        .get(&id)
+        .is_some_and(|(forbidden, required)| match on {
+            true => forbidden.contains(Modifiers::CAPS_LOCK),
+            false => required.contains(Modifiers::CAPS_LOCK),
+        })
+}
+
+// Whether an event of a hotkey reported without tracking its presses is left
+// out, see `caps_lock_mismatch`.
+fn left_out(id: u32, state: crate::HotKeyState, flags: CGEventFlags) -> bool {
+    let mut left_out = LEFT_OUT.lock().unwrap();
+    match state {
+        crate::HotKeyState::Pressed => {
+            let forbidden = caps_lock_mismatch(id, flags);
+            if forbidden {
+                left_out.insert(id);
+            }
+            forbidden
+        }
+        _ => left_out.remove(&id),
+    }
+}
+
+fn flags_match(flags: CGEventFlags, hotkey: &HotKey) -> bool {
+    let mut mods = Modifiers::empty();
+    if flags & kCGEventFlagMaskShift != 0 {
+        mods |= Modifiers::SHIFT;
+    }
+    if flags & kCGEventFlagMaskControl != 0 {
+        mods |= Modifiers::CONTROL;
+    }
+    if flags & kCGEventFlagMaskAlternate != 0 {
+        mods |= Modifiers::ALT;
+    }
+    if flags & kCGEventFlagMaskCommand != 0 {
+        mods |= Modifiers::SUPER;
+    }
+
+    if mods != hotkey.mods {
+        return false;
+    }
+
+    [
+        (hotkey.sides.shift, NX_DEVICELSHIFTKEYMASK, NX_DEVICERSHIFTKEYMASK),
+        (hotkey.sides.control, NX_DEVICELCTLKEYMASK, NX_DEVICERCTLKEYMASK),
+        (hotkey.sides.alt, NX_DEVICELALTKEYMASK, NX_DEVICERALTKEYMASK),
+        (hotkey.sides.super_key, NX_DEVICELCMDKEYMASK, NX_DEVICERCMDKEYMASK),
+    ]
+    .into_iter()
+    .all(|(side, left, right)| match side {
+        ModifierSide::Either => true,
+        ModifierSide::Left => flags & left != 0,
+        ModifierSide::Right => flags & right != 0,
+    })
+}
+
+fn register_event_hotkey(hotkey: HotKey) -> crate::Result<EventHotKeyRef> {
+    let Some(scan_code) = key_to_scancode(hotkey.key) else {
+        return Err(crate::Error::FailedToRegister {
+            hotkey,
+            reason: RegisterFailureReason::missing_key(hotkey.key),
+        });
+    };
+
+    let mut mods: u32 = 0;
+    if hotkey.mods.contains(Modifiers::SHIFT) {
+        mods |= 512;
+    }
+    if crate::canonical::fold(hotkey.mods).contains(Modifiers::SUPER) {
+        mods |= 256;
+    }
+    if hotkey.mods.contains(Modifiers::ALT) {
+        mods |= 2048;
+    }
+    if hotkey.mods.contains(Modifiers::CONTROL) {
+        mods |= 4096;
+    }
+
+    let hotkey_id = EventHotKeyID {
+        id: hotkey.id(),
+        signature: {
+            let mut res: u32 = 0;
+            // can't find a resource for "htrs" so we construct it manually
+            // the construction method below is taken from https://github.com/soffes/HotKey/blob/c13662730cb5bc28de4a799854bbb018a90649bf/Sources/HotKey/HotKeysController.swift#L27
+            // and confirmed by applying the same method to `kEventParamDragRef` which is equal to `drag` in C
+            // and converted to `1685217639` by rust-bindgen.
+            for c in "htrs".chars() {
+                res = (res << 8) + c as u32;
+            }
+            res
+        },
+    };
+
+    log::debug!(
+        "Registering `{}` (id {}) as scan code {:#x} with Carbon mods {:#x}",
+        hotkey,
+        hotkey.id(),
+        scan_code,
+        mods
+    );
+
+    unsafe {
+        let mut hotkey_ref: EventHotKeyRef = std::mem::zeroed();
+        let result = run_on_main_thread(|| {
+            RegisterEventHotKey(
+                scan_code,
+                mods,
+                hotkey_id,
+                GetApplicationEventTarget(),
+                0,
+                &mut hotkey_ref,
+            )
+        });
+
+        if result != noErr as _ {
+            log::warn!(
+                "RegisterEventHotKey failed for `{}` (id {}): {}",
+                hotkey,
+                hotkey.id(),
+                describe_os_status(result)
+            );
+            return Err(register_error(hotkey, result));
+        }
+
+        Ok(hotkey_ref)
+    }
+}
+
+// Carbon registrations can survive sleep and user switching while no longer
+// delivering events, so start over with fresh ones, giving the lost hotkeys
+// another chance. Returns the hotkeys that failed to come back.
+fn revalidate_event_hotkeys(
+    hotkeys: &Mutex<BTreeMap<u32, HotKeyWrapper>>,
+    lost_hotkeys: &Mutex<Vec<HotKey>>,
+    suspended: &Mutex<Option<Vec<(HotKey, HotKeyOptions)>>>,
+) -> Vec<HotKey> {
+    let mut suspended = suspended.lock().unwrap();
+    let mut lost_hotkeys = lost_hotkeys.lock().unwrap();
+
+    // They are registered with the OS when resuming.
+    if let Some(suspended) = suspended.as_mut() {
+        suspended.extend(lost_hotkeys.drain(..).map(|h| (h, HotKeyOptions::default())));
+        return Vec::new();
+    }
+
+    let mut hotkeys = hotkeys.lock().unwrap();
+
+    let mut registered = hotkeys.values().map(|w| w.hotkey).collect::<Vec<_>>();
+    registered.extend(lost_hotkeys.drain(..));
+    let failed = reregister(registered, |hotkey| {
+        if let Some(wrapper) = hotkeys.remove(&hotkey.id()) {
+            unsafe { UnregisterEventHotKey(wrapper.ptr) };
+        }
+        let ptr = register_event_hotkey(hotkey)?;
+        hotkeys.insert(hotkey.id(), HotKeyWrapper { ptr, hotkey });
+        Ok(())
+    });
+
+    lost_hotkeys.extend(failed.iter().copied());
+    failed
+}
+
+// Returns the observer tokens, which must be passed to `remove_wake_observers`.
+unsafe fn add_wake_observers(
+    hotkeys: &Arc<Mutex<BTreeMap<u32, HotKeyWrapper>>>,
+    lost_hotkeys: &Arc<Mutex<Vec<HotKey>>>,
+    suspended: &Arc<Mutex<Option<Vec<(HotKey, HotKeyOptions)>>>>,
+) -> Vec<Retained<AnyObject>> {
+    let center = workspace_notification_center();
+
+    [
+        b"NSWorkspaceDidWakeNotification\0".as_slice(),
+        b"NSWorkspaceSessionDidBecomeActiveNotification\0".as_slice(),
+    ]
+    .into_iter()
+    .map(|name| {
+        let hotkeys = hotkeys.clone();
+        let lost_hotkeys = lost_hotkeys.clone();
+        let suspended = suspended.clone();
+        let block = RcBlock::new(move |_notification: NonNull<AnyObject>| {
+            log::debug!("Woke up or the session became active, revalidating the hotkeys");
+            revalidate_event_hotkeys(&hotkeys, &lost_hotkeys, &suspended);
+        });
+
+        let name: Retained<AnyObject> =
+            msg_send![class!(NSString), stringWithUTF8String: name.as_ptr() as *const c_char];
+        msg_send![
+            &center,
+            addObserverForName: &*name,
+            object: ptr::null::<AnyObject>(),
+            queue: ptr::null::<AnyObject>(),
+            usingBlock: &*block
+        ]
+    })
+    .collect()
+}
+
+unsafe fn remove_wake_observers(observers: &[Retained<AnyObject>]) {
+    let center = workspace_notification_center();
+    for observer in observers {
+        let _: () = msg_send![&center, removeObserver: &**observer];
+    }
+}
+
+// Wake and session notifications are only posted to the workspace center.
+unsafe fn workspace_notification_center() -> Retained<AnyObject> {
+    let workspace: Retained<AnyObject> = msg_send![class!(NSWorkspace), sharedWorkspace];
+    msg_send![&workspace, notificationCenter]
+}
+
 #[derive(Clone, Copy, Debug)]
 struct HotKeyWrapper {
     ptr: EventHotKeyRef,
@@ -487,6 +1423,7 @@ pub fn key_to_scancode(code: Code) -> Option<u32> {
         Code::F20 => Some(0x5a),
         Code::Numpad8 => Some(0x5b),
         Code::Numpad9 => Some(0x5c),
+        Code::NumpadComma => Some(0x5f),
         Code::F5 => Some(0x60),
         Code::F6 => Some(0x61),
         Code::F7 => Some(0x62),
@@ -515,10 +1452,98 @@ pub fn key_to_scancode(code: Code) -> Option<u32> {
         Code::ArrowUp => Some(0x7e),
         Code::CapsLock => Some(0x39),
         Code::PrintScreen => Some(0x46),
+        // kVK_ContextualMenu, found on PC keyboards.
+        Code::ContextMenu => Some(0x6e),
+        // Keys only found on ISO and JIS keyboards.
+        Code::IntlBackslash => Some(0x0a),
+        Code::IntlYen => Some(0x5d),
+        Code::IntlRo => Some(0x5e),
+        Code::Lang2 => Some(0x66),
+        Code::Lang1 => Some(0x68),
         _ => None,
     }
 }
 
+// Maps a `RegisterEventHotKey` failure to the most specific error we have.
+pub fn has_input_monitoring_permission() -> bool {
+    unsafe { CGPreflightListenEventAccess() }
+}
+
+pub fn request_input_monitoring_permission() -> bool {
+    unsafe { CGRequestListenEventAccess() }
+}
+
+pub fn is_secure_input_enabled() -> bool {
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
+// The PID is only exposed through an undocumented key of the session
+// dictionary, which is what `ioreg -l | grep kCGSSessionSecureInputPID` reads.
+pub fn secure_input_pid() -> Option<i32> {
+    unsafe {
+        let session = CGSessionCopyCurrentDictionary();
+        if session.is_null() {
+            return None;
+        }
+
+        let key = CFStringCreateWithCString(
+            kCFAllocatorDefault,
+            b"kCGSSessionSecureInputPID\0".as_ptr() as *const c_char,
+            kCFStringEncodingUTF8,
+        );
+
+        let mut pid: i32 = 0;
+        let value = CFDictionaryGetValue(session, key as *const c_void) as CFNumberRef;
+        let found = !value.is_null()
+            && CFNumberGetValue(value, kCFNumberSInt32Type, &mut pid as *mut i32 as *mut c_void);
+
+        CFRelease(key as *const c_void);
+        CFRelease(session as *const c_void);
+
+        (found && pid != 0).then_some(pid)
+    }
+}
+
+fn register_error(hotkey: HotKey, status: OSStatus) -> crate::Error {
+    match status {
+        // Another application (or this one) already owns the combination.
+        #[allow(non_upper_case_globals)]
+        eventHotKeyExistsErr => crate::Error::AlreadyRegistered(hotkey),
+        #[allow(non_upper_case_globals)]
+        eventHotKeyInvalidErr => crate::Error::FailedToRegister {
+            hotkey,
+            reason: RegisterFailureReason::Invalid(Box::new(crate::Error::os(
+                status as i64,
+                describe_os_status(status),
+            ))),
+        },
+        _ => crate::Error::os(
+            status as i64,
+            format!(
+                "Unable to register hotkey {}: {}",
+                hotkey,
+                describe_os_status(status)
+            ),
+        ),
+    }
+}
+
+// Some OSStatus values are four-char codes, e.g. `'dupe'`, show them as such.
+fn describe_os_status(status: OSStatus) -> String {
+    let bytes = status.to_be_bytes();
+    if bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        format!("OSStatus {} ('{}')", status, String::from_utf8_lossy(&bytes))
+    } else {
+        format!("OSStatus {}", status)
+    }
+}
+
+pub fn is_supported(hotkey: &HotKey) -> bool {
+    // Sided hotkeys are matched by keycode in the event tap, so media keys
+    // can only be registered without sides.
+    key_to_scancode(hotkey.key).is_some() || (hotkey.sides.is_either() && is_media_key(hotkey.key))
+}
+
 fn is_media_key(code: Code) -> bool {
     matches!(
         code,
@@ -529,3 +1554,56 @@ fn is_media_key(code: Code) -> bool {
             | Code::MediaRewind
     )
 }
+
+#[test]
+fn test_register_error() {
+    let hotkey = HotKey::new(Some(Modifiers::SUPER), Code::KeyK);
+
+    assert!(matches!(
+        register_error(hotkey, eventHotKeyExistsErr),
+        crate::Error::AlreadyRegistered(h) if h == hotkey
+    ));
+    assert!(matches!(
+        register_error(hotkey, eventHotKeyInvalidErr),
+        crate::Error::FailedToRegister {
+            reason: RegisterFailureReason::Invalid(_),
+            ..
+        }
+    ));
+    assert!(matches!(
+        register_error(hotkey, -50),
+        crate::Error::OsError { code: -50, .. }
+    ));
+}
+
+#[test]
+fn test_key_to_scancode() {
+    // kVK_ISO_Section, kVK_JIS_Yen, kVK_JIS_Underscore, kVK_JIS_Eisu and kVK_JIS_Kana.
+    let table = [
+        (Code::IntlBackslash, 0x0a),
+        (Code::IntlYen, 0x5d),
+        (Code::IntlRo, 0x5e),
+        (Code::Lang2, 0x66),
+        (Code::Lang1, 0x68),
+        (Code::KeyA, 0x00),
+        (Code::Space, 0x31),
+        (Code::F1, 0x7a),
+        (Code::ContextMenu, 0x6e),
+    ];
+    for (code, scancode) in table {
+        assert_eq!(key_to_scancode(code), Some(scancode), "{code}");
+    }
+
+    assert!(HotKey::new(Some(Modifiers::SUPER), Code::IntlYen).is_supported());
+    assert!(HotKey::new(None, Code::MediaPlayPause).is_supported());
+    assert!(!HotKey::new(None, Code::Lang3).is_supported());
+}
+
+#[test]
+fn test_describe_os_status() {
+    assert_eq!(describe_os_status(-9878), "OSStatus -9878");
+    assert_eq!(
+        describe_os_status(i32::from_be_bytes(*b"dupe")),
+        "OSStatus 1685418085 ('dupe')"
+    );
+}
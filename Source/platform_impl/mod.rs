@@ -27,7 +27,54 @@ mod platform;
 	target_os = "netbsd",
 	target_os = "macos"
 )))]
-#[path = "no-op.rs"]
+#[path = "unsupported/mod.rs"]
 mod platform;
 
 pub(crate) use self::platform::*;
+
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+mod registered;
+
+/// Registers `hotkeys` again, e.g. after the system woke from sleep or the user
+/// session became active, returning the ones that failed to come back.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn reregister(
+	hotkeys:impl IntoIterator<Item = crate::hotkey::HotKey>,
+	mut register:impl FnMut(crate::hotkey::HotKey) -> crate::Result<()>,
+) -> Vec<crate::hotkey::HotKey> {
+	let mut failed = Vec::new();
+
+	for hotkey in hotkeys {
+		if let Err(e) = register(hotkey) {
+			log::warn!("Failed to register `{}` again: {}", hotkey, e);
+
+			failed.push(hotkey);
+		}
+	}
+
+	failed
+}
+
+/// Registers `hotkeys` one at a time, returning the result of each up to the
+/// first one that failed.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn register_until_failure(
+	hotkeys:&[crate::hotkey::HotKey],
+	mut register:impl FnMut(crate::hotkey::HotKey) -> crate::Result<()>,
+) -> Vec<crate::Result<()>> {
+	let mut results = Vec::with_capacity(hotkeys.len());
+
+	for hotkey in hotkeys {
+		let result = register(*hotkey);
+
+		let failed = result.is_err();
+
+		results.push(result);
+
+		if failed {
+			break;
+		}
+	}
+
+	results
+}
@@ -1,34 +0,0 @@
-// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
-// SPDX-License-Identifier: Apache-2.0
-// SPDX-License-Identifier: MIT
-// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
-// SPDX-License-Identifier: Apache-2.0
-// SPDX-License-Identifier: MIT
-
-use crate::hotkey::HotKey;
-
-pub struct GlobalHotKeyManager {}
-
-impl GlobalHotKeyManager {
-	pub fn new() -> crate::Result<Self> { Ok(Self {}) }
-
-	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> { Ok(()) }
-
-	pub fn unregister(&self, hotkey:HotKey) -> crate::Result<()> { Ok(()) }
-
-	pub fn register_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		for hotkey in hotkeys {
-			self.register(*hotkey)?;
-		}
-
-		Ok(())
-	}
-
-	pub fn unregister_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		for hotkey in hotkeys {
-			self.unregister(*hotkey)?;
-		}
-
-		Ok(())
-	}
-}
@@ -0,0 +1,197 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// The bookkeeping of the hotkeys a backend registered on the keys of the
+// platform, keeping the hotkeys themselves rather than bare ids so that the
+// backends don't need maps of their own to find them back.
+
+use std::collections::BTreeMap;
+
+use crate::{HotKeyOptions, LockState, hotkey::HotKey};
+
+/// A hotkey registered on a key of the platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegisteredEntry {
+	pub(crate) hotkey:HotKey,
+	pub(crate) options:HotKeyOptions,
+	/// The platform keycode the hotkey is registered on.
+	pub(crate) keycode:u32,
+	/// The platform modifier mask the hotkey is registered with.
+	pub(crate) mods:u32,
+	/// Whether a press was reported without its release yet.
+	pub(crate) pressed:bool,
+}
+
+impl RegisteredEntry {
+	pub(crate) fn new(hotkey:HotKey, options:&HotKeyOptions, keycode:u32, mods:u32) -> Self {
+		Self { hotkey, options:options.clone(), keycode, mods, pressed:false }
+	}
+
+	pub(crate) fn id(&self) -> u32 { self.hotkey.id() }
+}
+
+/// The registered entries by platform keycode, a hotkey registered on several
+/// keycodes has an entry on each. The entries of a keycode keep the order they
+/// were inserted in.
+#[derive(Debug, Default)]
+pub(crate) struct RegisteredEntries {
+	by_keycode:BTreeMap<u32, Vec<RegisteredEntry>>,
+}
+
+impl RegisteredEntries {
+	pub(crate) fn new() -> Self { Self::default() }
+
+	pub(crate) fn insert(&mut self, entry:RegisteredEntry) {
+		self.by_keycode.entry(entry.keycode).or_default().push(entry);
+	}
+
+	/// The entries registered on `keycode`.
+	pub(crate) fn on(&self, keycode:u32) -> &[RegisteredEntry] {
+		self.by_keycode.get(&keycode).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// The entries registered on `keycode`, `None` if there is none.
+	pub(crate) fn on_mut(&mut self, keycode:u32) -> Option<&mut [RegisteredEntry]> {
+		self.by_keycode.get_mut(&keycode).map(Vec::as_mut_slice)
+	}
+
+	/// Returns the entry of `keycode` with the same `mods` that a press of
+	/// `hotkey` in a state of the locks it accepts would trigger too, i.e. a
+	/// duplicate of it.
+	pub(crate) fn overlapping(
+		&self,
+		keycode:u32,
+		mods:u32,
+		hotkey:&HotKey,
+		locks:&LockState,
+	) -> Option<&RegisteredEntry> {
+		self.on(keycode).iter().find(|e| {
+			e.mods == mods
+				&& e.hotkey.sides.overlaps(&hotkey.sides)
+				&& e.options.lock_state().overlaps(locks)
+		})
+	}
+
+	/// Returns the entries of `keycode` with the same `mods`, which share a
+	/// registration with the platform.
+	pub(crate) fn sharing(
+		&self,
+		keycode:u32,
+		mods:u32,
+	) -> impl Iterator<Item = &RegisteredEntry> + Clone {
+		self.on(keycode).iter().filter(move |e| e.mods == mods)
+	}
+
+	/// Returns the keycodes the hotkey with this id is registered on.
+	pub(crate) fn keycodes_of(&self, id:u32) -> Vec<u32> {
+		self.iter().filter(|e| e.id() == id).map(|e| e.keycode).collect()
+	}
+
+	/// Removes and returns the entries of the hotkey with this id.
+	pub(crate) fn remove(&mut self, id:u32) -> Vec<RegisteredEntry> {
+		let mut removed = Vec::new();
+
+		self.by_keycode.retain(|_, entries| {
+			let (gone, kept) = std::mem::take(entries).into_iter().partition(|e| e.id() == id);
+
+			removed.extend::<Vec<_>>(gone);
+
+			*entries = kept;
+
+			!entries.is_empty()
+		});
+
+		removed
+	}
+
+	pub(crate) fn iter(&self) -> impl Iterator<Item = &RegisteredEntry> {
+		self.by_keycode.values().flatten()
+	}
+
+	pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut RegisteredEntry> {
+		self.by_keycode.values_mut().flatten()
+	}
+
+	pub(crate) fn clear(&mut self) { self.by_keycode.clear(); }
+}
+
+#[cfg(test)]
+fn registered_test_entries() -> (HotKey, HotKey, RegisteredEntries) {
+	let left = "LeftCtrl+KeyA".parse::<HotKey>().unwrap();
+
+	let right = "RightCtrl+KeyA".parse::<HotKey>().unwrap();
+
+	let mut entries = RegisteredEntries::new();
+
+	entries.insert(RegisteredEntry::new(left, &HotKeyOptions::default(), 38, 4));
+
+	entries.insert(RegisteredEntry::new(right, &HotKeyOptions::default(), 38, 4));
+
+	// Registered on two keycodes, like a numpad hotkey.
+	entries.insert(RegisteredEntry::new(left, &HotKeyOptions::default(), 90, 4));
+
+	(left, right, entries)
+}
+
+#[test]
+fn test_registered_entries_insert() {
+	let (left, right, entries) = registered_test_entries();
+
+	assert_eq!(entries.on(38).iter().map(RegisteredEntry::id).collect::<Vec<_>>(), [
+		left.id(),
+		right.id()
+	]);
+
+	assert!(entries.on(39).is_empty());
+
+	assert_eq!(entries.keycodes_of(left.id()), [38, 90]);
+
+	assert_eq!(entries.keycodes_of(right.id()), [38]);
+
+	assert!(entries.keycodes_of(right.id() + 1).is_empty());
+
+	assert!(entries.iter().all(|e| !e.pressed));
+}
+
+#[test]
+fn test_registered_entries_duplicates() {
+	let (left, right, entries) = registered_test_entries();
+
+	let either = "Ctrl+KeyA".parse::<HotKey>().unwrap();
+
+	let locks = LockState::EITHER;
+
+	assert_eq!(entries.overlapping(38, 4, &either, &locks).map(|e| e.hotkey), Some(left));
+
+	assert_eq!(entries.overlapping(90, 4, &right, &locks), None);
+
+	// Other modifiers are another combination.
+	assert_eq!(entries.overlapping(38, 1, &either, &locks), None);
+
+	assert_eq!(entries.sharing(38, 4).count(), 2);
+
+	assert_eq!(entries.sharing(38, 1).count(), 0);
+}
+
+#[test]
+fn test_registered_entries_remove() {
+	let (left, right, mut entries) = registered_test_entries();
+
+	let removed = entries.remove(left.id());
+
+	assert_eq!(removed.iter().map(|e| e.keycode).collect::<Vec<_>>(), [38, 90]);
+
+	assert!(entries.keycodes_of(left.id()).is_empty());
+
+	// Keycodes without entries are dropped.
+	assert!(entries.on_mut(90).is_none());
+
+	assert_eq!(entries.keycodes_of(right.id()), [38]);
+
+	assert!(entries.remove(left.id()).is_empty());
+
+	entries.clear();
+
+	assert_eq!(entries.iter().count(), 0);
+}
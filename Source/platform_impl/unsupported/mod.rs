@@ -0,0 +1,84 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+	Backend,
+	Capabilities,
+	ExtensionMessage,
+	GlobalHotKeyManagerOptions,
+	HotKeyOptions,
+	ManagerStatus,
+	RecordingHandle,
+	hotkey::HotKey,
+};
+
+pub(crate) const BACKEND:Backend = Backend::Unsupported;
+
+const UNSUPPORTED_MESSAGE:&str = "This platform does not support global hotkeys";
+
+pub struct GlobalHotKeyManager {}
+
+impl GlobalHotKeyManager {
+	pub fn new(_options:&GlobalHotKeyManagerOptions) -> crate::Result<Self> { Err(unsupported()) }
+
+	pub fn register(&self, _hotkey:HotKey) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn unregister(&self, _hotkey:HotKey) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn validate(&self, _hotkey:HotKey) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn register_all_with_options(
+		&self,
+		_hotkeys:&[HotKey],
+		_options:HotKeyOptions,
+	) -> Vec<crate::Result<()>> {
+		vec![Err(unsupported())]
+	}
+
+	pub fn register_all_atomic(&self, _hotkeys:&[HotKey]) -> crate::Result<()> {
+		Err(unsupported())
+	}
+
+	pub fn unregister_all(&self, _hotkeys:&[HotKey]) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn clear_all(&self) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn suspend(&self) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn resume(&self) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> { Err(unsupported()) }
+
+	pub fn status(&self) -> ManagerStatus {
+		ManagerStatus::backend_failed(BACKEND, unsupported(), [])
+	}
+
+	pub fn register_with_options(
+		&self,
+		_hotkey:HotKey,
+		_options:HotKeyOptions,
+	) -> crate::Result<()> {
+		Err(unsupported())
+	}
+
+	pub fn start_recording(&self) -> crate::Result<RecordingHandle> { Err(unsupported()) }
+
+	pub fn send_raw(&self, _message:ExtensionMessage) -> crate::Result<()> { Err(unsupported()) }
+
+	pub fn window_manager(&self) -> Option<crate::ProcessInfo> { None }
+
+	pub fn set_raw_event_hook(&self, _hook:crate::raw::RawEventHook) -> crate::Result<()> {
+		Err(unsupported())
+	}
+
+	pub fn main_thread_poster(&self) -> crate::Result<crate::main_thread::Poster> {
+		Err(unsupported())
+	}
+
+	pub fn capabilities(&self) -> Capabilities { Capabilities::default() }
+}
+
+pub fn is_supported(_hotkey:&HotKey) -> bool { false }
+
+fn unsupported() -> crate::Error { crate::Error::PlatformUnsupported(UNSUPPORTED_MESSAGE.to_string()) }
@@ -2,197 +2,1293 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::ptr;
+// There is no background thread on Windows: `RegisterHotKey` posts `WM_HOTKEY`
+// to a hidden window owned by the thread that created the manager, so the
+// messages are dispatched by whatever message loop that thread already runs
+// (winit, tao, a dialog...). Dropping the manager destroys the window, there is
+// no loop of ours to stop with `WM_QUIT`. Threads without a loop of their own
+// poll with `pump_messages`, like the X11 backend polls with `XPending`.
+//
+// `RegisterHotKey` and the keyboard hook only work from that thread, so calls
+// made from other threads are sent to the window with `SendMessageW`, which
+// waits for its loop to run them.
+//
+// With `WindowsBackend::RawInput` there is neither: the keys of every keyboard
+// come as `WM_INPUT` to the window of one of the managers in that mode, and
+// are matched like the ones of the keyboard hook, without swallowing them.
+
+use std::{
+	collections::BTreeMap,
+	ptr,
+	sync::{
+		Arc,
+		Mutex,
+		Weak,
+		atomic::{AtomicBool, Ordering},
+	},
+};
 
 use keyboard_types::{Code, Modifiers};
 use windows_sys::Win32::{
-	Foundation::{ERROR_HOTKEY_ALREADY_REGISTERED, HWND, LPARAM, LRESULT, WIN32_ERROR, WPARAM},
+	Foundation::{
+		ERROR_HOTKEY_ALREADY_REGISTERED,
+		ERROR_INSUFFICIENT_BUFFER,
+		HANDLE,
+		HWND,
+		LPARAM,
+		LRESULT,
+		WIN32_ERROR,
+		WPARAM,
+	},
+	System::{
+		RemoteDesktop::{
+			NOTIFY_FOR_THIS_SESSION,
+			WTSRegisterSessionNotification,
+			WTSUnRegisterSessionNotification,
+		},
+		Threading::GetCurrentThreadId,
+	},
 	UI::{
-		Input::KeyboardAndMouse::*,
+		Input::{
+			GetRawInputData,
+			GetRawInputDeviceInfoW,
+			GetRawInputDeviceList,
+			HRAWINPUT,
+			KeyboardAndMouse::*,
+			RAWINPUT,
+			RAWINPUTDEVICE,
+			RAWINPUTDEVICELIST,
+			RAWINPUTHEADER,
+			RID_INPUT,
+			RIDEV_INPUTSINK,
+			RIDEV_REMOVE,
+			RIDI_DEVICENAME,
+			RIM_TYPEKEYBOARD,
+			RegisterRawInputDevices,
+		},
 		WindowsAndMessaging::{
 			CW_USEDEFAULT,
+			CallNextHookEx,
 			CreateWindowExW,
 			DefWindowProcW,
 			DestroyWindow,
+			DispatchMessageW,
+			GWLP_USERDATA,
+			GetWindowThreadProcessId,
+			HC_ACTION,
+			KBDLLHOOKSTRUCT,
+			MSG,
+			PBT_APMRESUMEAUTOMATIC,
+			PM_REMOVE,
+			PeekMessageW,
+			PostMessageW,
+			RI_KEY_BREAK,
 			RegisterClassW,
+			SendMessageW,
+			SetWindowsHookExW,
+			TranslateMessage,
+			UnhookWindowsHookEx,
+			WH_KEYBOARD_LL,
+			WM_DISPLAYCHANGE,
 			WM_HOTKEY,
+			WM_INPUT,
+			WM_KEYDOWN,
+			WM_POWERBROADCAST,
+			WM_QUIT,
+			WM_SYSKEYDOWN,
+			WM_USER,
+			WM_WTSSESSION_CHANGE,
 			WNDCLASSW,
 			WS_EX_LAYERED,
 			WS_EX_NOACTIVATE,
 			WS_EX_TOOLWINDOW,
 			WS_EX_TRANSPARENT,
 			WS_OVERLAPPED,
+			WTS_SESSION_UNLOCK,
 		},
 	},
 };
 
-use crate::{GlobalHotKeyEvent, hotkey::HotKey};
+#[cfg(target_pointer_width = "32")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+	GetWindowLongW as GetWindowLongPtrW,
+	SetWindowLongW as SetWindowLongPtrW,
+};
+#[cfg(target_pointer_width = "64")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowLongPtrW, SetWindowLongPtrW};
+
+use crate::{
+	Backend,
+	Capabilities,
+	DeviceFilter,
+	ExtensionMessage,
+	GlobalHotKeyEvent,
+	GlobalHotKeyManagerOptions,
+	HotKeyOptions,
+	HotKeyStatus,
+	KeyboardDevice,
+	ManagerStatus,
+	RecordingHandle,
+	RegisterFailureReason,
+	WindowsBackend,
+	hotkey::HotKey,
+	main_thread::{Poster, Task},
+	platform_impl::{register_until_failure, reregister},
+	raw::{RawEventHook, RawPlatformEvent},
+};
+
+pub(crate) const BACKEND:Backend = Backend::Windows;
+
+pub struct GlobalHotKeyManager {
+	hwnd:HWND,
+	state:Arc<ManagerState>,
+	// Whether the keys come from Raw Input instead of `RegisterHotKey` and the hook.
+	raw_input:bool,
+}
+
+// Shared with the window procedure through `GWLP_USERDATA`.
+struct ManagerState {
+	// Kept so hotkeys can be re-registered with the OS on resume.
+	hotkeys:Mutex<BTreeMap<u32, HotKey>>,
+	suspended:AtomicBool,
+	// Hotkeys that failed to register again when revalidating.
+	lost:Mutex<Vec<HotKey>>,
+	// Hotkeys matched by the low-level keyboard hook, or by Raw Input, whether
+	// they are pressed and whether the hook swallows their key, see
+	// `register_hooked`.
+	hooked:Mutex<BTreeMap<u32, (HotKey, bool, bool)>>,
+	// The modifiers forbidden by the hotkeys registered with some, along with
+	// the locks they require on, by id.
+	locks:Mutex<BTreeMap<u32, (Modifiers, Modifiers)>>,
+	// The keyboards of the Raw Input hotkeys restricted to some, by id.
+	devices:Mutex<BTreeMap<u32, DeviceFilter>>,
+	raw_event_hook:Mutex<Option<RawEventHook>>,
+}
+
+// The window is only used from its own thread through `on_window_thread`, the
+// rest of the state is behind mutexes.
+unsafe impl Send for GlobalHotKeyManager {}
+unsafe impl Sync for GlobalHotKeyManager {}
+
+impl Drop for GlobalHotKeyManager {
+	fn drop(&mut self) {
+		// `DestroyWindow` fails from another thread than the window's.
+		let _ = self.on_window_thread(|| {
+			unhook_keyboard(&self.state);
+
+			stop_raw_input(self.hwnd);
+
+			unsafe {
+				WTSUnRegisterSessionNotification(self.hwnd);
+
+				let state = SetWindowLongPtrW(self.hwnd, GWLP_USERDATA, 0);
+
+				DestroyWindow(self.hwnd);
+
+				if state != 0 {
+					drop(Arc::from_raw(state as *const ManagerState));
+				}
+			};
+
+			Ok(())
+		});
+	}
+}
+
+impl GlobalHotKeyManager {
+	pub fn new(options:&GlobalHotKeyManagerOptions) -> crate::Result<Self> {
+		let class_name = encode_wide("global_hotkey_app");
+
+		unsafe {
+			let hinstance = get_instance_handle();
+
+			let wnd_class = WNDCLASSW {
+				lpfnWndProc:Some(global_hotkey_proc),
+				lpszClassName:class_name.as_ptr(),
+				hInstance:hinstance,
+				..std::mem::zeroed()
+			};
+
+			RegisterClassW(&wnd_class);
+
+			let hwnd = CreateWindowExW(
+				WS_EX_NOACTIVATE | WS_EX_TRANSPARENT | WS_EX_LAYERED |
+                // WS_EX_TOOLWINDOW prevents this window from ever showing up in the taskbar, which
+                // we want to avoid. If you remove this style, this window won't show up in the
+                // taskbar *initially*, but it can show up at some later point. This can sometimes
+                // happen on its own after several hours have passed, although this has proven
+                // difficult to reproduce. Alternatively, it can be manually triggered by killing
+                // `explorer.exe` and then starting the process back up.
+                // It is unclear why the bug is triggered by waiting for several hours.
+                WS_EX_TOOLWINDOW,
+				class_name.as_ptr(),
+				ptr::null(),
+				WS_OVERLAPPED,
+				CW_USEDEFAULT,
+				0,
+				CW_USEDEFAULT,
+				0,
+				std::ptr::null_mut(),
+				std::ptr::null_mut(),
+				hinstance,
+				std::ptr::null_mut(),
+			);
+
+			if hwnd.is_null() {
+				return Err(std::io::Error::last_os_error().into());
+			}
+
+			let state = Arc::new(ManagerState {
+				hotkeys:Mutex::new(BTreeMap::new()),
+				suspended:AtomicBool::new(false),
+				lost:Mutex::new(Vec::new()),
+				hooked:Mutex::new(BTreeMap::new()),
+				locks:Mutex::new(BTreeMap::new()),
+				devices:Mutex::new(BTreeMap::new()),
+				raw_event_hook:Mutex::new(None),
+			});
+
+			let raw_input = options.windows_backend == WindowsBackend::RawInput;
+
+			if raw_input {
+				if let Err(e) = start_raw_input(hwnd, &state) {
+					DestroyWindow(hwnd);
+
+					return Err(e);
+				}
+			}
+
+			SetWindowLongPtrW(hwnd, GWLP_USERDATA, Arc::into_raw(state.clone()) as _);
+
+			// Unlocking the session is reported as `WM_WTSSESSION_CHANGE`, without it
+			// the hotkeys aren't revalidated after a user switch.
+			if WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) == 0 {
+				log::warn!(
+					"Failed to watch session changes: {}",
+					std::io::Error::last_os_error()
+				);
+			}
+
+			Ok(Self { hwnd, state, raw_input })
+		}
+	}
+
+	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> {
+		if self.raw_input {
+			return self.register_hooked(hotkey, false);
+		}
+
+		if hotkey.key == Code::PrintScreen {
+			return self.register_hooked(hotkey, true);
+		}
+
+		self.on_window_thread(|| {
+			// RegisterHotKey only knows about MOD_SHIFT and friends, which are set by
+			// either key.
+			if !hotkey.sides.is_either() {
+				return Err(crate::Error::FailedToRegister {
+					hotkey,
+					reason:RegisterFailureReason::UnsupportedModifierSides,
+				});
+			}
+
+			let mut hotkeys = self.state.hotkeys.lock().unwrap();
+
+			let hooked = self.state.hooked.lock().unwrap();
+
+			let registered = hotkeys.values().chain(hooked.values().map(|(h, ..)| h));
+
+			if hotkeys.contains_key(&hotkey.id()) || hooked.contains_key(&hotkey.id()) {
+				return Err(crate::Error::AlreadyRegistered(hotkey).with_conflict(registered));
+			}
+
+			// Hotkeys with the same keys but another id are only caught by `RegisterHotKey`.
+			if !self.state.suspended.load(Ordering::SeqCst) {
+				register_os(self.hwnd, hotkey).map_err(|e| e.with_conflict(registered))?;
+			}
+
+			drop(hooked);
+
+			self.state.lost.lock().unwrap().retain(|h| h.id() != hotkey.id());
+
+			hotkeys.insert(hotkey.id(), hotkey);
+
+			Ok(())
+		})
+	}
+
+	pub fn unregister(&self, hotkey:HotKey) -> crate::Result<()> {
+		self.on_window_thread(|| {
+			if self.unregister_hooked(hotkey.id()) {
+				return Ok(());
+			}
+
+			let mut hotkeys = self.state.hotkeys.lock().unwrap();
+
+			let mut lost = self.state.lost.lock().unwrap();
+
+			// A lost hotkey is already unregistered from the OS.
+			if lost.iter().any(|h| h.id() == hotkey.id()) {
+				lost.retain(|h| h.id() != hotkey.id());
+
+				return Ok(());
+			}
+
+			if !self.state.suspended.load(Ordering::SeqCst) {
+				let result = unsafe { UnregisterHotKey(self.hwnd, hotkey.id() as _) };
+
+				if result == 0 {
+					return Err(crate::Error::FailedToUnRegister(hotkey));
+				}
+			}
+
+			hotkeys.remove(&hotkey.id());
+
+			Ok(())
+		})?;
+
+		self.state.locks.lock().unwrap().remove(&hotkey.id());
+
+		self.state.devices.lock().unwrap().remove(&hotkey.id());
+
+		Ok(())
+	}
+
+	pub fn validate(&self, hotkey:HotKey) -> crate::Result<()> {
+		// No other application can take the keys of the keyboard hook or of Raw
+		// Input.
+		if self.raw_input || hotkey.key == Code::PrintScreen {
+			return Ok(());
+		}
+
+		self.on_window_thread(|| {
+			if !hotkey.sides.is_either() {
+				return Err(crate::Error::FailedToRegister {
+					hotkey,
+					reason:RegisterFailureReason::UnsupportedModifierSides,
+				});
+			}
+
+			// The `WM_HOTKEY` posted in between isn't handled before it's
+			// unregistered, and is then left out as its id isn't registered.
+			register_os(self.hwnd, hotkey)?;
+
+			unsafe { UnregisterHotKey(self.hwnd, hotkey.id() as _) };
+
+			Ok(())
+		})
+	}
+
+	pub fn register_all_with_options(
+		&self,
+		hotkeys:&[HotKey],
+		options:HotKeyOptions,
+	) -> Vec<crate::Result<()>> {
+		register_until_failure(hotkeys, |hotkey| {
+			self.register_with_options(hotkey, options.clone())
+		})
+	}
+
+	pub fn register_all_atomic(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
+		for (i, hotkey) in hotkeys.iter().enumerate() {
+			if let Err(e) = self.register(*hotkey) {
+				for hotkey in &hotkeys[..i] {
+					let _ = self.unregister(*hotkey);
+				}
+
+				return Err(e);
+			}
+		}
+
+		Ok(())
+	}
+
+	pub fn unregister_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
+		for hotkey in hotkeys {
+			self.unregister(*hotkey)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn clear_all(&self) -> crate::Result<()> {
+		let mut hotkeys = self.state.hotkeys.lock().unwrap().values().copied().collect::<Vec<_>>();
+
+		hotkeys.extend(self.state.hooked.lock().unwrap().values().map(|(hotkey, ..)| *hotkey));
+
+		hotkeys.extend(self.state.lost.lock().unwrap().iter().copied());
+
+		// Keep going so one failure doesn't leave the other hotkeys registered.
+		let mut result = Ok(());
+
+		for hotkey in hotkeys {
+			if let Err(e) = self.unregister(hotkey) {
+				if result.is_ok() {
+					result = Err(e);
+				}
+			}
+		}
+
+		result
+	}
+
+	pub fn suspend(&self) -> crate::Result<()> {
+		self.on_window_thread(|| {
+			let hotkeys = self.state.hotkeys.lock().unwrap();
+
+			if !self.state.suspended.swap(true, Ordering::SeqCst) {
+				for id in hotkeys.keys() {
+					unsafe { UnregisterHotKey(self.hwnd, *id as _) };
+				}
+
+				// The hook ignores suspended managers, so no release would arrive.
+				for (id, (_, pressed, _)) in self.state.hooked.lock().unwrap().iter_mut() {
+					if *pressed {
+						GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+							*id,
+							crate::HotKeyState::Released,
+						));
+						*pressed = false;
+					}
+				}
+			}
+
+			Ok(())
+		})
+	}
+
+	pub fn resume(&self) -> crate::Result<()> {
+		self.on_window_thread(|| {
+			let hotkeys = self.state.hotkeys.lock().unwrap();
+
+			let mut result = Ok(());
+
+			if self.state.suspended.swap(false, Ordering::SeqCst) {
+				// Keep going so one hotkey taken by another app doesn't disable the rest.
+				for hotkey in hotkeys.values() {
+					if let Err(e) = register_os(self.hwnd, *hotkey) {
+						if result.is_ok() {
+							result = Err(e);
+						}
+					}
+				}
+			}
+
+			result
+		})
+	}
+
+	pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> {
+		self.on_window_thread(|| Ok(self.state.revalidate(self.hwnd)))
+	}
+
+	pub fn status(&self) -> ManagerStatus {
+		let suspended = self.state.suspended.load(Ordering::SeqCst);
+
+		let status = if suspended { HotKeyStatus::Suspended } else { HotKeyStatus::Active };
+
+		let mut hotkeys = self
+			.state
+			.hotkeys
+			.lock()
+			.unwrap()
+			.values()
+			.map(|hotkey| (*hotkey, status.clone()))
+			.collect::<Vec<_>>();
+
+		// Windows removes a hook that times out without telling it, so hooked
+		// hotkeys are reported active as long as they are registered.
+		let hooked = self.state.hooked.lock().unwrap();
+
+		hotkeys.extend(hooked.values().map(|(hotkey, ..)| (*hotkey, status.clone())));
+
+		let lost = self.state.lost.lock().unwrap();
+
+		hotkeys.extend(lost.iter().map(|hotkey| (*hotkey, HotKeyStatus::LostGrab)));
+
+		let backend = if self.raw_input { Backend::WindowsRawInput } else { BACKEND };
+
+		ManagerStatus::new(backend, suspended, hotkeys)
+	}
+
+	pub fn register_with_options(&self, hotkey:HotKey, options:HotKeyOptions) -> crate::Result<()> {
+		if options.tap_hold.is_some() {
+			return Err(crate::Error::UnsupportedOption(
+				"Tap-hold hotkeys aren't supported on Windows".to_string(),
+			));
+		}
+
+		if options.device.is_some() && !self.raw_input {
+			return Err(crate::Error::UnsupportedOption(
+				"Hotkeys can only be restricted to a keyboard device with \
+				 `WindowsBackend::RawInput`"
+					.to_string(),
+			));
+		}
+
+		if options.consume && self.raw_input {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` can't swallow its key with Raw Input, register it with `consume` set to \
+				 `false`",
+				hotkey
+			)));
+		}
+
+		// `RegisterHotKey` swallows the key whatever the locks, and takes it
+		// from the hotkeys only differing in their lock requirements.
+		if options.consume && options.require_locks.is_either() {
+			self.register(hotkey)?;
+		} else {
+			self.register_hooked(hotkey, options.consume)?;
+		}
+
+		let (forbidden, required) = (options.forbidden(), options.required_locks());
+
+		if !forbidden.is_empty() || !required.is_empty() {
+			self.state.locks.lock().unwrap().insert(hotkey.id(), (forbidden, required));
+		}
+
+		if let Some(filter) = options.device {
+			self.state.devices.lock().unwrap().insert(hotkey.id(), filter);
+		}
+
+		Ok(())
+	}
+
+	// `RegisterHotKey` always swallows the key, so non-consuming hotkeys are
+	// matched by a low-level keyboard hook instead. So is `PrintScreen`, which
+	// `RegisterHotKey` only reports once released, the hook swallows it then.
+	// With Raw Input every hotkey is matched the same way, without the hook.
+	fn register_hooked(&self, hotkey:HotKey, consume:bool) -> crate::Result<()> {
+		self.on_window_thread(|| {
+			if !is_supported(&hotkey) {
+				return Err(crate::Error::FailedToRegister {
+					hotkey,
+					reason:RegisterFailureReason::UnsupportedPassthrough,
+				});
+			}
+
+			let hotkeys = self.state.hotkeys.lock().unwrap();
+
+			let mut hooked = self.state.hooked.lock().unwrap();
+
+			if hotkeys.contains_key(&hotkey.id()) || hooked.contains_key(&hotkey.id()) {
+				return Err(crate::Error::AlreadyRegistered(hotkey)
+					.with_conflict(hotkeys.values().chain(hooked.values().map(|(h, ..)| h))));
+			}
+
+			if !self.raw_input {
+				hook_keyboard(&self.state)?;
+			}
+
+			log::debug!(
+				"Registering `{}` (id {}) with {}",
+				hotkey,
+				hotkey.id(),
+				if self.raw_input { "Raw Input" } else { "the keyboard hook" }
+			);
+
+			hooked.insert(hotkey.id(), (hotkey, false, consume));
+
+			Ok(())
+		})
+	}
+
+	// Returns `false` if `id` isn't matched by the keyboard hook.
+	fn unregister_hooked(&self, id:u32) -> bool {
+		let mut hooked = self.state.hooked.lock().unwrap();
+
+		if hooked.remove(&id).is_none() {
+			return false;
+		}
+
+		if hooked.is_empty() && !self.raw_input {
+			drop(hooked);
+
+			unhook_keyboard(&self.state);
+		}
+
+		true
+	}
+
+	fn on_window_thread<R, F:FnOnce() -> crate::Result<R>>(&self, f:F) -> crate::Result<R> {
+		on_window_thread(self.hwnd, f)
+	}
+
+	pub fn start_recording(&self) -> crate::Result<RecordingHandle> {
+		Err(crate::Error::PlatformUnsupported(
+			"Recording hotkeys is not supported on Windows".to_string(),
+		))
+	}
+
+	pub fn send_raw(&self, message:ExtensionMessage) -> crate::Result<()> {
+		self.on_window_thread(|| {
+			message.run();
+
+			Ok(())
+		})
+	}
+
+	// The shell has no window manager process of its own to report.
+	pub fn window_manager(&self) -> Option<crate::ProcessInfo> { None }
+
+	pub fn set_raw_event_hook(&self, hook:RawEventHook) -> crate::Result<()> {
+		*self.state.raw_event_hook.lock().unwrap() = Some(hook);
+
+		Ok(())
+	}
+
+	pub fn main_thread_poster(&self) -> crate::Result<Poster> {
+		if unsafe { GetWindowThreadProcessId(self.hwnd, ptr::null_mut()) != GetCurrentThreadId() } {
+			return Err(crate::Error::MainThreadUnavailable(
+				"The main thread dispatcher has to be set on the thread that created the manager"
+					.to_string(),
+			));
+		}
+
+		// `HWND` isn't `Send`, posting fails once the window is destroyed.
+		let hwnd = self.hwnd as isize;
+
+		Ok(Arc::new(move |task| {
+			let task = Box::into_raw(Box::new(task));
+
+			unsafe {
+				if PostMessageW(hwnd as HWND, WM_RUN_TASK, task as WPARAM, 0) == 0 {
+					drop(Box::from_raw(task));
+				}
+			}
+		}))
+	}
+
+	pub fn capabilities(&self) -> Capabilities {
+		Capabilities {
+			sided_modifiers:false,
+			consume:!self.raw_input,
+			passthrough:true,
+			tap_hold:false,
+			device_filter:self.raw_input,
+			// Only `RegisterHotKey` has a limit.
+			max_hotkeys:(!self.raw_input).then_some(MAX_HOTKEYS),
+		}
+	}
+}
+
+impl ManagerState {
+	fn raw_event(&self, event:RawPlatformEvent) {
+		if let Some(hook) = self.raw_event_hook.lock().unwrap().as_ref() {
+			hook(event);
+		}
+	}
+
+	// Registrations can silently stop working across sleep, user switches and
+	// display changes, so start over, giving the lost hotkeys another chance.
+	// Returns the hotkeys that failed to come back.
+	fn revalidate(&self, hwnd:HWND) -> Vec<HotKey> {
+		let mut hotkeys = self.hotkeys.lock().unwrap();
+
+		let mut lost = self.lost.lock().unwrap();
+
+		hotkeys.extend(lost.drain(..).map(|hotkey| (hotkey.id(), hotkey)));
+
+		// They are registered with the OS when resuming.
+		if self.suspended.load(Ordering::SeqCst) {
+			return Vec::new();
+		}
+
+		let failed = reregister(hotkeys.values().copied(), |hotkey| {
+			unsafe { UnregisterHotKey(hwnd, hotkey.id() as _) };
+
+			register_os(hwnd, hotkey)
+		});
+
+		for hotkey in &failed {
+			hotkeys.remove(&hotkey.id());
+		}
+
+		lost.extend(failed.iter().copied());
+
+		failed
+	}
+}
+
+// Applications may only use the ids 0x0000 through 0xBFFF with
+// `RegisterHotKey`, which caps the hotkeys of a window.
+const MAX_HOTKEYS:usize = 0xC000;
+
+// Sent with a `&mut &mut dyn FnMut()` to run on the thread of the window.
+const WM_RUN_ON_WINDOW_THREAD:u32 = WM_USER;
+
+// Runs the `Task` boxed in `wparam`, posted by `main_thread_poster`. Posted
+// messages are handled in order, so the events keep theirs.
+const WM_RUN_TASK:u32 = WM_USER + 1;
+
+// Posted to the window taking Raw Input over from a dropped manager, which has
+// to register for it from its own thread.
+const WM_TAKE_RAW_INPUT:u32 = WM_USER + 2;
+
+// Runs `f` on the thread that created `hwnd`, blocking until its message loop
+// got to it. `f` and its result are never used by two threads at once.
+fn on_window_thread<R, F:FnOnce() -> crate::Result<R>>(hwnd:HWND, f:F) -> crate::Result<R> {
+	let window_thread = unsafe { GetWindowThreadProcessId(hwnd, ptr::null_mut()) };
+
+	if window_thread == unsafe { GetCurrentThreadId() } {
+		return f();
+	}
+
+	let mut f = Some(f);
+
+	let mut result = None;
+
+	let mut run = || result = f.take().map(|f| f());
+
+	let mut run:&mut dyn FnMut() = &mut run;
+
+	unsafe {
+		SendMessageW(
+			hwnd,
+			WM_RUN_ON_WINDOW_THREAD,
+			&mut run as *mut &mut dyn FnMut() as WPARAM,
+			0,
+		)
+	};
+
+	// The window is gone, or its thread exited.
+	result.unwrap_or_else(|| Err(std::io::Error::last_os_error().into()))
+}
+
+fn register_os(hwnd:HWND, hotkey:HotKey) -> crate::Result<()> {
+	let mut mods = MOD_NOREPEAT;
+
+	if hotkey.mods.contains(Modifiers::SHIFT) {
+		mods |= MOD_SHIFT;
+	}
+
+	if crate::canonical::fold(hotkey.mods).contains(Modifiers::SUPER) {
+		mods |= MOD_WIN;
+	}
+
+	if hotkey.mods.contains(Modifiers::ALT) {
+		mods |= MOD_ALT;
+	}
+
+	if hotkey.mods.contains(Modifiers::CONTROL) {
+		mods |= MOD_CONTROL;
+	}
+
+	// get key scan code
+	match hotkey_vk(&hotkey) {
+		Some(vk_code) => {
+			log::debug!(
+				"Registering `{}` (id {}) as virtual key {:#x} with mods {:#x}",
+				hotkey,
+				hotkey.id(),
+				vk_code,
+				mods
+			);
+
+			let result = unsafe { RegisterHotKey(hwnd, hotkey.id() as _, mods, vk_code as _) };
+
+			if result == 0 {
+				let error = std::io::Error::last_os_error();
+
+				log::warn!(
+					"RegisterHotKey failed for `{}` (id {}): {}",
+					hotkey,
+					hotkey.id(),
+					error
+				);
+
+				return match error.raw_os_error() {
+					Some(raw_os_error) => {
+						let win32error = WIN32_ERROR::try_from(raw_os_error);
+
+						if let Ok(ERROR_HOTKEY_ALREADY_REGISTERED) = win32error {
+							Err(crate::Error::AlreadyRegistered(hotkey))
+						} else {
+							Err(error.into())
+						}
+					},
+
+					_ => Err(error.into()),
+				};
+			}
+		},
+
+		_ => {
+			return Err(crate::Error::FailedToRegister {
+				hotkey,
+				reason:RegisterFailureReason::missing_key(hotkey.key),
+			});
+		},
+	}
+
+	Ok(())
+}
+
+unsafe extern "system" fn global_hotkey_proc(
+	hwnd:HWND,
+	msg:u32,
+	wparam:WPARAM,
+	lparam:LPARAM,
+) -> LRESULT {
+	if msg == WM_RUN_ON_WINDOW_THREAD {
+		let run = &mut *(wparam as *mut &mut dyn FnMut());
+
+		run();
+
+		return 0;
+	}
+
+	if msg == WM_RUN_TASK {
+		let task = Box::from_raw(wparam as *mut Task);
+
+		task();
+
+		return 0;
+	}
+
+	if msg == WM_TAKE_RAW_INPUT {
+		let mut sink = RAW_INPUT.lock().unwrap();
+
+		if sink.target == 0 && register_raw_input(hwnd, RIDEV_INPUTSINK).is_ok() {
+			sink.target = hwnd as isize;
+		}
+
+		return 0;
+	}
+
+	// Still handed to `DefWindowProcW`, which frees the input.
+	if msg == WM_INPUT {
+		dispatch_raw_input(lparam as HRAWINPUT);
+	}
+
+	let state = (GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const ManagerState).as_ref();
+
+	if msg == WM_HOTKEY {
+		if let Some(state) = state {
+			state.raw_event(RawPlatformEvent::WindowsHotKey { wparam, lparam });
+		}
+
+		let left_out = state
+			.is_some_and(|state| locks_mismatch(&state.locks.lock().unwrap(), wparam as _));
+
+		// Posted for a hotkey unregistered since, e.g. one probed by `validate`.
+		let registered =
+			state.is_some_and(|state| state.hotkeys.lock().unwrap().contains_key(&(wparam as u32)));
+
+		// `RegisterHotKey` already swallowed the key, only its events are left out.
+		if !left_out && registered {
+			GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+				wparam as _,
+				crate::HotKeyState::Pressed,
+			));
+
+			std::thread::spawn(move || {
+				loop {
+					let state = GetAsyncKeyState(HIWORD(lparam as u32) as i32);
+
+					if state == 0 {
+						GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+							wparam as _,
+							crate::HotKeyState::Released,
+						));
+
+						break;
+					}
+				}
+			});
+		}
+	}
+
+	let lifecycle_change = match msg {
+		WM_POWERBROADCAST => wparam == PBT_APMRESUMEAUTOMATIC as _,
+		WM_WTSSESSION_CHANGE => wparam == WTS_SESSION_UNLOCK as _,
+		WM_DISPLAYCHANGE => true,
+		_ => false,
+	};
+
+	if lifecycle_change {
+		if let Some(state) = state {
+			log::debug!("Revalidating the hotkeys after message {:#x}", msg);
+
+			state.revalidate(hwnd);
+		}
+	}
+
+	DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+// The low-level keyboard hook is shared by every manager with hooked hotkeys,
+// its procedure gets no user data so it reaches them through here.
+struct KeyboardHook {
+	handle:isize,
+	states:Vec<Weak<ManagerState>>,
+}
+
+static KEYBOARD_HOOK:Mutex<KeyboardHook> =
+	Mutex::new(KeyboardHook { handle:0, states:Vec::new() });
+
+fn hook_keyboard(state:&Arc<ManagerState>) -> crate::Result<()> {
+	let mut hook = KEYBOARD_HOOK.lock().unwrap();
+
+	if hook.handle == 0 {
+		let handle = unsafe {
+			SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), get_instance_handle(), 0)
+		};
+
+		if handle.is_null() {
+			let error = std::io::Error::last_os_error();
+
+			log::warn!("Failed to install the low-level keyboard hook: {}", error);
+
+			return Err(error.into());
+		}
+
+		log::debug!("Installed the low-level keyboard hook");
+
+		hook.handle = handle as _;
+	}
+
+	if !hook.states.iter().any(|s| ptr::eq(s.as_ptr(), Arc::as_ptr(state))) {
+		hook.states.push(Arc::downgrade(state));
+	}
+
+	Ok(())
+}
+
+fn unhook_keyboard(state:&Arc<ManagerState>) {
+	let mut hook = KEYBOARD_HOOK.lock().unwrap();
+
+	hook.states.retain(|s| s.strong_count() > 0 && !ptr::eq(s.as_ptr(), Arc::as_ptr(state)));
+
+	if hook.states.is_empty() && hook.handle != 0 {
+		unsafe { UnhookWindowsHookEx(hook.handle as _) };
+
+		log::debug!("Removed the low-level keyboard hook");
+
+		hook.handle = 0;
+	}
+}
+
+unsafe extern "system" fn keyboard_hook_proc(code:i32, wparam:WPARAM, lparam:LPARAM) -> LRESULT {
+	if code == HC_ACTION as i32 {
+		let event = &*(lparam as *const KBDLLHOOKSTRUCT);
+
+		let is_key_down = matches!(wparam as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+
+		let states = KEYBOARD_HOOK.lock().unwrap().states.clone();
+
+		let mut swallow = false;
+
+		for state in states.iter().filter_map(Weak::upgrade) {
+			state.raw_event(RawPlatformEvent::WindowsKeyboardHook { wparam, event:*event });
+
+			if !state.suspended.load(Ordering::SeqCst) {
+				swallow |= dispatch_hooked(
+					&mut state.hooked.lock().unwrap(),
+					&state.locks.lock().unwrap(),
+					is_key_down,
+					event.vkCode,
+					|_| true,
+				);
+			}
+		}
+
+		if swallow {
+			return 1;
+		}
+	}
 
-pub struct GlobalHotKeyManager {
-	hwnd:HWND,
+	// Pass the key on, that's what makes these hotkeys non-consuming.
+	CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
 }
 
-impl Drop for GlobalHotKeyManager {
-	fn drop(&mut self) { unsafe { DestroyWindow(self.hwnd) }; }
+// The Raw Input of the process goes to a single window, shared by every
+// manager in Raw Input mode like the keyboard hook.
+struct RawInputSink {
+	// The window receiving `WM_INPUT`, `0` if none.
+	target:isize,
+	// The managers in Raw Input mode, with their window.
+	states:Vec<(isize, Weak<ManagerState>)>,
 }
 
-impl GlobalHotKeyManager {
-	pub fn new() -> crate::Result<Self> {
-		let class_name = encode_wide("global_hotkey_app");
+static RAW_INPUT:Mutex<RawInputSink> = Mutex::new(RawInputSink { target:0, states:Vec::new() });
 
-		unsafe {
-			let hinstance = get_instance_handle();
+// The HID usage of keyboards.
+const HID_USAGE_PAGE_GENERIC:u16 = 0x01;
 
-			let wnd_class = WNDCLASSW {
-				lpfnWndProc:Some(global_hotkey_proc),
-				lpszClassName:class_name.as_ptr(),
-				hInstance:hinstance,
-				..std::mem::zeroed()
-			};
+const HID_USAGE_GENERIC_KEYBOARD:u16 = 0x06;
 
-			RegisterClassW(&wnd_class);
+fn register_raw_input(hwnd:HWND, flags:u32) -> crate::Result<()> {
+	let device = RAWINPUTDEVICE {
+		usUsagePage:HID_USAGE_PAGE_GENERIC,
+		usUsage:HID_USAGE_GENERIC_KEYBOARD,
+		dwFlags:flags,
+		hwndTarget:hwnd,
+	};
 
-			let hwnd = CreateWindowExW(
-				WS_EX_NOACTIVATE | WS_EX_TRANSPARENT | WS_EX_LAYERED |
-                // WS_EX_TOOLWINDOW prevents this window from ever showing up in the taskbar, which
-                // we want to avoid. If you remove this style, this window won't show up in the
-                // taskbar *initially*, but it can show up at some later point. This can sometimes
-                // happen on its own after several hours have passed, although this has proven
-                // difficult to reproduce. Alternatively, it can be manually triggered by killing
-                // `explorer.exe` and then starting the process back up.
-                // It is unclear why the bug is triggered by waiting for several hours.
-                WS_EX_TOOLWINDOW,
-				class_name.as_ptr(),
-				ptr::null(),
-				WS_OVERLAPPED,
-				CW_USEDEFAULT,
-				0,
-				CW_USEDEFAULT,
-				0,
-				std::ptr::null_mut(),
-				std::ptr::null_mut(),
-				hinstance,
-				std::ptr::null_mut(),
-			);
+	let size = std::mem::size_of::<RAWINPUTDEVICE>() as u32;
 
-			if hwnd.is_null() {
-				return Err(crate::Error::OsError(std::io::Error::last_os_error()));
-			}
+	if unsafe { RegisterRawInputDevices(&device, 1, size) } == 0 {
+		let error = std::io::Error::last_os_error();
 
-			Ok(Self { hwnd })
-		}
+		log::warn!("Failed to register for Raw Input: {}", error);
+
+		return Err(error.into());
 	}
 
-	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> {
-		let mut mods = MOD_NOREPEAT;
+	Ok(())
+}
 
-		if hotkey.mods.contains(Modifiers::SHIFT) {
-			mods |= MOD_SHIFT;
-		}
+fn start_raw_input(hwnd:HWND, state:&Arc<ManagerState>) -> crate::Result<()> {
+	let mut sink = RAW_INPUT.lock().unwrap();
+
+	if sink.target == 0 {
+		// Received in the background too, hotkeys are global.
+		register_raw_input(hwnd, RIDEV_INPUTSINK)?;
+
+		log::debug!("Receiving Raw Input on window {:#x}", hwnd as isize);
+
+		sink.target = hwnd as isize;
+	}
+
+	sink.states.push((hwnd as isize, Arc::downgrade(state)));
+
+	Ok(())
+}
+
+fn stop_raw_input(hwnd:HWND) {
+	let mut sink = RAW_INPUT.lock().unwrap();
+
+	sink.states.retain(|(window, state)| *window != hwnd as isize && state.strong_count() > 0);
+
+	if sink.target != hwnd as isize {
+		return;
+	}
+
+	// Removing takes no window.
+	let _ = register_raw_input(ptr::null_mut(), RIDEV_REMOVE);
+
+	sink.target = 0;
+
+	if let Some((window, _)) = sink.states.first() {
+		unsafe { PostMessageW(*window as HWND, WM_TAKE_RAW_INPUT, 0, 0) };
+	}
+}
+
+// Matches a key received through Raw Input against the hotkeys of every
+// manager in Raw Input mode, none of them swallows it.
+unsafe fn dispatch_raw_input(handle:HRAWINPUT) {
+	let mut input:RAWINPUT = std::mem::zeroed();
+
+	let mut size = std::mem::size_of::<RAWINPUT>() as u32;
+
+	let header_size = std::mem::size_of::<RAWINPUTHEADER>() as u32;
 
-		if hotkey.mods.intersects(Modifiers::SUPER | Modifiers::META) {
-			mods |= MOD_WIN;
+	let data = &mut input as *mut RAWINPUT as _;
+
+	let read = GetRawInputData(handle, RID_INPUT, data, &mut size, header_size);
+
+	if read == u32::MAX || input.header.dwType != RIM_TYPEKEYBOARD {
+		return;
+	}
+
+	let (device, keyboard) = (input.header.hDevice, input.data.keyboard);
+
+	let is_key_down = keyboard.Flags as u32 & RI_KEY_BREAK == 0;
+
+	let states = RAW_INPUT.lock().unwrap().states.clone();
+
+	// Only looked up for the hotkeys restricted to some keyboards.
+	let mut keyboard_device = None;
+
+	for state in states.iter().filter_map(|(_, state)| state.upgrade()) {
+		state.raw_event(RawPlatformEvent::WindowsRawInput { device:device as isize, keyboard });
+
+		if state.suspended.load(Ordering::SeqCst) {
+			continue;
 		}
 
-		if hotkey.mods.contains(Modifiers::ALT) {
-			mods |= MOD_ALT;
+		let devices = state.devices.lock().unwrap();
+
+		dispatch_hooked(
+			&mut state.hooked.lock().unwrap(),
+			&state.locks.lock().unwrap(),
+			is_key_down,
+			keyboard.VKey.into(),
+			|id| {
+				devices.get(&id).map_or(true, |filter| {
+					keyboard_device
+						.get_or_insert_with(|| raw_input_device(device))
+						.as_ref()
+						.is_some_and(|keyboard_device| filter.matches(keyboard_device))
+				})
+			},
+		);
+	}
+}
+
+// The keyboard behind a Raw Input device handle, `None` if it's gone.
+fn raw_input_device(device:HANDLE) -> Option<KeyboardDevice> {
+	let mut len = 0;
+
+	unsafe { GetRawInputDeviceInfoW(device, RIDI_DEVICENAME, ptr::null_mut(), &mut len) };
+
+	let mut path = vec![0u16; len as usize];
+
+	let read = unsafe {
+		GetRawInputDeviceInfoW(device, RIDI_DEVICENAME, path.as_mut_ptr() as _, &mut len)
+	};
+
+	if read == u32::MAX || read == 0 {
+		return None;
+	}
+
+	let id = String::from_utf16_lossy(&path[..read as usize]).trim_end_matches('\0').to_string();
+
+	let (vendor_id, product_id) = crate::device::usb_ids(&id);
+
+	Some(KeyboardDevice { name:id.clone(), id, vendor_id, product_id })
+}
+
+pub(crate) fn keyboard_devices() -> crate::Result<Vec<KeyboardDevice>> {
+	let size = std::mem::size_of::<RAWINPUTDEVICELIST>() as u32;
+
+	loop {
+		let mut count = 0;
+
+		if unsafe { GetRawInputDeviceList(ptr::null_mut(), &mut count, size) } == u32::MAX {
+			return Err(std::io::Error::last_os_error().into());
 		}
 
-		if hotkey.mods.contains(Modifiers::CONTROL) {
-			mods |= MOD_CONTROL;
+		let mut list = Vec::<RAWINPUTDEVICELIST>::with_capacity(count as usize);
+
+		let read = unsafe { GetRawInputDeviceList(list.as_mut_ptr(), &mut count, size) };
+
+		if read == u32::MAX {
+			let error = std::io::Error::last_os_error();
+
+			// A device was plugged in between, the list is read again.
+			if error.raw_os_error() == Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+				continue;
+			}
+
+			return Err(error.into());
 		}
 
-		// get key scan code
-		match key_to_vk(&hotkey.key) {
-			Some(vk_code) => {
-				let result =
-					unsafe { RegisterHotKey(self.hwnd, hotkey.id() as _, mods, vk_code as _) };
+		unsafe { list.set_len(read as usize) };
 
-				if result == 0 {
-					let error = std::io::Error::last_os_error();
+		return Ok(list
+			.iter()
+			.filter(|device| device.dwType == RIM_TYPEKEYBOARD)
+			.filter_map(|device| raw_input_device(device.hDevice))
+			.collect());
+	}
+}
 
-					return match error.raw_os_error() {
-						Some(raw_os_error) => {
-							let win32error = WIN32_ERROR::try_from(raw_os_error);
+// Returns `true` if the key belongs to a consuming hotkey, which the hook
+// swallows from its press to its release. Only the hotkeys `accepts` the id
+// of are matched.
+fn dispatch_hooked(
+	hotkeys:&mut BTreeMap<u32, (HotKey, bool, bool)>,
+	locks:&BTreeMap<u32, (Modifiers, Modifiers)>,
+	is_key_down:bool,
+	vk_code:u32,
+	mut accepts:impl FnMut(u32) -> bool,
+) -> bool {
+	let mut held = None;
 
-							if let Ok(ERROR_HOTKEY_ALREADY_REGISTERED) = win32error {
-								Err(crate::Error::AlreadyRegistered(hotkey))
-							} else {
-								Err(crate::Error::OsError(error))
-							}
-						},
+	let mut swallow = false;
 
-						_ => Err(crate::Error::OsError(error)),
-					};
-				}
+	for (id, (hotkey, pressed, consume)) in hotkeys.iter_mut() {
+		if hotkey_vk(hotkey).map(u32::from) != Some(vk_code) || !accepts(*id) {
+			continue;
+		}
+
+		match is_key_down {
+			// Auto-repeat of a hotkey already reported as pressed.
+			true if *pressed => swallow |= *consume,
+
+			true if *held.get_or_insert_with(held_modifiers) == hotkey_modifiers(hotkey.mods)
+				&& !locks_mismatch(locks, *id) =>
+			{
+				GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(*id, crate::HotKeyState::Pressed));
+				*pressed = true;
+
+				swallow |= *consume;
 			},
 
-			_ => {
-				return Err(crate::Error::FailedToRegister(format!(
-					"Unable to register hotkey (unknown VKCode for this key: {}).",
-					hotkey.key
-				)));
+			false if *pressed => {
+				GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(*id, crate::HotKeyState::Released));
+				*pressed = false;
+
+				swallow |= *consume;
 			},
-		}
 
-		Ok(())
+			_ => {},
+		}
 	}
 
-	pub fn unregister(&self, hotkey:HotKey) -> crate::Result<()> {
-		let result = unsafe { UnregisterHotKey(self.hwnd, hotkey.id() as _) };
+	swallow
+}
 
-		if result == 0 {
-			return Err(crate::Error::FailedToUnRegister(hotkey));
-		}
+// The modifiers held before the key being processed by the hook.
+fn held_modifiers() -> Modifiers {
+	let is_down = |vk:VIRTUAL_KEY| unsafe { GetAsyncKeyState(vk as i32) } as u16 & 0x8000 != 0;
 
-		Ok(())
-	}
+	let mut mods = Modifiers::empty();
 
-	pub fn register_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		for hotkey in hotkeys {
-			self.register(*hotkey)?;
-		}
+	if is_down(VK_SHIFT) {
+		mods |= Modifiers::SHIFT;
+	}
 
-		Ok(())
+	if is_down(VK_LWIN) || is_down(VK_RWIN) {
+		mods |= Modifiers::SUPER;
 	}
 
-	pub fn unregister_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		for hotkey in hotkeys {
-			self.unregister(*hotkey)?;
-		}
+	if is_down(VK_MENU) {
+		mods |= Modifiers::ALT;
+	}
 
-		Ok(())
+	if is_down(VK_CONTROL) {
+		mods |= Modifiers::CONTROL;
 	}
+
+	mods
 }
-unsafe extern "system" fn global_hotkey_proc(
-	hwnd:HWND,
-	msg:u32,
-	wparam:WPARAM,
-	lparam:LPARAM,
-) -> LRESULT {
-	if msg == WM_HOTKEY {
-		GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-			id:wparam as _,
-			state:crate::HotKeyState::Pressed,
-		});
 
-		std::thread::spawn(move || {
-			loop {
-				let state = GetAsyncKeyState(HIWORD(lparam as u32) as i32);
+// Whether a lock forbidden by the hotkey with this id is toggled on, or one
+// it requires is off. Its other forbidden modifiers never match, hotkeys
+// require their modifiers exactly.
+fn locks_mismatch(locks:&BTreeMap<u32, (Modifiers, Modifiers)>, id:u32) -> bool {
+	locks.get(&id).is_some_and(|(forbidden, required)| {
+		let toggled = toggled_locks();
 
-				if state == 0 {
-					GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-						id:wparam as _,
-						state:crate::HotKeyState::Released,
-					});
+		forbidden.intersects(toggled) || !toggled.contains(*required)
+	})
+}
 
-					break;
-				}
-			}
-		});
+// The locks toggled on, which hotkeys otherwise ignore.
+fn toggled_locks() -> Modifiers {
+	let is_on = |vk:VIRTUAL_KEY| unsafe { GetKeyState(vk as i32) } & 1 != 0;
+
+	let mut mods = Modifiers::empty();
+
+	if is_on(VK_CAPITAL) {
+		mods |= Modifiers::CAPS_LOCK;
 	}
 
-	DefWindowProcW(hwnd, msg, wparam, lparam)
+	if is_on(VK_NUMLOCK) {
+		mods |= Modifiers::NUM_LOCK;
+	}
+
+	mods
+}
+
+// `META` is the same key as `SUPER` on Windows, see `register_os`.
+fn hotkey_modifiers(mods:Modifiers) -> Modifiers {
+	crate::canonical::fold(mods)
+		& (Modifiers::SHIFT | Modifiers::ALT | Modifiers::CONTROL | Modifiers::SUPER)
 }
 
 #[inline(always)]
@@ -220,7 +1316,93 @@ pub fn get_instance_handle() -> windows_sys::Win32::Foundation::HMODULE {
 	unsafe { &__ImageBase as *const _ as _ }
 }
 
+pub fn pump_messages() -> bool {
+	unsafe {
+		let mut msg:MSG = std::mem::zeroed();
+
+		while PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+			if msg.message == WM_QUIT {
+				return false;
+			}
+
+			TranslateMessage(&msg);
+
+			DispatchMessageW(&msg);
+		}
+	}
+
+	true
+}
+
+#[cfg(feature = "windows-integration-test")]
+pub fn synthesize_key_press(hotkey:&HotKey) -> crate::Result<()> {
+	use crate::hotkey::ModifierSide;
+
+	let Some(vk) = key_to_vk(&hotkey.key) else {
+		return Err(crate::Error::UnrecognizedHotKeyCode(hotkey.key.to_string()));
+	};
+
+	let mut keys = Vec::new();
+
+	for (modifier, left, right) in [
+		(Modifiers::CONTROL, VK_LCONTROL, VK_RCONTROL),
+		(Modifiers::SHIFT, VK_LSHIFT, VK_RSHIFT),
+		(Modifiers::ALT, VK_LMENU, VK_RMENU),
+		(Modifiers::SUPER, VK_LWIN, VK_RWIN),
+	] {
+		if hotkey.mods.contains(modifier) {
+			match hotkey.sides.side(modifier) {
+				ModifierSide::Right => keys.push(right),
+				ModifierSide::Either | ModifierSide::Left => keys.push(left),
+			}
+		}
+	}
+
+	keys.push(vk);
+
+	let input = |vk:VIRTUAL_KEY, flags:KEYBD_EVENT_FLAGS| {
+		INPUT {
+			r#type:INPUT_KEYBOARD,
+			Anonymous:INPUT_0 {
+				ki:KEYBDINPUT { wVk:vk, wScan:0, dwFlags:flags, time:0, dwExtraInfo:0 },
+			},
+		}
+	};
+
+	// Modifiers first, released in reverse order after the key.
+	let inputs = keys
+		.iter()
+		.map(|vk| input(*vk, 0))
+		.chain(keys.iter().rev().map(|vk| input(*vk, KEYEVENTF_KEYUP)))
+		.collect::<Vec<_>>();
+
+	let size = std::mem::size_of::<INPUT>() as i32;
+
+	let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), size) };
+
+	// Fewer events are inserted when another thread blocks the input, e.g. UIPI.
+	if sent as usize != inputs.len() {
+		return Err(std::io::Error::last_os_error().into());
+	}
+
+	Ok(())
+}
+
+pub fn is_supported(hotkey:&HotKey) -> bool {
+	hotkey.sides.is_either() && key_to_vk(&hotkey.key).is_some()
+}
+
+// `Control+Pause` produces `VK_CANCEL` rather than `VK_PAUSE`.
+fn hotkey_vk(hotkey:&HotKey) -> Option<VIRTUAL_KEY> {
+	if hotkey.key == Code::Pause && hotkey.mods.contains(Modifiers::CONTROL) {
+		return Some(VK_CANCEL);
+	}
+
+	key_to_vk(&hotkey.key)
+}
+
 // used to build accelerators table from Key
+
 fn key_to_vk(key:&Code) -> Option<VIRTUAL_KEY> {
 	Some(match key {
 		Code::KeyA => VK_A,
@@ -323,6 +1505,7 @@ fn key_to_vk(key:&Code) -> Option<VIRTUAL_KEY> {
 		Code::Numpad8 => VK_NUMPAD8,
 		Code::Numpad9 => VK_NUMPAD9,
 		Code::NumpadAdd => VK_ADD,
+		Code::NumpadComma => VK_SEPARATOR,
 		Code::NumpadDecimal => VK_DECIMAL,
 		Code::NumpadDivide => VK_DIVIDE,
 		Code::NumpadEnter => VK_RETURN,
@@ -340,6 +1523,316 @@ fn key_to_vk(key:&Code) -> Option<VIRTUAL_KEY> {
 		Code::MediaTrackNext => VK_MEDIA_NEXT_TRACK,
 		Code::MediaTrackPrevious => VK_MEDIA_PREV_TRACK,
 		Code::Pause => VK_PAUSE,
+		Code::ContextMenu => VK_APPS,
+		Code::IntlBackslash => return scancode_to_vk(SCANCODE_INTL_BACKSLASH),
+		Code::IntlRo => return scancode_to_vk(SCANCODE_INTL_RO),
+		Code::IntlYen => return scancode_to_vk(SCANCODE_INTL_YEN),
+		_ => return None,
+	})
+}
+
+// The extra keys of ISO and Japanese keyboards get a different virtual-key
+// code with every layout (e.g. `VK_OEM_102` is the key left of Z on ISO
+// layouts and the Ro key on Japanese ones), so they're resolved from their
+// scan code with the current layout.
+const SCANCODE_INTL_BACKSLASH:u32 = 0x56;
+
+const SCANCODE_INTL_RO:u32 = 0x73;
+
+const SCANCODE_INTL_YEN:u32 = 0x7d;
+
+fn scancode_to_vk(scancode:u32) -> Option<VIRTUAL_KEY> {
+	let vk = unsafe { MapVirtualKeyW(scancode, MAPVK_VSC_TO_VK_EX) };
+
+	VIRTUAL_KEY::try_from(vk).ok().filter(|vk| *vk != 0)
+}
+
+// The inverse of `key_to_vk`, keys sharing a virtual-key code map to the main one.
+pub(crate) fn vk_to_key(vk:VIRTUAL_KEY) -> Option<Code> {
+	Some(match vk {
+		VK_A => Code::KeyA,
+		VK_B => Code::KeyB,
+		VK_C => Code::KeyC,
+		VK_D => Code::KeyD,
+		VK_E => Code::KeyE,
+		VK_F => Code::KeyF,
+		VK_G => Code::KeyG,
+		VK_H => Code::KeyH,
+		VK_I => Code::KeyI,
+		VK_J => Code::KeyJ,
+		VK_K => Code::KeyK,
+		VK_L => Code::KeyL,
+		VK_M => Code::KeyM,
+		VK_N => Code::KeyN,
+		VK_O => Code::KeyO,
+		VK_P => Code::KeyP,
+		VK_Q => Code::KeyQ,
+		VK_R => Code::KeyR,
+		VK_S => Code::KeyS,
+		VK_T => Code::KeyT,
+		VK_U => Code::KeyU,
+		VK_V => Code::KeyV,
+		VK_W => Code::KeyW,
+		VK_X => Code::KeyX,
+		VK_Y => Code::KeyY,
+		VK_Z => Code::KeyZ,
+		VK_0 => Code::Digit0,
+		VK_1 => Code::Digit1,
+		VK_2 => Code::Digit2,
+		VK_3 => Code::Digit3,
+		VK_4 => Code::Digit4,
+		VK_5 => Code::Digit5,
+		VK_6 => Code::Digit6,
+		VK_7 => Code::Digit7,
+		VK_8 => Code::Digit8,
+		VK_9 => Code::Digit9,
+		VK_OEM_PLUS => Code::Equal,
+		VK_OEM_COMMA => Code::Comma,
+		VK_OEM_MINUS => Code::Minus,
+		VK_OEM_PERIOD => Code::Period,
+		VK_OEM_1 => Code::Semicolon,
+		VK_OEM_2 => Code::Slash,
+		VK_OEM_3 => Code::Backquote,
+		VK_OEM_4 => Code::BracketLeft,
+		VK_OEM_5 => Code::Backslash,
+		VK_OEM_6 => Code::BracketRight,
+		VK_OEM_7 => Code::Quote,
+		VK_BACK => Code::Backspace,
+		VK_TAB => Code::Tab,
+		VK_SPACE => Code::Space,
+		VK_RETURN => Code::Enter,
+		VK_CAPITAL => Code::CapsLock,
+		VK_ESCAPE => Code::Escape,
+		VK_PRIOR => Code::PageUp,
+		VK_NEXT => Code::PageDown,
+		VK_END => Code::End,
+		VK_HOME => Code::Home,
+		VK_LEFT => Code::ArrowLeft,
+		VK_UP => Code::ArrowUp,
+		VK_RIGHT => Code::ArrowRight,
+		VK_DOWN => Code::ArrowDown,
+		VK_SNAPSHOT => Code::PrintScreen,
+		VK_INSERT => Code::Insert,
+		VK_DELETE => Code::Delete,
+		VK_F1 => Code::F1,
+		VK_F2 => Code::F2,
+		VK_F3 => Code::F3,
+		VK_F4 => Code::F4,
+		VK_F5 => Code::F5,
+		VK_F6 => Code::F6,
+		VK_F7 => Code::F7,
+		VK_F8 => Code::F8,
+		VK_F9 => Code::F9,
+		VK_F10 => Code::F10,
+		VK_F11 => Code::F11,
+		VK_F12 => Code::F12,
+		VK_F13 => Code::F13,
+		VK_F14 => Code::F14,
+		VK_F15 => Code::F15,
+		VK_F16 => Code::F16,
+		VK_F17 => Code::F17,
+		VK_F18 => Code::F18,
+		VK_F19 => Code::F19,
+		VK_F20 => Code::F20,
+		VK_F21 => Code::F21,
+		VK_F22 => Code::F22,
+		VK_F23 => Code::F23,
+		VK_F24 => Code::F24,
+		VK_NUMLOCK => Code::NumLock,
+		VK_NUMPAD0 => Code::Numpad0,
+		VK_NUMPAD1 => Code::Numpad1,
+		VK_NUMPAD2 => Code::Numpad2,
+		VK_NUMPAD3 => Code::Numpad3,
+		VK_NUMPAD4 => Code::Numpad4,
+		VK_NUMPAD5 => Code::Numpad5,
+		VK_NUMPAD6 => Code::Numpad6,
+		VK_NUMPAD7 => Code::Numpad7,
+		VK_NUMPAD8 => Code::Numpad8,
+		VK_NUMPAD9 => Code::Numpad9,
+		VK_ADD => Code::NumpadAdd,
+		VK_SEPARATOR => Code::NumpadComma,
+		VK_DECIMAL => Code::NumpadDecimal,
+		VK_DIVIDE => Code::NumpadDivide,
+		VK_MULTIPLY => Code::NumpadMultiply,
+		VK_SUBTRACT => Code::NumpadSubtract,
+		VK_SCROLL => Code::ScrollLock,
+		VK_VOLUME_DOWN => Code::AudioVolumeDown,
+		VK_VOLUME_UP => Code::AudioVolumeUp,
+		VK_VOLUME_MUTE => Code::AudioVolumeMute,
+		VK_PLAY => Code::MediaPlay,
+		VK_MEDIA_PLAY_PAUSE => Code::MediaPlayPause,
+		VK_MEDIA_STOP => Code::MediaStop,
+		VK_MEDIA_NEXT_TRACK => Code::MediaTrackNext,
+		VK_MEDIA_PREV_TRACK => Code::MediaTrackPrevious,
+		VK_PAUSE => Code::Pause,
+		VK_APPS => Code::ContextMenu,
+		vk if scancode_to_vk(SCANCODE_INTL_BACKSLASH) == Some(vk) => Code::IntlBackslash,
+		vk if scancode_to_vk(SCANCODE_INTL_RO) == Some(vk) => Code::IntlRo,
+		vk if scancode_to_vk(SCANCODE_INTL_YEN) == Some(vk) => Code::IntlYen,
 		_ => return None,
 	})
 }
+
+#[test]
+fn test_vk_round_trip() {
+	for vk in 0..=u16::MAX {
+		if let Some(key) = vk_to_key(vk) {
+			assert_eq!(key_to_vk(&key), Some(vk), "{:?}", key);
+		}
+	}
+}
+
+#[test]
+fn test_special_keys() {
+	let print = HotKey::new(None, Code::PrintScreen).with_id(0x7f00_0100);
+
+	let passthrough = print.with_id(0x7f00_0101);
+
+	let mut hooked = BTreeMap::from([
+		(print.id(), (print, false, true)),
+		(passthrough.id(), (passthrough, false, false)),
+	]);
+
+	let locks = BTreeMap::new();
+
+	// Swallowed from press to release for the consuming hotkey, assuming no
+	// modifier is held while the test runs.
+	assert!(dispatch_hooked(&mut hooked, &locks, true, u32::from(VK_SNAPSHOT)));
+
+	assert!(hooked.values().all(|(_, pressed, _)| *pressed));
+
+	assert!(dispatch_hooked(&mut hooked, &locks, false, u32::from(VK_SNAPSHOT)));
+
+	assert!(!dispatch_hooked(&mut hooked, &locks, true, u32::from(VK_SPACE)));
+
+	hooked.remove(&print.id());
+
+	assert!(!dispatch_hooked(&mut hooked, &locks, true, u32::from(VK_SNAPSHOT)));
+
+	let control = Some(Modifiers::CONTROL);
+
+	assert_eq!(hotkey_vk(&HotKey::new(control, Code::Pause)), Some(VK_CANCEL));
+
+	assert_eq!(hotkey_vk(&HotKey::new(None, Code::Pause)), Some(VK_PAUSE));
+
+	assert_eq!(hotkey_vk(&HotKey::new(control, Code::ContextMenu)), Some(VK_APPS));
+}
+
+#[test]
+fn test_concurrent_calls() {
+	use std::time::{Duration, Instant};
+
+	let manager = Arc::new(crate::GlobalHotKeyManager::new().unwrap());
+
+	let (done_tx, done_rx) = crossbeam_channel::unbounded();
+
+	let keys = [
+		Code::F13,
+		Code::F14,
+		Code::F15,
+		Code::F16,
+		Code::F17,
+		Code::F18,
+		Code::F19,
+		Code::F20,
+	];
+
+	for key in keys {
+		let (manager, done_tx) = (manager.clone(), done_tx.clone());
+
+		std::thread::spawn(move || {
+			let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), key);
+
+			for _ in 0..25 {
+				if manager.register(hotkey).is_ok() {
+					manager.unregister(hotkey).unwrap();
+				}
+			}
+
+			done_tx.send(()).unwrap();
+		});
+	}
+
+	// The calls are run by the message loop of this thread, which owns the window.
+	let deadline = Instant::now() + Duration::from_secs(60);
+
+	let mut done = 0;
+
+	while done < keys.len() {
+		assert!(Instant::now() < deadline, "a thread never got its answer");
+
+		crate::platform::windows::pump_messages();
+
+		done += done_rx.try_iter().count();
+	}
+}
+
+#[test]
+fn test_raw_input_backend() {
+	let options = GlobalHotKeyManagerOptions::new().windows_backend(WindowsBackend::RawInput);
+
+	let manager = crate::GlobalHotKeyManager::with_options(options).unwrap();
+
+	let capabilities = manager.capabilities();
+
+	assert!(!capabilities.consume && capabilities.device_filter);
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F22);
+
+	assert!(matches!(
+		manager.register_with_options(hotkey, HotKeyOptions::new()),
+		Err(crate::Error::UnsupportedOption(_))
+	));
+
+	let pedal = HotKeyOptions::new().consume(false).device(DeviceFilter::UsbId {
+		vendor:0x0426,
+		product:0x3011,
+	});
+
+	manager.register_with_options(hotkey, pedal).unwrap();
+
+	assert_eq!(manager.status().backend, Backend::WindowsRawInput);
+
+	manager.unregister(hotkey).unwrap();
+
+	// Observed, so it's never taken from another manager.
+	manager.register(hotkey).unwrap();
+
+	manager.unregister(hotkey).unwrap();
+
+	assert!(crate::GlobalHotKeyManager::keyboard_devices().is_ok());
+}
+
+#[cfg(feature = "windows-integration-test")]
+#[test]
+fn test_synthesized_hotkey() {
+	use std::time::{Duration, Instant};
+
+	let manager = crate::GlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F21);
+
+	manager.register(hotkey).unwrap();
+
+	crate::platform::windows::synthesize_key_press(&hotkey).unwrap();
+
+	let deadline = Instant::now() + Duration::from_secs(5);
+
+	// The events are posted to the window of this thread, other tests may
+	// receive theirs from the same channel.
+	loop {
+		assert!(Instant::now() < deadline, "the synthesized hotkey never fired");
+
+		crate::platform::windows::pump_messages();
+
+		let mut events = crate::GlobalHotKeyEvent::event_receiver().try_iter();
+
+		if events.any(|event| event.id() == hotkey.id()) {
+			break;
+		}
+
+		std::thread::sleep(Duration::from_millis(10));
+	}
+
+	manager.unregister(hotkey).unwrap();
+}
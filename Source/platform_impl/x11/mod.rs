@@ -2,91 +2,384 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::{collections::BTreeMap, ffi::c_ulong, ptr};
+use std::{
+	collections::BTreeMap,
+	ffi::c_ulong,
+	sync::{Arc, Mutex, MutexGuard},
+	time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use keyboard_types::{Code, KeyState, Modifiers};
+use x11_dl::{keysym, xlib};
+
+use crate::{
+	Capabilities,
+	ExtensionMessage,
+	GlobalHotKeyEvent,
+	GlobalHotKeyManagerOptions,
+	HotKeyOptions,
+	HotKeyStatus,
+	LayoutChangedEvent,
+	ManagerStatus,
+	ProcessInfo,
+	RecordingHandle,
+	RegisterFailureReason,
+	gate,
+	hotkey::{HotKey, ModifierSide, ModifierSides},
+	raw::{RawEventHook, RawPlatformEvent},
+	recorder::{self, Recorder},
+	tap_hold::{Gesture, Next},
+};
+
+#[cfg(not(feature = "x11rb"))]
+#[path = "xlib.rs"]
+mod display;
+#[cfg(feature = "x11rb")]
+#[path = "x11rb.rs"]
+mod display;
 
-use crossbeam_channel::{Receiver, Sender, unbounded};
-use keyboard_types::{Code, Modifiers};
-use x11_dl::{
-	keysym,
-	xlib::{self, _XDisplay, Xlib},
+mod reply;
+
+pub(crate) use self::display::BACKEND;
+use self::{
+	display::Display,
+	reply::{Mailbox, Reply, ReplyTo},
 };
+use super::registered::{RegisteredEntries, RegisteredEntry};
+
+// What the event loop needs from the X server, see `Display::next_event`.
+enum Event {
+	Key { state:KeyState, keycode:u32, mods:u32, time:u32, raw:RawPlatformEvent },
+	// Keysyms moved to other keycodes, the mapping is already refreshed.
+	LayoutChanged,
+	// The connection to the X server is gone, no more events will come. Xlib
+	// exits the process instead, so only `x11rb` reports it.
+	#[cfg_attr(not(feature = "x11rb"), allow(dead_code))]
+	Disconnected(crate::Error),
+}
+
+// The window the hotkeys are grabbed on, see `GlobalHotKeyManagerOptions`.
+#[derive(Debug, Clone, Copy)]
+enum Root {
+	// The root window of a screen, the default one if `None`.
+	Screen(Option<i32>),
+	Window(c_ulong),
+}
 
-use crate::{GlobalHotKeyEvent, hotkey::HotKey};
+impl Root {
+	fn new(options:&GlobalHotKeyManagerOptions) -> Self {
+		match options.root_window_override {
+			Some(window) => Self::Window(window),
+			None => Self::Screen(options.screen_number),
+		}
+	}
+
+	fn no_screen(screen:i32) -> crate::Error {
+		crate::Error::os(0, format!("The X server has no screen {}", screen))
+	}
+}
 
+// The requests to the event thread, each answered with the `Reply` noted,
+// `Reply::One` if none is.
 enum ThreadMessage {
-	RegisterHotKey(HotKey, Sender<crate::Result<()>>),
-	RegisterHotKeys(Vec<HotKey>, Sender<crate::Result<()>>),
-	UnRegisterHotKey(HotKey, Sender<crate::Result<()>>),
-	UnRegisterHotKeys(Vec<HotKey>, Sender<crate::Result<()>>),
-	DropThread,
+	RegisterHotKey(HotKey, HotKeyOptions, ReplyTo),
+	// `Reply::Many`, up to the first hotkey that failed.
+	RegisterHotKeys(Vec<HotKey>, HotKeyOptions, ReplyTo),
+	RegisterHotKeysAtomic(Vec<HotKey>, ReplyTo),
+	UnRegisterHotKey(HotKey, ReplyTo),
+	// Grabs and releases the hotkey, answering whether the grab succeeded.
+	Validate(HotKey, ReplyTo),
+	// `Reply::Many`
+	UnRegisterHotKeys(Vec<HotKey>, ReplyTo),
+	ClearAll(ReplyTo),
+	Suspend(ReplyTo),
+	Resume(ReplyTo),
+	// `Reply::HotKeys` with the hotkeys that failed to come back.
+	Revalidate(ReplyTo),
+	// `Reply::Status`
+	QueryStatus(ReplyTo),
+	// `Reply::WindowManager`
+	QueryWindowManager(ReplyTo),
+	StartRecording(Recorder, ReplyTo),
+	StopRecording,
+	// Runs the task, also while the backend is failed.
+	Extension(ExtensionMessage, ReplyTo),
+	// Stops the thread if it has nothing to do, `Reply::Idle` with whether it did.
+	StopIfIdle(ReplyTo),
+	// Answered once the grabs are released and the connection is closed.
+	DropThread(ReplyTo),
 }
 
 pub struct GlobalHotKeyManager {
-	thread_tx:Sender<ThreadMessage>,
+	// The event thread, started by the first call needing it so managers that
+	// never register a hotkey don't connect to the X server. Locked while
+	// sending, so the thread can't be stopped with a message on its way.
+	thread_tx:Mutex<Option<Sender<ThreadMessage>>>,
+	// Where the event thread leaves its replies, shared by all its restarts.
+	mailbox:Arc<Mailbox>,
+	root:Root,
+	stop_when_idle:bool,
+	poll_interval:Duration,
+	// Shared with the event thread, kept here so it outlives its restarts.
+	raw_event_hook:Arc<Mutex<Option<RawEventHook>>>,
 }
 
 impl GlobalHotKeyManager {
-	pub fn new() -> crate::Result<Self> {
-		let (thread_tx, thread_rx) = unbounded();
+	pub fn new(options:&GlobalHotKeyManagerOptions) -> crate::Result<Self> {
+		Ok(Self {
+			thread_tx:Mutex::new(None),
+			mailbox:Mailbox::new(),
+			root:Root::new(options),
+			stop_when_idle:options.stop_when_idle,
+			poll_interval:options.poll_interval.unwrap_or(POLL_INTERVAL),
+			raw_event_hook:Arc::default(),
+		})
+	}
+
+	// Returns the event thread, starting it if it isn't running.
+	fn thread(&self) -> MutexGuard<'_, Option<Sender<ThreadMessage>>> {
+		let mut thread_tx = self.thread_tx.lock().unwrap();
+
+		if thread_tx.is_none() {
+			log::debug!("Starting the X11 event thread");
 
-		std::thread::spawn(|| events_processor(thread_rx));
+			let (tx, thread_rx) = unbounded();
 
-		Ok(Self { thread_tx })
+			let (ready_tx, ready_rx) = crossbeam_channel::bounded(1);
+
+			let (root, poll_interval) = (self.root, self.poll_interval);
+
+			let hook = self.raw_event_hook.clone();
+
+			std::thread::spawn(move || {
+				events_processor(thread_rx, root, poll_interval, &hook, &ready_tx)
+			});
+
+			// Wait for the first connection attempt, so the calls made right away
+			// are answered by a started backend or by `failed_processor`, never
+			// queued behind a connection still being opened.
+			let _ = ready_rx.recv();
+
+			*thread_tx = Some(tx);
+		}
+
+		thread_tx
 	}
 
-	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> {
-		let (tx, rx) = crossbeam_channel::bounded(1);
+	// Sends a message to the event thread and waits for its reply, failing
+	// instead of returning `Ok` if the thread is gone.
+	fn call(&self, message:impl FnOnce(ReplyTo) -> ThreadMessage) -> crate::Result<Reply> {
+		let (id, reply_to) = self.mailbox.reply_to();
 
-		let _ = self.thread_tx.send(ThreadMessage::RegisterHotKey(hotkey, tx));
+		match self.thread().as_ref() {
+			// A message the thread is gone for is dropped, answering the request.
+			Some(thread_tx) => {
+				let _ = thread_tx.send(message(reply_to));
+			},
+			None => return Err(thread_stopped()),
+		}
+
+		self.mailbox.wait(id).map_err(|_| thread_stopped())
+	}
 
-		if let Ok(result) = rx.recv() {
-			result?;
+	fn request(&self, message:impl FnOnce(ReplyTo) -> ThreadMessage) -> crate::Result<()> {
+		self.call(message)?.into_result()
+	}
+
+	// Stops the event thread once the last hotkey is gone, if the manager was
+	// created with `stop_when_idle`. The next registration starts it again.
+	fn stop_if_idle(&self) {
+		if !self.stop_when_idle {
+			return;
 		}
 
-		Ok(())
+		let mut thread_tx = self.thread_tx.lock().unwrap();
+
+		let Some(sender) = thread_tx.as_ref() else {
+			return;
+		};
+
+		let (id, reply_to) = self.mailbox.reply_to();
+
+		let _ = sender.send(ThreadMessage::StopIfIdle(reply_to));
+
+		// A thread that's gone is started again as well.
+		if matches!(self.mailbox.wait(id), Ok(Reply::Idle(true)) | Err(_)) {
+			log::debug!("No hotkey left, stopped the X11 event thread");
+
+			*thread_tx = None;
+		}
+	}
+
+	#[cfg(test)]
+	fn is_running(&self) -> bool { self.thread_tx.lock().unwrap().is_some() }
+
+	pub fn register(&self, hotkey:HotKey) -> crate::Result<()> {
+		self.register_with_options(hotkey, HotKeyOptions::default())
+	}
+
+	pub fn register_with_options(&self, hotkey:HotKey, options:HotKeyOptions) -> crate::Result<()> {
+		self.request(|tx| ThreadMessage::RegisterHotKey(hotkey, options, tx))
 	}
 
 	pub fn unregister(&self, hotkey:HotKey) -> crate::Result<()> {
-		let (tx, rx) = crossbeam_channel::bounded(1);
+		let result = self.request(|tx| ThreadMessage::UnRegisterHotKey(hotkey, tx));
 
-		let _ = self.thread_tx.send(ThreadMessage::UnRegisterHotKey(hotkey, tx));
+		self.stop_if_idle();
 
-		if let Ok(result) = rx.recv() {
-			result?;
-		}
+		result
+	}
 
-		Ok(())
+	pub fn validate(&self, hotkey:HotKey) -> crate::Result<()> {
+		let result = self.request(|tx| ThreadMessage::Validate(hotkey, tx));
+
+		self.stop_if_idle();
+
+		result
+	}
+
+	pub fn send_raw(&self, message:ExtensionMessage) -> crate::Result<()> {
+		let result = self.request(|tx| ThreadMessage::Extension(message, tx));
+
+		self.stop_if_idle();
+
+		result
 	}
 
-	pub fn register_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		let (tx, rx) = crossbeam_channel::bounded(1);
+	pub fn window_manager(&self) -> Option<ProcessInfo> {
+		let window_manager = match self.call(ThreadMessage::QueryWindowManager) {
+			Ok(Reply::WindowManager(window_manager)) => window_manager,
+			Ok(other) => unreachable!("{:?} doesn't answer a window manager query", other),
+			Err(_) => None,
+		};
+
+		self.stop_if_idle();
 
-		let _ = self.thread_tx.send(ThreadMessage::RegisterHotKeys(hotkeys.to_vec(), tx));
+		window_manager
+	}
 
-		if let Ok(result) = rx.recv() {
-			result?;
+	pub fn register_all_with_options(
+		&self,
+		hotkeys:&[HotKey],
+		options:HotKeyOptions,
+	) -> Vec<crate::Result<()>> {
+		match self.call(|tx| ThreadMessage::RegisterHotKeys(hotkeys.to_vec(), options, tx)) {
+			Ok(reply) => reply.into_results(),
+			Err(e) => vec![Err(e)],
 		}
+	}
 
-		Ok(())
+	// A single message so the hotkeys become active together.
+	pub fn register_all_atomic(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
+		self.request(|tx| ThreadMessage::RegisterHotKeysAtomic(hotkeys.to_vec(), tx))
 	}
 
 	pub fn unregister_all(&self, hotkeys:&[HotKey]) -> crate::Result<()> {
-		let (tx, rx) = crossbeam_channel::bounded(1);
+		let result = self.request(|tx| ThreadMessage::UnRegisterHotKeys(hotkeys.to_vec(), tx));
+
+		self.stop_if_idle();
+
+		result
+	}
+
+	pub fn clear_all(&self) -> crate::Result<()> {
+		let result = self.request(ThreadMessage::ClearAll);
+
+		self.stop_if_idle();
+
+		result
+	}
+
+	pub fn suspend(&self) -> crate::Result<()> {
+		self.request(ThreadMessage::Suspend)
+	}
+
+	pub fn resume(&self) -> crate::Result<()> {
+		self.request(ThreadMessage::Resume)
+	}
+
+	pub fn revalidate(&self) -> crate::Result<Vec<HotKey>> {
+		self.call(ThreadMessage::Revalidate)?.into_hotkeys()
+	}
+
+	pub fn status(&self) -> ManagerStatus {
+		let (id, reply_to) = self.mailbox.reply_to();
 
-		let _ = self.thread_tx.send(ThreadMessage::UnRegisterHotKeys(hotkeys.to_vec(), tx));
+		// Not started for this, there is nothing to report until then.
+		match self.thread_tx.lock().unwrap().as_ref() {
+			Some(thread_tx) => {
+				let _ = thread_tx.send(ThreadMessage::QueryStatus(reply_to));
+			},
+			None => return ManagerStatus::new(BACKEND, false, Vec::new()),
+		}
 
-		if let Ok(result) = rx.recv() {
-			result?;
+		match self.mailbox.wait(id) {
+			Ok(Reply::Status(status)) => status,
+			Ok(other) => unreachable!("{:?} doesn't answer a status query", other),
+			Err(_) => ManagerStatus::backend_failed(BACKEND, thread_stopped(), []),
 		}
+	}
+
+	pub fn start_recording(&self) -> crate::Result<RecordingHandle> {
+		let (recorder, handle) = recorder::recording();
+
+		self.request(|tx| ThreadMessage::StartRecording(recorder, tx))?;
+
+		let thread_tx = self.thread().clone().ok_or_else(thread_stopped)?;
+
+		Ok(handle.on_drop(move || {
+			let _ = thread_tx.send(ThreadMessage::StopRecording);
+		}))
+	}
+
+	pub fn set_raw_event_hook(&self, hook:RawEventHook) -> crate::Result<()> {
+		*self.raw_event_hook.lock().unwrap() = Some(hook);
 
 		Ok(())
 	}
+
+	pub fn main_thread_poster(&self) -> crate::Result<crate::main_thread::Poster> {
+		Err(crate::Error::MainThreadUnavailable(
+			"X11 has no main thread the events could be posted to, set a proxy instead".to_string(),
+		))
+	}
+
+	pub fn capabilities(&self) -> Capabilities {
+		Capabilities {
+			sided_modifiers:true,
+			consume:true,
+			passthrough:true,
+			tap_hold:true,
+			device_filter:false,
+			max_hotkeys:None,
+		}
+	}
 }
 
 impl Drop for GlobalHotKeyManager {
-	fn drop(&mut self) { let _ = self.thread_tx.send(ThreadMessage::DropThread); }
+	fn drop(&mut self) {
+		let Some(thread_tx) = self.thread_tx.get_mut().unwrap().take() else {
+			return;
+		};
+
+		let (id, reply_to) = self.mailbox.reply_to();
+
+		if thread_tx.send(ThreadMessage::DropThread(reply_to)).is_err() {
+			return;
+		}
+
+		// Waits for the grabs to be gone, so the hotkeys can be taken again as
+		// soon as the manager is. Bounded for a thread that's stuck, or that is
+		// the one dropping the manager, from a callback.
+		if let Err(RecvTimeoutError::Timeout) = self.mailbox.wait_timeout(id, DROP_TIMEOUT) {
+			log::warn!("The X11 event thread didn't release its grabs within {:?}", DROP_TIMEOUT);
+		}
+	}
 }
 
+fn thread_stopped() -> crate::Error { crate::Error::os(0, "The X11 event thread is not running") }
+
 // XGrabKey works only with the exact state (modifiers)
 // and since X11 considers NumLock, ScrollLock and CapsLock a modifier when it
 // is ON, we also need to register our shortcut combined with these extra
@@ -98,227 +391,1150 @@ const IGNORED_MODS:[u32; 4] = [
 	xlib::Mod2Mask | xlib::LockMask,
 ];
 
+// id -> (hotkey, options)
+type RegisteredHotKeys = BTreeMap<u32, (HotKey, HotKeyOptions)>;
+
+// How often the X events are read by default, see
+// `GlobalHotKeyManagerOptions::poll_interval`.
+const POLL_INTERVAL:Duration = Duration::from_millis(50);
+
+// How often a lost connection to the X server is tried again.
+const RECONNECT_INTERVAL:Duration = Duration::from_secs(2);
+
+// How long dropping the manager waits for the event thread to release its grabs.
+const DROP_TIMEOUT:Duration = Duration::from_secs(1);
+
 #[inline]
 fn register_hotkey(
-	xlib:&Xlib,
-	display:*mut _XDisplay,
-	root:c_ulong,
-	hotkeys:&mut BTreeMap<u32, Vec<(u32, u32, bool)>>,
+	display:&Display,
+	hotkeys:&mut RegisteredEntries,
 	hotkey:HotKey,
+	options:&HotKeyOptions,
+	suspended:bool,
 ) -> crate::Result<()> {
-	let (modifiers, key) =
-		(modifiers_to_x11_mods(hotkey.mods), keycode_to_x11_scancode(hotkey.key));
+	if options.device.is_some() {
+		return Err(crate::Error::UnsupportedOption(
+			"X11 key grabs can't be restricted to a keyboard device".to_string(),
+		));
+	}
 
-	if let Some(key) = key {
-		let keycode = unsafe { (xlib.XKeysymToKeycode)(display, key as _) };
+	let modifiers = modifiers_to_x11_mods(hotkey.mods);
 
-		for m in IGNORED_MODS {
-			let result = unsafe {
-				(xlib.XGrabKey)(
-					display,
-					keycode as _,
-					modifiers | m,
-					root,
-					0,
-					xlib::GrabModeAsync,
-					xlib::GrabModeAsync,
-				)
-			};
+	let locks = options.lock_state();
 
-			if result == xlib::BadAccess as _ {
-				for m in IGNORED_MODS {
-					unsafe { (xlib.XUngrabKey)(display, keycode as _, modifiers | m, root) };
-				}
+	let keycodes = hotkey_keycodes(|keysym| display.keysym_to_keycode(keysym), hotkey.key);
 
-				return Err(crate::Error::AlreadyRegistered(hotkey));
-			}
+	let Some(keycodes) = keycodes else {
+		return Err(crate::Error::FailedToRegister {
+			hotkey,
+			reason:RegisterFailureReason::UnknownKey,
+		});
+	};
+
+	log::debug!(
+		"Registering `{}` (id {}) on keycodes {:?} with X11 mods {:#x}",
+		hotkey,
+		hotkey.id(),
+		keycodes,
+		modifiers
+	);
+
+	let either_side = hotkey.sides.is_either();
+
+	for keycode in &keycodes {
+		if hotkeys.overlapping(*keycode, modifiers, &hotkey, &locks).is_some() {
+			return Err(crate::Error::AlreadyRegistered(hotkey));
 		}
 
-		let entry = hotkeys.entry(keycode as _).or_default();
+		// Hotkeys that only differ in modifier sides or lock requirements share
+		// the same grab.
+		if hotkeys.sharing(*keycode, modifiers).any(|e| e.options.consume != options.consume) {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` shares its grab with a hotkey registered with a different `consume` option",
+				hotkey
+			)));
+		}
 
-		match entry.iter().find(|e| e.1 == modifiers) {
-			None => {
-				entry.push((hotkey.id(), modifiers, false));
+		// Gated hotkeys decide on each press whether it's handed back, which
+		// takes a synchronous grab.
+		let gated = options.gate.is_some();
 
-				Ok(())
-			},
+		if hotkeys.sharing(*keycode, modifiers).any(|e| e.options.gate.is_some() != gated) {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` shares its grab with a hotkey registered with a different `gate` option",
+				hotkey
+			)));
+		}
 
-			Some(_) => Err(crate::Error::AlreadyRegistered(hotkey)),
+		// Sided hotkeys hand back the presses on the other side, which takes a
+		// synchronous grab too.
+		if hotkeys.sharing(*keycode, modifiers).any(|e| e.hotkey.sides.is_either() != either_side) {
+			return Err(crate::Error::UnsupportedOption(format!(
+				"`{}` shares its grab with a hotkey that differs in having sided modifiers",
+				hotkey
+			)));
 		}
-	} else {
-		Err(crate::Error::FailedToRegister(format!(
-			"Unable to register accelerator (unknown scancode for this key: {}).",
-			hotkey.key
-		)))
 	}
+
+	// While suspended the grabs are deferred until the manager resumes.
+	let mut grabbed = Vec::<(u32, Vec<u32>)>::new();
+
+	for keycode in &keycodes {
+		if suspended {
+			continue;
+		}
+
+		// Only the lock states no hotkey sharing the grab needed yet.
+		let shared = shared_ignored_mods(hotkeys, *keycode, modifiers);
+
+		let missing =
+			hotkey_ignored_mods(options).filter(|m| !shared.contains(m)).collect::<Vec<_>>();
+
+		if !grab_key(display, *keycode, modifiers, grab_async(&hotkey, options), &missing) {
+			for (keycode, ignored) in &grabbed {
+				ungrab_ignored_mods(display, *keycode, modifiers, ignored);
+			}
+
+			return Err(crate::Error::AlreadyRegistered(hotkey));
+		}
+
+		grabbed.push((*keycode, missing));
+	}
+
+	for keycode in keycodes {
+		hotkeys.insert(RegisteredEntry::new(hotkey, options, keycode, modifiers));
+	}
+
+	Ok(())
 }
 
 #[inline]
 fn unregister_hotkey(
-	xlib:&Xlib,
-	display:*mut _XDisplay,
-	root:c_ulong,
-	hotkeys:&mut BTreeMap<u32, Vec<(u32, u32, bool)>>,
+	display:&Display,
+	hotkeys:&mut RegisteredEntries,
 	hotkey:HotKey,
 ) -> crate::Result<()> {
-	let (modifiers, key) =
-		(modifiers_to_x11_mods(hotkey.mods), keycode_to_x11_scancode(hotkey.key));
+	if keycode_to_x11_scancode(hotkey.key).is_none() {
+		return Err(crate::Error::FailedToUnRegister(hotkey));
+	}
+
+	// Looked up by id rather than resolved again, so every keycode of a numpad
+	// hotkey is released even if the layout changed since.
+	for removed in hotkeys.remove(hotkey.id()) {
+		let (keycode, modifiers) = (removed.keycode, removed.mods);
+
+		log::debug!("Unregistering `{}` (id {}) from keycode {}", hotkey, hotkey.id(), keycode);
 
-	if let Some(key) = key {
-		let keycode = unsafe { (xlib.XKeysymToKeycode)(display, key as _) };
+		// Keep the grabs alive while another hotkey sharing them still needs
+		// them, e.g. a sided variant.
+		let kept = shared_ignored_mods(hotkeys, keycode, modifiers);
 
-		for m in IGNORED_MODS {
-			unsafe { (xlib.XUngrabKey)(display, keycode as _, modifiers | m, root) };
+		let released = IGNORED_MODS.into_iter().filter(|m| !kept.contains(m)).collect::<Vec<_>>();
+
+		ungrab_ignored_mods(display, keycode, modifiers, &released);
+	}
+
+	Ok(())
+}
+
+// The keycodes to grab for `key`, `None` if it has no keysym or the keymap
+// none of its keysyms. `keycode` looks the keycode of a keysym up in the
+// keymap, `0` if it has none.
+//
+// A numpad key produces a digit with NumLock on and a navigation keysym with
+// it off. Keymaps usually put both on the same keycode, but when they don't,
+// both are grabbed under the same hotkey id so it fires in either state.
+fn hotkey_keycodes(keycode:impl Fn(u32) -> u32, key:Code) -> Option<Vec<u32>> {
+	let keysyms = [keycode_to_x11_scancode(key)?].into_iter().chain(numpad_navigation_keysym(key));
+
+	let mut keycodes = Vec::new();
+
+	// `0` is `AnyKey` to `XGrabKey`, which would grab the whole keyboard.
+	for keycode in keysyms.map(keycode).filter(|k| *k != 0) {
+		if !keycodes.contains(&keycode) {
+			keycodes.push(keycode);
 		}
+	}
 
-		let entry = hotkeys.entry(keycode as _).or_default();
+	(!keycodes.is_empty()).then_some(keycodes)
+}
 
-		entry.retain(|k| k.1 != modifiers);
+// The keysym of a numpad key with NumLock off, see `hotkey_keycodes`.
+fn numpad_navigation_keysym(key:Code) -> Option<u32> {
+	Some(match key {
+		Code::Numpad0 => keysym::XK_KP_Insert,
+		Code::Numpad1 => keysym::XK_KP_End,
+		Code::Numpad2 => keysym::XK_KP_Down,
+		Code::Numpad3 => keysym::XK_KP_Next,
+		Code::Numpad4 => keysym::XK_KP_Left,
+		Code::Numpad5 => keysym::XK_KP_Begin,
+		Code::Numpad6 => keysym::XK_KP_Right,
+		Code::Numpad7 => keysym::XK_KP_Home,
+		Code::Numpad8 => keysym::XK_KP_Up,
+		Code::Numpad9 => keysym::XK_KP_Prior,
+		Code::NumpadDecimal => keysym::XK_KP_Delete,
+		_ => return None,
+	})
+}
 
-		Ok(())
-	} else {
-		Err(crate::Error::FailedToUnRegister(hotkey))
-	}
-}
-
-fn events_processor(thread_rx:Receiver<ThreadMessage>) {
-	//                           key    id,  mods, pressed
-	let mut hotkeys = BTreeMap::<u32, Vec<(u32, u32, bool)>>::new();
-
-	if let Ok(xlib) = xlib::Xlib::open() {
-		unsafe {
-			let display = (xlib.XOpenDisplay)(ptr::null());
-
-			let root:c_ulong = (xlib.XDefaultRootWindow)(display);
-
-			// Only trigger key release at end of repeated keys
-			let mut supported_rtrn:i32 = 0;
-			(xlib.XkbSetDetectableAutoRepeat)(display, 1, &mut supported_rtrn);
-
-			(xlib.XSelectInput)(display, root, xlib::KeyPressMask);
-
-			let mut event:xlib::XEvent = std::mem::zeroed();
-
-			loop {
-				// Always service all pending events to avoid a queue of events from building
-				// up.
-				while (xlib.XPending)(display) > 0 {
-					(xlib.XNextEvent)(display, &mut event);
-
-					match event.get_type() {
-						e @ xlib::KeyPress | e @ xlib::KeyRelease => {
-							let keycode = event.key.keycode;
-							// X11 sends masks for Lock keys also and we only care about the 4 below
-							let event_mods = event.key.state
-								& (xlib::ControlMask
-									| xlib::ShiftMask | xlib::Mod4Mask
-									| xlib::Mod1Mask);
-
-							if let Some(entry) = hotkeys.get_mut(&keycode) {
-								match e {
-									xlib::KeyPress => {
-										for (id, mods, pressed) in entry {
-											if event_mods == *mods && !*pressed {
-												GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-													id:*id,
-													state:crate::HotKeyState::Pressed,
-												});
-												*pressed = true;
-											}
-										}
-									},
-
-									xlib::KeyRelease => {
-										for (id, _, pressed) in entry {
-											if *pressed {
-												GlobalHotKeyEvent::send(GlobalHotKeyEvent {
-													id:*id,
-													state:crate::HotKeyState::Released,
-												});
-												*pressed = false;
-											}
-										}
-									},
-
-									_ => {},
-								}
-							}
-						},
+// The `IGNORED_MODS` a key is grabbed with, leaving out the locks a hotkey
+// forbids and the states lacking the locks it requires, so those presses go
+// to the focused window.
+fn ignored_mods(forbidden:u32, required:u32) -> impl Iterator<Item = u32> {
+	IGNORED_MODS.into_iter().filter(move |m| m & forbidden == 0 && m & required == required)
+}
 
-						_ => {},
-					}
-				}
+fn hotkey_ignored_mods(options:&HotKeyOptions) -> impl Iterator<Item = u32> {
+	let (forbidden, required) = lock_x11_masks(options);
 
-				if let Ok(msg) = thread_rx.try_recv() {
-					match msg {
-						ThreadMessage::RegisterHotKey(hotkey, tx) => {
-							let _ = tx.send(register_hotkey(
-								&xlib,
-								display,
-								root,
-								&mut hotkeys,
-								hotkey,
-							));
-						},
+	ignored_mods(forbidden, required)
+}
 
-						ThreadMessage::RegisterHotKeys(keys, tx) => {
-							for hotkey in keys {
-								if let Err(e) =
-									register_hotkey(&xlib, display, root, &mut hotkeys, hotkey)
-								{
-									let _ = tx.send(Err(e));
-								}
-							}
+// The `IGNORED_MODS` grabbed on `keycode` with `modifiers`, the ones any of
+// the hotkeys sharing the grab needs.
+fn shared_ignored_mods(hotkeys:&RegisteredEntries, keycode:u32, modifiers:u32) -> Vec<u32> {
+	let needed = hotkeys
+		.sharing(keycode, modifiers)
+		.flat_map(|e| hotkey_ignored_mods(&e.options))
+		.collect::<Vec<_>>();
 
-							let _ = tx.send(Ok(()));
-						},
+	IGNORED_MODS.into_iter().filter(|m| needed.contains(m)).collect()
+}
 
-						ThreadMessage::UnRegisterHotKey(hotkey, tx) => {
-							let _ = tx.send(unregister_hotkey(
-								&xlib,
-								display,
-								root,
-								&mut hotkeys,
-								hotkey,
-							));
-						},
+// Grabs the key with each of the `ignored` mods, returning `false` if another
+// client already grabbed one of them.
+//
+// Synchronous grabs freeze the keyboard on press until the event is replayed
+// to the focused window with `Display::replay_keyboard`, or swallowed with
+// `Display::consume_keyboard`.
+fn grab_key(
+	display:&Display,
+	keycode:u32,
+	modifiers:u32,
+	asynchronous:bool,
+	ignored:&[u32],
+) -> bool {
+	for (i, m) in ignored.iter().enumerate() {
+		if !display.grab_key(keycode, modifiers | m, asynchronous) {
+			log::warn!(
+				"Failed to grab keycode {} with X11 mods {:#x}, another client already grabbed it",
+				keycode,
+				modifiers | m
+			);
+
+			ungrab_ignored_mods(display, keycode, modifiers, &ignored[..i]);
+
+			return false;
+		}
+	}
 
-						ThreadMessage::UnRegisterHotKeys(keys, tx) => {
-							for hotkey in keys {
-								if let Err(e) =
-									unregister_hotkey(&xlib, display, root, &mut hotkeys, hotkey)
-								{
-									let _ = tx.send(Err(e));
-								}
-							}
+	true
+}
 
-							let _ = tx.send(Ok(()));
-						},
+fn ungrab_key(display:&Display, keycode:u32, modifiers:u32) {
+	ungrab_ignored_mods(display, keycode, modifiers, &IGNORED_MODS);
+}
 
-						ThreadMessage::DropThread => {
-							(xlib.XCloseDisplay)(display);
+fn ungrab_ignored_mods(display:&Display, keycode:u32, modifiers:u32, ignored:&[u32]) {
+	for m in ignored {
+		display.ungrab_key(keycode, modifiers | m);
+	}
+}
 
-							return;
-						},
-					}
+// Unique `(keycode, mods, asynchronous, ignored)` grabs currently in use, the
+// hotkeys only differing in modifier sides or lock requirements share one.
+fn grabbed_keys(hotkeys:&RegisteredEntries) -> Vec<(u32, u32, bool, Vec<u32>)> {
+	let mut keys = Vec::<(u32, u32, bool, Vec<u32>)>::new();
+
+	for e in hotkeys.iter() {
+		if keys.iter().any(|(keycode, mods, ..)| (*keycode, *mods) == (e.keycode, e.mods)) {
+			continue;
+		}
+
+		let ignored = shared_ignored_mods(hotkeys, e.keycode, e.mods);
+
+		keys.push((e.keycode, e.mods, grab_async(&e.hotkey, &e.options), ignored));
+	}
+
+	keys
+}
+
+// Whether the grab of `hotkey` lets the X server keep every press to itself,
+// the others freeze the keyboard until `handoff` decides on each.
+fn grab_async(hotkey:&HotKey, options:&HotKeyOptions) -> bool {
+	options.grab_async() && hotkey.sides.is_either()
+}
+
+// What becomes of a press on a synchronous grab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handoff {
+	// The grab is asynchronous, the press is already ours.
+	None,
+	// Handed over to the focused window.
+	Replay,
+	// Swallowed.
+	Consume,
+}
+
+// Decides on a press with the X11 `mods` of the keycode of `entry` once its
+// hotkeys saw it. A press is only swallowed while it holds a consuming
+// hotkey: a press on the wrong side of a sided one or dropped by a gate goes
+// to the focused window.
+fn handoff(entry:&[RegisteredEntry], mods:u32) -> Handoff {
+	let grab = || entry.iter().filter(|e| e.mods == hotkey_mods(mods));
+
+	// Unregistered since, a synchronous grab froze the keyboard until then.
+	if grab().next().is_none() {
+		return Handoff::Replay;
+	}
+
+	if grab().any(|e| !e.options.consume) {
+		return Handoff::Replay;
+	}
+
+	if grab().all(|e| grab_async(&e.hotkey, &e.options)) {
+		return Handoff::None;
+	}
+
+	// `pressed` rather than the ids of this press, the repeats of a swallowed
+	// key are swallowed as well.
+	match grab().any(|e| e.pressed && gate::passed(e.id())) {
+		true => Handoff::Consume,
+		false => Handoff::Replay,
+	}
+}
+
+// No release will arrive for keys held while the grabs change.
+fn release_pressed(hotkeys:&mut RegisteredEntries) {
+	for entry in hotkeys.iter_mut().filter(|e| e.pressed) {
+		entry.pressed = false;
+
+		GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(entry.id(), crate::HotKeyState::Released));
+	}
+}
+
+// The modifiers of the X11 `mods` hotkeys are grabbed with.
+fn hotkey_mods(mods:u32) -> u32 {
+	// X11 sends masks for Lock keys also and we only care about the 4 below
+	mods & (xlib::ControlMask | xlib::ShiftMask | xlib::Mod4Mask | xlib::Mod1Mask)
+}
+
+// Marks the hotkeys of `entry` matching a press with the X11 `mods` as pressed
+// and returns their ids. `sides_held` checks the sides of the sided ones.
+fn press(
+	entry:&mut [RegisteredEntry],
+	mods:u32,
+	mut sides_held:impl FnMut(&ModifierSides) -> bool,
+) -> Vec<u32> {
+	let event_mods = hotkey_mods(mods);
+
+	let mut ids = Vec::new();
+
+	for e in entry {
+		if event_mods != e.mods || e.pressed {
+			continue;
+		}
+
+		let (forbidden, required) = lock_x11_masks(&e.options);
+
+		// Also holds the locks, missing from `event_mods`.
+		if mods & forbidden != 0 || mods & required != required {
+			continue;
+		}
+
+		let sides = &e.hotkey.sides;
+
+		if !sides.is_either() && !sides_held(sides) {
+			continue;
+		}
+
+		e.pressed = true;
+
+		ids.push(e.id());
+	}
+
+	ids
+}
+
+// Marks the pressed hotkeys of `entry` as released and returns their ids.
+//
+// The modifiers of the release aren't matched: they're often let go before
+// the key, e.g. Ctrl before S, and every press has to get its release.
+fn release(entry:&mut [RegisteredEntry]) -> Vec<u32> {
+	let mut ids = Vec::new();
+
+	for e in entry.iter_mut().filter(|e| e.pressed) {
+		e.pressed = false;
+
+		ids.push(e.id());
+	}
+
+	ids
+}
+
+// Gives up the gesture in progress once its grab is gone.
+fn end_gesture(gesture:&mut Option<Gesture>) {
+	for event in gesture.take().iter_mut().flat_map(Gesture::abort) {
+		GlobalHotKeyEvent::send(event);
+	}
+}
+
+// A replayed key press ends the grab, so the release goes to the focused
+// window and is only noticed by polling the keymap. The presses a gate
+// dropped were replayed as well.
+fn release_passthrough(display:&Display, hotkeys:&mut RegisteredEntries) {
+	let mut keymap = None;
+
+	for e in hotkeys.iter_mut() {
+		if !e.pressed || (e.options.consume && gate::passed(e.id())) {
+			continue;
+		}
+
+		let keymap = keymap.get_or_insert_with(|| display.query_keymap());
+
+		if keymap[e.keycode as usize / 8] & (1 << (e.keycode % 8)) == 0 {
+			GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(e.id(), crate::HotKeyState::Released));
+			e.pressed = false;
+		}
+	}
+}
+
+// Keeps answering the manager after the backend failed to start or lost its
+// connection so calls report the failure instead of blocking forever,
+// `hotkeys` are the ones registered when it happened.
+//
+// Returns a new connection grabbing on `root` once the X server is reachable
+// again, when asked to revalidate or, after losing the connection, on its own.
+// `None` once the manager is dropped.
+fn failed_processor(
+	thread_rx:&Receiver<ThreadMessage>,
+	error:crate::Error,
+	hotkeys:&RegisteredHotKeys,
+	root:Root,
+	reconnect:bool,
+) -> Option<(Display, Option<ReplyTo>)> {
+	log::error!("The X11 backend failed: {}", error);
+
+	loop {
+		let msg = match thread_rx.recv_timeout(RECONNECT_INTERVAL) {
+			Ok(msg) => msg,
+			// Picks the hotkeys up again once the X server is back, e.g. after a restart.
+			Err(RecvTimeoutError::Timeout) => {
+				if let Some(display) = reconnect.then(|| Display::open(root)).and_then(Result::ok) {
+					return Some((display, None));
 				}
 
-				std::thread::sleep(std::time::Duration::from_millis(50));
-			}
+				continue;
+			},
+			Err(RecvTimeoutError::Disconnected) => return None,
 		};
-	} else {
-		#[cfg(debug_assertions)]
-		eprintln!(
-			"Failed to open Xlib, maybe you are not running under X11? Other window systems on \
-			 Linux are not supported by `global-hotkey` crate."
-		);
+
+		match msg {
+			ThreadMessage::RegisterHotKeys(keys, _, tx)
+			| ThreadMessage::UnRegisterHotKeys(keys, tx) => {
+				tx.send(Reply::Many(keys.iter().map(|_| Err(error.clone())).collect()));
+			},
+
+			ThreadMessage::RegisterHotKey(_, _, tx)
+			| ThreadMessage::RegisterHotKeysAtomic(_, tx)
+			| ThreadMessage::UnRegisterHotKey(_, tx)
+			| ThreadMessage::Validate(_, tx)
+			| ThreadMessage::ClearAll(tx)
+			| ThreadMessage::Suspend(tx)
+			| ThreadMessage::Resume(tx)
+			| ThreadMessage::StartRecording(_, tx) => {
+				tx.send(Reply::One(Err(error.clone())));
+			},
+
+			ThreadMessage::Revalidate(tx) => {
+				match Display::open(root) {
+					Ok(display) => return Some((display, Some(tx))),
+					Err(e) => tx.send(Reply::One(Err(e))),
+				}
+			},
+
+			ThreadMessage::QueryStatus(tx) => {
+				let hotkeys = hotkeys.values().map(|(hotkey, _)| *hotkey);
+
+				let status = ManagerStatus::backend_failed(BACKEND, error.clone(), hotkeys);
+
+				tx.send(Reply::Status(status));
+			},
+
+			ThreadMessage::QueryWindowManager(tx) => tx.send(Reply::WindowManager(None)),
+
+			ThreadMessage::StopRecording => {},
+
+			ThreadMessage::Extension(message, tx) => {
+				message.run();
+
+				tx.send(Reply::One(Ok(())));
+			},
+
+			ThreadMessage::StopIfIdle(tx) => {
+				tx.send(Reply::Idle(hotkeys.is_empty()));
+
+				if hotkeys.is_empty() {
+					return None;
+				}
+			},
+
+			ThreadMessage::DropThread(tx) => {
+				tx.send(Reply::One(Ok(())));
+
+				return None;
+			},
+		}
 	}
 }
 
-fn keycode_to_x11_scancode(key:Code) -> Option<u32> {
-	Some(match key {
-		Code::KeyA => keysym::XK_A,
-		Code::KeyB => keysym::XK_B,
+// Alternates between the event loop and `failed_processor` until the manager
+// is dropped, carrying the hotkeys over to every new connection.
+//
+// `ready` is signaled once the first connection attempt is over.
+fn events_processor(
+	thread_rx:Receiver<ThreadMessage>,
+	root:Root,
+	poll_interval:Duration,
+	raw_event_hook:&Mutex<Option<RawEventHook>>,
+	ready:&Sender<()>,
+) {
+	let mut hotkeys = RegisteredHotKeys::new();
+
+	let mut connection = Display::open(root).map(|display| (display, None));
+
+	let _ = ready.send(());
+
+	loop {
+		let (error, reconnect) = match connection {
+			Ok((display, revalidated)) => {
+				let lost = process_events(
+					&thread_rx,
+					display,
+					&mut hotkeys,
+					revalidated,
+					poll_interval,
+					raw_event_hook,
+				);
+
+				match lost {
+					Some(error) => (error, true),
+					None => return,
+				}
+			},
+			Err(error) => (error, false),
+		};
+
+		match failed_processor(&thread_rx, error, &hotkeys, root, reconnect) {
+			Some(reconnected) => connection = Ok(reconnected),
+			None => return,
+		}
+	}
+}
+
+// Runs the event loop on `display`, grabbing the `restored` hotkeys of a
+// previous connection first and sending the ones that failed to `revalidated`.
+//
+// Returns the error the connection was lost with, the hotkeys registered then
+// are left in `restored`. `None` once the manager is dropped.
+fn process_events(
+	thread_rx:&Receiver<ThreadMessage>,
+	mut display:Display,
+	restored:&mut RegisteredHotKeys,
+	revalidated:Option<ReplyTo>,
+	poll_interval:Duration,
+	raw_event_hook:&Mutex<Option<RawEventHook>>,
+) -> Option<crate::Error> {
+	log::debug!("Connected to the X server");
+
+	let mut hotkeys = RegisteredEntries::new();
+
+	// Needed to resolve the hotkeys to keycodes again when the layout changes.
+	let mut registered = RegisteredHotKeys::new();
+
+	// Hotkeys that couldn't be grabbed again after a layout change, on resume or
+	// when revalidating.
+	let mut lost = std::mem::take(restored);
+
+	let mut suspended = false;
+
+	let mut recording:Option<Recorder> = None;
+
+	// The tap-hold hotkey whose key is held, the keyboard stays grabbed until
+	// it's released so every key goes to it.
+	let mut gesture:Option<Gesture> = None;
+
+	if !lost.is_empty() || revalidated.is_some() {
+		let failed = revalidate(&display, &mut hotkeys, &mut registered, &mut lost, suspended);
+
+		if let Some(tx) = revalidated {
+			tx.send(Reply::HotKeys(failed));
+		}
+	}
+
+	loop {
+		let mut layout_changed = false;
+
+		// Always service all pending events to avoid a queue of events from building
+		// up.
+		while let Some(event) = display.next_event() {
+			if let Event::Key { raw, .. } = event {
+				if let Some(hook) = raw_event_hook.lock().unwrap().as_ref() {
+					hook(raw);
+				}
+			}
+
+			match event {
+				// A single change produces a burst of these, handle it once below.
+				Event::LayoutChanged => layout_changed = true,
+
+				Event::Disconnected(e) => {
+					end_gesture(&mut gesture);
+
+					release_pressed(&mut hotkeys);
+
+					registered.append(&mut lost);
+
+					*restored = registered;
+
+					return Some(e);
+				},
+
+				// The keyboard is grabbed, every key goes to the recording.
+				Event::Key { state, keycode, mods, .. } if recording.is_some() => {
+					let keysym = display.keycode_to_keysym(keycode);
+
+					if let (Some(recorder), Some(code)) =
+						(recording.as_mut(), x11_keysym_to_keycode(keysym))
+					{
+						recorder.send(state, code, x11_mods_to_modifiers(mods));
+					}
+				},
+
+				Event::Key { state, keycode, mods, time, .. } if gesture.is_some() => {
+					let code = x11_keysym_to_keycode(display.keycode_to_keysym(keycode));
+
+					let Some(held) = gesture.as_mut() else {
+						continue;
+					};
+
+					let mods = x11_mods_to_modifiers(mods);
+
+					let (events, next) = match code {
+						Some(code) => held.key(state, code, mods, Instant::now()),
+						None => (held.abort(), Next::Abort),
+					};
+
+					for event in events {
+						GlobalHotKeyEvent::send(event);
+					}
+
+					match next {
+						Next::Continue => display.next_key(time),
+						// The grab ends with the release of the tap key.
+						Next::Done => gesture = None,
+						Next::Abort => {
+							display.replay_keyboard(time);
+
+							gesture = None;
+						},
+					}
+				},
+
+				Event::Key { state, keycode, mods, time, .. } => {
+					if let Some(entry) = hotkeys.on_mut(keycode) {
+						match state {
+							KeyState::Down => {
+								let mut keymap = None;
+
+								let pressed = press(entry, mods, |sides| {
+									let keymap =
+										keymap.get_or_insert_with(|| display.query_keymap());
+
+									sides_pressed(&display, keymap, sides)
+								});
+
+								let tap_hold = entry
+									.iter()
+									.filter(|e| pressed.contains(&e.id()))
+									.find_map(|e| Some((e.hotkey, e.options.tap_hold.clone()?)));
+
+								// The gesture reports the tap itself once the key is released,
+								// meanwhile the keyboard stays grabbed.
+								if let Some((tap, tap_hold)) = tap_hold {
+									release(entry);
+
+									gesture = Some(Gesture::start(tap, tap_hold, Instant::now()));
+
+									display.next_key(time);
+
+									continue;
+								}
+
+								for id in pressed {
+									GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+										id,
+										crate::HotKeyState::Pressed,
+									));
+								}
+
+								match handoff(entry, mods) {
+									Handoff::None => {},
+									Handoff::Replay => display.replay_keyboard(time),
+									Handoff::Consume => display.consume_keyboard(time),
+								}
+							},
+
+							KeyState::Up => {
+								for id in release(entry) {
+									GlobalHotKeyEvent::send(GlobalHotKeyEvent::new(
+										id,
+										crate::HotKeyState::Released,
+									));
+								}
+							},
+						}
+					} else {
+						log::trace!(
+							"Ignoring {:?} of keycode {} with X11 mods {:#x}",
+							state,
+							keycode,
+							mods
+						);
+
+						// As in `handoff`, a grab unregistered since may have frozen the
+						// keyboard. Replaying a press no grab froze does nothing.
+						if state == KeyState::Down {
+							display.replay_keyboard(time);
+						}
+					}
+				},
+			}
+		}
+
+		release_passthrough(&display, &mut hotkeys);
+
+		if layout_changed {
+			let ids = resolve_hotkeys_again(
+				&display,
+				&mut hotkeys,
+				&mut registered,
+				&mut lost,
+				suspended,
+			);
+
+			if !ids.is_empty() {
+				LayoutChangedEvent::send(LayoutChangedEvent { ids });
+			}
+		}
+
+		// X events are read again once `poll_interval` passed, while the calls of
+		// the manager are answered as soon as they arrive.
+		let msg = match thread_rx.recv_timeout(poll_interval) {
+			Ok(msg) => msg,
+			Err(RecvTimeoutError::Timeout) => continue,
+			Err(RecvTimeoutError::Disconnected) => return None,
+		};
+
+		match msg {
+			ThreadMessage::RegisterHotKey(hotkey, options, tx) => {
+				let result = register_hotkey(
+					&display,
+					&mut hotkeys,
+					hotkey,
+					&options,
+					suspended,
+				)
+				.map_err(|e| e.with_conflict(registered.values().map(|(h, _)| h)));
+
+				if result.is_ok() {
+					lost.remove(&hotkey.id());
+
+					registered.insert(hotkey.id(), (hotkey, options));
+				}
+
+				tx.send(Reply::One(result));
+			},
+
+			ThreadMessage::RegisterHotKeys(keys, options, tx) => {
+				let mut results = Vec::with_capacity(keys.len());
+
+				for hotkey in keys {
+					let result = register_hotkey(
+						&display,
+						&mut hotkeys,
+						hotkey,
+						&options,
+						suspended,
+					)
+					.map_err(|e| e.with_conflict(registered.values().map(|(h, _)| h)));
+
+					let failed = result.is_err();
+
+					if !failed {
+						lost.remove(&hotkey.id());
+
+						registered.insert(hotkey.id(), (hotkey, options.clone()));
+					}
+
+					results.push(result);
+
+					if failed {
+						break;
+					}
+				}
+
+				tx.send(Reply::Many(results));
+			},
+
+			ThreadMessage::RegisterHotKeysAtomic(keys, tx) => {
+				let options = HotKeyOptions::default();
+
+				let mut result = Ok(());
+
+				for (i, hotkey) in keys.iter().enumerate() {
+					result = register_hotkey(
+						&display,
+						&mut hotkeys,
+						*hotkey,
+						&options,
+						suspended,
+					)
+					.map_err(|e| e.with_conflict(registered.values().map(|(h, _)| h)));
+
+					if result.is_err() {
+						// Roll back so the group is registered entirely or not at all.
+						for hotkey in &keys[..i] {
+							registered.remove(&hotkey.id());
+
+							let _ = unregister_hotkey(&display, &mut hotkeys, *hotkey);
+						}
+
+						break;
+					}
+
+					lost.remove(&hotkey.id());
+
+					registered.insert(hotkey.id(), (*hotkey, options.clone()));
+				}
+
+				tx.send(Reply::One(result));
+			},
+
+			ThreadMessage::UnRegisterHotKey(hotkey, tx) => {
+				registered.remove(&hotkey.id());
+
+				// Already ungrabbed when it was lost.
+				if lost.remove(&hotkey.id()).is_some() {
+					tx.send(Reply::One(Ok(())));
+
+					continue;
+				}
+
+				tx.send(Reply::One(unregister_hotkey(&display, &mut hotkeys, hotkey)));
+			},
+
+			ThreadMessage::Validate(hotkey, tx) => {
+				// Grabbed even while suspended. No event is read before the
+				// grab is released, the ones it caught find no hotkey.
+				let options = HotKeyOptions::default();
+
+				let result = register_hotkey(&display, &mut hotkeys, hotkey, &options, false)
+					.and_then(|()| unregister_hotkey(&display, &mut hotkeys, hotkey))
+					.map_err(|e| e.with_conflict(registered.values().map(|(h, _)| h)));
+
+				tx.send(Reply::One(result));
+			},
+
+			ThreadMessage::UnRegisterHotKeys(keys, tx) => {
+				let results = keys
+					.into_iter()
+					.map(|hotkey| {
+						registered.remove(&hotkey.id());
+
+						match lost.remove(&hotkey.id()) {
+							Some(_) => Ok(()),
+							None => unregister_hotkey(&display, &mut hotkeys, hotkey),
+						}
+					})
+					.collect();
+
+				tx.send(Reply::Many(results));
+			},
+
+			ThreadMessage::ClearAll(tx) => {
+				log::debug!("Clearing all {} hotkeys", registered.len());
+
+				// Suspended hotkeys are already ungrabbed, lost ones were never grabbed again.
+				if !suspended {
+					for (keycode, mods, ..) in grabbed_keys(&hotkeys) {
+						ungrab_key(&display, keycode, mods);
+					}
+				}
+
+				release_pressed(&mut hotkeys);
+
+				hotkeys.clear();
+
+				registered.clear();
+
+				lost.clear();
+
+				tx.send(Reply::One(Ok(())));
+			},
+
+			ThreadMessage::Suspend(tx) => {
+				if !suspended {
+					log::debug!("Suspending, releasing the grabs");
+
+					end_gesture(&mut gesture);
+
+					for (keycode, mods, ..) in grabbed_keys(&hotkeys) {
+						ungrab_key(&display, keycode, mods);
+					}
+
+					release_pressed(&mut hotkeys);
+
+					suspended = true;
+				}
+
+				tx.send(Reply::One(Ok(())));
+			},
+
+			ThreadMessage::Resume(tx) => {
+				let mut result = Ok(());
+
+				if suspended {
+					log::debug!("Resuming, grabbing the keys again");
+
+					for (keycode, mods, asynchronous, ignored) in grabbed_keys(&hotkeys) {
+						let ids = hotkeys
+							.sharing(keycode, mods)
+							.map(RegisteredEntry::id)
+							.collect::<Vec<_>>();
+
+						// Gone with a numpad hotkey lost on its other keycode.
+						if ids.is_empty()
+							|| grab_key(&display, keycode, mods, asynchronous, &ignored)
+						{
+							continue;
+						}
+
+						// The hotkeys sharing the grab are lost until registered again.
+						for id in ids {
+							if let Some(registration) = registered.remove(&id) {
+								if result.is_ok() {
+									result = Err(crate::Error::FailedToRegister {
+										hotkey:registration.0,
+										reason:RegisterFailureReason::TakenWhileSuspended,
+									});
+								}
+
+								// Also releases the other keycode of a numpad hotkey.
+								let hotkey = registration.0;
+
+								let _ = unregister_hotkey(&display, &mut hotkeys, hotkey);
+
+								lost.insert(id, registration);
+							}
+						}
+					}
+
+					suspended = false;
+				}
+
+				tx.send(Reply::One(result));
+			},
+
+			ThreadMessage::Revalidate(tx) => {
+				let failed =
+					revalidate(&display, &mut hotkeys, &mut registered, &mut lost, suspended);
+
+				tx.send(Reply::HotKeys(failed));
+			},
+
+			ThreadMessage::QueryStatus(tx) => {
+				let status =
+					if suspended { HotKeyStatus::Suspended } else { HotKeyStatus::Active };
+
+				let hotkeys = registered
+					.values()
+					.map(|(hotkey, _)| (*hotkey, status.clone()))
+					.chain(lost.values().map(|(hotkey, _)| (*hotkey, HotKeyStatus::LostGrab)))
+					.collect();
+
+				tx.send(Reply::Status(ManagerStatus::new(BACKEND, suspended, hotkeys)));
+			},
+
+			ThreadMessage::QueryWindowManager(tx) => {
+				tx.send(Reply::WindowManager(display.window_manager()));
+			},
+
+			ThreadMessage::StartRecording(recorder, tx) => {
+				let result = if recording.is_some() {
+					Err(crate::Error::RecordingInProgress)
+				} else {
+					display.grab_keyboard().map(|()| {
+						release_pressed(&mut hotkeys);
+
+						log::debug!("Grabbed the keyboard to record a hotkey");
+
+						recording = Some(recorder);
+					})
+				};
+
+				tx.send(Reply::One(result));
+			},
+
+			ThreadMessage::StopRecording => {
+				if recording.take().is_some() {
+					display.ungrab_keyboard();
+
+					log::debug!("Recording stopped, released the keyboard");
+				}
+			},
+
+			ThreadMessage::Extension(message, tx) => {
+				message.run();
+
+				tx.send(Reply::One(Ok(())));
+			},
+
+			ThreadMessage::StopIfIdle(tx) => {
+				let idle = registered.is_empty()
+					&& lost.is_empty()
+					&& !suspended
+					&& recording.is_none();
+
+				tx.send(Reply::Idle(idle));
+
+				if idle {
+					return None;
+				}
+			},
+
+			ThreadMessage::DropThread(tx) => {
+				log::debug!("Manager dropped, releasing the grabs");
+
+				if recording.take().is_some() {
+					display.ungrab_keyboard();
+				}
+
+				if !suspended {
+					for (keycode, mods, ..) in grabbed_keys(&hotkeys) {
+						ungrab_key(&display, keycode, mods);
+					}
+				}
+
+				release_pressed(&mut hotkeys);
+
+				// The manager is answered once the X server released the grabs,
+				// the connection is closed right after.
+				display.sync();
+
+				tx.send(Reply::One(Ok(())));
+
+				return None;
+			},
+		}
+	}
+}
+
+// Grabs every registered hotkey again along with the lost ones, e.g. after the
+// grabs died with the X server, returning the hotkeys that are still lost.
+fn revalidate(
+	display:&Display,
+	hotkeys:&mut RegisteredEntries,
+	registered:&mut RegisteredHotKeys,
+	lost:&mut RegisteredHotKeys,
+	suspended:bool,
+) -> Vec<HotKey> {
+	log::debug!("Revalidating {} hotkeys", registered.len() + lost.len());
+
+	release_pressed(hotkeys);
+
+	registered.append(lost);
+
+	resolve_hotkeys_again(display, hotkeys, registered, lost, suspended);
+
+	lost.values().map(|(hotkey, _)| *hotkey).collect()
+}
+
+// Grabs every registered hotkey again using the keycodes of the current
+// layout, returning the ids of the hotkeys that moved to another key or
+// couldn't be grabbed anymore.
+fn resolve_hotkeys_again(
+	display:&Display,
+	hotkeys:&mut RegisteredEntries,
+	registered:&mut RegisteredHotKeys,
+	lost:&mut RegisteredHotKeys,
+	suspended:bool,
+) -> Vec<u32> {
+	let old_keycodes =
+		registered.keys().map(|id| (*id, hotkeys.keycodes_of(*id))).collect::<BTreeMap<_, _>>();
+
+	if !suspended {
+		for (keycode, mods, ..) in grabbed_keys(hotkeys) {
+			ungrab_key(display, keycode, mods);
+		}
+	}
+
+	hotkeys.clear();
+
+	let mut changed = Vec::new();
+
+	registered.retain(|id, (hotkey, options)| {
+		if let Err(e) = register_hotkey(display, hotkeys, *hotkey, options, suspended) {
+			log::warn!("Failed to register `{}` again: {}", hotkey, e);
+
+			changed.push(*id);
+
+			lost.insert(*id, (*hotkey, options.clone()));
+
+			return false;
+		}
+
+		if old_keycodes.get(id) != Some(&hotkeys.keycodes_of(*id)) {
+			changed.push(*id);
+		}
+
+		true
+	});
+
+	changed
+}
+
+// Checks the keys currently held down against the sides required by a hotkey,
+// X11 modifier masks don't tell which of the two keys set them.
+fn sides_pressed(display:&Display, keymap:&[u8; 32], sides:&ModifierSides) -> bool {
+	let is_down = |keysym:u32| {
+		let keycode = display.keysym_to_keycode(keysym) as usize;
+
+		keycode != 0 && keymap[keycode / 8] & (1 << (keycode % 8)) != 0
+	};
+
+	[
+		(sides.shift, keysym::XK_Shift_L, keysym::XK_Shift_R),
+		(sides.control, keysym::XK_Control_L, keysym::XK_Control_R),
+		(sides.alt, keysym::XK_Alt_L, keysym::XK_Alt_R),
+		(sides.super_key, keysym::XK_Super_L, keysym::XK_Super_R),
+	]
+	.into_iter()
+	.all(|(side, left, right)| {
+		match side {
+			ModifierSide::Either => true,
+			ModifierSide::Left => is_down(left),
+			ModifierSide::Right => is_down(right),
+		}
+	})
+}
+
+pub fn is_supported(hotkey:&HotKey) -> bool { keycode_to_x11_scancode(hotkey.key).is_some() }
+
+fn keycode_to_x11_scancode(key:Code) -> Option<u32> {
+	Some(match key {
+		Code::KeyA => keysym::XK_A,
+		Code::KeyB => keysym::XK_B,
 		Code::KeyC => keysym::XK_C,
 		Code::KeyD => keysym::XK_D,
 		Code::KeyE => keysym::XK_E,
@@ -390,14 +1606,16 @@ fn keycode_to_x11_scancode(key:Code) -> Option<u32> {
 		Code::Numpad8 => keysym::XK_KP_8,
 		Code::Numpad9 => keysym::XK_KP_9,
 		Code::NumpadAdd => keysym::XK_KP_Add,
+		Code::NumpadComma => keysym::XK_KP_Separator,
 		Code::NumpadDecimal => keysym::XK_KP_Decimal,
 		Code::NumpadDivide => keysym::XK_KP_Divide,
+		Code::NumpadEnter => keysym::XK_KP_Enter,
 		Code::NumpadMultiply => keysym::XK_KP_Multiply,
 		Code::NumpadSubtract => keysym::XK_KP_Subtract,
 		Code::Escape => keysym::XK_Escape,
 		Code::PrintScreen => keysym::XK_Print,
 		Code::ScrollLock => keysym::XK_Scroll_Lock,
-		Code::NumLock => keysym::XK_F1,
+		Code::NumLock => keysym::XK_Num_Lock,
 		Code::F1 => keysym::XK_F1,
 		Code::F2 => keysym::XK_F2,
 		Code::F3 => keysym::XK_F3,
@@ -418,11 +1636,185 @@ fn keycode_to_x11_scancode(key:Code) -> Option<u32> {
 		Code::MediaStop => keysym::XF86XK_AudioStop,
 		Code::MediaTrackNext => keysym::XF86XK_AudioNext,
 		Code::MediaTrackPrevious => keysym::XF86XK_AudioPrev,
+		Code::BrightnessUp => keysym::XF86XK_MonBrightnessUp,
+		Code::BrightnessDown => keysym::XF86XK_MonBrightnessDown,
+		Code::Eject => keysym::XF86XK_Eject,
+		Code::Power => keysym::XF86XK_PowerOff,
+		// Grabbing it may keep the power manager of the desktop from waking up
+		// the screen, see `HotKey`.
+		Code::WakeUp => keysym::XF86XK_WakeUp,
+		Code::LaunchApp1 => keysym::XF86XK_Launch0,
+		Code::LaunchApp2 => keysym::XF86XK_Launch1,
+		Code::LaunchMail => keysym::XF86XK_Mail,
+		// The media player key, `LaunchMediaPlayer` in the UI Events spec.
+		Code::MediaSelect => keysym::XF86XK_AudioMedia,
 		Code::Pause => keysym::XK_Pause,
+		Code::ContextMenu => keysym::XK_Menu,
+		// The key left of Z on ISO keyboards, `less` in most layouts with it.
+		Code::IntlBackslash => keysym::XK_less,
+		Code::IntlRo => keysym::XK_Romaji,
+		Code::IntlYen => keysym::XK_yen,
+		_ => return None,
+	})
+}
+
+// The keysym of the first level is used, so letters come in lower case.
+//
+// Also maps the modifier keys, which the recordings see but no hotkey can be
+// registered on.
+pub(crate) fn x11_keysym_to_keycode(keysym:u32) -> Option<Code> {
+	Some(match keysym {
+		keysym::XK_a => Code::KeyA,
+		keysym::XK_b => Code::KeyB,
+		keysym::XK_c => Code::KeyC,
+		keysym::XK_d => Code::KeyD,
+		keysym::XK_e => Code::KeyE,
+		keysym::XK_f => Code::KeyF,
+		keysym::XK_g => Code::KeyG,
+		keysym::XK_h => Code::KeyH,
+		keysym::XK_i => Code::KeyI,
+		keysym::XK_j => Code::KeyJ,
+		keysym::XK_k => Code::KeyK,
+		keysym::XK_l => Code::KeyL,
+		keysym::XK_m => Code::KeyM,
+		keysym::XK_n => Code::KeyN,
+		keysym::XK_o => Code::KeyO,
+		keysym::XK_p => Code::KeyP,
+		keysym::XK_q => Code::KeyQ,
+		keysym::XK_r => Code::KeyR,
+		keysym::XK_s => Code::KeyS,
+		keysym::XK_t => Code::KeyT,
+		keysym::XK_u => Code::KeyU,
+		keysym::XK_v => Code::KeyV,
+		keysym::XK_w => Code::KeyW,
+		keysym::XK_x => Code::KeyX,
+		keysym::XK_y => Code::KeyY,
+		keysym::XK_z => Code::KeyZ,
+		keysym::XK_backslash => Code::Backslash,
+		keysym::XK_bracketleft => Code::BracketLeft,
+		keysym::XK_bracketright => Code::BracketRight,
+		keysym::XK_quoteleft => Code::Backquote,
+		keysym::XK_comma => Code::Comma,
+		keysym::XK_0 => Code::Digit0,
+		keysym::XK_1 => Code::Digit1,
+		keysym::XK_2 => Code::Digit2,
+		keysym::XK_3 => Code::Digit3,
+		keysym::XK_4 => Code::Digit4,
+		keysym::XK_5 => Code::Digit5,
+		keysym::XK_6 => Code::Digit6,
+		keysym::XK_7 => Code::Digit7,
+		keysym::XK_8 => Code::Digit8,
+		keysym::XK_9 => Code::Digit9,
+		keysym::XK_equal => Code::Equal,
+		keysym::XK_minus => Code::Minus,
+		keysym::XK_period => Code::Period,
+		keysym::XK_apostrophe | keysym::XK_leftsinglequotemark => Code::Quote,
+		keysym::XK_semicolon => Code::Semicolon,
+		keysym::XK_slash => Code::Slash,
+		keysym::XK_BackSpace => Code::Backspace,
+		keysym::XK_Caps_Lock => Code::CapsLock,
+		keysym::XK_Return => Code::Enter,
+		keysym::XK_space => Code::Space,
+		keysym::XK_Tab => Code::Tab,
+		keysym::XK_Delete => Code::Delete,
+		keysym::XK_End => Code::End,
+		keysym::XK_Home => Code::Home,
+		keysym::XK_Insert => Code::Insert,
+		keysym::XK_Page_Down => Code::PageDown,
+		keysym::XK_Page_Up => Code::PageUp,
+		keysym::XK_Down => Code::ArrowDown,
+		keysym::XK_Left => Code::ArrowLeft,
+		keysym::XK_Right => Code::ArrowRight,
+		keysym::XK_Up => Code::ArrowUp,
+		keysym::XK_KP_0 | keysym::XK_KP_Insert => Code::Numpad0,
+		keysym::XK_KP_1 | keysym::XK_KP_End => Code::Numpad1,
+		keysym::XK_KP_2 | keysym::XK_KP_Down => Code::Numpad2,
+		keysym::XK_KP_3 | keysym::XK_KP_Next => Code::Numpad3,
+		keysym::XK_KP_4 | keysym::XK_KP_Left => Code::Numpad4,
+		keysym::XK_KP_5 | keysym::XK_KP_Begin => Code::Numpad5,
+		keysym::XK_KP_6 | keysym::XK_KP_Right => Code::Numpad6,
+		keysym::XK_KP_7 | keysym::XK_KP_Home => Code::Numpad7,
+		keysym::XK_KP_8 | keysym::XK_KP_Up => Code::Numpad8,
+		keysym::XK_KP_9 | keysym::XK_KP_Prior => Code::Numpad9,
+		keysym::XK_KP_Add => Code::NumpadAdd,
+		keysym::XK_KP_Separator => Code::NumpadComma,
+		keysym::XK_KP_Decimal | keysym::XK_KP_Delete => Code::NumpadDecimal,
+		keysym::XK_KP_Divide => Code::NumpadDivide,
+		keysym::XK_KP_Enter => Code::NumpadEnter,
+		keysym::XK_KP_Multiply => Code::NumpadMultiply,
+		keysym::XK_KP_Subtract => Code::NumpadSubtract,
+		keysym::XK_Escape => Code::Escape,
+		keysym::XK_Print => Code::PrintScreen,
+		keysym::XK_Scroll_Lock => Code::ScrollLock,
+		keysym::XK_Num_Lock => Code::NumLock,
+		keysym::XK_F1 => Code::F1,
+		keysym::XK_F2 => Code::F2,
+		keysym::XK_F3 => Code::F3,
+		keysym::XK_F4 => Code::F4,
+		keysym::XK_F5 => Code::F5,
+		keysym::XK_F6 => Code::F6,
+		keysym::XK_F7 => Code::F7,
+		keysym::XK_F8 => Code::F8,
+		keysym::XK_F9 => Code::F9,
+		keysym::XK_F10 => Code::F10,
+		keysym::XK_F11 => Code::F11,
+		keysym::XK_F12 => Code::F12,
+		keysym::XK_Shift_L => Code::ShiftLeft,
+		keysym::XK_Shift_R => Code::ShiftRight,
+		keysym::XK_Control_L => Code::ControlLeft,
+		keysym::XK_Control_R => Code::ControlRight,
+		keysym::XK_Alt_L => Code::AltLeft,
+		keysym::XK_Alt_R => Code::AltRight,
+		keysym::XK_Super_L => Code::MetaLeft,
+		keysym::XK_Super_R => Code::MetaRight,
+		keysym::XF86XK_AudioLowerVolume => Code::AudioVolumeDown,
+		keysym::XF86XK_AudioMute => Code::AudioVolumeMute,
+		keysym::XF86XK_AudioRaiseVolume => Code::AudioVolumeUp,
+		keysym::XF86XK_AudioPlay => Code::MediaPlay,
+		keysym::XF86XK_AudioPause => Code::MediaPause,
+		keysym::XF86XK_AudioStop => Code::MediaStop,
+		keysym::XF86XK_AudioNext => Code::MediaTrackNext,
+		keysym::XF86XK_AudioPrev => Code::MediaTrackPrevious,
+		keysym::XF86XK_MonBrightnessUp => Code::BrightnessUp,
+		keysym::XF86XK_MonBrightnessDown => Code::BrightnessDown,
+		keysym::XF86XK_Eject => Code::Eject,
+		keysym::XF86XK_PowerOff => Code::Power,
+		keysym::XF86XK_WakeUp => Code::WakeUp,
+		keysym::XF86XK_Launch0 => Code::LaunchApp1,
+		keysym::XF86XK_Launch1 => Code::LaunchApp2,
+		keysym::XF86XK_Mail => Code::LaunchMail,
+		keysym::XF86XK_AudioMedia => Code::MediaSelect,
+		keysym::XK_Pause => Code::Pause,
+		keysym::XK_Menu => Code::ContextMenu,
+		keysym::XK_less => Code::IntlBackslash,
+		keysym::XK_Romaji => Code::IntlRo,
+		keysym::XK_yen => Code::IntlYen,
 		_ => return None,
 	})
 }
 
+fn x11_mods_to_modifiers(state:u32) -> Modifiers {
+	let mut modifiers = Modifiers::empty();
+
+	if state & xlib::ShiftMask != 0 {
+		modifiers |= Modifiers::SHIFT;
+	}
+
+	if state & xlib::Mod4Mask != 0 {
+		modifiers |= Modifiers::SUPER;
+	}
+
+	if state & xlib::Mod1Mask != 0 {
+		modifiers |= Modifiers::ALT;
+	}
+
+	if state & xlib::ControlMask != 0 {
+		modifiers |= Modifiers::CONTROL;
+	}
+
+	modifiers
+}
+
 fn modifiers_to_x11_mods(modifiers:Modifiers) -> u32 {
 	let mut x11mods = 0;
 
@@ -430,7 +1822,7 @@ fn modifiers_to_x11_mods(modifiers:Modifiers) -> u32 {
 		x11mods |= xlib::ShiftMask;
 	}
 
-	if modifiers.intersects(Modifiers::SUPER | Modifiers::META) {
+	if crate::canonical::fold(modifiers).contains(Modifiers::SUPER) {
 		x11mods |= xlib::Mod4Mask;
 	}
 
@@ -444,3 +1836,757 @@ fn modifiers_to_x11_mods(modifiers:Modifiers) -> u32 {
 
 	x11mods
 }
+
+// The X11 masks of the modifiers a hotkey forbids, including the locks it
+// requires off, and of the locks it requires on.
+fn lock_x11_masks(options:&HotKeyOptions) -> (u32, u32) {
+	(x11_mods_with_locks(options.forbidden()), x11_mods_with_locks(options.required_locks()))
+}
+
+// The X11 mask of `modifiers`, including the locks.
+fn x11_mods_with_locks(modifiers:Modifiers) -> u32 {
+	let mut x11mods = modifiers_to_x11_mods(modifiers);
+
+	if modifiers.contains(Modifiers::CAPS_LOCK) {
+		x11mods |= xlib::LockMask;
+	}
+
+	if modifiers.contains(Modifiers::NUM_LOCK) {
+		x11mods |= xlib::Mod2Mask;
+	}
+
+	x11mods
+}
+
+#[test]
+fn test_concurrent_calls() {
+	use std::time::Duration;
+
+	// Without a display every call is answered with an error, which still goes
+	// through the same round trip to the event thread.
+	let manager = std::sync::Arc::new(crate::GlobalHotKeyManager::new().unwrap());
+
+	let (done_tx, done_rx) = unbounded();
+
+	let keys = [
+		Code::F13,
+		Code::F14,
+		Code::F15,
+		Code::F16,
+		Code::F17,
+		Code::F18,
+		Code::F19,
+		Code::F20,
+	];
+
+	for key in keys {
+		let (manager, done_tx) = (manager.clone(), done_tx.clone());
+
+		std::thread::spawn(move || {
+			let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), key);
+
+			// Each call waits on its own reply among the ones of the other
+			// threads, a reply of the wrong kind panics.
+			for _ in 0..25 {
+				let _ = manager.register(hotkey);
+				let _ = manager.register_all(&[hotkey, hotkey]);
+				let _ = manager.revalidate();
+				let _ = manager.status();
+				let _ = manager.unregister_all(&[hotkey]);
+				let _ = manager.unregister(hotkey);
+			}
+
+			done_tx.send(()).unwrap();
+		});
+	}
+
+	for _ in keys {
+		done_rx.recv_timeout(Duration::from_secs(60)).expect("a thread never got its answer");
+	}
+}
+
+#[test]
+fn test_no_silent_ok() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F22);
+
+	// A backend whose event thread is gone, e.g. after a panic.
+	let (thread_tx, thread_rx) = unbounded();
+
+	drop(thread_rx);
+
+	let manager = GlobalHotKeyManager {
+		thread_tx:Mutex::new(Some(thread_tx)),
+		mailbox:Mailbox::new(),
+		root:Root::Screen(None),
+		stop_when_idle:false,
+		poll_interval:POLL_INTERVAL,
+		raw_event_hook:Arc::default(),
+	};
+
+	assert!(matches!(manager.register(hotkey), Err(crate::Error::OsError { .. })));
+
+	assert!(manager.revalidate().is_err());
+
+	assert!(manager.start_recording().is_err());
+
+	assert!(matches!(manager.status().health, crate::Health::Failed(_)));
+
+	// Without an X server the first call already gets the failure, it isn't
+	// queued behind the connection attempt.
+	if std::env::var_os("DISPLAY").is_none() {
+		let manager = GlobalHotKeyManager::new(&GlobalHotKeyManagerOptions::default()).unwrap();
+
+		assert!(manager.register(hotkey).is_err());
+	}
+}
+
+// Presses `keysyms` in order and releases them in reverse through XTEST,
+// returning the keycodes they're on. Needs an X server with the XTEST
+// extension, the tests using it are ignored unless run with
+// `xvfb-run cargo test -- --ignored`.
+#[cfg(test)]
+fn fake_keys(keysyms:&[u32]) -> Vec<u32> {
+	use std::ptr;
+
+	use x11_dl::xtest::Xf86vmode;
+
+	let (xlib, xtest) = (xlib::Xlib::open().unwrap(), Xf86vmode::open().unwrap());
+
+	unsafe {
+		let display = (xlib.XOpenDisplay)(ptr::null());
+
+		assert!(!display.is_null(), "No X server to fake keys on");
+
+		let keycodes = keysyms
+			.iter()
+			.map(|keysym| u32::from((xlib.XKeysymToKeycode)(display, (*keysym).into())))
+			.collect::<Vec<_>>();
+
+		for keycode in &keycodes {
+			(xtest.XTestFakeKeyEvent)(display, *keycode, 1, 0);
+		}
+
+		for keycode in keycodes.iter().rev() {
+			(xtest.XTestFakeKeyEvent)(display, *keycode, 0, 0);
+		}
+
+		(xlib.XFlush)(display);
+
+		(xlib.XCloseDisplay)(display);
+
+		keycodes
+	}
+}
+
+#[test]
+#[ignore = "needs an X server with the XTEST extension"]
+fn test_numpad_in_both_numlock_states() {
+	use std::sync::Arc;
+
+	use crate::HotKeyState;
+
+	let manager = crate::GlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::Numpad1);
+
+	// A callback, as other tests may drain the shared channel.
+	let (events_tx, events_rx) = unbounded();
+
+	manager.add_callback(0, Arc::new(move |event| {
+		let _ = events_tx.send(event.state());
+	}));
+
+	manager.register(hotkey).unwrap();
+
+	// Toggling twice tries both states and leaves NumLock as it was.
+	for _ in 0..2 {
+		fake_keys(&[keysym::XK_Num_Lock]);
+
+		fake_keys(&[keysym::XK_Control_L, keysym::XK_Alt_L, keysym::XK_KP_1]);
+
+		for state in [HotKeyState::Pressed, HotKeyState::Released] {
+			assert_eq!(events_rx.recv_timeout(Duration::from_secs(5)), Ok(state));
+		}
+	}
+
+	manager.unregister(hotkey).unwrap();
+}
+
+#[test]
+fn test_launch_keys() {
+	for (key, keysym) in [
+		(Code::LaunchApp1, keysym::XF86XK_Launch0),
+		(Code::LaunchApp2, keysym::XF86XK_Launch1),
+		(Code::LaunchMail, keysym::XF86XK_Mail),
+		(Code::MediaSelect, keysym::XF86XK_AudioMedia),
+	] {
+		assert_eq!(keycode_to_x11_scancode(key), Some(keysym));
+
+		assert_eq!(x11_keysym_to_keycode(keysym), Some(key));
+
+		assert!(is_supported(&HotKey::new(None, key)));
+	}
+}
+
+#[test]
+fn test_wake_up() {
+	assert_eq!(keycode_to_x11_scancode(Code::WakeUp), Some(keysym::XF86XK_WakeUp));
+
+	assert_eq!(x11_keysym_to_keycode(keysym::XF86XK_WakeUp), Some(Code::WakeUp));
+
+	assert!(is_supported(&HotKey::new(None, Code::WakeUp)));
+}
+
+#[test]
+fn test_keysym_round_trip() {
+	let modifier_keys = [
+		Code::ShiftLeft,
+		Code::ShiftRight,
+		Code::ControlLeft,
+		Code::ControlRight,
+		Code::AltLeft,
+		Code::AltRight,
+		Code::MetaLeft,
+		Code::MetaRight,
+	];
+
+	// The letters are grabbed upper case.
+	let lower = |keysym:u32| match keysym {
+		keysym::XK_A..=keysym::XK_Z => keysym + (keysym::XK_a - keysym::XK_A),
+		_ => keysym,
+	};
+
+	// The Latin-1 and misc keysyms, then the `XF86XK_` ones.
+	let keysyms = (0..=0xffff).chain(0x1008_ff00..=0x1008_ffff);
+
+	for keysym in keysyms {
+		let Some(key) = x11_keysym_to_keycode(keysym) else {
+			continue;
+		};
+
+		if modifier_keys.contains(&key) {
+			assert_eq!(keycode_to_x11_scancode(key), None, "{:?}", key);
+
+			continue;
+		}
+
+		let grabbed = keycode_to_x11_scancode(key).map(lower);
+
+		// The numpad keys and the quote have a second keysym, the other one is
+		// grabbed.
+		let alias =
+			keysym == keysym::XK_apostrophe || numpad_navigation_keysym(key) == Some(keysym);
+
+		match alias {
+			true => assert_eq!(grabbed.and_then(x11_keysym_to_keycode), Some(key)),
+			false => assert_eq!(grabbed, Some(keysym), "{:?} of keysym {:#x}", key, keysym),
+		}
+	}
+}
+
+#[test]
+fn test_context_menu() {
+	assert_eq!(keycode_to_x11_scancode(Code::ContextMenu), Some(keysym::XK_Menu));
+
+	assert_eq!(x11_keysym_to_keycode(keysym::XK_Menu), Some(Code::ContextMenu));
+
+	// `Apps` names the same key, keyboard-types has no code of its own for it.
+	assert_eq!("Apps".parse::<HotKey>().unwrap().key, Code::ContextMenu);
+}
+
+#[test]
+#[ignore = "needs an X server with the XTEST extension"]
+fn test_context_menu_press() {
+	use std::sync::Arc;
+
+	use crate::HotKeyState;
+
+	let manager = crate::GlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::ContextMenu);
+
+	let (events_tx, events_rx) = unbounded();
+
+	manager.add_callback(0, Arc::new(move |event| {
+		let _ = events_tx.send(event.state());
+	}));
+
+	manager.register(hotkey).unwrap();
+
+	fake_keys(&[keysym::XK_Control_L, keysym::XK_Alt_L, keysym::XK_Menu]);
+
+	for state in [HotKeyState::Pressed, HotKeyState::Released] {
+		assert_eq!(events_rx.recv_timeout(Duration::from_secs(5)), Ok(state));
+	}
+
+	manager.unregister(hotkey).unwrap();
+}
+
+#[test]
+fn test_forbidden_locks() {
+	let options = HotKeyOptions::new().forbid_mods(Modifiers::CAPS_LOCK | Modifiers::SHIFT);
+
+	assert_eq!(lock_x11_masks(&options), (xlib::LockMask | xlib::ShiftMask, 0));
+
+	// Only the CapsLock variants of the grab are left out.
+	assert_eq!(hotkey_ignored_mods(&options).collect::<Vec<_>>(), [0, xlib::Mod2Mask]);
+
+	let shift = HotKeyOptions::new().forbid_mods(Modifiers::SHIFT);
+
+	assert_eq!(hotkey_ignored_mods(&shift).collect::<Vec<_>>(), IGNORED_MODS);
+}
+
+#[test]
+#[ignore = "needs an X server with the XTEST extension"]
+fn test_forbidden_locks_press() {
+	use std::sync::Arc;
+
+	use crate::{HotKeyOptions, HotKeyState};
+
+	let manager = crate::GlobalHotKeyManager::new().unwrap();
+
+	// Control+Alt+F8 switches virtual terminals, the manager refuses it.
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::F8);
+
+	let (events_tx, events_rx) = unbounded();
+
+	manager.add_callback(0, Arc::new(move |event| {
+		let _ = events_tx.send(event.state());
+	}));
+
+	manager
+		.register_with_options(hotkey, HotKeyOptions::new().forbid_mods(Modifiers::CAPS_LOCK))
+		.unwrap();
+
+	// Toggling twice leaves CapsLock as it was, only one state triggers.
+	let mut triggered = 0;
+
+	for _ in 0..2 {
+		fake_keys(&[keysym::XK_Caps_Lock]);
+
+		fake_keys(&[keysym::XK_Control_L, keysym::XK_Shift_L, keysym::XK_F8]);
+
+		if events_rx.recv_timeout(Duration::from_millis(500)) == Ok(HotKeyState::Pressed) {
+			let released = events_rx.recv_timeout(Duration::from_secs(5));
+
+			assert_eq!(released, Ok(HotKeyState::Released));
+
+			triggered += 1;
+		}
+	}
+
+	assert_eq!(triggered, 1);
+
+	manager.unregister(hotkey).unwrap();
+}
+
+#[test]
+fn test_release_ignores_mods() {
+	let control = xlib::ControlMask;
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS);
+
+	let grabbed = |options:HotKeyOptions| RegisteredEntry::new(hotkey, &options, 39, control);
+
+	let mut entry = vec![grabbed(HotKeyOptions::default())];
+
+	// The locks don't get in the way, auto-repeat isn't pressed again.
+	assert_eq!(press(&mut entry, control | xlib::Mod2Mask | xlib::LockMask, |_| true), [
+		hotkey.id()
+	]);
+
+	assert!(press(&mut entry, control, |_| true).is_empty());
+
+	assert_eq!(release(&mut entry), [hotkey.id()]);
+
+	// Ctrl let go before S still releases it.
+	assert_eq!(press(&mut entry, control, |_| true), [hotkey.id()]);
+
+	assert_eq!(release(&mut entry), [hotkey.id()]);
+
+	// Nothing to release without a press, or after a press of other modifiers.
+	assert!(release(&mut entry).is_empty());
+
+	assert!(press(&mut entry, control | xlib::ShiftMask, |_| true).is_empty());
+
+	assert!(release(&mut entry).is_empty());
+
+	let mut entry = vec![grabbed(HotKeyOptions::default().forbid_mods(Modifiers::CAPS_LOCK))];
+
+	assert!(press(&mut entry, control | xlib::LockMask, |_| true).is_empty());
+
+	assert!(release(&mut entry).is_empty());
+
+	let sided = "RightCtrl+KeyS".parse::<HotKey>().unwrap();
+
+	let mut entry = vec![RegisteredEntry::new(sided, &HotKeyOptions::default(), 39, control)];
+
+	assert!(press(&mut entry, control, |_| false).is_empty());
+
+	assert_eq!(press(&mut entry, control, |_| true), [sided.id()]);
+
+	assert_eq!(release(&mut entry), [sided.id()]);
+}
+
+#[test]
+fn test_required_locks() {
+	use crate::{LockState, LockToggle};
+
+	let control = xlib::ControlMask;
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::Numpad5);
+
+	let require = |num_lock| {
+		HotKeyOptions::new().require_locks(LockState::new(num_lock, LockToggle::Either))
+	};
+
+	let (on, off) = (require(LockToggle::On), require(LockToggle::Off));
+
+	assert_eq!(hotkey_ignored_mods(&on).collect::<Vec<_>>(), [
+		xlib::Mod2Mask,
+		xlib::Mod2Mask | xlib::LockMask
+	]);
+
+	assert_eq!(hotkey_ignored_mods(&off).collect::<Vec<_>>(), [0, xlib::LockMask]);
+
+	let (digit, navigation) = (hotkey.with_id(1), hotkey.with_id(2));
+
+	let mut hotkeys = RegisteredEntries::new();
+
+	hotkeys.insert(RegisteredEntry::new(digit, &on, 84, control));
+
+	// Both share the grab, which covers every lock state between them.
+	assert!(hotkeys.overlapping(84, control, &navigation, &off.lock_state()).is_none());
+
+	assert!(hotkeys.overlapping(84, control, &navigation, &LockState::EITHER).is_some());
+
+	hotkeys.insert(RegisteredEntry::new(navigation, &off, 84, control));
+
+	assert_eq!(shared_ignored_mods(&hotkeys, 84, control), IGNORED_MODS);
+
+	let entry = hotkeys.on_mut(84).unwrap();
+
+	assert_eq!(press(entry, control | xlib::Mod2Mask, |_| true), [1]);
+
+	assert_eq!(release(entry), [1]);
+
+	assert_eq!(press(entry, control | xlib::LockMask, |_| true), [2]);
+
+	assert_eq!(release(entry), [2]);
+
+	hotkeys.remove(2);
+
+	assert_eq!(shared_ignored_mods(&hotkeys, 84, control), [
+		xlib::Mod2Mask,
+		xlib::Mod2Mask | xlib::LockMask
+	]);
+}
+
+#[test]
+fn test_press() {
+	let (control, alt) = (xlib::ControlMask, xlib::Mod1Mask);
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS);
+
+	let (left, right) = (
+		hotkey.with_side(Modifiers::CONTROL, ModifierSide::Left),
+		hotkey.with_side(Modifiers::CONTROL, ModifierSide::Right),
+	);
+
+	let with_alt = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyS);
+
+	let options = HotKeyOptions::new();
+
+	let mut entry = [left, right]
+		.into_iter()
+		.map(|hotkey| RegisteredEntry::new(hotkey, &options, 39, control))
+		.chain([RegisteredEntry::new(with_alt, &options, 39, control | alt)])
+		.collect::<Vec<_>>();
+
+	// Only the side held is pressed, the locks are left out of the mods.
+	let held = |side:ModifierSide| move |sides:&ModifierSides| sides.control == side;
+
+	assert_eq!(press(&mut entry, control | xlib::Mod2Mask, held(ModifierSide::Right)), [
+		right.id()
+	]);
+
+	// Pressed until released.
+	assert!(press(&mut entry, control, held(ModifierSide::Right)).is_empty());
+
+	assert_eq!(press(&mut entry, control, held(ModifierSide::Left)), [left.id()]);
+
+	assert_eq!(release(&mut entry), [left.id(), right.id()]);
+
+	// Every modifier has to match, no more and no less.
+	assert!(press(&mut entry, control | xlib::ShiftMask, |_| true).is_empty());
+
+	assert!(press(&mut entry, alt, |_| true).is_empty());
+
+	// The sides of an unsided hotkey aren't checked.
+	assert_eq!(press(&mut entry, control | alt, |_| false), [with_alt.id()]);
+}
+
+#[test]
+fn test_ignored_mods() {
+	let (num_lock, caps_lock) = (xlib::Mod2Mask, xlib::LockMask);
+
+	assert_eq!(ignored_mods(0, 0).collect::<Vec<_>>(), IGNORED_MODS);
+
+	assert_eq!(ignored_mods(num_lock, 0).collect::<Vec<_>>(), [0, caps_lock]);
+
+	assert_eq!(ignored_mods(0, caps_lock).collect::<Vec<_>>(), [
+		caps_lock,
+		num_lock | caps_lock
+	]);
+
+	assert_eq!(ignored_mods(caps_lock, num_lock).collect::<Vec<_>>(), [num_lock]);
+
+	// Contradicting options leave nothing to grab.
+	assert_eq!(ignored_mods(num_lock, num_lock).count(), 0);
+
+	// Forbidding a modifier that isn't a lock doesn't change the grabs.
+	assert_eq!(ignored_mods(xlib::ShiftMask, 0).collect::<Vec<_>>(), IGNORED_MODS);
+}
+
+#[test]
+fn test_hotkey_keycodes() {
+	// A keymap with the navigation keysym of `Numpad1` on its own keycode.
+	let keymap = |keysym:u32| {
+		match keysym {
+			keysym::XK_S => 39,
+			keysym::XK_KP_1 => 87,
+			keysym::XK_KP_End => 88,
+			keysym::XK_KP_5 | keysym::XK_KP_Begin => 84,
+			_ => 0,
+		}
+	};
+
+	assert_eq!(hotkey_keycodes(keymap, Code::KeyS), Some(vec![39]));
+
+	assert_eq!(hotkey_keycodes(keymap, Code::Numpad1), Some(vec![87, 88]));
+
+	// Both keysyms on the same keycode.
+	assert_eq!(hotkey_keycodes(keymap, Code::Numpad5), Some(vec![84]));
+
+	// Not in the keymap, or without a keysym at all.
+	assert_eq!(hotkey_keycodes(keymap, Code::Numpad9), None);
+
+	assert_eq!(hotkey_keycodes(keymap, Code::Fn), None);
+}
+
+#[test]
+fn test_handoff() {
+	let control = xlib::ControlMask;
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL), Code::KeyS);
+
+	let right = hotkey.with_side(Modifiers::CONTROL, ModifierSide::Right);
+
+	let consume = HotKeyOptions::new();
+
+	let mut hotkeys = RegisteredEntries::new();
+
+	hotkeys.insert(RegisteredEntry::new(hotkey, &consume, 39, control));
+
+	hotkeys.insert(RegisteredEntry::new(right, &consume, 40, control));
+
+	let pass_through = consume.clone().consume(false);
+
+	hotkeys.insert(RegisteredEntry::new(hotkey, &pass_through, 41, control));
+
+	// Another grab on the same key, which the presses with Ctrl alone don't hit.
+	let alt = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyS);
+
+	hotkeys.insert(RegisteredEntry::new(alt, &pass_through, 40, control | xlib::Mod1Mask));
+
+	// The X server keeps the presses of plain consuming grabs on its own.
+	let entry = hotkeys.on_mut(39).unwrap();
+
+	assert_eq!(press(entry, control, |_| true), [hotkey.id()]);
+
+	assert_eq!(handoff(entry, control), Handoff::None);
+
+	// A press on the wrong side goes to the focused window, `LeftCtrl+S` still
+	// saves.
+	let entry = hotkeys.on_mut(40).unwrap();
+
+	assert!(press(entry, control, |_| false).is_empty());
+
+	assert_eq!(handoff(entry, control), Handoff::Replay);
+
+	assert_eq!(press(entry, control, |_| true), [right.id()]);
+
+	assert_eq!(handoff(entry, control), Handoff::Consume);
+
+	// So are its repeats, whatever the locks.
+	assert!(press(entry, control | xlib::Mod2Mask, |_| true).is_empty());
+
+	assert_eq!(handoff(entry, control | xlib::Mod2Mask), Handoff::Consume);
+
+	// No grab of the keycode has these mods anymore, a press it froze the
+	// keyboard for is handed back.
+	assert_eq!(handoff(entry, xlib::ShiftMask), Handoff::Replay);
+
+	let entry = hotkeys.on_mut(41).unwrap();
+
+	assert_eq!(press(entry, control, |_| true), [hotkey.id()]);
+
+	assert_eq!(handoff(entry, control), Handoff::Replay);
+}
+
+#[test]
+fn test_lazy_event_thread() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F9);
+
+	let options = GlobalHotKeyManagerOptions::new().stop_when_idle(true);
+
+	let manager = GlobalHotKeyManager::new(&options).unwrap();
+
+	// Nothing is started until a hotkey is registered.
+	assert!(!manager.is_running());
+
+	assert!(manager.status().hotkeys.is_empty());
+
+	assert!(!manager.is_running());
+
+	// Connection failures are reported by every registration that starts it.
+	let connected = std::env::var_os("DISPLAY").is_some();
+
+	for _ in 0..2 {
+		assert_eq!(manager.register(hotkey).is_ok(), connected);
+
+		assert!(manager.is_running());
+
+		let _ = manager.unregister(hotkey);
+
+		assert!(!manager.is_running());
+	}
+
+	// Kept running while suspended, the state would be lost otherwise.
+	if connected {
+		manager.suspend().unwrap();
+
+		manager.clear_all().unwrap();
+
+		assert!(manager.is_running());
+	}
+
+	let manager = GlobalHotKeyManager::new(&GlobalHotKeyManagerOptions::default()).unwrap();
+
+	let _ = manager.register(hotkey);
+
+	let _ = manager.unregister(hotkey);
+
+	assert!(manager.is_running());
+}
+
+#[test]
+fn test_screen_number() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F9);
+
+	let options = GlobalHotKeyManagerOptions::new().screen_number(i32::MAX);
+
+	let manager = GlobalHotKeyManager::new(&options).unwrap();
+
+	let result = manager.register(hotkey);
+
+	if std::env::var_os("DISPLAY").is_some() {
+		assert!(matches!(result, Err(crate::Error::OsError { .. })));
+	} else {
+		assert!(result.is_err());
+	}
+
+	// The window overrides the screen.
+	let options = options.root_window_override(0);
+
+	assert!(matches!(Root::new(&options), Root::Window(0)));
+}
+
+#[test]
+fn test_poll_interval() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F23);
+
+	let interval = Duration::from_secs(1);
+
+	let options = GlobalHotKeyManagerOptions::new().poll_interval(interval);
+
+	let manager = GlobalHotKeyManager::new(&options).unwrap();
+
+	// Starts the event thread, which then waits for the next call.
+	let registered = manager.register(hotkey).is_ok();
+
+	std::thread::sleep(Duration::from_millis(100));
+
+	for _ in 0..3 {
+		let start = std::time::Instant::now();
+
+		if registered {
+			manager.unregister(hotkey).unwrap();
+
+			manager.register(hotkey).unwrap();
+		} else {
+			assert!(manager.register(hotkey).is_err());
+		}
+
+		// Answered right away, not at the next poll.
+		assert!(start.elapsed() < interval / 4, "took {:?}", start.elapsed());
+	}
+
+	if registered {
+		manager.unregister(hotkey).unwrap();
+	}
+}
+
+#[test]
+fn test_drop_releases_grabs() {
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F17);
+
+	let connected = std::env::var_os("DISPLAY").is_some();
+
+	for _ in 0..2 {
+		let manager = GlobalHotKeyManager::new(&GlobalHotKeyManagerOptions::default()).unwrap();
+
+		// Taken again right away, the grabs of the previous manager are gone.
+		assert_eq!(manager.register(hotkey).is_ok(), connected);
+
+		let start = std::time::Instant::now();
+
+		drop(manager);
+
+		assert!(start.elapsed() < DROP_TIMEOUT);
+	}
+}
+
+#[test]
+#[ignore = "needs an X server with the XTEST extension"]
+fn test_raw_event_hook() {
+	let manager = crate::GlobalHotKeyManager::new().unwrap();
+
+	let (raw_tx, raw_rx) = unbounded();
+
+	manager
+		.set_raw_event_hook(move |event| {
+			let keycode:u32 = match event {
+				#[cfg(not(feature = "x11rb"))]
+				RawPlatformEvent::XKey(event) => event.keycode,
+				#[cfg(feature = "x11rb")]
+				RawPlatformEvent::X11rbKey(event) => event.detail.into(),
+			};
+
+			let _ = raw_tx.send(keycode);
+		})
+		.unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::F10);
+
+	manager.register(hotkey).unwrap();
+
+	let keycodes = fake_keys(&[keysym::XK_Control_L, keysym::XK_Shift_L, keysym::XK_F10]);
+
+	// The press and the release of the grabbed key.
+	for _ in 0..2 {
+		assert_eq!(raw_rx.recv_timeout(Duration::from_secs(5)), Ok(keycodes[2]));
+	}
+
+	manager.unregister(hotkey).unwrap();
+}
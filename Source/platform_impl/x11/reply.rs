@@ -0,0 +1,219 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// How the event thread answers the manager. Every request carries an id and
+// its reply is left in a single mailbox under that id, so any number of
+// threads can wait on their own reply at once without a channel per call.
+
+use std::{
+	collections::HashMap,
+	sync::{
+		Arc,
+		Condvar,
+		Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
+};
+
+use crossbeam_channel::{RecvError, RecvTimeoutError};
+
+use crate::{ManagerStatus, ProcessInfo, hotkey::HotKey};
+
+/// The answer of the event thread to a request.
+#[derive(Debug)]
+pub(super) enum Reply {
+	One(crate::Result<()>),
+	/// A result per hotkey of a bulk request, in the order they were given.
+	Many(Vec<crate::Result<()>>),
+	/// The hotkeys an inspection request asked for.
+	HotKeys(Vec<HotKey>),
+	Status(ManagerStatus),
+	/// Whether the thread stopped for having nothing to do.
+	Idle(bool),
+	/// The window manager found running, if any.
+	WindowManager(Option<ProcessInfo>),
+}
+
+impl Reply {
+	/// The result of a request answered with [`Reply::One`] or
+	/// [`Reply::Many`], the first error of the latter.
+	pub(super) fn into_result(self) -> crate::Result<()> {
+		match self {
+			Reply::One(result) => result,
+			Reply::Many(results) => results.into_iter().collect(),
+			other => unreachable!("{:?} doesn't answer a request for a result", other),
+		}
+	}
+
+	/// The result of each hotkey of a request answered with [`Reply::Many`], or
+	/// the single result of [`Reply::One`].
+	pub(super) fn into_results(self) -> Vec<crate::Result<()>> {
+		match self {
+			Reply::Many(results) => results,
+			other => vec![other.into_result()],
+		}
+	}
+
+	/// The hotkeys of a request answered with [`Reply::HotKeys`], or its error.
+	pub(super) fn into_hotkeys(self) -> crate::Result<Vec<HotKey>> {
+		match self {
+			Reply::HotKeys(hotkeys) => Ok(hotkeys),
+			other => other.into_result().map(|()| Vec::new()),
+		}
+	}
+}
+
+/// The replies the event thread left for the requests waiting on them, `None`
+/// for a request it dropped without answering.
+#[derive(Debug, Default)]
+pub(super) struct Mailbox {
+	next_id:AtomicU64,
+	replies:Mutex<HashMap<u64, Option<Reply>>>,
+	arrived:Condvar,
+}
+
+impl Mailbox {
+	pub(super) fn new() -> Arc<Self> { Arc::default() }
+
+	/// Returns where the reply to a new request is to be sent, along with the
+	/// id to wait on.
+	pub(super) fn reply_to(self:&Arc<Self>) -> (u64, ReplyTo) {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+		(id, ReplyTo { id, mailbox:self.clone(), answered:false })
+	}
+
+	/// Waits for the reply to request `id`, failing if it was dropped
+	/// unanswered, e.g. because the event thread is gone.
+	pub(super) fn wait(&self, id:u64) -> Result<Reply, RecvError> {
+		let mut replies = self.replies.lock().unwrap();
+
+		loop {
+			if let Some(reply) = replies.remove(&id) {
+				return reply.ok_or(RecvError);
+			}
+
+			replies = self.arrived.wait(replies).unwrap();
+		}
+	}
+
+	/// Waits for the reply to request `id` for up to `timeout`.
+	pub(super) fn wait_timeout(&self, id:u64, timeout:Duration) -> Result<Reply, RecvTimeoutError> {
+		let deadline = Instant::now() + timeout;
+
+		let mut replies = self.replies.lock().unwrap();
+
+		loop {
+			if let Some(reply) = replies.remove(&id) {
+				return reply.ok_or(RecvTimeoutError::Disconnected);
+			}
+
+			let left =
+				deadline.checked_duration_since(Instant::now()).ok_or(RecvTimeoutError::Timeout)?;
+
+			replies = self.arrived.wait_timeout(replies, left).unwrap().0;
+		}
+	}
+
+	fn deliver(&self, id:u64, reply:Option<Reply>) {
+		self.replies.lock().unwrap().insert(id, reply);
+
+		// The waiters look for their own id, so all of them are woken up.
+		self.arrived.notify_all();
+	}
+}
+
+/// Where the reply to a request goes, part of the message sent to the event
+/// thread. Dropping it unanswered, along with an unprocessed message, wakes
+/// the request up with no reply.
+#[derive(Debug)]
+pub(super) struct ReplyTo {
+	id:u64,
+	mailbox:Arc<Mailbox>,
+	answered:bool,
+}
+
+impl ReplyTo {
+	pub(super) fn send(mut self, reply:Reply) {
+		self.answered = true;
+
+		self.mailbox.deliver(self.id, Some(reply));
+	}
+}
+
+impl Drop for ReplyTo {
+	fn drop(&mut self) {
+		if !self.answered {
+			self.mailbox.deliver(self.id, None);
+		}
+	}
+}
+
+#[test]
+fn test_mailbox_interleaved_replies() {
+	let mailbox = Mailbox::new();
+
+	let requests = (0..8).map(|_| mailbox.reply_to()).collect::<Vec<_>>();
+
+	let ids = requests.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+	// Answered in reverse order from another thread while all of them wait.
+	let answers = std::thread::spawn(move || {
+		for (id, reply_to) in requests.into_iter().rev() {
+			let results = (0..id).map(|_| Ok(())).collect();
+
+			reply_to.send(Reply::Many(results));
+		}
+	});
+
+	std::thread::scope(|scope| {
+		let waiters = ids
+			.iter()
+			.map(|id| (*id, scope.spawn(|| mailbox.wait(*id))))
+			.collect::<Vec<_>>();
+
+		for (id, waiter) in waiters {
+			match waiter.join().unwrap() {
+				Ok(Reply::Many(results)) => assert_eq!(results.len() as u64, id),
+				other => panic!("request {} got {:?}", id, other),
+			}
+		}
+	});
+
+	answers.join().unwrap();
+
+	assert!(mailbox.replies.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_mailbox_dropped_requests() {
+	let mailbox = Mailbox::new();
+
+	let (answered, reply_to) = mailbox.reply_to();
+
+	reply_to.send(Reply::Idle(true));
+
+	let (dropped, reply_to) = mailbox.reply_to();
+
+	assert_ne!(answered, dropped);
+
+	drop(reply_to);
+
+	assert!(mailbox.wait(dropped).is_err());
+
+	assert!(matches!(mailbox.wait(answered), Ok(Reply::Idle(true))));
+
+	let (late, _reply_to) = mailbox.reply_to();
+
+	let timeout = Duration::from_millis(10);
+
+	assert!(matches!(mailbox.wait_timeout(late, timeout), Err(RecvTimeoutError::Timeout)));
+
+	let failed = Reply::Many(vec![Ok(()), Err(crate::Error::RecordingInProgress), Ok(())]);
+
+	assert!(matches!(failed.into_result(), Err(crate::Error::RecordingInProgress)));
+
+	assert!(Reply::One(Err(crate::Error::RecordingInProgress)).into_hotkeys().is_err());
+}
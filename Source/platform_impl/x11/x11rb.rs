@@ -0,0 +1,332 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The X server connection through the X11 protocol implementation of
+//! `x11rb`, which doesn't need libX11 to be installed.
+
+use keyboard_types::KeyState;
+use x11rb::{
+	CURRENT_TIME,
+	connection::Connection,
+	errors::ReplyError,
+	protocol::{
+		ErrorKind,
+		Event as XEvent,
+		xkb::{self, ConnectionExt as _},
+		xproto::{
+			Allow,
+			Atom,
+			AtomEnum,
+			ChangeWindowAttributesAux,
+			ConnectionExt as _,
+			EventMask,
+			GetKeyboardMappingReply,
+			GetPropertyReply,
+			GrabMode,
+			GrabStatus,
+			ModMask,
+			Window,
+		},
+	},
+	rust_connection::RustConnection,
+	wrapper::ConnectionExt as _,
+};
+
+use super::{Event, Root};
+use crate::{Backend, ProcessInfo, RawPlatformEvent};
+
+pub(crate) const BACKEND:Backend = Backend::X11rb;
+
+pub(super) struct Display {
+	conn:RustConnection,
+	root:Window,
+	min_keycode:u8,
+	mapping:Option<GetKeyboardMappingReply>,
+}
+
+impl Display {
+	/// Connects to the X server, grabbing on `root`.
+	pub(super) fn open(root:Root) -> crate::Result<Self> {
+		let (conn, screen) = x11rb::connect(None).map_err(|e| {
+			crate::Error::PlatformUnsupported(format!(
+				"Failed to connect to the X11 display ({}), make sure the DISPLAY environment \
+				 variable is set.",
+				e
+			))
+		})?;
+
+		let root = match root {
+			Root::Window(window) => {
+				Window::try_from(window).map_err(|_| {
+					crate::Error::os(0, format!("{:#x} is not a valid X11 window", window))
+				})?
+			},
+			Root::Screen(screen_number) => {
+				let screen = screen_number.map_or(Some(screen), |n| usize::try_from(n).ok());
+
+				match screen.and_then(|screen| conn.setup().roots.get(screen)) {
+					Some(screen) => screen.root,
+					None => return Err(Root::no_screen(screen_number.unwrap_or_default())),
+				}
+			},
+		};
+
+		let min_keycode = conn.setup().min_keycode;
+
+		let mut display = Self { conn, root, min_keycode, mapping:None };
+
+		display.refresh_mapping();
+
+		let _ = display.conn.change_window_attributes(
+			root,
+			&ChangeWindowAttributesAux::new().event_mask(EventMask::KEY_PRESS),
+		);
+
+		// Only trigger key release at end of repeated keys, and listen for
+		// keyboard layout changes, which move keysyms to other keycodes.
+		let xkb = display.conn.xkb_use_extension(1, 0).ok().and_then(|c| c.reply().ok());
+
+		if xkb.is_some_and(|reply| reply.supported) {
+			let device = xkb::ID::USE_CORE_KBD.into();
+
+			let flag = xkb::PerClientFlag::DETECTABLE_AUTO_REPEAT;
+
+			let _ = display.conn.xkb_per_client_flags(
+				device,
+				flag,
+				flag,
+				0u32.into(),
+				0u32.into(),
+				0u32.into(),
+			);
+
+			let _ = display.conn.xkb_select_events(
+				device,
+				0u16.into(),
+				xkb::EventType::MAP_NOTIFY,
+				xkb::MapPart::KEY_SYMS,
+				xkb::MapPart::KEY_SYMS,
+				&xkb::SelectEventsAux::new(),
+			);
+		}
+
+		let _ = display.conn.flush();
+
+		Ok(display)
+	}
+
+	fn refresh_mapping(&mut self) {
+		let count = self.conn.setup().max_keycode - self.min_keycode + 1;
+
+		self.mapping = self
+			.conn
+			.get_keyboard_mapping(self.min_keycode, count)
+			.ok()
+			.and_then(|c| c.reply().ok());
+	}
+
+	/// Returns the next queued event without blocking.
+	pub(super) fn next_event(&mut self) -> Option<Event> {
+		let _ = self.conn.flush();
+
+		loop {
+			let event = match self.conn.poll_for_event() {
+				Ok(Some(event)) => event,
+
+				Ok(None) => return None,
+
+				Err(e) => {
+					let message = format!("Lost the connection to the X server: {}", e);
+
+					return Some(Event::Disconnected(crate::Error::os(0, message)));
+				},
+			};
+
+			match event {
+				XEvent::XkbMapNotify(_) => {
+					self.refresh_mapping();
+
+					return Some(Event::LayoutChanged);
+				},
+
+				XEvent::KeyPress(event) => {
+					return Some(Event::Key {
+						state:KeyState::Down,
+						keycode:event.detail.into(),
+						mods:u16::from(event.state).into(),
+						time:event.time,
+						raw:RawPlatformEvent::X11rbKey(event),
+					});
+				},
+
+				XEvent::KeyRelease(event) => {
+					return Some(Event::Key {
+						state:KeyState::Up,
+						keycode:event.detail.into(),
+						mods:u16::from(event.state).into(),
+						time:event.time,
+						raw:RawPlatformEvent::X11rbKey(event),
+					});
+				},
+
+				_ => {},
+			}
+		}
+	}
+
+	/// Returns `0` if no key of the current layout produces `keysym`.
+	///
+	/// Searches the same way as `XKeysymToKeycode`, column by column.
+	pub(super) fn keysym_to_keycode(&self, keysym:u32) -> u32 {
+		let Some(mapping) = &self.mapping else {
+			return 0;
+		};
+
+		let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+
+		(0..per_keycode)
+			.find_map(|column| {
+				mapping
+					.keysyms
+					.chunks(per_keycode)
+					.position(|keysyms| keysyms.get(column) == Some(&keysym))
+			})
+			.map_or(0, |i| u32::from(self.min_keycode) + i as u32)
+	}
+
+	/// Returns the keysym of the first level, so letters come in lower case.
+	pub(super) fn keycode_to_keysym(&self, keycode:u32) -> u32 {
+		let Some(mapping) = &self.mapping else {
+			return 0;
+		};
+
+		let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+
+		keycode
+			.checked_sub(self.min_keycode.into())
+			.and_then(|i| mapping.keysyms.get(i as usize * per_keycode))
+			.copied()
+			.unwrap_or(0)
+	}
+
+	// Returns `false` if another client already grabbed this combination.
+	pub(super) fn grab_key(&self, keycode:u32, modifiers:u32, asynchronous:bool) -> bool {
+		let keyboard_mode = if asynchronous { GrabMode::ASYNC } else { GrabMode::SYNC };
+
+		let result = self
+			.conn
+			.grab_key(
+				false,
+				self.root,
+				ModMask::from(modifiers as u16),
+				keycode as u8,
+				GrabMode::ASYNC,
+				keyboard_mode,
+			)
+			.map_err(ReplyError::from)
+			.and_then(|cookie| cookie.check());
+
+		!matches!(result, Err(ReplyError::X11Error(e)) if e.error_kind == ErrorKind::Access)
+	}
+
+	pub(super) fn ungrab_key(&self, keycode:u32, modifiers:u32) {
+		let _ = self.conn.ungrab_key(keycode as u8, self.root, ModMask::from(modifiers as u16));
+	}
+
+	pub(super) fn query_keymap(&self) -> [u8; 32] {
+		self.conn
+			.query_keymap()
+			.ok()
+			.and_then(|c| c.reply().ok())
+			.map_or([0; 32], |reply| reply.keys)
+	}
+
+	/// Hands a key press caught by a synchronous grab over to the focused window.
+	pub(super) fn replay_keyboard(&self, time:u32) {
+		let _ = self.conn.allow_events(Allow::REPLAY_KEYBOARD, time);
+	}
+
+	/// Swallows a key press caught by a synchronous grab, the keyboard is no
+	/// longer frozen.
+	pub(super) fn consume_keyboard(&self, time:u32) {
+		let _ = self.conn.allow_events(Allow::ASYNC_KEYBOARD, time);
+	}
+
+	/// Keeps the keyboard grabbed by a synchronous grab until the next key.
+	pub(super) fn next_key(&self, time:u32) {
+		let _ = self.conn.allow_events(Allow::SYNC_KEYBOARD, time);
+	}
+
+	pub(super) fn grab_keyboard(&self) -> crate::Result<()> {
+		let reply = self
+			.conn
+			.grab_keyboard(false, self.root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+			.map_err(ReplyError::from)
+			.and_then(|cookie| cookie.reply());
+
+		match reply {
+			Ok(reply) if reply.status == GrabStatus::SUCCESS => Ok(()),
+
+			Ok(reply) => {
+				Err(crate::Error::os(
+					u8::from(reply.status).into(),
+					"Failed to grab the keyboard, another client may have grabbed it",
+				))
+			},
+
+			Err(e) => {
+				let code = match &e {
+					ReplyError::X11Error(error) => error.error_code.into(),
+					_ => 0,
+				};
+
+				Err(crate::Error::os(code, e.to_string()))
+			},
+		}
+	}
+
+	pub(super) fn ungrab_keyboard(&self) {
+		let _ = self.conn.ungrab_keyboard(CURRENT_TIME);
+
+		let _ = self.conn.flush();
+	}
+
+	// Waits for the X server to process every request sent so far.
+	pub(super) fn sync(&self) { let _ = self.conn.sync(); }
+
+	/// The window manager as it advertises itself to EWMH pagers, `None` if it
+	/// doesn't.
+	pub(super) fn window_manager(&self) -> Option<ProcessInfo> {
+		let check = self.property(self.root, b"_NET_SUPPORTING_WM_CHECK", AtomEnum::WINDOW.into())?;
+
+		let window = check.value32()?.next()?;
+
+		let utf8 = self.atom(b"UTF8_STRING")?;
+
+		let name = self
+			.property(window, b"_NET_WM_NAME", utf8)
+			.and_then(|name| String::from_utf8(name.value).ok());
+
+		let pid = self
+			.property(window, b"_NET_WM_PID", AtomEnum::CARDINAL.into())
+			.and_then(|pid| pid.value32()?.next());
+
+		ProcessInfo::new(pid, name)
+	}
+
+	fn atom(&self, name:&[u8]) -> Option<Atom> {
+		Some(self.conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+	}
+
+	// Property `name` of `window`, `None` if it's missing or of another type.
+	fn property(&self, window:Window, name:&[u8], kind:Atom) -> Option<GetPropertyReply> {
+		let property = self.atom(name)?;
+
+		let reply = self.conn.get_property(false, window, property, kind, 0, 1024).ok()?;
+
+		let reply = reply.reply().ok()?;
+
+		(reply.type_ == kind).then_some(reply)
+	}
+}
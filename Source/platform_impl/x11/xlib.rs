@@ -0,0 +1,304 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The X server connection through Xlib, loaded at runtime by `x11-dl`.
+
+use std::{
+	ffi::{CString, c_int, c_ulong},
+	ptr,
+};
+
+use keyboard_types::KeyState;
+use x11_dl::xlib::{self, _XDisplay, Xlib};
+
+use super::{Event, Root};
+use crate::{Backend, ProcessInfo, RawPlatformEvent};
+
+pub(crate) const BACKEND:Backend = Backend::Xlib;
+
+// `XkbUseCoreKbd` from XKB.h, missing from x11-dl.
+const XKB_USE_CORE_KBD:u32 = 0x0100;
+
+pub(super) struct Display {
+	xlib:Xlib,
+	display:*mut _XDisplay,
+	root:c_ulong,
+	xkb_event_base:c_int,
+}
+
+impl Display {
+	/// Connects to the X server, grabbing on `root`.
+	pub(super) fn open(root:Root) -> crate::Result<Self> {
+		let xlib = xlib::Xlib::open().map_err(|_| {
+			crate::Error::PlatformUnsupported(
+				"Failed to open Xlib, maybe you are not running under X11? Other window systems \
+				 on Linux are not supported by `global-hotkey` crate."
+					.to_string(),
+			)
+		})?;
+
+		unsafe {
+			let display = (xlib.XOpenDisplay)(ptr::null());
+
+			if display.is_null() {
+				return Err(crate::Error::PlatformUnsupported(
+					"Failed to open the X11 display, make sure the DISPLAY environment variable \
+					 is set."
+						.to_string(),
+				));
+			}
+
+			let root:c_ulong = match root {
+				Root::Window(window) => window,
+				Root::Screen(None) => (xlib.XDefaultRootWindow)(display),
+				Root::Screen(Some(screen)) => {
+					if !(0..(xlib.XScreenCount)(display)).contains(&screen) {
+						(xlib.XCloseDisplay)(display);
+
+						return Err(Root::no_screen(screen));
+					}
+
+					(xlib.XRootWindow)(display, screen)
+				},
+			};
+
+			// Only trigger key release at end of repeated keys
+			let mut supported_rtrn:i32 = 0;
+			(xlib.XkbSetDetectableAutoRepeat)(display, 1, &mut supported_rtrn);
+
+			(xlib.XSelectInput)(display, root, xlib::KeyPressMask);
+
+			// Listen for keyboard layout changes, which move keysyms to other keycodes.
+			let (mut opcode, mut xkb_event_base, mut xkb_error_base) = (0, -1, 0);
+
+			let (mut major, mut minor) = (1, 0);
+
+			if (xlib.XkbQueryExtension)(
+				display,
+				&mut opcode,
+				&mut xkb_event_base,
+				&mut xkb_error_base,
+				&mut major,
+				&mut minor,
+			) != 0
+			{
+				(xlib.XkbSelectEvents)(
+					display,
+					XKB_USE_CORE_KBD,
+					xlib::XkbMapNotifyMask,
+					xlib::XkbMapNotifyMask,
+				);
+			}
+
+			Ok(Self { xlib, display, root, xkb_event_base })
+		}
+	}
+
+	/// Returns the next queued event without blocking.
+	pub(super) fn next_event(&mut self) -> Option<Event> {
+		unsafe {
+			while (self.xlib.XPending)(self.display) > 0 {
+				let mut event:xlib::XEvent = std::mem::zeroed();
+
+				(self.xlib.XNextEvent)(self.display, &mut event);
+
+				match event.get_type() {
+					e if e == self.xkb_event_base => {
+						let xkb_event =
+							&mut *(&mut event as *mut xlib::XEvent as *mut xlib::XkbMapNotifyEvent);
+
+						if xkb_event.xkb_type == xlib::XkbMapNotify {
+							(self.xlib.XkbRefreshKeyboardMapping)(xkb_event);
+
+							return Some(Event::LayoutChanged);
+						}
+					},
+
+					e @ xlib::KeyPress | e @ xlib::KeyRelease => {
+						return Some(Event::Key {
+							state:if e == xlib::KeyPress { KeyState::Down } else { KeyState::Up },
+							keycode:event.key.keycode,
+							mods:event.key.state,
+							time:event.key.time as _,
+							raw:RawPlatformEvent::XKey(event.key),
+						});
+					},
+
+					_ => {},
+				}
+			}
+		}
+
+		None
+	}
+
+	/// Returns `0` if no key of the current layout produces `keysym`.
+	pub(super) fn keysym_to_keycode(&self, keysym:u32) -> u32 {
+		unsafe { (self.xlib.XKeysymToKeycode)(self.display, keysym as _) as _ }
+	}
+
+	/// Returns the keysym of the first level, so letters come in lower case.
+	pub(super) fn keycode_to_keysym(&self, keycode:u32) -> u32 {
+		unsafe { (self.xlib.XkbKeycodeToKeysym)(self.display, keycode as _, 0, 0) as _ }
+	}
+
+	// Returns `false` if another client already grabbed this combination.
+	pub(super) fn grab_key(&self, keycode:u32, modifiers:u32, asynchronous:bool) -> bool {
+		let keyboard_mode = if asynchronous { xlib::GrabModeAsync } else { xlib::GrabModeSync };
+
+		let result = unsafe {
+			(self.xlib.XGrabKey)(
+				self.display,
+				keycode as _,
+				modifiers,
+				self.root,
+				0,
+				xlib::GrabModeAsync,
+				keyboard_mode,
+			)
+		};
+
+		result != xlib::BadAccess as c_int
+	}
+
+	pub(super) fn ungrab_key(&self, keycode:u32, modifiers:u32) {
+		unsafe { (self.xlib.XUngrabKey)(self.display, keycode as _, modifiers, self.root) };
+	}
+
+	pub(super) fn query_keymap(&self) -> [u8; 32] {
+		let mut keymap = [0; 32];
+
+		unsafe { (self.xlib.XQueryKeymap)(self.display, keymap.as_mut_ptr() as _) };
+
+		keymap
+	}
+
+	/// Hands a key press caught by a synchronous grab over to the focused window.
+	pub(super) fn replay_keyboard(&self, time:u32) {
+		unsafe { (self.xlib.XAllowEvents)(self.display, xlib::ReplayKeyboard, time as _) };
+	}
+
+	/// Swallows a key press caught by a synchronous grab, the keyboard is no
+	/// longer frozen.
+	pub(super) fn consume_keyboard(&self, time:u32) {
+		unsafe { (self.xlib.XAllowEvents)(self.display, xlib::AsyncKeyboard, time as _) };
+	}
+
+	/// Keeps the keyboard grabbed by a synchronous grab until the next key.
+	pub(super) fn next_key(&self, time:u32) {
+		unsafe { (self.xlib.XAllowEvents)(self.display, xlib::SyncKeyboard, time as _) };
+	}
+
+	pub(super) fn grab_keyboard(&self) -> crate::Result<()> {
+		match unsafe {
+			(self.xlib.XGrabKeyboard)(
+				self.display,
+				self.root,
+				0,
+				xlib::GrabModeAsync,
+				xlib::GrabModeAsync,
+				xlib::CurrentTime,
+			)
+		} {
+			xlib::GrabSuccess => Ok(()),
+
+			code => {
+				Err(crate::Error::os(
+					code.into(),
+					"Failed to grab the keyboard, another client may have grabbed it",
+				))
+			},
+		}
+	}
+
+	pub(super) fn ungrab_keyboard(&self) {
+		unsafe { (self.xlib.XUngrabKeyboard)(self.display, xlib::CurrentTime) };
+	}
+
+	// Waits for the X server to process every request sent so far.
+	pub(super) fn sync(&self) { unsafe { (self.xlib.XSync)(self.display, xlib::False) }; }
+
+	/// The window manager as it advertises itself to EWMH pagers, `None` if it
+	/// doesn't.
+	pub(super) fn window_manager(&self) -> Option<ProcessInfo> {
+		// The check window of a window manager that exited is gone, the error
+		// must not reach the default handler, which exits the process.
+		let previous = unsafe { (self.xlib.XSetErrorHandler)(Some(ignore_error)) };
+
+		let check =
+			self.property::<c_ulong>(self.root, "_NET_SUPPORTING_WM_CHECK", xlib::XA_WINDOW);
+
+		let window_manager = check.and_then(|check| check.first().copied()).and_then(|window| {
+			let utf8 = self.atom("UTF8_STRING");
+
+			let name = self
+				.property::<u8>(window, "_NET_WM_NAME", utf8)
+				.and_then(|name| String::from_utf8(name).ok());
+
+			let pid = self
+				.property::<c_ulong>(window, "_NET_WM_PID", xlib::XA_CARDINAL)
+				.and_then(|pid| pid.first().map(|pid| *pid as u32));
+
+			ProcessInfo::new(pid, name)
+		});
+
+		self.sync();
+
+		unsafe { (self.xlib.XSetErrorHandler)(previous) };
+
+		window_manager
+	}
+
+	fn atom(&self, name:&str) -> c_ulong {
+		let name = CString::new(name).expect("atom names have no nul byte");
+
+		unsafe { (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False) }
+	}
+
+	// The items of property `name` of `window`, `c_ulong` for the 32-bit
+	// format and `u8` for the 8-bit one. `None` if the property is missing or
+	// of another type.
+	fn property<T:Copy>(&self, window:c_ulong, name:&str, kind:c_ulong) -> Option<Vec<T>> {
+		let expected_format = if std::mem::size_of::<T>() == 1 { 8 } else { 32 };
+
+		let (mut actual_kind, mut format, mut items, mut after) = (0, 0, 0, 0);
+
+		let mut data = ptr::null_mut();
+
+		let status = unsafe {
+			(self.xlib.XGetWindowProperty)(
+				self.display,
+				window,
+				self.atom(name),
+				0,
+				1024,
+				xlib::False,
+				kind,
+				&mut actual_kind,
+				&mut format,
+				&mut items,
+				&mut after,
+				&mut data,
+			)
+		};
+
+		if status != xlib::Success as c_int || data.is_null() {
+			return None;
+		}
+
+		let values = (actual_kind == kind && format == expected_format).then(|| {
+			unsafe { std::slice::from_raw_parts(data as *const T, items as usize) }.to_vec()
+		});
+
+		unsafe { (self.xlib.XFree)(data as _) };
+
+		values
+	}
+}
+
+unsafe extern "C" fn ignore_error(_:*mut _XDisplay, _:*mut xlib::XErrorEvent) -> c_int { 0 }
+
+impl Drop for Display {
+	fn drop(&mut self) { unsafe { (self.xlib.XCloseDisplay)(self.display) }; }
+}
@@ -0,0 +1,162 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+	collections::HashMap,
+	sync::{
+		Mutex,
+		atomic::{AtomicUsize, Ordering},
+	},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::hotkey::{Code, HotKey, Modifiers};
+
+/// The id of a manager, unique within the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ManagerId(usize);
+
+impl ManagerId {
+	pub(crate) fn get(self) -> usize { self.0 }
+}
+
+impl Default for ManagerId {
+	fn default() -> Self {
+		static NEXT:AtomicUsize = AtomicUsize::new(1);
+
+		Self(NEXT.fetch_add(1, Ordering::Relaxed))
+	}
+}
+
+// The manager holding a combination, along with its hotkeys using it.
+// Hotkeys only differing by their modifier sides are grabbed as the same
+// combination by every backend, so they share the claim.
+struct Claim {
+	manager:ManagerId,
+	hotkeys:Vec<HotKey>,
+}
+
+static CLAIMS:Lazy<Mutex<HashMap<(Modifiers, Code), Claim>>> = Lazy::new(Default::default);
+
+/// Claims `hotkeys` for `manager`, failing if another manager of the process
+/// holds one of their combinations, and returns the ones it didn't hold yet.
+///
+/// Hotkeys already claimed by `manager` are left to its backend to reject.
+pub(crate) fn claim(manager:ManagerId, hotkeys:&[HotKey]) -> crate::Result<Vec<HotKey>> {
+	let mut claims = CLAIMS.lock().unwrap();
+
+	let taken = hotkeys.iter().find_map(|hotkey| {
+		claims
+			.get(&(hotkey.mods, hotkey.key))
+			.filter(|claim| claim.manager != manager)
+			.map(|claim| (*hotkey, claim.manager))
+	});
+
+	if let Some((attempted, holder)) = taken {
+		return Err(crate::Error::AlreadyRegisteredInProcess {
+			attempted,
+			by_manager:holder.get(),
+		});
+	}
+
+	let mut claimed = Vec::new();
+
+	for hotkey in hotkeys {
+		let claim = claims
+			.entry((hotkey.mods, hotkey.key))
+			.or_insert_with(|| Claim { manager, hotkeys:Vec::new() });
+
+		if !claim.hotkeys.iter().any(|h| h.id() == hotkey.id()) {
+			claim.hotkeys.push(*hotkey);
+
+			claimed.push(*hotkey);
+		}
+	}
+
+	Ok(claimed)
+}
+
+/// Releases the claims of `manager` on `hotkeys`, the ones it doesn't hold are
+/// ignored.
+pub(crate) fn release(manager:ManagerId, hotkeys:&[HotKey]) {
+	let mut claims = CLAIMS.lock().unwrap();
+
+	for hotkey in hotkeys {
+		let combination = (hotkey.mods, hotkey.key);
+
+		let Some(claim) = claims.get_mut(&combination).filter(|c| c.manager == manager) else {
+			continue;
+		};
+
+		claim.hotkeys.retain(|h| h.id() != hotkey.id());
+
+		if claim.hotkeys.is_empty() {
+			claims.remove(&combination);
+		}
+	}
+}
+
+/// Returns the manager holding the hotkey with this id.
+pub(crate) fn holder(id:u32) -> Option<ManagerId> {
+	let claims = CLAIMS.lock().unwrap();
+
+	claims.values().find(|claim| claim.hotkeys.iter().any(|h| h.id() == id)).map(|c| c.manager)
+}
+
+/// Returns the hotkey with this id registered by any manager of the process.
+pub(crate) fn registered(id:u32) -> Option<HotKey> {
+	let claims = CLAIMS.lock().unwrap();
+
+	claims.values().flat_map(|claim| &claim.hotkeys).find(|h| h.id() == id).copied()
+}
+
+/// Releases every claim of `manager`, once it's dropped.
+pub(crate) fn release_all(manager:ManagerId) {
+	CLAIMS.lock().unwrap().retain(|_, claim| claim.manager != manager);
+}
+
+#[test]
+fn test_process_claims() {
+	let (a, b) = (ManagerId::default(), ManagerId::default());
+
+	let left = "LeftCtrl+Alt+Shift+Super+KeyQ".parse::<HotKey>().unwrap();
+
+	let right = "RightCtrl+Alt+Shift+Super+KeyQ".parse::<HotKey>().unwrap();
+
+	assert_eq!(claim(a, &[left]).unwrap(), [left]);
+
+	assert_eq!(holder(left.id()), Some(a));
+
+	assert_eq!(registered(left.id()), Some(left));
+
+	assert!(claim(a, &[left]).unwrap().is_empty());
+
+	// The other side is the same combination for the backends.
+	assert!(matches!(
+		claim(b, &[right]),
+		Err(crate::Error::AlreadyRegisteredInProcess { attempted, by_manager })
+			if attempted == right && by_manager == a.get()
+	));
+
+	assert_eq!(claim(a, &[right]).unwrap(), [right]);
+
+	release(a, &[left]);
+
+	assert!(claim(b, &[left]).is_err());
+
+	release(b, &[right]);
+
+	release(a, &[right]);
+
+	claim(b, &[left, right]).unwrap();
+
+	release_all(b);
+
+	assert_eq!(registered(right.id()), None);
+
+	claim(a, &[left]).unwrap();
+
+	release_all(a);
+}
@@ -0,0 +1,86 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The key events of the platforms as the backends receive them, see
+//! [`GlobalHotKeyManager::set_raw_event_hook`](crate::GlobalHotKeyManager::set_raw_event_hook).
+//!
+//! The platform types are re-exported from the crates the backends use, so
+//! they don't have to be added as dependencies, and always match their version.
+
+#[cfg(all(
+	any(
+		target_os = "linux",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "openbsd",
+		target_os = "netbsd"
+	),
+	not(feature = "x11rb")
+))]
+pub use x11_dl::xlib::XKeyEvent;
+#[cfg(all(
+	any(
+		target_os = "linux",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "openbsd",
+		target_os = "netbsd"
+	),
+	feature = "x11rb"
+))]
+pub use x11rb::protocol::xproto::KeyPressEvent;
+#[cfg(target_os = "windows")]
+pub use windows_sys::Win32::UI::{Input::RAWKEYBOARD, WindowsAndMessaging::KBDLLHOOKSTRUCT};
+
+/// A key event received by the backend, before it's matched against the
+/// registered hotkeys.
+///
+/// Only the variants of the current platform exist, more may be added.
+#[non_exhaustive]
+#[derive(Clone, Copy)]
+pub enum RawPlatformEvent {
+	/// A `KeyPress` or `KeyRelease` of a grabbed key, or of any key while
+	/// recording, with the Xlib backend.
+	#[cfg(all(
+		any(
+			target_os = "linux",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "openbsd",
+			target_os = "netbsd"
+		),
+		not(feature = "x11rb")
+	))]
+	XKey(XKeyEvent),
+	/// A `KeyPress` or `KeyRelease` of a grabbed key, or of any key while
+	/// recording, with the `x11rb` backend. Releases have the same fields,
+	/// their `response_type` tells them apart.
+	#[cfg(all(
+		any(
+			target_os = "linux",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "openbsd",
+			target_os = "netbsd"
+		),
+		feature = "x11rb"
+	))]
+	X11rbKey(KeyPressEvent),
+	/// A `WM_HOTKEY` message of a hotkey registered with `RegisterHotKey`.
+	/// `wparam` is the hotkey id, `lparam` holds its modifiers and virtual-key
+	/// code.
+	#[cfg(target_os = "windows")]
+	WindowsHotKey { wparam:usize, lparam:isize },
+	/// A key event seen by the low-level keyboard hook matching non-consuming
+	/// hotkeys, `wparam` is its message such as `WM_KEYDOWN`.
+	#[cfg(target_os = "windows")]
+	WindowsKeyboardHook { wparam:usize, event:KBDLLHOOKSTRUCT },
+	/// A key event received through Raw Input with
+	/// [`WindowsBackend::RawInput`](crate::WindowsBackend::RawInput), `device`
+	/// is the `HANDLE` of the keyboard it came from.
+	#[cfg(target_os = "windows")]
+	WindowsRawInput { device:isize, keyboard:RAWKEYBOARD },
+}
+
+pub(crate) type RawEventHook = Box<dyn Fn(RawPlatformEvent) + Send>;
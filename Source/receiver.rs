@@ -0,0 +1,124 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use crossbeam_channel::Receiver;
+
+use crate::GlobalHotKeyEvent;
+
+/// The receiving end of the hotkey event channel, obtained with
+/// [`GlobalHotKeyEvent::event_receiver`].
+///
+/// Unlike the channel it wraps, its methods don't expose the channel
+/// implementation, which may change between releases. The channel is shared
+/// by the whole process, each event is received once by whichever thread asks
+/// first.
+///
+/// No event is received while a handler is set with
+/// [`GlobalHotKeyEvent::set_event_handler`].
+pub struct GlobalHotKeyEventReceiver {
+	// Hooks such as per-manager receivers or filters belong next to the
+	// channel, so that callers keep using the same methods.
+	channel:Receiver<GlobalHotKeyEvent>,
+}
+
+impl GlobalHotKeyEventReceiver {
+	pub(crate) fn new(channel:Receiver<GlobalHotKeyEvent>) -> Self { Self { channel } }
+
+	pub(crate) fn channel(&self) -> &Receiver<GlobalHotKeyEvent> { &self.channel }
+
+	/// Returns the oldest pending event without blocking.
+	pub fn try_recv(&self) -> Option<GlobalHotKeyEvent> { self.channel.try_recv().ok() }
+
+	/// Blocks until an event is pending and returns it.
+	pub fn recv(&self) -> GlobalHotKeyEvent {
+		// The sender lives in a `static`, the channel is never disconnected.
+		self.channel.recv().expect("the hotkey event channel is never disconnected")
+	}
+
+	/// Blocks until an event is pending or `timeout` elapsed.
+	pub fn recv_timeout(&self, timeout:Duration) -> Option<GlobalHotKeyEvent> {
+		self.channel.recv_timeout(timeout).ok()
+	}
+
+	/// Returns every pending event without blocking, e.g. once per frame of
+	/// a render loop. Nothing is allocated when no event is pending.
+	pub fn try_drain(&self) -> Vec<GlobalHotKeyEvent> {
+		if self.channel.is_empty() {
+			return Vec::new();
+		}
+
+		self.try_iter().collect()
+	}
+
+	/// Returns an iterator over the pending events, ending once none is left.
+	pub fn try_iter(&self) -> impl Iterator<Item = GlobalHotKeyEvent> + '_ {
+		self.channel.try_iter()
+	}
+
+	/// Returns an iterator blocking for every next event, it never ends.
+	pub fn iter(&self) -> impl Iterator<Item = GlobalHotKeyEvent> + '_ { self.channel.iter() }
+
+	/// Returns the number of pending events.
+	pub fn len(&self) -> usize { self.channel.len() }
+
+	/// Returns `true` if no event is pending.
+	pub fn is_empty(&self) -> bool { self.channel.is_empty() }
+}
+
+impl std::fmt::Debug for GlobalHotKeyEventReceiver {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("GlobalHotKeyEventReceiver").field("pending", &self.len()).finish()
+	}
+}
+
+#[test]
+fn test_event_receiver() {
+	use crate::{
+		HotKeyState,
+		MockGlobalHotKeyManager,
+		hotkey::{Code, HotKey, Modifiers},
+	};
+
+	let receiver = GlobalHotKeyEvent::event_receiver();
+
+	let manager = MockGlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::KeyK);
+
+	manager.register(hotkey).unwrap();
+
+	manager.simulate_press(&hotkey);
+
+	manager.simulate_release(&hotkey);
+
+	// Other tests share the channel, only look at our own events.
+	let ours = |events:Vec<GlobalHotKeyEvent>| {
+		events.into_iter().filter(|e| e.id() == hotkey.id()).map(|e| e.state()).collect::<Vec<_>>()
+	};
+
+	assert_eq!(ours(receiver.try_drain()), [HotKeyState::Pressed, HotKeyState::Released]);
+
+	assert!(ours(receiver.try_drain()).is_empty());
+
+	manager.simulate_press(&hotkey);
+
+	assert_eq!(ours(GlobalHotKeyEvent::drain()), [HotKeyState::Pressed]);
+
+	// Other tests may send events in the meantime.
+	let drained = GlobalHotKeyEvent::drain();
+
+	if drained.is_empty() {
+		assert_eq!(drained.capacity(), 0);
+	}
+
+	let start = std::time::Instant::now();
+
+	while let Some(event) = receiver.recv_timeout(Duration::from_millis(50)) {
+		assert_ne!(event.id(), hotkey.id());
+	}
+
+	assert!(start.elapsed() >= Duration::from_millis(50));
+}
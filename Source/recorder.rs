@@ -0,0 +1,181 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Location, Modifiers};
+
+use crate::hotkey::HotKey;
+
+/// Keeps the keyboard grabbed for recording a hotkey, returned by
+/// [`GlobalHotKeyManager::start_recording`](crate::GlobalHotKeyManager::start_recording).
+///
+/// Dropping it releases the keyboard and registered hotkeys fire again.
+pub struct RecordingHandle {
+	receiver:Receiver<KeyboardEvent>,
+	last:Arc<Mutex<Option<HotKey>>>,
+	stop:Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl RecordingHandle {
+	/// Gets the channel receiving every key press and release while recording.
+	///
+	/// Only [`KeyboardEvent::state`], [`KeyboardEvent::code`],
+	/// [`KeyboardEvent::location`], [`KeyboardEvent::modifiers`] and
+	/// [`KeyboardEvent::repeat`] are filled, `key` is always
+	/// [`Key::Unidentified`].
+	pub fn receiver(&self) -> &Receiver<KeyboardEvent> { &self.receiver }
+
+	/// Stops recording and returns the last combo pressed, made of a
+	/// non-modifier key and the modifiers held with it.
+	///
+	/// Returns `None` if only modifier keys, or nothing, were pressed.
+	pub fn into_hotkey(self) -> Option<HotKey> { *self.last.lock().unwrap() }
+
+	/// Sets what releases the keyboard, only once the backend started recording
+	/// so a failed start doesn't end a recording already in progress.
+	#[allow(dead_code)]
+	pub(crate) fn on_drop<F:FnOnce() + Send + 'static>(mut self, stop:F) -> Self {
+		self.stop = Some(Box::new(stop));
+
+		self
+	}
+}
+
+impl Drop for RecordingHandle {
+	fn drop(&mut self) {
+		if let Some(stop) = self.stop.take() {
+			stop();
+		}
+	}
+}
+
+impl std::fmt::Debug for RecordingHandle {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("RecordingHandle").field("last", &self.last).finish_non_exhaustive()
+	}
+}
+
+/// The backend side of a [`RecordingHandle`].
+#[allow(dead_code)]
+pub(crate) struct Recorder {
+	tx:Sender<KeyboardEvent>,
+	last:Arc<Mutex<Option<HotKey>>>,
+	held:Vec<Code>,
+}
+
+#[allow(dead_code)]
+impl Recorder {
+	/// Builds the event with the fields a backend knows about, keeps track of
+	/// auto-repeat and of the last combo, then delivers it.
+	pub(crate) fn send(&mut self, state:KeyState, code:Code, modifiers:Modifiers) {
+		let repeat = match state {
+			KeyState::Down if self.held.contains(&code) => true,
+			KeyState::Down => {
+				self.held.push(code);
+
+				false
+			},
+			KeyState::Up => {
+				self.held.retain(|c| *c != code);
+
+				false
+			},
+		};
+
+		if state == KeyState::Down && !repeat && !is_modifier(code) {
+			let mods = modifiers
+				& (Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER);
+
+			*self.last.lock().unwrap() = Some(HotKey::new(Some(mods), code));
+		}
+
+		let _ = self.tx.send(KeyboardEvent {
+			state,
+			key:Key::Unidentified,
+			code,
+			location:location(code),
+			modifiers,
+			repeat,
+			is_composing:false,
+		});
+	}
+}
+
+/// Creates the two ends of a recording.
+#[allow(dead_code)]
+pub(crate) fn recording() -> (Recorder, RecordingHandle) {
+	let (tx, receiver) = unbounded();
+
+	let last = Arc::new(Mutex::new(None));
+
+	(
+		Recorder { tx, last:last.clone(), held:Vec::new() },
+		RecordingHandle { receiver, last, stop:None },
+	)
+}
+
+pub(crate) fn is_modifier(code:Code) -> bool {
+	matches!(
+		code,
+		Code::ShiftLeft
+			| Code::ShiftRight
+			| Code::ControlLeft
+			| Code::ControlRight
+			| Code::AltLeft
+			| Code::AltRight
+			| Code::MetaLeft
+			| Code::MetaRight
+	)
+}
+
+fn location(code:Code) -> Location {
+	match code {
+		Code::ShiftLeft | Code::ControlLeft | Code::AltLeft | Code::MetaLeft => Location::Left,
+		Code::ShiftRight | Code::ControlRight | Code::AltRight | Code::MetaRight => {
+			Location::Right
+		},
+		Code::Numpad0
+		| Code::Numpad1
+		| Code::Numpad2
+		| Code::Numpad3
+		| Code::Numpad4
+		| Code::Numpad5
+		| Code::Numpad6
+		| Code::Numpad7
+		| Code::Numpad8
+		| Code::Numpad9
+		| Code::NumpadAdd
+		| Code::NumpadComma
+		| Code::NumpadDecimal
+		| Code::NumpadDivide
+		| Code::NumpadEnter
+		| Code::NumpadMultiply
+		| Code::NumpadSubtract => Location::Numpad,
+		_ => Location::Standard,
+	}
+}
+
+#[test]
+fn test_recorder() {
+	let (mut recorder, handle) = recording();
+
+	recorder.send(KeyState::Down, Code::ControlLeft, Modifiers::empty());
+	recorder.send(KeyState::Down, Code::KeyK, Modifiers::CONTROL);
+	recorder.send(KeyState::Down, Code::KeyK, Modifiers::CONTROL);
+	recorder.send(KeyState::Up, Code::KeyK, Modifiers::CONTROL);
+	recorder.send(KeyState::Up, Code::ControlLeft, Modifiers::CONTROL);
+	recorder.send(KeyState::Down, Code::ShiftLeft, Modifiers::empty());
+
+	let events = handle.receiver().try_iter().collect::<Vec<_>>();
+
+	assert_eq!(events.len(), 6);
+	assert_eq!(events[0].location, Location::Left);
+	assert!(!events[1].repeat);
+	assert!(events[2].repeat);
+
+	// The trailing modifier press doesn't replace the combo.
+	assert_eq!(handle.into_hotkey(), Some(HotKey::new(Some(Modifiers::CONTROL), Code::KeyK)));
+}
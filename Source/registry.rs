@@ -0,0 +1,148 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::{GlobalHotKeyEvent, GlobalHotKeyManager, hotkey::HotKey};
+
+/// The actions of the hotkeys registered through it, looked up from their
+/// events instead of matching ids by hand.
+///
+/// ```no_run
+/// # use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyRegistry, HotKeyState};
+/// # use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+/// enum Action {
+///     ToggleOverlay,
+///     Quit,
+/// }
+///
+/// # fn main() -> global_hotkey::Result<()> {
+/// let manager = GlobalHotKeyManager::new()?;
+///
+/// let mut registry = HotKeyRegistry::new();
+///
+/// registry.register_with_action(&manager, HotKey::new(None, Code::F9), Action::ToggleOverlay)?;
+///
+/// registry.register_with_action(
+///     &manager,
+///     HotKey::new(Some(Modifiers::CONTROL), Code::KeyQ),
+///     Action::Quit,
+/// )?;
+///
+/// let event = GlobalHotKeyEvent::event_receiver().recv();
+///
+/// match registry.handle_event(&event) {
+///     Some(Action::ToggleOverlay) if event.state() == HotKeyState::Pressed => {},
+///     Some(Action::Quit) => {},
+///     _ => {},
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The registry doesn't own the hotkeys, they stay registered with the
+/// manager when it's dropped.
+#[derive(Debug, Clone)]
+pub struct HotKeyRegistry<A> {
+	actions:HashMap<u32, (HotKey, A)>,
+}
+
+impl<A> Default for HotKeyRegistry<A> {
+	fn default() -> Self { Self { actions:HashMap::new() } }
+}
+
+impl<A> HotKeyRegistry<A> {
+	pub fn new() -> Self { Self::default() }
+
+	/// Registers `hotkey` with `manager` and keeps `action` for its events.
+	///
+	/// Nothing is kept if the registration fails, e.g. with
+	/// [`Error::AlreadyRegistered`](crate::Error::AlreadyRegistered) when the
+	/// hotkey was already registered through the registry.
+	pub fn register_with_action(
+		&mut self,
+		manager:&GlobalHotKeyManager,
+		hotkey:HotKey,
+		action:A,
+	) -> crate::Result<()> {
+		manager.register(hotkey)?;
+
+		self.actions.insert(hotkey.id(), (hotkey, action));
+
+		Ok(())
+	}
+
+	/// Unregisters `hotkey` from `manager` and returns its action, `None` if it
+	/// wasn't registered through the registry.
+	pub fn unregister(
+		&mut self,
+		manager:&GlobalHotKeyManager,
+		hotkey:HotKey,
+	) -> crate::Result<Option<A>> {
+		manager.unregister(hotkey)?;
+
+		Ok(self.actions.remove(&hotkey.id()).map(|(_, action)| action))
+	}
+
+	/// Returns the action of the hotkey `event` is for, whether it was pressed
+	/// or released, `None` if it wasn't registered through the registry.
+	pub fn handle_event(&self, event:&GlobalHotKeyEvent) -> Option<&A> {
+		self.actions.get(&event.id()).map(|(_, action)| action)
+	}
+
+	/// Returns the hotkeys registered through the registry along with their
+	/// actions, in no particular order.
+	pub fn iter(&self) -> impl Iterator<Item = (&HotKey, &A)> {
+		self.actions.values().map(|(hotkey, action)| (hotkey, action))
+	}
+
+	/// Returns the number of hotkeys registered through the registry.
+	pub fn len(&self) -> usize { self.actions.len() }
+
+	/// Returns `true` if no hotkey was registered through the registry.
+	pub fn is_empty(&self) -> bool { self.actions.is_empty() }
+}
+
+#[test]
+fn test_hotkey_registry() {
+	use crate::{
+		HotKeyState,
+		hotkey::{Code, Modifiers},
+	};
+
+	let manager = GlobalHotKeyManager::new().unwrap();
+
+	let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::F18);
+
+	let mut registry = HotKeyRegistry::new();
+
+	let pressed = GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Pressed);
+
+	// Fails without a display, nothing is kept then.
+	if registry.register_with_action(&manager, hotkey, "overlay").is_err() {
+		assert!(registry.is_empty());
+
+		assert_eq!(registry.handle_event(&pressed), None);
+
+		return;
+	}
+
+	assert_eq!(registry.handle_event(&pressed), Some(&"overlay"));
+
+	let released = GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Released);
+
+	assert_eq!(registry.handle_event(&released), Some(&"overlay"));
+
+	let other = GlobalHotKeyEvent::new(hotkey.id() + 1, HotKeyState::Pressed);
+
+	assert_eq!(registry.handle_event(&other), None);
+
+	assert!(registry.register_with_action(&manager, hotkey, "again").is_err());
+
+	assert_eq!(registry.iter().collect::<Vec<_>>(), [(&hotkey, &"overlay")]);
+
+	assert_eq!(registry.unregister(&manager, hotkey).unwrap(), Some("overlay"));
+
+	assert!(registry.is_empty());
+}
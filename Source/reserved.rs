@@ -0,0 +1,132 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt::Display;
+
+use keyboard_types::{Code, Modifiers};
+
+use crate::hotkey::HotKey;
+
+/// A key combination handled by the operating system before any application,
+/// see [`HotKey::conflicts_with_system`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemShortcut {
+	mods:Modifiers,
+	key:Code,
+	description:&'static str,
+}
+
+impl SystemShortcut {
+	const fn new(mods:Modifiers, key:Code, description:&'static str) -> Self {
+		Self { mods, key, description }
+	}
+
+	/// Returns the reserved combination as a [`HotKey`].
+	pub fn hotkey(&self) -> HotKey { HotKey::new(Some(self.mods), self.key) }
+
+	/// Returns what the system does with this combination, e.g. `"Lock the screen"`.
+	pub fn description(&self) -> &'static str { self.description }
+
+	/// Returns the reserved combinations of the current platform.
+	pub fn all() -> &'static [SystemShortcut] {
+		#[cfg(target_os = "windows")]
+		return WINDOWS;
+		#[cfg(target_os = "macos")]
+		return MACOS;
+		#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+		return X11;
+	}
+}
+
+impl Display for SystemShortcut {
+	fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ({})", self.hotkey(), self.description)
+	}
+}
+
+// Only combinations that can't be taken over are listed, the ones a user can
+// free up in the system settings (e.g. `PrintScreen`) are left out on purpose.
+
+const CONTROL_ALT:Modifiers = Modifiers::CONTROL.union(Modifiers::ALT);
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const WINDOWS:&[SystemShortcut] = &[
+	SystemShortcut::new(CONTROL_ALT, Code::Delete, "Secure attention sequence"),
+	SystemShortcut::new(Modifiers::SUPER, Code::KeyL, "Lock the workstation"),
+];
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const MACOS:&[SystemShortcut] = &[
+	SystemShortcut::new(Modifiers::SUPER, Code::Tab, "Switch applications"),
+	SystemShortcut::new(
+		Modifiers::SUPER.union(Modifiers::SHIFT),
+		Code::Tab,
+		"Switch applications backwards",
+	),
+	SystemShortcut::new(
+		Modifiers::SUPER.union(Modifiers::ALT),
+		Code::Escape,
+		"Force quit applications",
+	),
+	SystemShortcut::new(Modifiers::SUPER.union(Modifiers::CONTROL), Code::KeyQ, "Lock the screen"),
+];
+
+// The X server switches virtual terminals before clients see these keys.
+#[cfg_attr(any(target_os = "windows", target_os = "macos"), allow(dead_code))]
+const X11:&[SystemShortcut] = &[
+	SystemShortcut::new(CONTROL_ALT, Code::F1, "Switch to virtual terminal 1"),
+	SystemShortcut::new(CONTROL_ALT, Code::F2, "Switch to virtual terminal 2"),
+	SystemShortcut::new(CONTROL_ALT, Code::F3, "Switch to virtual terminal 3"),
+	SystemShortcut::new(CONTROL_ALT, Code::F4, "Switch to virtual terminal 4"),
+	SystemShortcut::new(CONTROL_ALT, Code::F5, "Switch to virtual terminal 5"),
+	SystemShortcut::new(CONTROL_ALT, Code::F6, "Switch to virtual terminal 6"),
+	SystemShortcut::new(CONTROL_ALT, Code::F7, "Switch to virtual terminal 7"),
+	SystemShortcut::new(CONTROL_ALT, Code::F8, "Switch to virtual terminal 8"),
+	SystemShortcut::new(CONTROL_ALT, Code::F9, "Switch to virtual terminal 9"),
+	SystemShortcut::new(CONTROL_ALT, Code::F10, "Switch to virtual terminal 10"),
+	SystemShortcut::new(CONTROL_ALT, Code::F11, "Switch to virtual terminal 11"),
+	SystemShortcut::new(CONTROL_ALT, Code::F12, "Switch to virtual terminal 12"),
+];
+
+pub(crate) fn find(hotkey:&HotKey) -> Option<SystemShortcut> {
+	SystemShortcut::all()
+		.iter()
+		.find(|shortcut| {
+			shortcut.mods == crate::canonical::fold(hotkey.mods) && shortcut.key == hotkey.key
+		})
+		.copied()
+}
+
+#[test]
+fn test_reserved_tables() {
+	for table in [WINDOWS, MACOS, X11] {
+		for (i, shortcut) in table.iter().enumerate() {
+			assert!(
+				!table[..i].iter().any(|s| s.hotkey() == shortcut.hotkey()),
+				"{} is listed twice",
+				shortcut
+			);
+
+			// `HotKey::new` would fold `META` into `SUPER` and break the comparison.
+			assert!(!shortcut.mods.contains(Modifiers::META));
+		}
+	}
+
+	for shortcut in SystemShortcut::all() {
+		assert_eq!(shortcut.hotkey().conflicts_with_system(), Some(*shortcut));
+	}
+
+	// Parsed hotkeys match too, while extra modifiers make a different combination.
+	let (reserved, free) = if cfg!(target_os = "windows") {
+		("ctrl+alt+delete", "ctrl+alt+shift+delete")
+	} else if cfg!(target_os = "macos") {
+		("cmd+tab", "cmd+ctrl+tab")
+	} else {
+		("ctrl+alt+f2", "ctrl+alt+shift+f2")
+	};
+
+	assert!(reserved.parse::<HotKey>().unwrap().conflicts_with_system().is_some());
+
+	assert_eq!(free.parse::<HotKey>().unwrap().conflicts_with_system(), None);
+}
@@ -0,0 +1,221 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::hotkey::HotKey;
+
+/// The platform backend a [`GlobalHotKeyManager`](crate::GlobalHotKeyManager) runs on.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+	/// `RegisterHotKey` and a low-level keyboard hook.
+	Windows,
+	/// Raw Input, see [`WindowsBackend::RawInput`](crate::WindowsBackend::RawInput).
+	WindowsRawInput,
+	/// Carbon hotkeys, event taps and `NSEvent` monitors.
+	MacOS,
+	/// Xlib, loaded at runtime.
+	Xlib,
+	/// The X11 protocol implementation of `x11rb`, with the `x11rb` feature.
+	X11rb,
+	/// [`MockGlobalHotKeyManager`](crate::MockGlobalHotKeyManager), which never
+	/// talks to the operating system.
+	Mock,
+	/// The platform has no backend.
+	Unsupported,
+}
+
+/// Whether the backend of a manager delivers events, see [`ManagerStatus::health`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum Health {
+	/// Every registered hotkey emits events.
+	Healthy,
+	/// The backend works but some hotkeys don't emit events, their
+	/// [`HotKeyStatus`] tells why.
+	Degraded,
+	/// The manager was suspended with
+	/// [`GlobalHotKeyManager::suspend`](crate::GlobalHotKeyManager::suspend),
+	/// hotkeys are kept but no events are emitted until it is resumed.
+	Suspended,
+	/// The backend stopped working, e.g. it lost its connection to the X server,
+	/// and no hotkey emits events anymore.
+	Failed(crate::Error),
+}
+
+/// Whether a hotkey known to a manager emits events, see [`ManagerStatus::hotkeys`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum HotKeyStatus {
+	/// The hotkey is registered and emits events.
+	Active,
+	/// The hotkey is kept while the manager is suspended.
+	Suspended,
+	/// The hotkey was registered but the system dropped it, e.g. when waking
+	/// from sleep or after a keyboard layout change, and it couldn't be
+	/// registered again. It is no longer counted as registered, registering it
+	/// again may succeed once its keys are free.
+	LostGrab,
+	/// The hotkey is registered but can't emit events for now, e.g. because a
+	/// permission was revoked.
+	Failed(crate::Error),
+}
+
+// Errors don't implement `PartialEq`, their messages are compared instead.
+impl PartialEq for Health {
+	fn eq(&self, other:&Self) -> bool {
+		match (self, other) {
+			(Self::Failed(a), Self::Failed(b)) => a.to_string() == b.to_string(),
+			(a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+		}
+	}
+}
+
+impl PartialEq for HotKeyStatus {
+	fn eq(&self, other:&Self) -> bool {
+		match (self, other) {
+			(Self::Failed(a), Self::Failed(b)) => a.to_string() == b.to_string(),
+			(a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+		}
+	}
+}
+
+/// The operational state of a [`GlobalHotKeyManager`](crate::GlobalHotKeyManager).
+///
+/// Obtained through [`GlobalHotKeyManager::status`](crate::GlobalHotKeyManager::status),
+/// or with every change through
+/// [`GlobalHotKeyManager::set_status_handler`](crate::GlobalHotKeyManager::set_status_handler).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerStatus {
+	/// The backend the manager runs on.
+	pub backend:Backend,
+	/// Whether the backend delivers events.
+	pub health:Health,
+	/// Every hotkey known to the manager along with its status, ordered by id.
+	pub hotkeys:Vec<(HotKey, HotKeyStatus)>,
+}
+
+impl ManagerStatus {
+	/// The health is [`Health::Suspended`] if `suspended`, and otherwise
+	/// [`Health::Degraded`] if a hotkey doesn't emit events.
+	pub(crate) fn new(
+		backend:Backend,
+		suspended:bool,
+		mut hotkeys:Vec<(HotKey, HotKeyStatus)>,
+	) -> Self {
+		hotkeys.sort_by_key(|(hotkey, _)| hotkey.id());
+
+		let health = if suspended {
+			Health::Suspended
+		} else if hotkeys.iter().any(|(_, s)| !matches!(s, HotKeyStatus::Active)) {
+			Health::Degraded
+		} else {
+			Health::Healthy
+		};
+
+		Self { backend, health, hotkeys }
+	}
+
+	/// Every hotkey of a failed backend fails with its error.
+	pub(crate) fn backend_failed<I:IntoIterator<Item = HotKey>>(
+		backend:Backend,
+		error:crate::Error,
+		hotkeys:I,
+	) -> Self {
+		let mut hotkeys = hotkeys
+			.into_iter()
+			.map(|hotkey| (hotkey, HotKeyStatus::Failed(error.clone())))
+			.collect::<Vec<_>>();
+
+		hotkeys.sort_by_key(|(hotkey, _)| hotkey.id());
+
+		Self { backend, health:Health::Failed(error), hotkeys }
+	}
+
+	/// Returns the number of registered hotkeys, active or suspended.
+	///
+	/// Lost hotkeys are not counted, and neither are any if the backend failed.
+	pub fn registered_count(&self) -> usize {
+		if matches!(self.health, Health::Failed(_)) {
+			return 0;
+		}
+
+		self.hotkeys
+			.iter()
+			.filter(|(_, s)| !matches!(s, HotKeyStatus::LostGrab))
+			.count()
+	}
+
+	/// Returns the number of hotkeys kept while the manager is suspended.
+	pub fn suspended_count(&self) -> usize {
+		self.hotkeys.iter().filter(|(_, s)| matches!(s, HotKeyStatus::Suspended)).count()
+	}
+
+	/// Returns the status of the hotkey with this id, `None` if the manager
+	/// doesn't know it.
+	pub fn hotkey_status(&self, id:u32) -> Option<&HotKeyStatus> {
+		self.hotkeys.iter().find(|(hotkey, _)| hotkey.id() == id).map(|(_, s)| s)
+	}
+
+	/// Returns the hotkeys that don't emit events while the manager isn't
+	/// suspended.
+	pub fn failed(&self) -> Vec<HotKey> {
+		self.hotkeys
+			.iter()
+			.filter(|(_, s)| matches!(s, HotKeyStatus::LostGrab | HotKeyStatus::Failed(_)))
+			.map(|(hotkey, _)| *hotkey)
+			.collect()
+	}
+}
+
+#[test]
+fn test_manager_status() {
+	use crate::hotkey::{Code, Modifiers};
+
+	let a = HotKey::new(Some(Modifiers::SHIFT), Code::KeyA);
+
+	let b = HotKey::new(Some(Modifiers::SHIFT), Code::KeyB);
+
+	let healthy = ManagerStatus::new(Backend::Mock, false, vec![(a, HotKeyStatus::Active)]);
+
+	assert_eq!(healthy.health, Health::Healthy);
+
+	assert_eq!(healthy.registered_count(), 1);
+
+	let degraded = ManagerStatus::new(
+		Backend::Mock,
+		false,
+		vec![(b, HotKeyStatus::LostGrab), (a, HotKeyStatus::Active)],
+	);
+
+	assert_eq!(degraded.health, Health::Degraded);
+
+	assert_eq!(degraded.registered_count(), 1);
+
+	assert_eq!(degraded.failed(), [b]);
+
+	assert_eq!(degraded.hotkey_status(b.id()), Some(&HotKeyStatus::LostGrab));
+
+	assert_ne!(healthy, degraded);
+
+	let suspended = ManagerStatus::new(
+		Backend::Mock,
+		true,
+		vec![(a, HotKeyStatus::Suspended), (b, HotKeyStatus::LostGrab)],
+	);
+
+	assert_eq!(suspended.health, Health::Suspended);
+
+	assert_eq!((suspended.registered_count(), suspended.suspended_count()), (1, 1));
+
+	let error = crate::Error::PlatformUnsupported("Gone".to_string());
+
+	let failed = ManagerStatus::backend_failed(Backend::Mock, error.clone(), [a]);
+
+	assert_eq!(failed.health, Health::Failed(error.clone()));
+
+	assert_eq!(failed.hotkey_status(a.id()), Some(&HotKeyStatus::Failed(error)));
+
+	assert_eq!(failed.registered_count(), 0);
+}
@@ -0,0 +1,288 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::time::{Duration, Instant};
+
+use keyboard_types::{Code, KeyState, Modifiers};
+
+use crate::{GlobalHotKeyEvent, HotKeyState, canonical, hotkey::HotKey};
+
+/// What a hotkey registered with
+/// [`GlobalHotKeyManager::register_tap_hold`](crate::GlobalHotKeyManager::register_tap_hold)
+/// does while it's held, kept in its [`HotKeyOptions`](crate::HotKeyOptions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct TapHold {
+	/// The combinations pressed while the tap key is held, their modifiers
+	/// leave out the ones of the tap hotkey.
+	pub(crate) hold_with:Vec<HotKey>,
+	/// How long the tap key may be held for a release to count as a tap.
+	pub(crate) threshold:Duration,
+}
+
+impl TapHold {
+	pub(crate) fn new(hold_with:&[HotKey], threshold:Duration) -> Self {
+		Self { hold_with:hold_with.to_vec(), threshold }
+	}
+
+	/// Fails if `tap` can't tell its own key apart from the combinations.
+	pub(crate) fn check(&self, tap:&HotKey) -> crate::Result<()> {
+		match self.hold_with.iter().find(|hotkey| hotkey.key == tap.key) {
+			Some(hotkey) => {
+				Err(crate::Error::UnsupportedOption(format!(
+					"`{}` can't be held with `{}`, which uses the same key",
+					tap, hotkey
+				)))
+			},
+			None => Ok(()),
+		}
+	}
+}
+
+/// What the backend does with the key that was just fed to a [`Gesture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Next {
+	/// The gesture goes on, the key was part of it.
+	Continue,
+	/// The tap key was released, the gesture is over.
+	Done,
+	/// The key isn't part of the gesture, which is given up so the key goes
+	/// to the focused window. The tap doesn't fire once the key is released.
+	Abort,
+}
+
+/// The state of a tap-hold hotkey from the press of its tap key to its
+/// release, fed with every key event in between.
+#[derive(Debug)]
+pub(crate) struct Gesture {
+	tap:HotKey,
+	tap_hold:TapHold,
+	pressed_at:Instant,
+	// Whether a combination was pressed, a tap doesn't fire then.
+	used:bool,
+	// The combinations pressed and not released yet.
+	active:Vec<HotKey>,
+}
+
+impl Gesture {
+	/// Starts the gesture of `tap`, its key was pressed at `at`.
+	pub(crate) fn start(tap:HotKey, tap_hold:TapHold, at:Instant) -> Self {
+		Self { tap, tap_hold, pressed_at:at, used:false, active:Vec::new() }
+	}
+
+	/// Handles a key pressed or released at `at` while the tap key is held,
+	/// returning the events to send and what happens to the key.
+	pub(crate) fn key(
+		&mut self,
+		state:KeyState,
+		code:Code,
+		mods:Modifiers,
+		at:Instant,
+	) -> (Vec<GlobalHotKeyEvent>, Next) {
+		if code == self.tap.key {
+			return match state {
+				// Auto-repeat of the tap key.
+				KeyState::Down => (Vec::new(), Next::Continue),
+				KeyState::Up => (self.release(at), Next::Done),
+			};
+		}
+
+		if crate::recorder::is_modifier(code) {
+			return (Vec::new(), Next::Continue);
+		}
+
+		match state {
+			KeyState::Down => {
+				if self.active.iter().any(|hotkey| hotkey.key == code) {
+					return (Vec::new(), Next::Continue);
+				}
+
+				let tap_mods = canonical::fold(self.tap.mods);
+
+				let mods = canonical::fold(mods & HELD_MODS).difference(tap_mods);
+
+				let combination = self
+					.tap_hold
+					.hold_with
+					.iter()
+					.find(|h| h.key == code && canonical::fold(h.mods) == mods);
+
+				match combination {
+					Some(hotkey) => {
+						self.used = true;
+
+						self.active.push(*hotkey);
+
+						let event = GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Pressed);
+
+						(vec![event], Next::Continue)
+					},
+					None => (self.abort(), Next::Abort),
+				}
+			},
+			KeyState::Up => {
+				let released = self.active.iter().position(|hotkey| hotkey.key == code);
+
+				let events = released
+					.map(|i| self.active.remove(i))
+					.map(|hotkey| GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Released))
+					.into_iter()
+					.collect();
+
+				// A key held since before the gesture is ignored.
+				(events, Next::Continue)
+			},
+		}
+	}
+
+	/// Gives the gesture up, e.g. when the grabs are released, returning the
+	/// releases of the combinations still pressed.
+	pub(crate) fn abort(&mut self) -> Vec<GlobalHotKeyEvent> {
+		self.used = true;
+
+		self.active
+			.drain(..)
+			.map(|hotkey| GlobalHotKeyEvent::new(hotkey.id(), HotKeyState::Released))
+			.collect()
+	}
+
+	// The tap key was released, a tap fires if nothing else happened in time.
+	fn release(&mut self, at:Instant) -> Vec<GlobalHotKeyEvent> {
+		let tapped = !self.used && at.duration_since(self.pressed_at) < self.tap_hold.threshold;
+
+		let mut events = self.abort();
+
+		if tapped {
+			events.push(GlobalHotKeyEvent::new(self.tap.id(), HotKeyState::Pressed));
+
+			events.push(GlobalHotKeyEvent::new(self.tap.id(), HotKeyState::Released));
+		}
+
+		events
+	}
+}
+
+// The modifiers the combinations are matched with, the locks are ignored.
+const HELD_MODS:Modifiers = Modifiers::SHIFT
+	.union(Modifiers::CONTROL)
+	.union(Modifiers::ALT)
+	.union(Modifiers::SUPER)
+	.union(Modifiers::META);
+
+#[cfg(test)]
+fn tap_hold_test_setup() -> (HotKey, HotKey, HotKey, Gesture, Instant) {
+	let tap = HotKey::new(None, Code::CapsLock);
+
+	let (j, shift_j) =
+		(HotKey::new(None, Code::KeyJ), HotKey::new(Some(Modifiers::SHIFT), Code::KeyJ));
+
+	let start = Instant::now();
+
+	let tap_hold = TapHold::new(&[j, shift_j], Duration::from_millis(200));
+
+	let gesture = Gesture::start(tap, tap_hold, start);
+
+	(tap, j, shift_j, gesture, start)
+}
+
+#[cfg(test)]
+fn states(events:Vec<GlobalHotKeyEvent>) -> Vec<(u32, HotKeyState)> {
+	events.into_iter().map(|e| (e.id(), e.state())).collect()
+}
+
+#[test]
+fn test_tap_hold_tap() {
+	let (tap, _, _, mut gesture, start) = tap_hold_test_setup();
+
+	let at = |ms| start + Duration::from_millis(ms);
+
+	// Auto-repeat and modifiers don't get in the way of a tap.
+	for (code, ms) in [(Code::CapsLock, 30), (Code::ShiftLeft, 40)] {
+		let (events, next) = gesture.key(KeyState::Down, code, Modifiers::empty(), at(ms));
+
+		assert!(events.is_empty());
+
+		assert_eq!(next, Next::Continue);
+	}
+
+	let (events, next) = gesture.key(KeyState::Up, Code::CapsLock, Modifiers::SHIFT, at(150));
+
+	assert_eq!(next, Next::Done);
+
+	assert_eq!(states(events), [
+		(tap.id(), HotKeyState::Pressed),
+		(tap.id(), HotKeyState::Released),
+	]);
+
+	// Held past the threshold, nothing fires.
+	let (_, _, _, mut gesture, start) = tap_hold_test_setup();
+
+	let at = start + Duration::from_millis(200);
+
+	let (events, next) = gesture.key(KeyState::Up, Code::CapsLock, Modifiers::empty(), at);
+
+	assert_eq!(next, Next::Done);
+
+	assert!(events.is_empty());
+}
+
+#[test]
+fn test_tap_hold_combinations() {
+	let (_, j, shift_j, mut gesture, start) = tap_hold_test_setup();
+
+	let at = |ms| start + Duration::from_millis(ms);
+
+	let (events, next) = gesture.key(KeyState::Down, Code::KeyJ, Modifiers::empty(), at(10));
+
+	assert_eq!((states(events), next), (vec![(j.id(), HotKeyState::Pressed)], Next::Continue));
+
+	// Auto-repeat of the combination.
+	assert!(gesture.key(KeyState::Down, Code::KeyJ, Modifiers::empty(), at(20)).0.is_empty());
+
+	let (events, _) = gesture.key(KeyState::Up, Code::KeyJ, Modifiers::SHIFT, at(30));
+
+	assert_eq!(states(events), [(j.id(), HotKeyState::Released)]);
+
+	let (events, _) = gesture.key(KeyState::Down, Code::KeyJ, Modifiers::SHIFT, at(40));
+
+	assert_eq!(states(events), [(shift_j.id(), HotKeyState::Pressed)]);
+
+	// Released along with the tap key, which doesn't fire after a combination.
+	let (events, next) = gesture.key(KeyState::Up, Code::CapsLock, Modifiers::SHIFT, at(50));
+
+	assert_eq!(next, Next::Done);
+
+	assert_eq!(states(events), [(shift_j.id(), HotKeyState::Released)]);
+}
+
+#[test]
+fn test_tap_hold_abort() {
+	let (_, j, _, mut gesture, start) = tap_hold_test_setup();
+
+	let at = |ms| start + Duration::from_millis(ms);
+
+	gesture.key(KeyState::Down, Code::KeyJ, Modifiers::empty(), at(10));
+
+	// A key that isn't part of the gesture gives it up.
+	let (events, next) = gesture.key(KeyState::Down, Code::KeyK, Modifiers::empty(), at(20));
+
+	assert_eq!(next, Next::Abort);
+
+	assert_eq!(states(events), [(j.id(), HotKeyState::Released)]);
+
+	let (_, _, _, mut gesture, start) = tap_hold_test_setup();
+
+	let (_, next) = gesture.key(KeyState::Down, Code::KeyJ, Modifiers::CONTROL, start);
+
+	assert_eq!(next, Next::Abort);
+
+	// The tap doesn't fire even if the tap key was only held briefly.
+	assert!(gesture.key(KeyState::Up, Code::CapsLock, Modifiers::empty(), start).0.is_empty());
+
+	let tap = HotKey::new(None, Code::KeyJ);
+
+	assert!(TapHold::new(&[HotKey::new(Some(Modifiers::SHIFT), Code::KeyJ)], Duration::ZERO)
+		.check(&tap)
+		.is_err());
+}
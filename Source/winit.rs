@@ -0,0 +1,133 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Receiving hotkey events inside a [`winit`](https://docs.rs/winit) event loop.
+//!
+//! [`HotKeyPlugin`] creates the manager on the thread of the event loop, which
+//! is what Windows and macOS require, and delivers [`GlobalHotKeyEvent`]s as
+//! user events next to the window events. Other threads register hotkeys by
+//! sending a [`HotKeyRequest`] through an
+//! [`EventLoopProxy`](::winit::event_loop::EventLoopProxy), which the
+//! application hands over to [`HotKeyPlugin::handle`].
+//!
+//! ```no_run
+//! use global_hotkey::{GlobalHotKeyEvent, winit::{HotKeyPlugin, HotKeyRequest}};
+//! use winit::event_loop::EventLoop;
+//!
+//! enum AppEvent {
+//!     HotKey(GlobalHotKeyEvent),
+//!     Request(HotKeyRequest),
+//! }
+//!
+//! impl From<GlobalHotKeyEvent> for AppEvent {
+//!     fn from(event: GlobalHotKeyEvent) -> Self {
+//!         AppEvent::HotKey(event)
+//!     }
+//! }
+//!
+//! let event_loop = EventLoop::<AppEvent>::with_user_event().build().unwrap();
+//! let plugin = HotKeyPlugin::new(&event_loop).unwrap();
+//! ```
+//!
+//! See `examples/winit_plugin.rs` for a complete application. With `tao`, whose
+//! event loop works the same way, forward the events with
+//! [`GlobalHotKeyEvent::set_event_handler`] as in `examples/tao.rs`.
+
+use std::sync::Mutex;
+
+use ::winit::event_loop::EventLoop;
+
+use crate::{GlobalHotKeyEvent, GlobalHotKeyManager, hotkey::HotKey};
+
+/// A change to the registered hotkeys, requested from any thread and applied
+/// on the event loop thread by [`HotKeyPlugin::handle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotKeyRequest {
+	Register(HotKey),
+	Unregister(HotKey),
+}
+
+/// Owns a [`GlobalHotKeyManager`] living on the thread of a `winit` event loop.
+///
+/// The registered hotkeys are unregistered when the plugin is dropped, or
+/// earlier with [`HotKeyPlugin::exiting`].
+pub struct HotKeyPlugin {
+	manager:GlobalHotKeyManager,
+	registered:Vec<HotKey>,
+}
+
+impl HotKeyPlugin {
+	/// Creates the manager and sends every [`GlobalHotKeyEvent`] to `event_loop`
+	/// as a user event.
+	///
+	/// Call it on the thread that created `event_loop`, before running it.
+	///
+	/// ## Note
+	///
+	/// This takes over [`GlobalHotKeyEvent::set_event_handler`], which can only
+	/// be set once, so [`GlobalHotKeyEvent::event_receiver`] won't get any event.
+	pub fn new<T:From<GlobalHotKeyEvent> + Send + 'static>(
+		event_loop:&EventLoop<T>,
+	) -> crate::Result<Self> {
+		let manager = GlobalHotKeyManager::new()?;
+
+		let proxy = Mutex::new(event_loop.create_proxy());
+
+		GlobalHotKeyEvent::set_event_handler(Some(move |event| {
+			// Fails only once the event loop is gone, the event has nowhere to go.
+			let _ = proxy.lock().unwrap().send_event(T::from(event));
+		}));
+
+		Ok(Self { manager, registered:Vec::new() })
+	}
+
+	/// Returns the underlying manager, for the features not covered by the
+	/// plugin.
+	///
+	/// Hotkeys registered through it directly aren't unregistered by
+	/// [`HotKeyPlugin::exiting`].
+	pub fn manager(&self) -> &GlobalHotKeyManager { &self.manager }
+
+	pub fn register(&mut self, hotkey:HotKey) -> crate::Result<()> {
+		self.manager.register(hotkey)?;
+
+		self.registered.push(hotkey);
+
+		Ok(())
+	}
+
+	pub fn unregister(&mut self, hotkey:HotKey) -> crate::Result<()> {
+		self.manager.unregister(hotkey)?;
+
+		self.registered.retain(|h| h.id() != hotkey.id());
+
+		Ok(())
+	}
+
+	/// Applies a [`HotKeyRequest`] received as a user event.
+	pub fn handle(&mut self, request:HotKeyRequest) -> crate::Result<()> {
+		match request {
+			HotKeyRequest::Register(hotkey) => self.register(hotkey),
+			HotKeyRequest::Unregister(hotkey) => self.unregister(hotkey),
+		}
+	}
+
+	/// Unregisters the hotkeys registered through the plugin, call it from
+	/// [`ApplicationHandler::exiting`](::winit::application::ApplicationHandler::exiting).
+	pub fn exiting(&mut self) {
+		let registered = std::mem::take(&mut self.registered);
+
+		if let Err(e) = self.manager.unregister_all(&registered) {
+			log::warn!("Failed to unregister hotkeys on exit: {}", e);
+		}
+	}
+}
+
+impl Drop for HotKeyPlugin {
+	fn drop(&mut self) {
+		if !self.registered.is_empty() {
+			self.exiting();
+		}
+	}
+}
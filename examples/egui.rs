@@ -13,11 +13,11 @@ fn main() -> Result<(), eframe::Error> {
 
 	manager.register(hotkey).unwrap();
 
-	let receiver = GlobalHotKeyEvent::receiver();
+	let receiver = GlobalHotKeyEvent::event_receiver();
 
 	std::thread::spawn(|| {
 		loop {
-			if let Ok(event) = receiver.try_recv() {
+			if let Some(event) = receiver.try_recv() {
 				println!("tray event: {event:?}");
 			}
 
@@ -0,0 +1,68 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Binds the accelerators of a configuration to actions with a `HotKeyMap`,
+//! moving the palette to `Ctrl+Shift+O` once it was opened.
+
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyMap};
+use tao::event_loop::{ControlFlow, EventLoopBuilder};
+
+#[derive(Debug, PartialEq)]
+enum Action {
+	CommandPalette,
+	ToggleSidebar,
+	Quit,
+}
+
+// As it would be read from a configuration file.
+const CONFIG:&[(&str, Action)] = &[
+	("Ctrl+Shift+P", Action::CommandPalette),
+	("Ctrl+Shift+B", Action::ToggleSidebar),
+	("Ctrl+Shift+Q", Action::Quit),
+];
+
+fn main() {
+	let event_loop = EventLoopBuilder::new().build();
+
+	let hotkeys_manager = GlobalHotKeyManager::new().unwrap();
+
+	let mut map = HotKeyMap::new();
+
+	for (accelerator, action) in CONFIG {
+		if let Err(e) = map.insert(accelerator, action) {
+			eprintln!("Skipping `{}`: {}", accelerator, e);
+		}
+	}
+
+	for (hotkey, result) in map.attach(&hotkeys_manager) {
+		if let Err(e) = result {
+			eprintln!("`{}` isn't available: {}", hotkey, e);
+		}
+	}
+
+	let global_hotkey_channel = GlobalHotKeyEvent::event_receiver();
+
+	let mut rebound = false;
+
+	event_loop.run(move |_event, _, control_flow| {
+		*control_flow = ControlFlow::Poll;
+
+		let Some(event) = global_hotkey_channel.try_recv() else {
+			return;
+		};
+
+		match map.resolve(&event) {
+			Some(&&Action::CommandPalette) if !rebound => {
+				println!("Command palette, now on Ctrl+Shift+O");
+
+				map.rebind(&hotkeys_manager, &&Action::CommandPalette, "Ctrl+Shift+O").unwrap();
+
+				rebound = true;
+			},
+			Some(&&Action::Quit) => *control_flow = ControlFlow::Exit,
+			Some(action) => println!("{:?} {:?}", action, event.state()),
+			None => {},
+		}
+	})
+}
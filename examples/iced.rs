@@ -75,10 +75,10 @@ impl Example {
 	pub fn hotkey_sub(&self) -> Subscription<ProgramCommands> {
 		iced::subscription::channel(0, 32, |mut sender| {
 			async move {
-				let receiver = GlobalHotKeyEvent::receiver();
+				let receiver = GlobalHotKeyEvent::event_receiver();
 				// poll for global hotkey events every 50ms
 				loop {
-					if let Ok(event) = receiver.try_recv() {
+					if let Some(event) = receiver.try_recv() {
 						sender
 							.send(ProgramCommands::Received(format!("{:?}", event)))
 							.await
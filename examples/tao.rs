@@ -40,15 +40,15 @@ fn main() {
 
 	hotkeys_manager.register(hotkey4).unwrap();
 
-	let global_hotkey_channel = GlobalHotKeyEvent::receiver();
+	let global_hotkey_channel = GlobalHotKeyEvent::event_receiver();
 
 	event_loop.run(move |_event, _, control_flow| {
 		*control_flow = ControlFlow::Poll;
 
-		if let Ok(event) = global_hotkey_channel.try_recv() {
+		if let Some(event) = global_hotkey_channel.try_recv() {
 			println!("{event:?}");
 
-			if hotkey2.id() == event.id && event.state == HotKeyState::Released {
+			if hotkey2.id() == event.id() && event.state() == HotKeyState::Released {
 				hotkeys_manager.unregister(hotkey2).unwrap();
 			}
 		}
@@ -63,7 +63,7 @@ impl ApplicationHandler<AppEvent> for App {
             AppEvent::HotKey(event) => {
                 println!("{event:?}");
 
-                if self.hotkey2.id() == event.id && event.state == HotKeyState::Released {
+                if self.hotkey2.id() == event.id() && event.state() == HotKeyState::Released {
                     self.hotkeys_manager.unregister(self.hotkey2).unwrap();
                 }
             }
@@ -0,0 +1,93 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A window-less winit application reacting to `shift+alt+KeyD`, run with
+//! `cargo run --example winit_plugin --features winit`.
+
+use std::{thread, time::Duration};
+
+use global_hotkey::{
+	GlobalHotKeyEvent,
+	HotKeyState,
+	hotkey::{Code, HotKey, Modifiers},
+	winit::{HotKeyPlugin, HotKeyRequest},
+};
+use winit::{
+	application::ApplicationHandler,
+	event::WindowEvent,
+	event_loop::{ActiveEventLoop, EventLoop},
+	window::WindowId,
+};
+
+#[derive(Debug)]
+enum AppEvent {
+	HotKey(GlobalHotKeyEvent),
+	Request(HotKeyRequest),
+}
+
+impl From<GlobalHotKeyEvent> for AppEvent {
+	fn from(event:GlobalHotKeyEvent) -> Self { AppEvent::HotKey(event) }
+}
+
+struct App {
+	plugin:HotKeyPlugin,
+	quit:HotKey,
+}
+
+impl ApplicationHandler<AppEvent> for App {
+	fn resumed(&mut self, _event_loop:&ActiveEventLoop) {}
+
+	fn window_event(
+		&mut self,
+		_event_loop:&ActiveEventLoop,
+		_window_id:WindowId,
+		_event:WindowEvent,
+	) {
+	}
+
+	fn user_event(&mut self, event_loop:&ActiveEventLoop, event:AppEvent) {
+		match event {
+			AppEvent::HotKey(event) => {
+				println!("{event:?}");
+
+				if event.id() == self.quit.id() && event.state() == HotKeyState::Released {
+					event_loop.exit();
+				}
+			},
+
+			AppEvent::Request(request) => {
+				if let Err(e) = self.plugin.handle(request) {
+					eprintln!("{request:?} failed: {e}");
+				}
+			},
+		}
+	}
+
+	fn exiting(&mut self, _event_loop:&ActiveEventLoop) { self.plugin.exiting(); }
+}
+
+fn main() {
+	let event_loop = EventLoop::<AppEvent>::with_user_event().build().unwrap();
+
+	let mut plugin = HotKeyPlugin::new(&event_loop).unwrap();
+
+	let quit = HotKey::new(Some(Modifiers::SHIFT | Modifiers::ALT), Code::KeyD);
+
+	plugin.register(quit).unwrap();
+
+	// Another thread asks the event loop to register a hotkey.
+	let proxy = event_loop.create_proxy();
+
+	thread::spawn(move || {
+		thread::sleep(Duration::from_secs(1));
+
+		let hotkey = HotKey::new(Some(Modifiers::SHIFT), Code::KeyD);
+
+		let _ = proxy.send_event(AppEvent::Request(HotKeyRequest::Register(hotkey)));
+	});
+
+	println!("Press shift+alt+D to quit");
+
+	event_loop.run_app(&mut App { plugin, quit }).unwrap();
+}
@@ -0,0 +1,16 @@
+// Copyright 2022-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![no_main]
+
+use global_hotkey::hotkey::HotKey;
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary input is either rejected with an error or parses into a hotkey
+// that survives a round trip through its string form.
+fuzz_target!(|hotkey:&str| {
+	if let Ok(parsed) = hotkey.parse::<HotKey>() {
+		assert_eq!(parsed.to_string().parse::<HotKey>().unwrap(), parsed);
+	}
+});
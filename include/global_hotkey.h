@@ -0,0 +1,108 @@
+#ifndef GLOBAL_HOTKEY_H
+#define GLOBAL_HOTKEY_H
+
+/* Generated with cbindgen from Source/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of every function of the C API.
+typedef enum GhkStatus {
+  GHK_STATUS_OK = 0,
+  // A pointer was null or a string wasn't valid UTF-8.
+  GHK_STATUS_INVALID_ARGUMENT = 1,
+  // The hotkey string couldn't be parsed.
+  GHK_STATUS_PARSE_ERROR = 2,
+  // The hotkey is already registered, by this application or another one.
+  GHK_STATUS_ALREADY_REGISTERED = 3,
+  // The platform or the hotkey isn't supported.
+  GHK_STATUS_UNSUPPORTED = 4,
+  // The operating system reported an error.
+  GHK_STATUS_OS_ERROR = 5,
+  // The library panicked, the handles involved shouldn't be used anymore.
+  GHK_STATUS_PANIC = 6,
+  // Any other error, see [`ghk_last_error_message`].
+  GHK_STATUS_OTHER = 7,
+} GhkStatus;
+
+// A parsed hotkey, see [`HotKey`].
+typedef struct GhkHotKey GhkHotKey;
+
+// A manager, see [`GlobalHotKeyManager`].
+typedef struct GhkManager GhkManager;
+
+// Called for every hotkey event with the hotkey id, `0` for pressed and `1`
+// for released, and the `user` pointer given to [`ghk_set_callback`].
+typedef void (*GhkCallback)(uint32_t id, int32_t state, void *user);
+
+// Creates a manager and stores it in `*out`.
+//
+// # Safety
+//
+// `out` must be valid for writes.
+GhkStatus ghk_manager_new(GhkManager **out);
+
+// Drops a manager, which unregisters its hotkeys. `manager` may be null.
+//
+// # Safety
+//
+// `manager` must come from [`ghk_manager_new`] and not be used afterwards.
+GhkStatus ghk_manager_free(GhkManager *manager);
+
+// Parses a hotkey such as `"shift+alt+KeyQ"` and stores it in `*out`.
+//
+// # Safety
+//
+// `hotkey` must be a nul-terminated string and `out` must be valid for writes.
+GhkStatus ghk_hotkey_parse(const char *hotkey, GhkHotKey **out);
+
+// Returns the id events of `hotkey` are emitted with, `0` if it is null.
+//
+// # Safety
+//
+// `hotkey` must come from [`ghk_hotkey_parse`].
+uint32_t ghk_hotkey_id(const GhkHotKey *hotkey);
+
+// Drops a hotkey, registrations made with it are kept. `hotkey` may be null.
+//
+// # Safety
+//
+// `hotkey` must come from [`ghk_hotkey_parse`] and not be used afterwards.
+GhkStatus ghk_hotkey_free(GhkHotKey *hotkey);
+
+// Registers `hotkey` with `manager`.
+//
+// # Safety
+//
+// `manager` and `hotkey` must come from [`ghk_manager_new`] and
+// [`ghk_hotkey_parse`].
+GhkStatus ghk_register(const GhkManager *manager, const GhkHotKey *hotkey);
+
+// Unregisters `hotkey` from `manager`.
+//
+// # Safety
+//
+// `manager` and `hotkey` must come from [`ghk_manager_new`] and
+// [`ghk_hotkey_parse`].
+GhkStatus ghk_unregister(const GhkManager *manager, const GhkHotKey *hotkey);
+
+// Sets the function called for every hotkey event, replacing the previous
+// one. A null `callback` drops the events.
+//
+// The callback runs on the thread delivering the events, which depends on
+// the platform, `user` is passed back untouched.
+//
+// Events go through [`GlobalHotKeyEvent::set_event_handler`], so this has no
+// effect if the application installed its own handler from Rust.
+GhkStatus ghk_set_callback(GhkCallback callback, void *user);
+
+// Returns the message of the last error on this thread, or null if the last
+// call succeeded.
+//
+// The string is owned by the library and valid until the next call on the
+// same thread.
+const char *ghk_last_error_message(void);
+
+#endif  /* GLOBAL_HOTKEY_H */
@@ -0,0 +1,42 @@
+/* Exercises the C API, compiled by `test_ffi_header` in Source/ffi.rs. */
+
+#include <stdio.h>
+
+#include "global_hotkey.h"
+
+static void on_hotkey(uint32_t id, int32_t state, void *user) {
+  (void)user;
+  printf("hotkey %u %s\n", id, state == 0 ? "pressed" : "released");
+}
+
+int main(void) {
+  GhkManager *manager = NULL;
+  GhkHotKey *hotkey = NULL;
+
+  if (ghk_manager_new(&manager) != GHK_STATUS_OK) {
+    fprintf(stderr, "ghk_manager_new: %s\n", ghk_last_error_message());
+    return 1;
+  }
+
+  if (ghk_hotkey_parse("shift+alt+KeyQ", &hotkey) != GHK_STATUS_OK) {
+    fprintf(stderr, "ghk_hotkey_parse: %s\n", ghk_last_error_message());
+    ghk_manager_free(manager);
+    return 1;
+  }
+
+  ghk_set_callback(on_hotkey, NULL);
+
+  GhkStatus status = ghk_register(manager, hotkey);
+  if (status != GHK_STATUS_OK) {
+    fprintf(stderr, "ghk_register (%d): %s\n", status, ghk_last_error_message());
+  } else {
+    printf("registered hotkey %u\n", ghk_hotkey_id(hotkey));
+    ghk_unregister(manager, hotkey);
+  }
+
+  ghk_set_callback(NULL, NULL);
+  ghk_hotkey_free(hotkey);
+  ghk_manager_free(manager);
+
+  return status == GHK_STATUS_OK ? 0 : 1;
+}